@@ -3,14 +3,816 @@ declare module '@wasm/hypercube_wasm' {
   function initModule(module_or_path?: WebAssembly.Module | string | URL | Request | Record<string, any>): Promise<any>;
   export default initModule;
   
+  // Отчет о панике: хук устанавливается в init(), обработчик получает CrashInfo
+  // (сводка собирается без ожидания блокировок; занятые системы - в locked_systems)
+  export function init(): void;
+  export class CrashInfo {
+    free(): void;
+    readonly message: string;
+    readonly module: string;
+    readonly line: number;
+    readonly systems: number;
+    readonly locked_systems: number;
+    readonly comets: number;
+    readonly energy_spheres: number;
+    readonly effects: number;
+    readonly pending_spawns: number | undefined;
+  }
+  export function set_panic_callback(callback: (info: CrashInfo) => void): void;
+  export function clear_panic_callback(): OpResult;
+  export function get_last_crash_info(): CrashInfo | undefined;
+  
+  // Рукопожатие с бинарником: версия крейта, уровень API, возможности сборки и версии форматов
+  export class ModuleInfo {
+    free(): void;
+    readonly crate_version: string;
+    readonly api_level: number;
+    readonly features: string[];
+    readonly state_stream_version: number;
+    readonly delta_layout_version: number;
+    readonly gltf_version: number;
+    readonly packed_layout_version: number;
+    readonly packed_layout: string;
+    supports_api_level(level: number): boolean;
+  }
+  export function get_module_info(): ModuleInfo;
+  
+  // Результат изменяющих вызовов: ok, код ошибки и текст (пустой при успехе)
+  export enum ErrorCode {
+    Ok = 0,
+    SystemNotFound = 1,
+    NotFound = 2,
+    InvalidArgument = 3,
+    // 4 - бывший LockPoisoned, не используется
+    SpawningStopped = 5,
+  }
+  export class OpResult {
+    free(): void;
+    readonly ok: boolean;
+    readonly code: ErrorCode;
+    readonly message: string;
+  }
+  
   // Space Objects
   export function create_space_object_system(viewport_size_percent: number, fov_degrees: number): number;
-  export function update_space_object_system(system_id: number, dt: number): boolean;
+  export function update_space_object_system(system_id: number, dt: number): OpResult;
+  export function get_camera_impulse(system_id: number): Vec3Wrapper;
+  export function reseed_space_object_system(system_id: number, seed: bigint): OpResult;
+  export function get_system_seed(system_id: number): bigint | undefined;
+  export function set_entropy_mode(system_id: number, enabled: boolean): OpResult;
+  export function get_frame_stats(system_id: number): FrameStats | undefined;
+  
+  // Статистика последнего обновления системы
+  export interface FrameStats {
+    readonly requested_dt: number;
+    readonly applied_dt: number;
+    readonly objects_updated: number;
+    readonly objects_sleeping: number;
+    readonly crossings: number;
+    readonly crossings_deduplicated: number;
+    readonly speed_clamped: number;
+    readonly sanitized: number;
+    readonly sanitized_total: number;
+  }
+  
+  export function predict_object_path(system_id: number, object_id: number, seconds: number, steps: number): PredictedPath | undefined;
+  
+  // Предсказанная траектория объекта
+  export interface PredictedPath {
+    readonly points: Float32Array;
+    readonly crossing_time: number | undefined;
+    readonly crossing_point: Vec3Wrapper | undefined;
+  }
+  
+  // Vec3Wrapper - JS-обертка над трехмерным вектором
+  export interface Vec3Wrapper {
+    readonly x: number;
+    readonly y: number;
+    readonly z: number;
+    to_array(): Float32Array;
+  }
+  
+  // Vec2Wrapper - JS-обертка над двумерным вектором
+  export interface Vec2Wrapper {
+    readonly x: number;
+    readonly y: number;
+    to_array(): Float32Array;
+  }
+  
+  // horizon_seconds ограничен MAX_UPCOMING_CROSSING_HORIZON (10 с)
+  export function get_upcoming_crossings(system_id: number, horizon_seconds: number): UpcomingCrossings | undefined;
+  
+  // Объекты, которые скоро пересекут видовую плоскость (по возрастанию ETA)
+  export interface UpcomingCrossings {
+    readonly ids: Uint32Array;
+    readonly etas: Float32Array;
+    readonly uvs: Float32Array;
+  }
+  
+  export function get_plane_crossings(system_id: number): PlaneCrossingArray | undefined;
+  // Пересечения попадают в события ближайшего update_space_object_system
+  export function track_hypercube_crossings(system_id: number, hypercube: unknown, w_camera: number, center: Vec3Wrapper, scale: number, dt: number): number;
+  
+  // События пересечения видовой плоскости за последний кадр
+  export interface PlaneCrossingArray {
+    readonly ids: Uint32Array;
+    readonly types: SpaceObjectType[];
+    readonly positions: Float32Array;
+    readonly velocities: Float32Array;
+    readonly uvs: Float32Array;
+    readonly frame_times: Float32Array;
+    // Момент пересечения в секундах относительно конца кадра (<= 0)
+    readonly time_offsets: Float32Array;
+    // Звук: сила удара, группа (0 - тихий, 1 - средний, 2 - сильный), панорама -1..1, громкость 0..1
+    readonly impact_strengths: Float32Array;
+    readonly strength_buckets: Uint8Array;
+    readonly pans: Float32Array;
+    readonly volumes: Float32Array;
+  }
+  
+  export enum SpaceObjectType {
+    NeonComet = 0,
+    EnergySphere = 1,
+    PolygonalCrystal = 2,
+    Hypercube = 3,
+  }
+  
+  export function set_acceleration_profile(system_id: number, profile: AccelerationProfile, strength: number): OpResult;
+  export function get_acceleration_profile(system_id: number): AccelerationProfile | undefined;
+  export function get_acceleration_strength(system_id: number): number | undefined;
+  
+  // Глубина резкости (aperture = 0 - выключена) и экспоненциальный туман (density = 0 - выключен)
+  export function set_depth_of_field(system_id: number, focus_distance: number, aperture: number): OpResult;
+  export function set_fog_density(system_id: number, density: number): OpResult;
+  
+  // Области перекрытия: объекты внутри помечаются occluded, их хвосты не строятся
+  export function add_occlusion_box(system_id: number, min: Vec3Wrapper, max: Vec3Wrapper): number | undefined;
+  export function clear_occlusion_boxes(system_id: number): OpResult;
+  
+  // Направление пересечения видовой плоскости (PlaneCrossingArray.crossing_types)
+  export enum CrossingType {
+    Entry = 0,
+    Exit = 1,
+  }
+  
+  // Профиль разгона объектов к видовой плоскости
+  export enum AccelerationProfile {
+    ConstantVelocity = 0,
+    Linear = 1,
+    Exponential = 2,
+    EaseInOut = 3,
+  }
+  
+  // Видимость вкладки и политика догоняния после возвращения
+  export function notify_visibility_change(hidden: boolean): void;
+  export function set_catch_up_policy(policy: CatchUpPolicy): void;
+  export function get_catch_up_policy(): CatchUpPolicy;
+  export function is_simulation_hidden(): boolean;
+  
+  export enum CatchUpPolicy {
+    Skip = 0,
+    FastForward = 1,
+    Pause = 2,
+  }
+  
+  // Кривые сглаживания и профили прозрачности по типам объектов
+  export enum EasingCurve {
+    Linear = 0,
+    EaseInQuad = 1,
+    EaseOutQuad = 2,
+    EaseInOutQuad = 3,
+    EaseInCubic = 4,
+    EaseOutCubic = 5,
+    Smoothstep = 6,
+  }
+  
+  export class OpacityProfile {
+    constructor();
+    free(): void;
+    fade_in_seconds: number;
+    fade_in_curve: EasingCurve;
+    travel_curve: EasingCurve;
+    travel_min_opacity: number;
+    fade_out_distance: number;
+    fade_out_curve: EasingCurve;
+    // Кривая из реестра вместо встроенной (false - неизвестный дескриптор)
+    set_fade_in_curve_handle(handle: number): boolean;
+    set_travel_curve_handle(handle: number): boolean;
+    set_fade_out_curve_handle(handle: number): boolean;
+  }
+  
+  export function set_opacity_profile(system_id: number, object_type: SpaceObjectType, profile: OpacityProfile): OpResult;
+  export function get_opacity_profile(system_id: number, object_type: SpaceObjectType): OpacityProfile | undefined;
+  export function evaluate_easing(curve: EasingCurve, t: number): number;
+  
+  // Реестр кривых анимации: дескрипторы 0..6 - значения EasingCurve.
+  // В симуляции кривые из реестра применяются только в OpacityProfile;
+  // крутизна экспоненты ограничена 30, время пружины - сам t = 0..1
+  export function create_bezier_curve(x1: number, y1: number, x2: number, y2: number): number | undefined;
+  export function create_hermite_curve(p0: number, p1: number, m0: number, m1: number): number | undefined;
+  export function create_exponential_curve(steepness: number): number | undefined;
+  export function create_spring_curve(stiffness: number, damping: number): number | undefined;
+  export function evaluate_curve(curve_handle: number, t: number): number | undefined;
+  
+  // Дельта-экспорт: только изменившиеся поля с квантованием и ключевыми кадрами
+  export function get_object_deltas(system_id: number, since_frame: number): ObjectDeltas | undefined;
+  export function get_system_frame(system_id: number): number | undefined;
+  
+  // masks: бит 0 - позиция, бит 1 - прозрачность, бит 2 - размер
+  export interface ObjectDeltas {
+    readonly frame: number;
+    readonly keyframe: boolean;
+    readonly ids: Uint32Array;
+    readonly masks: Uint8Array;
+    readonly positions: Int32Array;
+    readonly opacities: Uint8Array;
+    readonly sizes: Uint16Array;
+    readonly removed: Uint32Array;
+    readonly position_quantum: number;
+    readonly size_quantum: number;
+  }
+  
+  // Двоичный поток состояния сцены (формат описан в wasm/src/state_stream.rs)
+  export function encode_scene_state(system_id: number): Uint8Array | undefined;
+  export function get_state_stream_version(): number;
+  
+  // Экспорт снимка сцены в glTF 2.0 (GLB)
+  export function export_gltf(system_id: number): Uint8Array | undefined;
+  export function export_gltf_with_hypercube(system_id: number, hypercube: unknown, w_camera: number, center: Vec3Wrapper, scale: number): Uint8Array | undefined;
+  
+  // Силуэты кадра в SVG (пути комет, каркас гиперкуба) для статических превью
+  export function export_silhouette_svg(system_id: number, width: number, height: number): string | undefined;
+  export function export_silhouette_svg_with_hypercube(system_id: number, width: number, height: number, hypercube: unknown, w_camera: number, center: Vec3Wrapper, scale: number): string | undefined;
+  
+  // Запрос объектов по маске компонентов: 1 - позиция, 2 - скорость, 4 - размер,
+  // 8 - прозрачность, 16 - время жизни, 32 - вращение, 64 - размытие, 128 - туман,
+  // 256 - подсветка выделения.
+  // region: [min_x, min_y, min_z, max_x, max_y, max_z]
+  export function query_entities(system_id: number, component_mask: number, region?: Float32Array | number[]): EntityQueryResult | undefined;
+  
+  export interface EntityQueryResult {
+    readonly mask: number;
+    readonly ids: Uint32Array;
+    readonly types: SpaceObjectType[];
+    readonly positions: Float32Array;
+    readonly velocities: Float32Array;
+    readonly sizes: Float32Array;
+    readonly opacities: Float32Array;
+    readonly lifetimes: Float32Array;
+    readonly rotations: Float32Array;
+    readonly blurs: Float32Array;
+    readonly fogs: Float32Array;
+    readonly highlights: Float32Array;
+  }
+  
+  // Порядок проходов обновления, вычисленный по их зависимостям, и граф в формате DOT
+  export function get_update_graph_order(): string[];
+  export function get_update_graph_dot(): string;
+  
+  // Отчет о памяти: хранилища систем объектов и общая очередь появлений
+  export function get_memory_report(): MemoryReport;
+  
+  export interface MemoryReport {
+    readonly names: string[];
+    readonly lens: Uint32Array;
+    readonly capacities: Uint32Array;
+    readonly used_bytes: Uint32Array;
+    readonly reserved_bytes: Uint32Array;
+    readonly total_reserved_bytes: number;
+    readonly system_count: number;
+  }
+  
+  // Точка видовой плоскости -> нормированные координаты окна (0..1, ось Y вниз)
+  export function plane_point_to_viewport(system_id: number, px: number, py: number): Vec2Wrapper | undefined;
+  
+  // Наблюдатель и слои параллакса (глубина: 0 - видовая плоскость, 1 - дальняя граница)
+  export function set_observer_position(system_id: number, x: number, y: number, z: number): OpResult;
+  export function add_parallax_layer(system_id: number, depth_factor: number): number | undefined;
+  export function clear_parallax_layers(system_id: number): OpResult;
+  export function set_parallax_input(system_id: number, offset_x: number, offset_y: number): OpResult;
+  export function get_parallax_offsets(system_id: number): Float32Array | undefined;
+  
+  // Ориентация устройства (градусы, как в DeviceOrientationEvent) и направление взгляда
+  export function set_device_orientation(system_id: number, alpha: number, beta: number, gamma: number): OpResult;
+  export function get_view_direction(system_id: number): Vec3Wrapper | undefined;
+  
+  // Свободный полет: ввод геймпада/клавиатуры (-1..1 по осям), сглаженное движение наблюдателя
+  export function set_navigation_input(system_id: number, move_vec: Vec3Wrapper, look_vec: Vec2Wrapper, dt: number): OpResult;
+  export function get_observer_velocity(system_id: number): Vec3Wrapper | undefined;
+  export function get_observer_position(system_id: number): Vec3Wrapper | undefined;
+  export function get_field_of_view(system_id: number): number | undefined;
+  
+  // Закладки камеры: полет к сохраненной точке зрения с плавностью, события прибытия (ID полетов)
+  export function save_viewpoint(system_id: number, name: string): OpResult;
+  export function remove_viewpoint(system_id: number, name: string): OpResult;
+  export function fly_to_viewpoint(system_id: number, name: string, duration: number): number | undefined;
+  export function get_viewpoint_flight_progress(system_id: number): number | undefined;
+  export function take_viewpoint_arrivals(system_id: number): Uint32Array;
+  
+  // Габариты активных видимых объектов и кадр (положение наблюдателя, угол обзора), вмещающий сцену
+  export class SceneBounds {
+    free(): void;
+    readonly min: Float32Array;
+    readonly max: Float32Array;
+    readonly center: Float32Array;
+    readonly radius: number;
+    readonly count: number;
+  }
+  export class SceneFraming {
+    free(): void;
+    readonly position: Float32Array;
+    readonly field_of_view: number;
+  }
+  export function get_scene_bounds(system_id: number): SceneBounds | undefined;
+  export function frame_scene(system_id: number, padding: number): SceneFraming | undefined;
+  
+  // Стерео/VR: позиции глаз и off-axis пирамиды [tan_left, tan_right, tan_bottom, tan_top, near, far]
+  export class StereoViewParams {
+    free(): void;
+    readonly left_eye: Float32Array;
+    readonly right_eye: Float32Array;
+    readonly left_frustum: Float32Array;
+    readonly right_frustum: Float32Array;
+  }
+  export function get_stereo_view_params(system_id: number, ipd: number): StereoViewParams | undefined;
+  export function get_visible_ids_for_eye(system_id: number, ipd: number, right_eye: boolean): Uint32Array | undefined;
+  
+  // Обработчик кадра: вызывается после update_space_object_system с прокси системы
+  export class FrameHookView {
+    free(): void;
+    readonly system_id: number;
+    ids(): Uint32Array;
+    get_velocity(object_id: number): Vec3Wrapper | undefined;
+    set_velocity(object_id: number, x: number, y: number, z: number): boolean;
+    set_color(object_id: number, r: number, g: number, b: number): boolean;
+  }
+  export function set_frame_hook(system_id: number, hook: (view: FrameHookView) => void): OpResult;
+  export function clear_frame_hook(system_id: number): OpResult;
+  
+  // Стек модификаторов поведения: push возвращает дескриптор, pop снимает верхний
+  export function push_speed_modifier(system_id: number, multiplier: number): number | undefined;
+  export function push_color_tint_modifier(system_id: number, r: number, g: number, b: number, amount: number): number | undefined;
+  export function push_noise_jitter_modifier(system_id: number, amplitude: number, frequency: number): number | undefined;
+  export function push_size_clamp_modifier(system_id: number, min_scale: number, max_scale: number): number | undefined;
+  export function pop_modifier(system_id: number): OpResult;
+  export function remove_modifier(system_id: number, handle: number): OpResult;
+  export function get_modifier_count(system_id: number): number | undefined;
+  
+  // Эффекты ударов комет о плоскость (создаются из пересечений и стареют вместе с системой)
+  export enum CometEffectKind {
+    Flash = 0,
+    ShockwaveRing = 1,
+    ParticleSpray = 2,
+  }
+  export class CometEffectArray {
+    free(): void;
+    readonly kinds: CometEffectKind[];
+    readonly positions: Float32Array;
+    readonly directions: Float32Array;
+    readonly colors: Float32Array;
+    readonly radii: Float32Array;
+    readonly opacities: Float32Array;
+    readonly progress: Float32Array;
+  }
+  export function update_comet_effects(system_id: number, dt: number): OpResult;
+  export function get_comet_effects(system_id: number): CometEffectArray | undefined;
+  export function create_comet_effect_at_intersection(system_id: number, kind: CometEffectKind, x: number, y: number, z: number, r: number, g: number, b: number, intensity: number, radius: number): OpResult;
+  export function clear_comet_effects(system_id: number): OpResult;
+  
+  // Разгон "прыжка в гиперпространство": множитель скорости по Z растет до цели и сам возвращается к 1
+  export function start_warp(system_id: number, target_speed_multiplier: number, ramp_seconds: number): OpResult;
+  export function get_warp_multiplier(system_id: number): number | undefined;
+  export function is_warping(system_id: number): boolean;
+  
+  // Переходы между страницами: смещение объектов поверх их траекторий на duration секунд
+  export enum TransitionKind {
+    SweepLeft = 0,
+    ConvergeToCenter = 1,
+    ExplodeThroughPlane = 2,
+  }
+  export function play_transition(system_id: number, kind: TransitionKind, duration: number): number | undefined;
+  export function get_transition_progress(system_id: number): number | undefined;
+  // ID переходов, завершившихся с прошлого вызова
+  export function take_completed_transitions(system_id: number): Uint32Array;
+  
+  // Поиск объектов под курсором (CSS-пиксели); результат - от ближнего к дальнему
+  export class HitTestResult {
+    free(): void;
+    readonly ids: Uint32Array;
+    readonly depths: Float32Array;
+    readonly distances_px: Float32Array;
+  }
+  export function set_screen_size(system_id: number, width_px: number, height_px: number): OpResult;
+  export function hit_test(system_id: number, screen_x: number, screen_y: number, tolerance_px: number): HitTestResult | undefined;
+  
+  // Выделение объекта: флаг selected и пульсирующая подсветка highlights в данных объектов
+  export function select_object(system_id: number, object_id: number): OpResult;
+  export function clear_selection(system_id: number): OpResult;
+  export function get_selected_object(system_id: number): number | undefined;
+  
+  // Население: счетчики появления, исчезновения по причинам и респауна по типам объектов
+  export class PopulationStats {
+    free(): void;
+    readonly types: SpaceObjectType[];
+    readonly active: Uint32Array;
+    readonly waiting: Uint32Array;
+    readonly spawned: Uint32Array;
+    readonly respawned: Uint32Array;
+    readonly despawned_out_of_bounds: Uint32Array;
+    readonly despawned_lifetime: Uint32Array;
+    readonly despawned_opacity: Uint32Array;
+    readonly despawned_crossing: Uint32Array;
+  }
+  export function get_population_stats(system_id: number): PopulationStats | undefined;
+  // Готовность сцены для экрана загрузки (0..1): доля ожививших объектов среди запрошенных
+  export function get_scene_readiness(system_id: number): number | undefined;
+  
+  // Диагностика: частые сообщения выводятся не чаще раза в min_interval_seconds на ключ
+  export class DiagnosticsStats {
+    free(): void;
+    readonly keys: string[];
+    readonly suppressed: Uint32Array;
+    // Блокировки, восстановленные после паники (о каждой сообщается один раз)
+    readonly recovered_locks: string[];
+    readonly total_suppressed: number;
+  }
+  export function set_diagnostics_mode(enabled: boolean, min_interval_seconds: number): OpResult;
+  export function get_diagnostics_stats(): DiagnosticsStats;
+  export function reset_diagnostics(): void;
+  
+  // Свечение портала: агрегаты накопленной энергии ударов о видовую плоскость
+  export interface PortalGlowSummary {
+    readonly total_energy: number;
+    readonly peak_energy: number;
+    readonly hottest_u: number;
+    readonly hottest_v: number;
+  }
+  export function get_portal_glow(system_id: number): PortalGlowSummary | undefined;
+  export function clear_portal_glow(system_id: number): OpResult;
+  
+  // Пакетные запросы по точкам (x, y, z подряд); undefined - длина не кратна 3 или нет системы
+  export function check_points_in_space(system_id: number, points: Float32Array): Uint8Array | undefined;
+  export function check_points_in_view_frustum(system_id: number, points: Float32Array): Uint8Array | undefined;
+  export function calculate_distances_to_viewing_plane(system_id: number, points: Float32Array): Float32Array | undefined;
+  
+  // Видовые плоскости: 0 - основная; дополнительные активны одновременно, пересечение
+  // засчитывается ближайшей по ходу движения плоскости (при равенстве - с большим приоритетом)
+  export function add_viewing_plane(system_id: number, center_x: number, center_y: number, z: number, width: number, height: number, priority: number): number | undefined;
+  export function remove_viewing_plane(system_id: number, plane_index: number): OpResult;
+  export function set_viewing_plane_priority(system_id: number, plane_index: number, priority: number): OpResult;
+  export function get_viewing_plane_indices(system_id: number): Uint32Array;
+  
+  // Углы видовой плоскости (x, y, z подряд) в порядке UV (0,0), (1,0), (1,1), (0,1)
+  export function get_plane_corners(system_id: number, plane_index: number): Float32Array | undefined;
+  
+  // Стиль видовой плоскости: цвет, свечение края, пульсация и сдвиг градиента прокруткой (анимируется в Rust)
+  export interface PlaneStyleState {
+    readonly r: number;
+    readonly g: number;
+    readonly b: number;
+    readonly a: number;
+    readonly edge_glow: number;
+    readonly pulse: number;
+    readonly gradient_offset: number;
+  }
+  export function set_plane_style(system_id: number, plane_index: number, r: number, g: number, b: number, a: number, edge_glow: number, pulse_frequency: number, gradient_scroll_factor: number): OpResult;
+  export function set_scroll_offset(system_id: number, offset: number): OpResult;
+  export function get_plane_style(system_id: number, plane_index: number): PlaneStyleState | undefined;
+  
+  // Плавная очистка сцены: остановка появлений и угасание объектов за fade_seconds;
+  // по завершении объекты удаляются, а ID очистки попадает в take_completed_despawns.
+  // Появление возобновляется вызовом spawn_neon_comets
+  export function despawn_all(system_id: number, fade_seconds: number): number | undefined;
+  export function get_despawn_progress(system_id: number): number | undefined;
+  export function take_completed_despawns(system_id: number): Uint32Array;
+  
+  // Энергетические сферы: пульсация и вихрь частиц (vortex_particle_count частиц на сферу,
+  // vortex_positions - (x, y, z) частиц, сферы подряд)
+  // Не больше MAX_SPHERES_PER_SPAWN (64) за вызов, иначе InvalidArgument
+  export function spawn_energy_spheres(system_id: number, count: number): OpResult;
+  export function get_energy_spheres(system_id: number): EnergySphereDataArray | undefined;
+  export class EnergySphereDataArray {
+    free(): void;
+    readonly ids: Uint32Array;
+    readonly positions: Float32Array;
+    readonly radii: Float32Array;
+    readonly scales: Float32Array;
+    readonly opacities: Float32Array;
+    readonly colors: Float32Array;
+    readonly pulses: Float32Array;
+    readonly vortex_positions: Float32Array;
+    readonly vortex_particle_count: number;
+    readonly emissive: Float32Array;
+    readonly motion_vectors: Float32Array;
+    // World-space bounds including the vortex ring: AABB min/max (3 floats each), sphere (cx, cy, cz, r)
+    readonly bounds_min: Float32Array;
+    readonly bounds_max: Float32Array;
+    readonly bounding_spheres: Float32Array;
+    readonly color_seeds: Uint32Array;
+    readonly palette_indices: Uint8Array;
+  }
+  
+  // Поле искажения ("гравитационная линза" энергетических сфер): смещение точки и
+  // сетка columns x rows по видовой плоскости (построчно от UV (0, 0), по 3 float на ячейку,
+  // не больше 128 по каждой оси)
+  export function sample_distortion(system_id: number, x: number, y: number, z: number): Vec3Wrapper | undefined;
+  export function sample_distortion_grid(system_id: number, plane_index: number, columns: number, rows: number): Float32Array | undefined;
+  
+  // События жизненного цикла объектов (в порядке возникновения; очередь до 4096 событий,
+  // при переполнении отбрасываются самые старые - их число в dropped)
+  export enum LifecycleEventKind {
+    Spawned = 0,
+    Activated = 1,
+    CrossedPlane = 2,
+    Despawned = 3,
+  }
+  // Respawn - активация после респауна; Entry/Exit - направление пересечения;
+  // OutOfBounds..Crossing - причина исчезновения
+  export enum LifecycleReason {
+    None = 0,
+    Respawn = 1,
+    Entry = 2,
+    Exit = 3,
+    OutOfBounds = 4,
+    Lifetime = 5,
+    Opacity = 6,
+    Crossing = 7,
+  }
+  export class LifecycleEventArray {
+    free(): void;
+    readonly kinds: LifecycleEventKind[];
+    readonly ids: Uint32Array;
+    readonly types: SpaceObjectType[];
+    readonly reasons: LifecycleReason[];
+    readonly dropped: number;
+  }
+  export function take_lifecycle_events(system_id: number): LifecycleEventArray | undefined;
+  
+  // Пошаговое наполнение системы: create_system_async возвращает пустую систему,
+  // continue_init создает объекты в пределах budget_ms (минимум один за вызов,
+  // нечисловой бюджет - ноль); счетчики ограничены 1024 кометами и 256 сферами
+  // и возвращает прогресс 0..1
+  export function create_system_async(viewport_size_percent: number, fov_degrees: number, comet_count: number, sphere_count: number): number;
+  export function continue_init(system_id: number, budget_ms: number): number | undefined;
+  export function get_init_progress(system_id: number): number | undefined;
+  
+  // Потеря WebGL-контекста: минимальное состояние для запасного рендера
+  // (objects - x, y, z, scale на объект в порядке ids) и полный снимок дельт
+  // при следующем get_object_deltas после mark_all_dirty
+  export class MinimalState {
+    free(): void;
+    readonly ids: Uint32Array;
+    readonly objects: Float32Array;
+  }
+  export function get_minimal_state(system_id: number): MinimalState | undefined;
+  export function mark_all_dirty(system_id: number): OpResult;
+  
+  // Физический мир rapier (шаг мира делает хост)
+  export function init_world(): number;
+  export function step_simulation(world_id: number, dt: number): OpResult;
+  
+  // Обломки ударов: входы в основную плоскость быстрее min_speed порождают тела
+  // в физическом мире, которые отскакивают от коллайдера плоскости и гаснут за 2 секунды
+  export function enable_impact_debris(system_id: number, world_id: number, min_speed: number): OpResult;
+  export function disable_impact_debris(system_id: number): OpResult;
+  export function get_impact_debris(system_id: number): DebrisDataArray | undefined;
+  export class DebrisDataArray {
+    free(): void;
+    readonly positions: Float32Array;
+    readonly radii: Float32Array;
+    readonly opacities: Float32Array;
+  }
+  
+  // Physics colliders of viewing planes
+  export enum PhysicsSyncMode {
+    Static = 0,
+    Kinematic = 1,
+  }
+  export function sync_plane_to_physics(system_id: number, plane_index: number, world_id: number, mode: PhysicsSyncMode): OpResult;
+  export function unsync_plane_from_physics(system_id: number, plane_index: number): OpResult;
+  export function get_plane_collider_center(system_id: number, plane_index: number): Float32Array | undefined;
+
+  // Sleeping of distant hidden objects
+  export function set_object_sleeping(system_id: number, enabled: boolean, interval: number, distance: number): OpResult;
+  export function get_sleep_skipped_total(system_id: number): number | undefined;
+
+  // Packed comet export (f16 / unorm8)
+  export enum PackedColorFormat {
+    Half = 0,
+    Unorm8 = 1,
+  }
+  export interface PackedCometArray {
+    readonly ids: Uint32Array;
+    readonly positions: Uint16Array;
+    readonly scales: Uint16Array;
+    readonly opacities: Uint8Array;
+    readonly colors_half: Uint16Array;
+    readonly colors_unorm8: Uint8Array;
+    readonly color_format: PackedColorFormat;
+    free(): void;
+  }
+  export function get_packed_neon_comets(system_id: number, color_format: PackedColorFormat): PackedCometArray | undefined;
+
+  // Morton ordering of object storage
+  export interface MortonStats {
+    readonly reorders: number;
+    readonly last_objects: number;
+    readonly last_cost_ms: number;
+    readonly total_cost_ms: number;
+  }
+  export function set_morton_ordering(system_id: number, enabled: boolean, interval: number): OpResult;
+  export function get_morton_stats(system_id: number): MortonStats | undefined;
+
+  // Internal requestAnimationFrame driver
+  export function start_frame_driver(system_ids: Uint32Array, render: (dt: number, comets: Array<CometDataArray | undefined>) => void): OpResult;
+  export function stop_frame_driver(): OpResult;
+  export function is_frame_driver_running(): boolean;
+  export function get_frame_driver_frames(): number;
+
+  // Idle throttling
+  export function notify_user_idle(system_id: number, idle: boolean): OpResult;
+  export function is_user_idle(system_id: number): boolean | undefined;
+
+  // Power profiles
+  export enum PowerProfile {
+    Performance = 0,
+    Balanced = 1,
+    Saver = 2,
+  }
+  export interface PowerBudget {
+    readonly particle_scale: number;
+    readonly substeps: number;
+    readonly tail_scale: number;
+  }
+  export function set_power_profile(system_id: number, profile: PowerProfile): OpResult;
+  export function get_power_profile(system_id: number): PowerProfile | undefined;
+  export function get_power_budget(system_id: number): PowerBudget | undefined;
+
+  // Color spaces of exported color buffers (colors are authored in sRGB)
+  export enum ColorSpace {
+    Srgb = 0,
+    Linear = 1,
+  }
+  export function set_export_color_space(system_id: number, color_space: ColorSpace): OpResult;
+  export function get_export_color_space(system_id: number): ColorSpace | undefined;
+  export function convert_colors(colors: Float32Array, from: ColorSpace, to: ColorSpace): Float32Array;
+
+  // Palettes for palette_indices of comets and energy spheres ([r, g, b] per entry, sRGB)
+  export function get_comet_palette(): Float32Array;
+  export function get_sphere_palette(): Float32Array;
+
+  // Comet tail particles in a shared budgeted pool
+  export interface TailParticleArray {
+    readonly comet_ids: Uint32Array;
+    readonly range_starts: Uint32Array;
+    readonly range_counts: Uint32Array;
+    readonly positions: Float32Array;
+    readonly opacities: Float32Array;
+    readonly starved: number;
+    free(): void;
+  }
+  export function set_tail_particle_budget(system_id: number, budget: number): OpResult;
+  export function set_tail_field(system_id: number, turbulence: number, frequency: number, wind: Vec3Wrapper): OpResult;
+  export function set_comet_tail_turbulence(system_id: number, comet_id: number, strength: number): OpResult;
+  export function get_tail_particles(system_id: number): TailParticleArray | undefined;
+
+  // Global particle budget shared by particle subsystems
+  export enum ParticleSubsystem {
+    Tails = 0,
+    Vortices = 1,
+    Effects = 2,
+    Debris = 3,
+  }
+  export interface ParticleBudgetStats {
+    readonly total: number | undefined;
+    readonly demands: Uint32Array;
+    readonly allocations: Uint32Array;
+    free(): void;
+  }
+  export function set_particle_budget(system_id: number, total?: number): OpResult;
+  export function set_particle_priority(system_id: number, subsystem: ParticleSubsystem, priority: number): OpResult;
+  export function get_particle_budget(system_id: number): ParticleBudgetStats | undefined;
+
+  // Near-miss events: comets passing close to the observer without crossing a plane
+  export interface NearMissEventArray {
+    readonly comet_ids: Uint32Array;
+    readonly points: Float32Array;
+    readonly distances: Float32Array;
+    readonly relative_speeds: Float32Array;
+    readonly time_offsets: Float32Array;
+    readonly dropped: number;
+    free(): void;
+  }
+  export function set_near_miss_distance(system_id: number, distance?: number): OpResult;
+  export function take_near_miss_events(system_id: number): NearMissEventArray | undefined;
+
+  // Scene sync (host / mirror)
+  export function encode_sync_packet(system_id: number, since_frame: number): Uint8Array | undefined;
+  export function create_scene_mirror(delay: number): number;
+  export function destroy_scene_mirror(mirror_id: number): OpResult;
+  export function apply_sync_packet(mirror_id: number, packet: Uint8Array): OpResult;
+  export function update_scene_mirror(mirror_id: number, dt: number): OpResult;
+  export function get_scene_mirror_state(mirror_id: number): MirrorState | undefined;
+  export function get_scene_mirror_frame(mirror_id: number): number | undefined;
+
+  export class MirrorState {
+    free(): void;
+    readonly ids: Uint32Array;
+    readonly types: SpaceObjectType[];
+    readonly positions: Float32Array;
+    readonly opacities: Float32Array;
+    readonly sizes: Float32Array;
+    readonly playback_time: number | undefined;
+    readonly needs_keyframe: boolean;
+  }
+
+  // Input command queue (JSON commands applied at step boundaries):
+  // spawn, attract, palette, rotate (поворот привязанного гиперкуба, angles: [xy, xz, xw, yz, yw, zw])
+  export function enqueue_command(system_id: number, json: string): OpResult;
+  export function get_queued_command_count(system_id: number): number | undefined;
+  export function take_command_log(system_id: number): string[];
+
+  // Occupancy of the volume in front of a viewing plane (octant bits: x | y << 1 | z << 2)
+  export function get_plane_occupancy(system_id: number, plane_index: number): PlaneOccupancy | undefined;
+
+  export class PlaneOccupancy {
+    free(): void;
+    readonly count: number;
+    readonly octant_counts: Uint32Array;
+    readonly octant_densities: Float32Array;
+    readonly least_occupied_octant: number;
+    readonly min: Float32Array;
+    readonly max: Float32Array;
+  }
+
+  // Spawn spacing (minimum projected distance between spawns, fraction of viewport height)
+  export function set_spawn_separation(system_id: number, separation?: number): OpResult;
+  export function get_spawn_separation(system_id: number): number | undefined;
+
+  // Spawn density map over the far-plane spawn zone (row-major weights, row 0 = top)
+  export function set_spawn_density_map(system_id: number, width: number, height: number, weights: Float32Array): OpResult;
+  export function clear_spawn_density_map(system_id: number): OpResult;
+  export function has_spawn_density_map(system_id: number): boolean;
+
+  // Protected screen rect (viewport fractions, y down; trajectories re-rolled on spawn)
+  export function set_protected_screen_rect(system_id: number, x: number, y: number, w: number, h: number, near: number, far: number): OpResult;
+  export function clear_protected_screen_rect(system_id: number): OpResult;
+  export function crosses_protected_rect(system_id: number, object_id: number): boolean | undefined;
+
+  // Plane-crossing statistics over a sliding window of simulation time
+  // (0.5 s buckets, up to 120 s); UV is in the crossed plane's coordinates
+  export class CrossingStats {
+    free(): void;
+    readonly window: number;
+    readonly count: number;
+    readonly entries: number;
+    readonly exits: number;
+    readonly rate: number;
+    readonly mean_speed: number;
+    // Hottest UV cell (u0, v0, u1, v1); empty when there were no crossings
+    readonly hottest_region: Float32Array;
+    readonly hottest_count: number;
+    // heat_grid x heat_grid crossing counts, row-major from UV (0, 0)
+    readonly heat: Uint32Array;
+    readonly heat_grid: number;
+  }
+  export function get_crossing_stats(system_id: number, window_seconds: number): CrossingStats | undefined;
+  
+  // Batched crossing export for analytics: every interval_seconds of simulation time the
+  // pending crossings become one compact JSON array string, e.g.
+  // [{"t":12.345,"id":17,"obj":4,"type":"NeonComet","plane":0,"dir":"entry","uv":[0.412,0.733],"speed":31.25}]
+  // Empty intervals produce no batch; the host POSTs the strings itself
+  export function set_analytics_batch_interval(system_id: number, interval_seconds?: number): OpResult;
+  export function get_analytics_batch_interval(system_id: number): number | undefined;
+  export function flush_analytics_batch(system_id: number): OpResult;
+  export function take_analytics_batches(system_id: number): string[];
+  
+  // Runtime subsystem toggles for A/B testing scene components. A disabled subsystem is paused,
+  // hidden and skips its per-frame passes: "comets", "crystals", "spheres" (objects are parked and
+  // resume unchanged), "particles" (tails, impact effects, vortices), "physics" (impact debris and
+  // plane colliders), "intersections" (plane crossings and everything driven by them)
+  export function set_subsystem_enabled(system_id: number, name: string, enabled: boolean): OpResult;
+  export function is_subsystem_enabled(system_id: number, name: string): boolean | undefined;
   
   // Neon Comets
-  export function spawn_neon_comets(system_id: number, count: number): boolean;
+  export function spawn_neon_comets(system_id: number, count: number): OpResult;
   export function process_neon_comet_spawns(dt: number): number;
+  export function presimulate(system_id: number, seconds: number): number;
   export function get_active_neon_comets_count(system_id: number): number;
+  export function get_spawn_queue_stats(): SpawnQueueStats;
+  
+  // Spawn scheduler stats (shared queue, per-system quotas and a global cap)
+  export class SpawnQueueStats {
+    free(): void;
+    readonly total_pending: number;
+    readonly system_ids: Uint32Array;
+    readonly pending_per_system: Uint32Array;
+    readonly spawned_total: number;
+    readonly rejected_total: number;
+    readonly deferred_last_frame: number;
+    readonly global_cap: number;
+    readonly per_system_quota: number;
+  }
   
   // CometDataArray interface - can be accessed either via properties or getter functions
   export interface CometDataArray {
@@ -26,7 +828,50 @@ declare module '@wasm/hypercube_wasm' {
     colors: number[] | (() => number[]);
     tail_lengths: number[] | (() => number[]);
     glow_intensities: number[] | (() => number[]);
+    occluded: Uint8Array | (() => Uint8Array);
+    selected: Uint8Array | (() => Uint8Array);
+    highlights: number[] | (() => number[]);
+    emissive: number[] | (() => number[]);
+    // Screen-space motion over the last step (UV, y down), 2 floats per comet
+    motion_vectors: number[] | (() => number[]);
+    // World-space bounds including the tail: AABB min/max (3 floats each), sphere (cx, cy, cz, r)
+    bounds_min: number[] | (() => number[]);
+    bounds_max: number[] | (() => number[]);
+    bounding_spheres: number[] | (() => number[]);
+    color_seeds: Uint32Array | (() => Uint32Array);
+    palette_indices: Uint8Array | (() => Uint8Array);
   }
   
   export function get_visible_neon_comets(system_id: number): CometDataArray | null;
+  
+  // 4D шум (OpenSimplex), согласованный с симуляцией
+  export class Noise4D {
+    constructor(seed: number);
+    readonly seed: number;
+    sample(x: number, y: number, z: number, w: number): number;
+    sample_batch(points: Float64Array): Float64Array;
+    // octaves ограничено MAX_FRACTAL_OCTAVES (8)
+    fractal(x: number, y: number, z: number, w: number, octaves: number): number;
+    free(): void;
+  }
+  
+  export function get_noise_seed(): number;
+  export function noise4d_sample(x: number, y: number, z: number, w: number): number;
+  export function noise4d_sample_batch(points: Float64Array): Float64Array;
+  export function nebula_density(x: number, y: number, z: number, time: number): number;
+  
+  // Модуляция сцены гиперкубом
+  export interface SceneModulation {
+    spawn_rate: number;
+    palette_t: number;
+    plane_glow: number;
+  }
+  
+  // Привязка - снимок состояния гиперкуба до unbind: переприменяется каждый шаг, в том числе к респаунящимся кометам.
+  // После поворота гиперкуба вызывайте bind_hypercube_to_scene заново (раз в кадр, до update_space_object_system)
+  export function bind_hypercube_to_scene(system_id: number, hypercube: unknown, w_camera: number): OpResult;
+  export function unbind_hypercube_from_scene(system_id: number): OpResult;
+  export function get_scene_modulation(system_id: number): SceneModulation | undefined;
+  // Копия привязанного гиперкуба с поворотами команд rotate (undefined - не привязан)
+  export function get_bound_hypercube(system_id: number): unknown;
 } 
\ No newline at end of file
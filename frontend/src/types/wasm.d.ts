@@ -28,6 +28,22 @@ declare module 'hypercube-wasm' {
     rotate(xy_angle: number, xz_angle: number, xw_angle: number, yz_angle: number, yw_angle: number, zw_angle: number): void;
     get_projected_vertices(w_camera: number): number[];
     get_edges(): Uint32Array;
+    project_schlegel(cell_index: number): Float64Array | undefined;
+    get_projected_cell_volumes(w_camera: number): Float64Array;
+    // segments ограничено MAX_EDGE_SEGMENTS (256)
+    get_projected_edges_subdivided(segments: number, w_camera: number): Float64Array;
+    compute_shading(light_dir4: Float64Array, w_camera: number): HypercubeShading | undefined;
+    apply_drag_rotation(dx: number, dy: number, modifier: boolean): void;
+    update_inertia(dt: number): boolean;
+    stop_inertia(): void;
+  }
+  
+  // 4D затенение гиперкуба
+  export interface HypercubeShading {
+    readonly vertex_intensities: Float32Array;
+    readonly vertex_fog: Float32Array;
+    readonly face_indices: Uint32Array;
+    readonly face_intensities: Float32Array;
   }
   
   // Функции для инициализации и работы с физикой
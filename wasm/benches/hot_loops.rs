@@ -0,0 +1,42 @@
+// Бенчмарки горячих циклов симуляции. Используют только чистые Rust-функции,
+// поэтому запускаются нативно: `cargo bench --bench hot_loops`
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use glam::Vec3;
+use hypercube_wasm::{collect_visible_neon_comets, spawn_neon_comet, SpaceDefinition, SpaceObjectSystem};
+
+const COMET_COUNT: usize = 200;
+
+fn populated_system() -> SpaceObjectSystem {
+    let mut system = SpaceObjectSystem::default();
+    for _ in 0..COMET_COUNT {
+        spawn_neon_comet(&mut system);
+    }
+    system
+}
+
+fn bench_space_object_update(c: &mut Criterion) {
+    let mut system = populated_system();
+    c.bench_function("space_object_update", |b| {
+        b.iter(|| system.update(black_box(1.0 / 60.0)))
+    });
+}
+
+fn bench_visible_comets_export(c: &mut Criterion) {
+    let system = populated_system();
+    c.bench_function("visible_comets_export", |b| {
+        b.iter(|| collect_visible_neon_comets(black_box(&system)))
+    });
+}
+
+fn bench_plane_crossing(c: &mut Criterion) {
+    let space = SpaceDefinition::new();
+    let from = Vec3::new(3.0, -4.0, 5.0);
+    let to = Vec3::new(2.5, -3.5, -5.0);
+    c.bench_function("plane_crossing", |b| {
+        b.iter(|| space.segment_plane_crossing(black_box(&from), black_box(&to)))
+    });
+}
+
+criterion_group!(benches, bench_space_object_update, bench_visible_comets_export, bench_plane_crossing);
+criterion_main!(benches);
@@ -0,0 +1,195 @@
+/*
+ * analytics.rs
+ *
+ * Пакетная выгрузка пересечений для аналитики. Отправлять каждое событие
+ * из JS - это вызов через границу wasm и запрос на каждое пересечение;
+ * вместо этого система (если выгрузка включена) копит краткие записи
+ * пересечений и раз в interval секунд времени симуляции собирает из них
+ * готовый компактный JSON-массив. Хосту остается забрать готовые пакеты
+ * (take_analytics_batches) и отправить их - сам крейт сеть не трогает.
+ *
+ * Запись пересечения:
+ *   {"t": 12.345, "id": 17, "obj": 4, "type": "NeonComet", "plane": 0,
+ *    "dir": "entry", "uv": [0.412, 0.733], "speed": 31.25}
+ * t - время пересечения в секундах от включения выгрузки, uv - в
+ * координатах пересеченной плоскости. Числа округлены (время и UV до
+ * тысячных, скорость до сотых), чтобы пакет был короче.
+ *
+ * Пустые интервалы пакетов не дают. Пакет, набравший MAX_BATCH_RECORDS
+ * пересечений, собирается досрочно, и следующий интервал отсчитывается с
+ * этого момента - всплеск пересечений не теряется. Готовых пакетов хранится
+ * не больше MAX_ANALYTICS_BATCHES - если хост их не забирает, старые
+ * вытесняются.
+ */
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use glam::Vec2;
+
+use crate::errors::OpResult;
+use crate::space_core::CrossingType;
+use crate::space_objects::{PlaneCrossing, SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+
+// Пределы пакета и очереди готовых пакетов
+pub const MAX_BATCH_RECORDS: usize = 1024;
+pub const MAX_ANALYTICS_BATCHES: usize = 64;
+
+// Короче интервал не бывает (пакет на каждый кадр теряет смысл)
+const MIN_BATCH_INTERVAL: f32 = 0.1;
+
+fn round_to(value: f32, scale: f32) -> f32 {
+    (value * scale).round() / scale
+}
+
+/// Краткая запись пересечения
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct CrossingRecord {
+    t: f64,
+    id: u32,
+    obj: usize,
+    #[serde(rename = "type")]
+    object_type: SpaceObjectType,
+    plane: usize,
+    dir: &'static str,
+    uv: [f32; 2],
+    speed: f32,
+}
+
+/// Накопление пересечений и готовые пакеты
+#[derive(Clone, Debug, Default)]
+pub struct AnalyticsBatcher {
+    interval: Option<f32>,
+    time: f64,
+    batch_start: f64,
+    pending: Vec<CrossingRecord>,
+    ready: VecDeque<String>,
+}
+
+impl AnalyticsBatcher {
+    pub fn interval(&self) -> Option<f32> {
+        self.interval
+    }
+
+    // Включить выгрузку с интервалом или выключить (None); накопленное сбрасывается
+    pub fn set_interval(&mut self, interval: Option<f32>) {
+        *self = Self {
+            interval,
+            ..Self::default()
+        };
+    }
+
+    // Продвинуть время на dt и добавить пересечения кадра (с UV их плоскостей)
+    pub fn record<'a>(&mut self, dt: f32, crossings: impl Iterator<Item = (&'a PlaneCrossing, Vec2)>) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+
+        for (crossing, uv) in crossings {
+            if self.pending.len() >= MAX_BATCH_RECORDS {
+                self.flush();
+            }
+            let time = self.time + dt.max(0.0) as f64 + crossing.time_offset(dt) as f64;
+            self.pending.push(CrossingRecord {
+                t: (time * 1000.0).round() / 1000.0,
+                id: crossing.id,
+                obj: crossing.object_id,
+                object_type: crossing.object_type,
+                plane: crossing.plane,
+                dir: match crossing.crossing_type {
+                    CrossingType::Entry => "entry",
+                    CrossingType::Exit => "exit",
+                },
+                uv: [round_to(uv.x, 1000.0), round_to(uv.y, 1000.0)],
+                speed: round_to(crossing.velocity.length(), 100.0),
+            });
+        }
+
+        self.time += dt.max(0.0) as f64;
+        if self.time - self.batch_start >= interval as f64 {
+            self.flush();
+        }
+    }
+
+    // Собрать пакет из накопленных записей досрочно (например, при закрытии страницы)
+    pub fn flush(&mut self) {
+        self.batch_start = self.time;
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let records = std::mem::take(&mut self.pending);
+        if let Ok(batch) = serde_json::to_string(&records) {
+            if self.ready.len() >= MAX_ANALYTICS_BATCHES {
+                self.ready.pop_front();
+            }
+            self.ready.push_back(batch);
+        }
+    }
+
+    pub fn take_batches(&mut self) -> Vec<String> {
+        self.ready.drain(..).collect()
+    }
+
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    // Память накопленных записей и готовых пакетов (текст пакетов не учитывается)
+    pub fn memory_usage(&self) -> (usize, usize, usize, usize) {
+        let record = std::mem::size_of::<CrossingRecord>();
+        let batch = std::mem::size_of::<String>();
+        (
+            self.pending.len() + self.ready.len(),
+            self.pending.capacity() + self.ready.capacity(),
+            self.pending.len() * record + self.ready.len() * batch,
+            self.pending.capacity() * record + self.ready.capacity() * batch,
+        )
+    }
+}
+
+// Включить пакетную выгрузку пересечений с интервалом interval_seconds
+// времени симуляции; undefined - выключить (накопленное сбрасывается)
+#[wasm_bindgen]
+pub fn set_analytics_batch_interval(system_id: usize, interval_seconds: Option<f32>) -> OpResult {
+    if interval_seconds.is_some_and(|interval| !interval.is_finite() || interval < MIN_BATCH_INTERVAL) {
+        return OpResult::invalid_argument(format!("batch interval must be finite and at least {} s", MIN_BATCH_INTERVAL));
+    }
+
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.analytics.set_interval(interval_seconds);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_analytics_batch_interval(system_id: usize) -> Option<f32> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .and_then(|system| system.analytics.interval())
+}
+
+// Собрать пакет из накопленных пересечений, не дожидаясь конца интервала
+#[wasm_bindgen]
+pub fn flush_analytics_batch(system_id: usize) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.analytics.flush();
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Готовые пакеты (JSON-массивы записей пересечений) в порядке сборки
+#[wasm_bindgen]
+pub fn take_analytics_batches(system_id: usize) -> Vec<String> {
+    SPACE_OBJECT_SYSTEMS
+        .get_mut(&system_id)
+        .map(|mut system| system.analytics.take_batches())
+        .unwrap_or_default()
+}
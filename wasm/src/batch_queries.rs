@@ -0,0 +1,48 @@
+/*
+ * batch_queries.rs
+ *
+ * Пакетные геометрические запросы: JS передает точки одним Float32Array
+ * (x, y, z подряд) и получает результаты одним типизированным массивом,
+ * вместо вызова через границу wasm для каждого объекта.
+ *
+ * Длина массива точек должна быть кратна 3, иначе возвращается None.
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+
+use crate::space_core::SpaceDefinition;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+// Применить запрос к каждой точке пространства системы
+fn map_points<T>(system_id: usize, points: &[f32], query: impl Fn(&SpaceDefinition, &Vec3) -> T) -> Option<Vec<T>> {
+    if !points.len().is_multiple_of(3) {
+        return None;
+    }
+
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    Some(
+        points
+            .chunks_exact(3)
+            .map(|p| query(&system.space, &Vec3::new(p[0], p[1], p[2])))
+            .collect()
+    )
+}
+
+// 1 - точка внутри границ пространства, 0 - снаружи
+#[wasm_bindgen]
+pub fn check_points_in_space(system_id: usize, points: &[f32]) -> Option<Vec<u8>> {
+    map_points(system_id, points, |space, point| space.contains(point) as u8)
+}
+
+// 1 - точка в видимой области наблюдателя, 0 - нет
+#[wasm_bindgen]
+pub fn check_points_in_view_frustum(system_id: usize, points: &[f32]) -> Option<Vec<u8>> {
+    map_points(system_id, points, |space, point| space.is_in_view_frustum(point) as u8)
+}
+
+// Расстояния до видовой плоскости со знаком (положительные - за плоскостью)
+#[wasm_bindgen]
+pub fn calculate_distances_to_viewing_plane(system_id: usize, points: &[f32]) -> Option<Vec<f32>> {
+    map_points(system_id, points, |space, point| space.distance_to_viewing_plane(point))
+}
@@ -0,0 +1,83 @@
+/*
+ * color_space.rs
+ *
+ * Цветовые пространства экспортируемых цветов. Палитры комет и сфер, оттенки
+ * модификаторов и цвета, задаваемые из JS (set_color, spawn_comet_effect),
+ * задаются и хранятся в sRGB - так их подбирают дизайнеры. Рендерер с
+ * линейным освещением (three.js с ColorManagement, glTF) ждет линейные
+ * значения; без явного пространства гамма применялась дважды.
+ *
+ * Пространство экспорта задается для системы (set_export_color_space) и
+ * действует на все буферы цветов: кометы, энергетические сферы, эффекты
+ * ударов и упакованный экспорт. По умолчанию цвета отдаются в sRGB, как и
+ * раньше. Преобразования - точные кусочные функции IEC 61966-2-1.
+ */
+
+use wasm_bindgen::prelude::*;
+
+use crate::errors::OpResult;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+/// Цветовое пространство буферов цветов
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    #[default]
+    Srgb = 0,
+    Linear = 1,
+}
+
+// Компонента sRGB (0..1) -> линейная
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+// Линейная компонента -> sRGB
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+impl ColorSpace {
+    // Перевести цвет из sRGB (пространства хранения) в это пространство
+    pub fn encode(self, color: [f32; 3]) -> [f32; 3] {
+        match self {
+            ColorSpace::Srgb => color,
+            ColorSpace::Linear => color.map(srgb_to_linear),
+        }
+    }
+}
+
+// Пространство, в котором система отдает буферы цветов
+#[wasm_bindgen]
+pub fn set_export_color_space(system_id: usize, color_space: ColorSpace) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.export_color_space = color_space;
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_export_color_space(system_id: usize) -> Option<ColorSpace> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| system.export_color_space)
+}
+
+// Перевести плоский массив компонент из одного пространства в другое
+#[wasm_bindgen]
+pub fn convert_colors(colors: Vec<f32>, from: ColorSpace, to: ColorSpace) -> Vec<f32> {
+    match (from, to) {
+        (ColorSpace::Srgb, ColorSpace::Linear) => colors.into_iter().map(srgb_to_linear).collect(),
+        (ColorSpace::Linear, ColorSpace::Srgb) => colors.into_iter().map(linear_to_srgb).collect(),
+        _ => colors,
+    }
+}
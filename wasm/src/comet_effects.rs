@@ -0,0 +1,376 @@
+/*
+ * comet_effects.rs
+ *
+ * Эффекты ударов неоновых комет о видовую плоскость: вспышка, ударное
+ * кольцо и сноп частиц. Эффекты создаются проходом обновления системы
+ * из пересечений кадра (точка пересечения уже интерполирована внутри
+ * кадра, а возраст эффекта учитывает момент удара внутри кадра), стареют
+ * вместе с системой и выдаются в JS массивами.
+ *
+ * Хранилище - пул фиксированного размера: истекшие эффекты освобождают
+ * слоты для повторного использования, а при заполнении пула новый эффект
+ * занимает место самого "старого" (с наибольшей долей прожитой жизни).
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+
+use crate::color_space::ColorSpace;
+use crate::errors::OpResult;
+use crate::space_objects::{PlaneCrossing, SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+
+// Максимальное количество одновременно живущих эффектов системы
+pub const MAX_COMET_EFFECTS: usize = 256;
+
+// Время жизни эффектов (сек)
+const FLASH_LIFETIME: f32 = 0.15;
+const SHOCKWAVE_LIFETIME: f32 = 0.6;
+const SPRAY_LIFETIME: f32 = 0.9;
+
+// Конечный радиус эффекта на единицу размера кометы
+const EFFECT_RADIUS_PER_SIZE: f32 = 0.05;
+
+// Сила удара, при которой эффекты достигают полной интенсивности
+const FULL_INTENSITY_STRENGTH: f32 = 0.75;
+
+// Сила удара, начиная с которой добавляются кольцо и сноп частиц
+const SHOCKWAVE_MIN_STRENGTH: f32 = 0.25;
+const SPRAY_MIN_STRENGTH: f32 = 0.75;
+
+/// Вид эффекта удара
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CometEffectKind {
+    Flash = 0,         // Короткая вспышка в точке удара
+    ShockwaveRing = 1, // Расширяющееся кольцо по плоскости
+    ParticleSpray = 2, // Сноп частиц вдоль отраженного направления
+}
+
+impl CometEffectKind {
+    fn lifetime(self) -> f32 {
+        match self {
+            CometEffectKind::Flash => FLASH_LIFETIME,
+            CometEffectKind::ShockwaveRing => SHOCKWAVE_LIFETIME,
+            CometEffectKind::ParticleSpray => SPRAY_LIFETIME,
+        }
+    }
+}
+
+/// Один эффект удара
+#[derive(Clone, Copy, Debug)]
+pub struct CometEffect {
+    pub kind: CometEffectKind,
+    pub position: Vec3,
+    // Направление снопа частиц (для остальных видов - направление удара)
+    pub direction: Vec3,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    pub max_radius: f32,
+    pub age: f32,
+    pub lifetime: f32,
+}
+
+impl CometEffect {
+    pub fn new(kind: CometEffectKind, position: Vec3, direction: Vec3, color: [f32; 3], intensity: f32, max_radius: f32) -> Self {
+        Self {
+            kind,
+            position,
+            direction: direction.normalize_or_zero(),
+            color,
+            intensity: intensity.clamp(0.0, 1.0),
+            max_radius: max_radius.max(0.0),
+            age: 0.0,
+            lifetime: kind.lifetime(),
+        }
+    }
+
+    // Доля прожитой жизни (0..1)
+    pub fn progress(&self) -> f32 {
+        (self.age / self.lifetime).clamp(0.0, 1.0)
+    }
+
+    // Текущий радиус: быстрое расширение с замедлением к концу
+    pub fn radius(&self) -> f32 {
+        let p = self.progress();
+        self.max_radius * (1.0 - (1.0 - p) * (1.0 - p))
+    }
+
+    // Текущая непрозрачность: квадратичное затухание
+    pub fn opacity(&self) -> f32 {
+        let remaining = 1.0 - self.progress();
+        self.intensity * remaining * remaining
+    }
+
+    fn is_expired(&self) -> bool {
+        self.age >= self.lifetime
+    }
+}
+
+/// Пул эффектов системы с повторным использованием слотов
+#[derive(Clone, Debug, Default)]
+pub struct CometEffectPool {
+    slots: Vec<Option<CometEffect>>,
+    free: Vec<usize>,
+    recycled_total: usize,
+}
+
+impl CometEffectPool {
+    // Добавить эффект; при заполненном пуле вытесняет самый старый
+    pub fn spawn(&mut self, effect: CometEffect) {
+        if let Some(index) = self.free.pop() {
+            self.slots[index] = Some(effect);
+            return;
+        }
+
+        if self.slots.len() < MAX_COMET_EFFECTS {
+            self.slots.push(Some(effect));
+            return;
+        }
+
+        let oldest = self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| slot.map(|e| (index, e.progress())))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _)| index);
+        if let Some(index) = oldest {
+            self.slots[index] = Some(effect);
+            self.recycled_total += 1;
+        }
+    }
+
+    // Эффекты удара кометы: вспышка всегда, кольцо и сноп - для более сильных ударов.
+    // Возраст учитывает момент удара внутри кадра длительностью frame_dt
+    pub fn spawn_from_crossing(&mut self, crossing: &PlaneCrossing, color: [f32; 3], frame_dt: f32) {
+        let strength = crossing.impact_strength();
+        let intensity = (strength / FULL_INTENSITY_STRENGTH).min(1.0);
+        let max_radius = crossing.size * EFFECT_RADIUS_PER_SIZE;
+        let age = -crossing.time_offset(frame_dt);
+
+        // Сноп летит обратно от плоскости: отражаем скорость от грани пересечения
+        let reflected = crossing.velocity - 2.0 * crossing.velocity.dot(crossing.normal) * crossing.normal;
+
+        let mut kinds = vec![CometEffectKind::Flash];
+        if strength >= SHOCKWAVE_MIN_STRENGTH {
+            kinds.push(CometEffectKind::ShockwaveRing);
+        }
+        if strength >= SPRAY_MIN_STRENGTH {
+            kinds.push(CometEffectKind::ParticleSpray);
+        }
+
+        for kind in kinds {
+            let direction = if kind == CometEffectKind::ParticleSpray { reflected } else { crossing.velocity };
+            let mut effect = CometEffect::new(kind, crossing.position, direction, color, intensity, max_radius);
+            effect.age = age;
+            self.spawn(effect);
+        }
+    }
+
+    // Состарить эффекты на dt и освободить слоты истекших
+    pub fn update(&mut self, dt: f32) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if let Some(effect) = slot {
+                effect.age += dt;
+                if effect.is_expired() {
+                    *slot = None;
+                    self.free.push(index);
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.free.clear();
+    }
+
+    // Оставить не больше limit эффектов, вытеснив самые старые
+    pub fn truncate_oldest(&mut self, limit: usize) {
+        while self.len() > limit {
+            let oldest = self.slots
+                .iter()
+                .enumerate()
+                .filter_map(|(index, slot)| slot.map(|e| (index, e.progress())))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(index, _)| index);
+            let Some(index) = oldest else {
+                break;
+            };
+            self.slots[index] = None;
+            self.free.push(index);
+            self.recycled_total += 1;
+        }
+    }
+
+    // Живые эффекты
+    pub fn iter(&self) -> impl Iterator<Item = &CometEffect> {
+        self.slots.iter().flatten()
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Количество вытесненных до истечения эффектов за все время
+    pub fn recycled_total(&self) -> usize {
+        self.recycled_total
+    }
+
+    // Занятая и зарезервированная память слотов (байты)
+    pub fn memory_usage(&self) -> (usize, usize, usize, usize) {
+        let element = std::mem::size_of::<Option<CometEffect>>();
+        (self.slots.len(), self.slots.capacity(), self.slots.len() * element, self.slots.capacity() * element)
+    }
+}
+
+// Создавать эффекты только для ударов неоновых комет и вершин гиперкуба
+pub fn is_effect_source(crossing: &PlaneCrossing) -> bool {
+    matches!(crossing.object_type, SpaceObjectType::NeonComet | SpaceObjectType::Hypercube)
+}
+
+/// Живые эффекты системы
+#[wasm_bindgen]
+pub struct CometEffectArray {
+    kinds: Vec<CometEffectKind>,
+    positions: Vec<f32>,
+    directions: Vec<f32>,
+    colors: Vec<f32>,
+    radii: Vec<f32>,
+    opacities: Vec<f32>,
+    progress: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl CometEffectArray {
+    #[wasm_bindgen(getter)]
+    pub fn kinds(&self) -> Vec<CometEffectKind> {
+        self.kinds.clone()
+    }
+
+    // x, y, z на эффект
+    #[wasm_bindgen(getter)]
+    pub fn positions(&self) -> Vec<f32> {
+        self.positions.clone()
+    }
+
+    // Единичное направление x, y, z на эффект
+    #[wasm_bindgen(getter)]
+    pub fn directions(&self) -> Vec<f32> {
+        self.directions.clone()
+    }
+
+    // r, g, b на эффект
+    #[wasm_bindgen(getter)]
+    pub fn colors(&self) -> Vec<f32> {
+        self.colors.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn radii(&self) -> Vec<f32> {
+        self.radii.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn opacities(&self) -> Vec<f32> {
+        self.opacities.clone()
+    }
+
+    // Доля прожитой жизни (0..1)
+    #[wasm_bindgen(getter)]
+    pub fn progress(&self) -> Vec<f32> {
+        self.progress.clone()
+    }
+}
+
+// Собрать живые эффекты пула (цвета - в пространстве color_space)
+pub fn collect_comet_effects(pool: &CometEffectPool, color_space: ColorSpace) -> CometEffectArray {
+    let count = pool.len();
+    let mut result = CometEffectArray {
+        kinds: Vec::with_capacity(count),
+        positions: Vec::with_capacity(count * 3),
+        directions: Vec::with_capacity(count * 3),
+        colors: Vec::with_capacity(count * 3),
+        radii: Vec::with_capacity(count),
+        opacities: Vec::with_capacity(count),
+        progress: Vec::with_capacity(count),
+    };
+
+    for effect in pool.iter() {
+        result.kinds.push(effect.kind);
+        result.positions.extend_from_slice(&effect.position.to_array());
+        result.directions.extend_from_slice(&effect.direction.to_array());
+        result.colors.extend_from_slice(&color_space.encode(effect.color));
+        result.radii.push(effect.radius());
+        result.opacities.push(effect.opacity());
+        result.progress.push(effect.progress());
+    }
+
+    result
+}
+
+// Эффекты стареют вместе с системой (update_space_object_system). Этот вызов
+// нужен, когда симуляция стоит на паузе, а начатые эффекты должны доиграть
+#[wasm_bindgen]
+pub fn update_comet_effects(system_id: usize, dt: f32) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            if dt.is_finite() && dt > 0.0 {
+                system.effects.update(dt);
+            }
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_comet_effects(system_id: usize) -> Option<CometEffectArray> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| collect_comet_effects(&system.effects, system.export_color_space))
+}
+
+// Создать эффект вручную (например, по клику) в точке плоскости
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn create_comet_effect_at_intersection(
+    system_id: usize,
+    kind: CometEffectKind,
+    x: f32,
+    y: f32,
+    z: f32,
+    r: f32,
+    g: f32,
+    b: f32,
+    intensity: f32,
+    radius: f32
+) -> OpResult {
+    let position = Vec3::new(x, y, z);
+    if !position.is_finite() {
+        return OpResult::invalid_argument("effect position must be finite");
+    }
+
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            let color = [r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0)];
+            system.effects.spawn(CometEffect::new(kind, position, Vec3::NEG_Z, color, intensity, radius));
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn clear_comet_effects(system_id: usize) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.effects.clear();
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
@@ -0,0 +1,253 @@
+/*
+ * commands.rs
+ *
+ * Очередь входных команд. Внешнее управление сценой (UI, MIDI, удаленный
+ * пульт, сценарий) может приходить из нескольких источников в любой момент
+ * между кадрами; если применять его сразу, результат зависит от того, где
+ * именно между шагами симуляции оказался вызов. enqueue_command вместо этого
+ * кладет команду в очередь системы, а update выполняет ее на границе шага -
+ * до всех проходов кадра, номер которого указан в команде (или ближайшего).
+ *
+ * Команды выполняются в порядке (кадр, порядок поступления) и используют
+ * только генератор случайных чисел системы, поэтому журнал выполненных
+ * команд (take_command_log) вместе с seed системы воспроизводит сцену:
+ * достаточно подать записи журнала в enqueue_command новой системе с тем же
+ * seed и тем же шагом dt.
+ *
+ * Формат команды - JSON с полем "type" и необязательным "frame":
+ *   {"type": "spawn", "object_type": "NeonComet", "count": 3}
+ *   {"type": "attract", "point": [0, 0, -40], "strength": 5, "radius": 30}
+ *   {"type": "palette", "shift": 0.5}
+ *   {"type": "rotate", "angles": [0, 0, 0.1, 0, 0, 0]}
+ * rotate поворачивает привязанный к сцене гиперкуб (bind_hypercube_to_scene)
+ * в 4D плоскостях XY, XZ, XW, YZ, YW, ZW; без привязки команда ничего не
+ * делает. Видовые плоскости не поворачиваются: они всегда ориентированы
+ * вдоль осей.
+ */
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+
+use crate::energy_spheres::spawn_energy_sphere;
+use crate::errors::OpResult;
+use crate::neon_comets::spawn_neon_comet;
+use crate::space_objects::{SpaceObjectSystem, SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+
+// Ограничения очереди и журнала (команды сверх лимита отклоняются,
+// старые записи журнала вытесняются)
+pub const MAX_QUEUED_COMMANDS: usize = 1024;
+pub const MAX_COMMAND_LOG: usize = 4096;
+
+// Больше объектов за одну команду появления не создается
+const MAX_SPAWN_PER_COMMAND: usize = 256;
+
+/// Команда управления сценой
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Command {
+    // Создать count объектов сразу (без очереди появлений)
+    Spawn { object_type: SpaceObjectType, count: usize },
+    // Однократный толчок скоростей к точке: strength в центре, 0 на радиусе radius
+    Attract { point: [f32; 3], strength: f32, radius: f32 },
+    // Смещение палитры комет к следующему цвету (0.0 - 1.0)
+    Palette { shift: f32 },
+    // Поворот привязанного гиперкуба: углы в плоскостях XY, XZ, XW, YZ, YW, ZW (радианы)
+    Rotate { angles: [f64; 6] },
+}
+
+/// Команда в очереди: кадр, перед которым она выполняется, и порядковый номер
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QueuedCommand {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frame: Option<u32>,
+    #[serde(flatten)]
+    pub command: Command,
+}
+
+impl Command {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Command::Spawn { object_type, count } => {
+                if !matches!(object_type, SpaceObjectType::NeonComet | SpaceObjectType::EnergySphere) {
+                    return Err(format!("Spawning {:?} by command is not supported", object_type));
+                }
+                if *count > MAX_SPAWN_PER_COMMAND {
+                    return Err(format!("count must not exceed {}", MAX_SPAWN_PER_COMMAND));
+                }
+            }
+            Command::Attract { point, strength, radius } => {
+                if !point.iter().chain([strength, radius]).all(|value| value.is_finite()) || *radius <= 0.0 {
+                    return Err("attract requires a finite point, strength and a positive radius".to_string());
+                }
+            }
+            Command::Palette { shift } => {
+                if !shift.is_finite() {
+                    return Err("palette shift must be finite".to_string());
+                }
+            }
+            Command::Rotate { angles } => {
+                if !angles.iter().all(|angle| angle.is_finite()) {
+                    return Err("rotate angles must be finite".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Выполнить команду над системой
+    pub fn apply(&self, system: &mut SpaceObjectSystem) {
+        match *self {
+            Command::Spawn { object_type, count } => {
+                system.fade.resume_spawning();
+                for _ in 0..count {
+                    match object_type {
+                        SpaceObjectType::NeonComet => spawn_neon_comet(system),
+                        SpaceObjectType::EnergySphere => spawn_energy_sphere(system),
+                        _ => continue,
+                    };
+                }
+            }
+            Command::Attract { point, strength, radius } => {
+                let point = Vec3::from(point);
+                for obj in system.get_objects_mut().values_mut().flatten() {
+                    if obj.is_waiting_for_respawn() {
+                        continue;
+                    }
+                    let data = obj.get_data_mut();
+                    let offset = point - data.position;
+                    let distance = offset.length();
+                    if distance < radius {
+                        data.velocity += offset.normalize_or_zero() * strength * (1.0 - distance / radius);
+                    }
+                }
+            }
+            Command::Palette { shift } => {
+                // Цвет живущих комет сдвигает apply_scene_modulation сразу после команд шага
+                system.modulation.palette_t = shift.clamp(0.0, 1.0);
+            }
+            Command::Rotate { angles } => {
+                // Модуляцию от повернутого гиперкуба применяет apply_scene_modulation
+                system.rotate_bound_hypercube(angles);
+            }
+        }
+    }
+}
+
+/// Очередь команд системы и журнал выполненных
+#[derive(Clone, Debug, Default)]
+pub struct CommandQueue {
+    pending: Vec<(u32, u64, Command)>,
+    next_sequence: u64,
+    log: VecDeque<QueuedCommand>,
+}
+
+impl CommandQueue {
+    // Разобрать и поставить команду в очередь. Команда без кадра или с уже
+    // прошедшим кадром выполняется перед ближайшим шагом
+    pub fn enqueue(&mut self, json: &str, current_frame: u32) -> Result<(), String> {
+        let queued: QueuedCommand = serde_json::from_str(json).map_err(|e| format!("Invalid command: {}", e))?;
+        queued.command.validate()?;
+        if self.pending.len() >= MAX_QUEUED_COMMANDS {
+            return Err(format!("Command queue is full ({} commands)", MAX_QUEUED_COMMANDS));
+        }
+
+        let next_frame = current_frame.wrapping_add(1);
+        let frame = queued.frame.map_or(next_frame, |frame| frame.max(next_frame));
+        self.pending.push((frame, self.next_sequence, queued.command));
+        self.next_sequence += 1;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    // Извлечь команды, которые выполняются перед кадром frame, в порядке выполнения
+    pub fn take_due(&mut self, frame: u32) -> Vec<Command> {
+        let (mut due, pending): (Vec<_>, Vec<_>) = self.pending.drain(..).partition(|(at, _, _)| *at <= frame);
+        self.pending = pending;
+        due.sort_unstable_by_key(|(at, sequence, _)| (*at, *sequence));
+
+        due.into_iter()
+            .map(|(_, _, command)| {
+                if self.log.len() >= MAX_COMMAND_LOG {
+                    self.log.pop_front();
+                }
+                self.log.push_back(QueuedCommand { frame: Some(frame), command: command.clone() });
+                command
+            })
+            .collect()
+    }
+
+    // Забрать журнал выполненных команд
+    pub fn drain_log(&mut self) -> Vec<QueuedCommand> {
+        self.log.drain(..).collect()
+    }
+
+    // Память очереди и журнала команд (строки журнала не учитываются)
+    pub fn memory_usage(&self) -> (usize, usize, usize, usize) {
+        let pending = std::mem::size_of::<(u32, u64, Command)>();
+        let logged = std::mem::size_of::<QueuedCommand>();
+        (
+            self.pending.len() + self.log.len(),
+            self.pending.capacity() + self.log.capacity(),
+            self.pending.len() * pending + self.log.len() * logged,
+            self.pending.capacity() * pending + self.log.capacity() * logged,
+        )
+    }
+}
+
+// Выполнить команды, назначенные на следующий кадр системы (граница шага:
+// вызывается из update до проходов кадра)
+pub fn run_due_commands(system: &mut SpaceObjectSystem) {
+    if system.commands.is_empty() {
+        return;
+    }
+
+    let frame = system.frame.wrapping_add(1);
+    for command in system.commands.take_due(frame) {
+        command.apply(system);
+    }
+}
+
+// Поставить команду (JSON) в очередь системы
+#[wasm_bindgen]
+pub fn enqueue_command(system_id: usize, json: &str) -> OpResult {
+    let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) else {
+        return OpResult::system_not_found(system_id);
+    };
+
+    let frame = system.frame;
+    match system.commands.enqueue(json, frame) {
+        Ok(()) => OpResult::success(),
+        Err(message) => OpResult::invalid_argument(message),
+    }
+}
+
+// Количество команд, ожидающих своего кадра
+#[wasm_bindgen]
+pub fn get_queued_command_count(system_id: usize) -> Option<usize> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| system.commands.len())
+}
+
+// Журнал выполненных команд с номерами кадров (JSON-строки для enqueue_command)
+#[wasm_bindgen]
+pub fn take_command_log(system_id: usize) -> Vec<String> {
+    let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) else {
+        return Vec::new();
+    };
+
+    system
+        .commands
+        .drain_log()
+        .iter()
+        .filter_map(|entry| serde_json::to_string(entry).ok())
+        .collect()
+}
@@ -0,0 +1,182 @@
+/*
+ * crash.rs
+ *
+ * Отчет о панике для хоста. Хук паники (utils::set_panic_hook) собирает
+ * CrashInfo - текст паники, модуль и строку, где она произошла, и сводку
+ * состояния по подсистемам - и передает его зарегистрированному
+ * JS-обработчику, чтобы хост показал запасной интерфейс и отправил отчет.
+ * Последний отчет можно забрать и без обработчика (get_last_crash_info).
+ *
+ * Паника может произойти, пока удерживается блокировка системы или очереди
+ * появлений, поэтому сводка собирается только неблокирующими попытками:
+ * занятые системы считаются отдельно, а не ожидаются (иначе хук завис бы).
+ */
+
+use std::cell::RefCell;
+use std::panic::PanicHookInfo;
+use std::sync::Mutex;
+
+use dashmap::try_result::TryResult;
+use once_cell::sync::Lazy;
+use wasm_bindgen::prelude::*;
+
+use crate::errors::OpResult;
+use crate::neon_comets::try_pending_spawns;
+use crate::space_objects::{system_id_bound, SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+
+thread_local! {
+    static PANIC_CALLBACK: RefCell<Option<js_sys::Function>> = const { RefCell::new(None) };
+}
+
+static LAST_CRASH: Lazy<Mutex<Option<CrashInfo>>> = Lazy::new(|| Mutex::new(None));
+
+/// Отчет о панике: место и сводка состояния в момент паники
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default)]
+pub struct CrashInfo {
+    message: String,
+    module: String,
+    line: u32,
+    systems: u32,
+    locked_systems: u32,
+    comets: u32,
+    energy_spheres: u32,
+    effects: u32,
+    pending_spawns: Option<u32>,
+}
+
+#[wasm_bindgen]
+impl CrashInfo {
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    // Модуль крейта по файлу паники ("neon_comets"); пусто, если место неизвестно
+    #[wasm_bindgen(getter)]
+    pub fn module(&self) -> String {
+        self.module.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    // Системы объектов, включая занятые в момент паники
+    #[wasm_bindgen(getter)]
+    pub fn systems(&self) -> u32 {
+        self.systems
+    }
+
+    // Системы, заблокированные в момент паники (их объекты не посчитаны)
+    #[wasm_bindgen(getter)]
+    pub fn locked_systems(&self) -> u32 {
+        self.locked_systems
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn comets(&self) -> u32 {
+        self.comets
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn energy_spheres(&self) -> u32 {
+        self.energy_spheres
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn effects(&self) -> u32 {
+        self.effects
+    }
+
+    // Заявки в очереди появлений; undefined - очередь была заблокирована
+    #[wasm_bindgen(getter)]
+    pub fn pending_spawns(&self) -> Option<u32> {
+        self.pending_spawns
+    }
+}
+
+// Модуль крейта по пути файла: "src/neon_comets.rs" -> "neon_comets"
+fn module_name(file: &str) -> String {
+    file.rsplit(['/', '\\'])
+        .next()
+        .and_then(|name| name.strip_suffix(".rs"))
+        .unwrap_or(file)
+        .to_string()
+}
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    let payload = info.payload();
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+// Сводка состояния без ожидания блокировок
+fn summarize(info: &mut CrashInfo) {
+    for system_id in 0..system_id_bound() {
+        match SPACE_OBJECT_SYSTEMS.try_get(&system_id) {
+            TryResult::Present(system) => {
+                info.systems += 1;
+                let count = |object_type| system.get_objects().get(&object_type).map_or(0, |objects| objects.len() as u32);
+                info.comets += count(SpaceObjectType::NeonComet);
+                info.energy_spheres += count(SpaceObjectType::EnergySphere);
+                info.effects += system.effects.len() as u32;
+            }
+            TryResult::Locked => {
+                info.systems += 1;
+                info.locked_systems += 1;
+            }
+            TryResult::Absent => {}
+        }
+    }
+
+    info.pending_spawns = try_pending_spawns().map(|pending| pending as u32);
+}
+
+// Вызывается хуком паники после стандартного вывода
+pub(crate) fn report_panic(info: &PanicHookInfo) {
+    let mut crash = CrashInfo {
+        message: panic_message(info),
+        ..CrashInfo::default()
+    };
+    if let Some(location) = info.location() {
+        crash.module = module_name(location.file());
+        crash.line = location.line();
+    }
+    summarize(&mut crash);
+
+    if let Ok(mut last) = LAST_CRASH.try_lock() {
+        *last = Some(crash.clone());
+    }
+
+    // Обработчик вызывается вне заимствования: он может перерегистрировать себя
+    let callback = PANIC_CALLBACK.with(|callback| callback.borrow().clone());
+    if let Some(callback) = callback {
+        let _ = callback.call1(&JsValue::NULL, &JsValue::from(crash));
+    }
+}
+
+// Зарегистрировать обработчик паники: callback(info: CrashInfo).
+// Хук паники устанавливается при init()
+#[wasm_bindgen]
+pub fn set_panic_callback(callback: js_sys::Function) {
+    PANIC_CALLBACK.with(|slot| *slot.borrow_mut() = Some(callback));
+}
+
+#[wasm_bindgen]
+pub fn clear_panic_callback() -> OpResult {
+    match PANIC_CALLBACK.with(|slot| slot.borrow_mut().take()) {
+        Some(_) => OpResult::success(),
+        None => OpResult::not_found("no panic callback registered"),
+    }
+}
+
+// Отчет о последней панике (если она была)
+#[wasm_bindgen]
+pub fn get_last_crash_info() -> Option<CrashInfo> {
+    LAST_CRASH.try_lock().ok().and_then(|last| last.clone())
+}
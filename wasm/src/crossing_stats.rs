@@ -0,0 +1,232 @@
+/*
+ * crossing_stats.rs
+ *
+ * Сводная статистика пересечений видовых плоскостей за скользящее окно
+ * времени: число пересечений (входов и выходов), средняя скорость и самая
+ * "горячая" область плоскости. Дашбордам и адаптивной логике (например,
+ * подстройке плотности) нужна сводка, а не сырые события каждого кадра.
+ *
+ * Пересечения не хранятся поштучно: каждый кадр они складываются в
+ * корзину длиной STATS_BUCKET_SECONDS времени симуляции (счетчики, сумма
+ * скоростей и сетка HEAT_GRID x HEAT_GRID по UV плоскости). Корзины лежат в
+ * кольцевом буфере на MAX_STATS_WINDOW секунд, поэтому память и запрос не
+ * зависят от числа пересечений. Окно запроса округляется до целых корзин.
+ *
+ * UV - в координатах пересеченной плоскости (как в get_plane_crossings, v
+ * вверх); пересечения всех плоскостей попадают в одну сетку.
+ */
+
+use std::collections::VecDeque;
+
+use wasm_bindgen::prelude::*;
+use glam::Vec2;
+
+use crate::space_core::CrossingType;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+// Длительность одной корзины, секунды
+pub const STATS_BUCKET_SECONDS: f64 = 0.5;
+
+// Наибольшее окно статистики, секунды
+pub const MAX_STATS_WINDOW: f32 = 120.0;
+
+// Сетка областей плоскости для поиска самой горячей
+pub const HEAT_GRID: usize = 4;
+
+const MAX_STATS_BUCKETS: usize = (MAX_STATS_WINDOW as f64 / STATS_BUCKET_SECONDS) as usize;
+
+#[derive(Clone, Debug, Default)]
+struct StatsBucket {
+    index: u64,
+    entries: u32,
+    exits: u32,
+    speed_sum: f32,
+    heat: [u32; HEAT_GRID * HEAT_GRID],
+}
+
+/// Кольцевой буфер корзин статистики пересечений
+#[derive(Clone, Debug, Default)]
+pub struct CrossingStatsRing {
+    time: f64,
+    buckets: VecDeque<StatsBucket>,
+}
+
+/// Сводка за окно
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CrossingSummary {
+    pub window: f32,
+    pub entries: u32,
+    pub exits: u32,
+    pub mean_speed: f32,
+    pub heat: Vec<u32>,
+}
+
+impl CrossingSummary {
+    pub fn count(&self) -> u32 {
+        self.entries + self.exits
+    }
+
+    // Самая горячая ячейка сетки (при равенстве - первая по строкам)
+    pub fn hottest_cell(&self) -> Option<usize> {
+        let (cell, count) = self.heat.iter().enumerate().max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(&a.0)))?;
+        (*count > 0).then_some(cell)
+    }
+}
+
+// Ячейка сетки для UV (за пределами плоскости - крайняя)
+fn heat_cell(uv: Vec2) -> usize {
+    let cell = |value: f32| ((value * HEAT_GRID as f32) as isize).clamp(0, HEAT_GRID as isize - 1) as usize;
+    cell(uv.y) * HEAT_GRID + cell(uv.x)
+}
+
+// UV-прямоугольник ячейки сетки: (min, max)
+pub fn heat_cell_rect(cell: usize) -> (Vec2, Vec2) {
+    let size = 1.0 / HEAT_GRID as f32;
+    let min = Vec2::new((cell % HEAT_GRID) as f32, (cell / HEAT_GRID) as f32) * size;
+    (min, min + Vec2::splat(size))
+}
+
+impl CrossingStatsRing {
+    // Продвинуть время на dt и учесть пересечения кадра: (направление, скорость, UV)
+    pub fn record(&mut self, dt: f32, crossings: impl Iterator<Item = (CrossingType, f32, Vec2)>) {
+        self.time += dt.max(0.0) as f64;
+        let index = (self.time / STATS_BUCKET_SECONDS) as u64;
+        if self.buckets.back().is_none_or(|bucket| bucket.index != index) {
+            self.buckets.push_back(StatsBucket { index, ..StatsBucket::default() });
+        }
+        while self.buckets.front().is_some_and(|bucket| bucket.index + MAX_STATS_BUCKETS as u64 <= index) {
+            self.buckets.pop_front();
+        }
+
+        let Some(bucket) = self.buckets.back_mut() else {
+            return;
+        };
+        for (crossing_type, speed, uv) in crossings {
+            match crossing_type {
+                CrossingType::Entry => bucket.entries += 1,
+                CrossingType::Exit => bucket.exits += 1,
+            }
+            bucket.speed_sum += speed;
+            bucket.heat[heat_cell(uv)] += 1;
+        }
+    }
+
+    // Сводка за последние window секунд (не больше MAX_STATS_WINDOW)
+    pub fn summary(&self, window: f32) -> CrossingSummary {
+        let buckets = ((window.clamp(0.0, MAX_STATS_WINDOW) as f64 / STATS_BUCKET_SECONDS).ceil() as u64).max(1);
+        let current = (self.time / STATS_BUCKET_SECONDS) as u64;
+        let first = (current + 1).saturating_sub(buckets);
+
+        let mut summary = CrossingSummary {
+            window: (buckets as f64 * STATS_BUCKET_SECONDS).min(self.time.max(STATS_BUCKET_SECONDS)) as f32,
+            heat: vec![0; HEAT_GRID * HEAT_GRID],
+            ..CrossingSummary::default()
+        };
+        let mut speed_sum = 0.0;
+        for bucket in self.buckets.iter().filter(|bucket| bucket.index >= first) {
+            summary.entries += bucket.entries;
+            summary.exits += bucket.exits;
+            speed_sum += bucket.speed_sum;
+            for (total, count) in summary.heat.iter_mut().zip(bucket.heat) {
+                *total += count;
+            }
+        }
+        if summary.count() > 0 {
+            summary.mean_speed = speed_sum / summary.count() as f32;
+        }
+        summary
+    }
+
+    // Занятая и зарезервированная память корзин (байты)
+    pub fn memory_usage(&self) -> (usize, usize, usize, usize) {
+        let element = std::mem::size_of::<StatsBucket>();
+        (self.buckets.len(), self.buckets.capacity(), self.buckets.len() * element, self.buckets.capacity() * element)
+    }
+}
+
+/// Статистика пересечений за окно
+#[wasm_bindgen]
+pub struct CrossingStats {
+    summary: CrossingSummary,
+}
+
+#[wasm_bindgen]
+impl CrossingStats {
+    // Фактическая длина окна (округлена до корзин, не больше прошедшего времени), секунды
+    #[wasm_bindgen(getter)]
+    pub fn window(&self) -> f32 {
+        self.summary.window
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> u32 {
+        self.summary.count()
+    }
+
+    // Пересечения к наблюдателю
+    #[wasm_bindgen(getter)]
+    pub fn entries(&self) -> u32 {
+        self.summary.entries
+    }
+
+    // Пересечения от наблюдателя
+    #[wasm_bindgen(getter)]
+    pub fn exits(&self) -> u32 {
+        self.summary.exits
+    }
+
+    // Пересечений в секунду за окно
+    #[wasm_bindgen(getter)]
+    pub fn rate(&self) -> f32 {
+        self.summary.count() as f32 / self.summary.window.max(f32::EPSILON)
+    }
+
+    // Средняя скорость объектов в момент пересечения
+    #[wasm_bindgen(getter)]
+    pub fn mean_speed(&self) -> f32 {
+        self.summary.mean_speed
+    }
+
+    // Самая горячая область плоскости: UV-прямоугольник (u0, v0, u1, v1); пусто - пересечений не было
+    #[wasm_bindgen(getter)]
+    pub fn hottest_region(&self) -> Vec<f32> {
+        self.summary
+            .hottest_cell()
+            .map(|cell| {
+                let (min, max) = heat_cell_rect(cell);
+                vec![min.x, min.y, max.x, max.y]
+            })
+            .unwrap_or_default()
+    }
+
+    // Пересечений в самой горячей области
+    #[wasm_bindgen(getter)]
+    pub fn hottest_count(&self) -> u32 {
+        self.summary.hottest_cell().map(|cell| self.summary.heat[cell]).unwrap_or(0)
+    }
+
+    // Пересечения по областям: сетка heat_grid x heat_grid по строкам от UV (0, 0)
+    #[wasm_bindgen(getter)]
+    pub fn heat(&self) -> Vec<u32> {
+        self.summary.heat.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn heat_grid(&self) -> usize {
+        HEAT_GRID
+    }
+}
+
+// Сводка пересечений за последние window_seconds секунд времени симуляции
+// (не больше MAX_STATS_WINDOW); undefined - нет системы или окно не положительное
+#[wasm_bindgen]
+pub fn get_crossing_stats(system_id: usize, window_seconds: f32) -> Option<CrossingStats> {
+    if window_seconds.is_nan() || window_seconds <= 0.0 {
+        return None;
+    }
+
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    Some(CrossingStats {
+        summary: system.crossing_stats.summary(window_seconds),
+    })
+}
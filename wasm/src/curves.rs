@@ -0,0 +1,210 @@
+/*
+ * curves.rs
+ *
+ * Библиотека кривых анимации, общая для симуляции и UI. Кроме стандартных
+ * кривых сглаживания (EasingCurve) поддерживаются кубическая кривая Безье
+ * (как cubic-bezier в CSS), кривая Эрмита, экспонента и пружина.
+ *
+ * Кривые регистрируются в глобальном реестре и доступны из JS по
+ * дескриптору: evaluate_curve(handle, t). Дескрипторы 0..6 заняты
+ * встроенными кривыми и совпадают со значениями EasingCurve.
+ *
+ * В симуляции зарегистрированные кривые подключаются только к фазам
+ * OpacityProfile (set_*_curve_handle). Переходы, перелеты камеры, warp и
+ * рост комет используют фиксированные EasingCurve; для UI-анимаций JS
+ * вычисляет те же кривые через evaluate_curve.
+ */
+
+use std::sync::Mutex;
+
+use wasm_bindgen::prelude::*;
+use once_cell::sync::Lazy;
+
+use crate::easing::EasingCurve;
+use crate::utils::lock_or_recover;
+
+// Ограничение размера реестра: дескрипторы не освобождаются
+const MAX_CURVES: usize = 1024;
+
+// Точность и число итераций поиска параметра кривой Безье по t
+const BEZIER_EPSILON: f32 = 1e-5;
+const BEZIER_NEWTON_ITERATIONS: usize = 8;
+const BEZIER_BISECTION_ITERATIONS: usize = 24;
+
+// Предел крутизны экспоненты: exp(k) в f32 переполняется уже при k ~ 88
+const MAX_EXPONENTIAL_STEEPNESS: f32 = 30.0;
+
+/// Кривая анимации на отрезке t = 0..1
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Curve {
+    Easing(EasingCurve),
+    // Контрольные точки (x1, y1), (x2, y2); концы - (0, 0) и (1, 1)
+    CubicBezier(f32, f32, f32, f32),
+    // Значения p0, p1 и касательные m0, m1 на концах
+    Hermite(f32, f32, f32, f32),
+    // Крутизна k: k > 0 - медленный старт, k < 0 - быстрый, 0 - линейная.
+    // При регистрации ограничивается MAX_EXPONENTIAL_STEEPNESS
+    Exponential(f32),
+    // Отклик пружины на скачок: жесткость и коэффициент затухания. Время -
+    // сам параметр t = 0..1, поэтому жесткость задается в единицах отрезка
+    // (при жесткости в сотни пружина успокаивается к t = 1)
+    Spring(f32, f32),
+}
+
+impl Curve {
+    pub fn evaluate(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match *self {
+            Curve::Easing(curve) => curve.apply(t),
+            Curve::CubicBezier(x1, y1, x2, y2) => {
+                let s = bezier_parameter_for_x(x1, x2, t);
+                bezier_component(y1, y2, s)
+            }
+            Curve::Hermite(p0, p1, m0, m1) => {
+                let t2 = t * t;
+                let t3 = t2 * t;
+                (2.0 * t3 - 3.0 * t2 + 1.0) * p0
+                    + (t3 - 2.0 * t2 + t) * m0
+                    + (-2.0 * t3 + 3.0 * t2) * p1
+                    + (t3 - t2) * m1
+            }
+            Curve::Exponential(k) => {
+                if k.abs() < 1e-4 {
+                    t
+                } else {
+                    ((k * t).exp() - 1.0) / (k.exp() - 1.0)
+                }
+            }
+            Curve::Spring(stiffness, damping) => {
+                let omega = stiffness.max(1e-4).sqrt();
+                let zeta = damping.max(0.0) / (2.0 * omega);
+
+                if zeta < 1.0 {
+                    // Недодемпфированная пружина: затухающие колебания вокруг 1
+                    let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+                    let envelope = (-zeta * omega * t).exp();
+                    1.0 - envelope * ((omega_d * t).cos() + zeta * omega / omega_d * (omega_d * t).sin())
+                } else {
+                    // Критическое (и сильное) затухание - без перелета
+                    1.0 - (-omega * t).exp() * (1.0 + omega * t)
+                }
+            }
+        }
+    }
+}
+
+// Компонента кубической кривой Безье с концами 0 и 1
+fn bezier_component(c1: f32, c2: f32, s: f32) -> f32 {
+    let inv = 1.0 - s;
+    3.0 * inv * inv * s * c1 + 3.0 * inv * s * s * c2 + s * s * s
+}
+
+fn bezier_derivative(c1: f32, c2: f32, s: f32) -> f32 {
+    let inv = 1.0 - s;
+    3.0 * inv * inv * c1 + 6.0 * inv * s * (c2 - c1) + 3.0 * s * s * (1.0 - c2)
+}
+
+// Параметр кривой, при котором x(s) = x: метод Ньютона, при неудаче - деление пополам
+fn bezier_parameter_for_x(x1: f32, x2: f32, x: f32) -> f32 {
+    let mut s = x;
+    for _ in 0..BEZIER_NEWTON_ITERATIONS {
+        let error = bezier_component(x1, x2, s) - x;
+        if error.abs() < BEZIER_EPSILON {
+            return s;
+        }
+        let slope = bezier_derivative(x1, x2, s);
+        if slope.abs() < 1e-6 {
+            break;
+        }
+        s = (s - error / slope).clamp(0.0, 1.0);
+    }
+
+    let (mut low, mut high) = (0.0, 1.0);
+    s = x;
+    for _ in 0..BEZIER_BISECTION_ITERATIONS {
+        let value = bezier_component(x1, x2, s);
+        if (value - x).abs() < BEZIER_EPSILON {
+            break;
+        }
+        if value < x {
+            low = s;
+        } else {
+            high = s;
+        }
+        s = (low + high) * 0.5;
+    }
+    s
+}
+
+const BUILTIN_CURVES: [EasingCurve; 7] = [
+    EasingCurve::Linear,
+    EasingCurve::EaseInQuad,
+    EasingCurve::EaseOutQuad,
+    EasingCurve::EaseInOutQuad,
+    EasingCurve::EaseInCubic,
+    EasingCurve::EaseOutCubic,
+    EasingCurve::Smoothstep,
+];
+
+static CURVES: Lazy<Mutex<Vec<Curve>>> = Lazy::new(|| {
+    Mutex::new(BUILTIN_CURVES.iter().map(|&curve| Curve::Easing(curve)).collect())
+});
+
+// Зарегистрировать кривую; None - некорректные параметры или реестр заполнен
+pub fn register_curve(curve: Curve) -> Option<u32> {
+    let valid = match curve {
+        Curve::Easing(_) => true,
+        Curve::CubicBezier(x1, y1, x2, y2) => {
+            [x1, y1, x2, y2].iter().all(|v| v.is_finite()) && (0.0..=1.0).contains(&x1) && (0.0..=1.0).contains(&x2)
+        }
+        Curve::Hermite(p0, p1, m0, m1) => [p0, p1, m0, m1].iter().all(|v| v.is_finite()),
+        Curve::Exponential(k) => k.is_finite(),
+        Curve::Spring(stiffness, damping) => stiffness.is_finite() && damping.is_finite() && stiffness > 0.0,
+    };
+    if !valid {
+        return None;
+    }
+    let curve = match curve {
+        Curve::Exponential(k) => Curve::Exponential(k.clamp(-MAX_EXPONENTIAL_STEEPNESS, MAX_EXPONENTIAL_STEEPNESS)),
+        curve => curve,
+    };
+
+    let mut curves = lock_or_recover(&CURVES, "CURVES");
+    if curves.len() >= MAX_CURVES {
+        return None;
+    }
+    curves.push(curve);
+    Some((curves.len() - 1) as u32)
+}
+
+// Кривая по дескриптору
+pub fn curve(handle: u32) -> Option<Curve> {
+    lock_or_recover(&CURVES, "CURVES").get(handle as usize).copied()
+}
+
+#[wasm_bindgen]
+pub fn create_bezier_curve(x1: f32, y1: f32, x2: f32, y2: f32) -> Option<u32> {
+    register_curve(Curve::CubicBezier(x1, y1, x2, y2))
+}
+
+#[wasm_bindgen]
+pub fn create_hermite_curve(p0: f32, p1: f32, m0: f32, m1: f32) -> Option<u32> {
+    register_curve(Curve::Hermite(p0, p1, m0, m1))
+}
+
+#[wasm_bindgen]
+pub fn create_exponential_curve(steepness: f32) -> Option<u32> {
+    register_curve(Curve::Exponential(steepness))
+}
+
+#[wasm_bindgen]
+pub fn create_spring_curve(stiffness: f32, damping: f32) -> Option<u32> {
+    register_curve(Curve::Spring(stiffness, damping))
+}
+
+// Значение кривой в точке t; None - неизвестный дескриптор
+#[wasm_bindgen]
+pub fn evaluate_curve(curve_handle: u32, t: f32) -> Option<f32> {
+    curve(curve_handle).map(|curve| curve.evaluate(t))
+}
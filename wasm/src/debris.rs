@@ -0,0 +1,297 @@
+/*
+ * debris.rs
+ *
+ * Обломки ударов о видовую плоскость - мост между визуальной симуляцией и
+ * физическим миром rapier. Если система связана с физическим миром
+ * (enable_impact_debris), в мире создается тонкий коллайдер основной
+ * видовой плоскости, а каждое достаточно быстрое пересечение плоскости по
+ * направлению к наблюдателю порождает несколько маленьких тел-обломков
+ * с дальней стороны плоскости. Обломки отскакивают от коллайдера, гаснут
+ * за DEBRIS_LIFETIME секунд и удаляются из мира.
+ *
+ * Мир шагает хост (step_simulation); система только создает, старит и
+ * удаляет тела. Разлет обломков детерминирован: seed берется из seed
+ * системы и ID пересечения, RNG системы не тратится.
+ */
+
+use std::collections::VecDeque;
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use rapier3d::prelude::*;
+
+use crate::errors::OpResult;
+use crate::physics::{with_world, PhysicsWorld};
+use crate::planes::{ViewingPlane, MAIN_PLANE_INDEX};
+use crate::space_core::CrossingType;
+use crate::space_objects::{derive_seed, PlaneCrossing, SPACE_OBJECT_SYSTEMS};
+
+// Предел одновременно живущих обломков системы (лишние - самые старые - удаляются)
+pub const MAX_DEBRIS_CHUNKS: usize = 64;
+
+const DEBRIS_PER_IMPACT: usize = 4;         // Обломков на один удар
+const DEBRIS_LIFETIME: f32 = 2.0;           // Время жизни и угасания обломка (сек)
+const DEBRIS_RADIUS_FACTOR: f32 = 0.02;     // Радиус обломка относительно размера объекта
+const MIN_DEBRIS_RADIUS: f32 = 0.1;
+const MAX_DEBRIS_RADIUS: f32 = 0.6;
+const DEBRIS_SPEED_SHARE: f32 = 0.3;        // Доля скорости объекта, передаваемая обломкам
+const DEBRIS_SPREAD: f32 = 4.0;             // Случайный разлет поперек плоскости (ед/сек)
+const DEBRIS_RESTITUTION: f32 = 0.7;
+const PLANE_RESTITUTION: f32 = 0.6;
+const PLANE_HALF_THICKNESS: f32 = 0.05;
+
+#[derive(Clone, Copy, Debug)]
+struct DebrisChunk {
+    body: RigidBodyHandle,
+    radius: f32,
+    age: f32,
+}
+
+#[derive(Debug)]
+struct DebrisLink {
+    world_id: usize,
+    min_speed: f32,
+    plane_collider: ColliderHandle,
+    chunks: VecDeque<DebrisChunk>,
+}
+
+/// Связь системы с физическим миром для обломков ударов
+#[derive(Debug, Default)]
+pub struct ImpactDebris {
+    link: Option<DebrisLink>,
+}
+
+impl ImpactDebris {
+    pub fn is_enabled(&self) -> bool {
+        self.link.is_some()
+    }
+
+    // Живые обломки
+    pub fn len(&self) -> usize {
+        self.link.as_ref().map_or(0, |link| link.chunks.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Оставить не больше limit обломков, удалив из мира самые старые
+    pub fn truncate_oldest(&mut self, limit: usize) {
+        let Some(link) = self.link.as_mut() else {
+            return;
+        };
+        if link.chunks.len() <= limit {
+            return;
+        }
+
+        with_world(link.world_id, |world| {
+            while link.chunks.len() > limit {
+                if let Some(chunk) = link.chunks.pop_front() {
+                    world.remove_body(chunk.body);
+                }
+            }
+        });
+    }
+
+    pub fn world_id(&self) -> Option<usize> {
+        self.link.as_ref().map(|link| link.world_id)
+    }
+
+    // Связать с миром world_id и создать в нем коллайдер плоскости; false - мира нет
+    pub fn enable(&mut self, world_id: usize, min_speed: f32, plane: &ViewingPlane) -> bool {
+        self.disable();
+
+        let half = plane.size * 0.5;
+        let collider = ColliderBuilder::cuboid(half.x, half.y, PLANE_HALF_THICKNESS)
+            .translation(vector![plane.center.x, plane.center.y, plane.center.z])
+            .restitution(PLANE_RESTITUTION)
+            .build();
+        let Some(plane_collider) = with_world(world_id, |world| world.collider_set.insert(collider)) else {
+            return false;
+        };
+
+        self.link = Some(DebrisLink {
+            world_id,
+            min_speed: min_speed.max(0.0),
+            plane_collider,
+            chunks: VecDeque::new(),
+        });
+        true
+    }
+
+    // Удалить из мира все обломки и коллайдер плоскости
+    pub fn disable(&mut self) {
+        let Some(link) = self.link.take() else {
+            return;
+        };
+
+        with_world(link.world_id, |world| {
+            for chunk in &link.chunks {
+                world.remove_body(chunk.body);
+            }
+            world.collider_set.remove(
+                link.plane_collider,
+                &mut world.island_manager,
+                &mut world.rigid_body_set,
+                true,
+            );
+        });
+    }
+
+    // Состарить обломки и создать новые для быстрых входов в основную плоскость
+    pub fn update(&mut self, dt: f32, crossings: &[PlaneCrossing], seed: u64) {
+        let Some(link) = self.link.as_mut() else {
+            return;
+        };
+
+        let min_speed = link.min_speed;
+        let impacts: Vec<&PlaneCrossing> = crossings
+            .iter()
+            .filter(|crossing| {
+                crossing.plane == MAIN_PLANE_INDEX
+                    && crossing.crossing_type == CrossingType::Entry
+                    && crossing.velocity.length() >= min_speed
+            })
+            .collect();
+
+        let world_alive = with_world(link.world_id, |world| {
+            for chunk in link.chunks.iter_mut() {
+                chunk.age += dt;
+            }
+            while link.chunks.front().is_some_and(|chunk| chunk.age >= DEBRIS_LIFETIME) {
+                if let Some(chunk) = link.chunks.pop_front() {
+                    world.remove_body(chunk.body);
+                }
+            }
+
+            for crossing in &impacts {
+                spawn_chunks(world, &mut link.chunks, crossing, seed);
+            }
+            while link.chunks.len() > MAX_DEBRIS_CHUNKS {
+                if let Some(chunk) = link.chunks.pop_front() {
+                    world.remove_body(chunk.body);
+                }
+            }
+        });
+
+        // Мир удален хостом - связь больше не действует
+        if world_alive.is_none() {
+            self.link = None;
+        }
+    }
+
+    // Память записей живых обломков (тела и коллайдеры хранит физический мир)
+    pub fn memory_usage(&self) -> (usize, usize, usize, usize) {
+        let element = std::mem::size_of::<DebrisChunk>();
+        let (len, capacity) = self.link.as_ref().map_or((0, 0), |link| (link.chunks.len(), link.chunks.capacity()));
+        (len, capacity, len * element, capacity * element)
+    }
+}
+
+fn spawn_chunks(world: &mut PhysicsWorld, chunks: &mut VecDeque<DebrisChunk>, crossing: &PlaneCrossing, seed: u64) {
+    let mut rng = StdRng::seed_from_u64(derive_seed(seed, crossing.object_id as u64, crossing.id as u64));
+    let radius = (crossing.size * DEBRIS_RADIUS_FACTOR).clamp(MIN_DEBRIS_RADIUS, MAX_DEBRIS_RADIUS);
+
+    for _ in 0..DEBRIS_PER_IMPACT {
+        // Обломки появляются с дальней стороны плоскости и летят в нее
+        let offset = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.0) * radius * 2.0;
+        let position = crossing.position + offset + crossing.normal * (radius + PLANE_HALF_THICKNESS * 2.0);
+        let spread = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.0) * DEBRIS_SPREAD;
+        let velocity = crossing.velocity * DEBRIS_SPEED_SHARE + spread;
+
+        let body = RigidBodyBuilder::dynamic()
+            .translation(vector![position.x, position.y, position.z])
+            .linvel(vector![velocity.x, velocity.y, velocity.z])
+            .build();
+        let handle = world.rigid_body_set.insert(body);
+        let collider = ColliderBuilder::ball(radius).restitution(DEBRIS_RESTITUTION).build();
+        world.collider_set.insert_with_parent(collider, handle, &mut world.rigid_body_set);
+
+        chunks.push_back(DebrisChunk { body: handle, radius, age: 0.0 });
+    }
+}
+
+/// Обломки для рендера: позиции из физического мира, радиусы и прозрачность угасания
+#[wasm_bindgen]
+pub struct DebrisDataArray {
+    positions: Vec<f32>,
+    radii: Vec<f32>,
+    opacities: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl DebrisDataArray {
+    #[wasm_bindgen(getter)]
+    pub fn positions(&self) -> Vec<f32> {
+        self.positions.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn radii(&self) -> Vec<f32> {
+        self.radii.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn opacities(&self) -> Vec<f32> {
+        self.opacities.clone()
+    }
+}
+
+// Включить обломки ударов быстрее min_speed (ед/сек) в физическом мире world_id
+#[wasm_bindgen]
+pub fn enable_impact_debris(system_id: usize, world_id: usize, min_speed: f32) -> OpResult {
+    if !min_speed.is_finite() {
+        return OpResult::invalid_argument("min_speed must be finite");
+    }
+
+    let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) else {
+        return OpResult::system_not_found(system_id);
+    };
+    let Some(plane) = system.viewing_planes.get(&system.space, MAIN_PLANE_INDEX) else {
+        return OpResult::not_found("main viewing plane not found");
+    };
+    if !system.debris.enable(world_id, min_speed, &plane) {
+        return OpResult::not_found(format!("physics world {} not found", world_id));
+    }
+
+    OpResult::success()
+}
+
+#[wasm_bindgen]
+pub fn disable_impact_debris(system_id: usize) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.debris.disable();
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_impact_debris(system_id: usize) -> Option<DebrisDataArray> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let mut result = DebrisDataArray {
+        positions: Vec::new(),
+        radii: Vec::new(),
+        opacities: Vec::new(),
+    };
+
+    let Some(link) = system.debris.link.as_ref() else {
+        return Some(result);
+    };
+    with_world(link.world_id, |world| {
+        for chunk in &link.chunks {
+            let Some(body) = world.rigid_body_set.get(chunk.body) else {
+                continue;
+            };
+            let position = body.translation();
+            result.positions.extend_from_slice(&[position.x, position.y, position.z]);
+            result.radii.push(chunk.radius);
+            result.opacities.push(1.0 - chunk.age / DEBRIS_LIFETIME);
+        }
+    });
+
+    Some(result)
+}
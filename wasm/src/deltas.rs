@@ -0,0 +1,292 @@
+/*
+ * deltas.rs
+ * 
+ * Дельта-сжатие экспортируемых данных объектов для удаленного рендеринга
+ * и зеркалирования сцены. Трекер хранит квантованное состояние каждого
+ * объекта и номер кадра последнего изменения каждого поля, поэтому клиент,
+ * знающий номер своего последнего кадра, получает только изменившиеся поля.
+ * Каждые KEYFRAME_INTERVAL кадров клиент получает полный снимок.
+ */
+
+use wasm_bindgen::prelude::*;
+use std::collections::HashMap;
+
+use crate::space_objects::{SpaceObjectData, SPACE_OBJECT_SYSTEMS};
+
+// Полный снимок отдается не реже, чем раз в KEYFRAME_INTERVAL кадров
+pub const KEYFRAME_INTERVAL: u32 = 120;
+
+// Шаги квантования полей
+pub(crate) const POSITION_QUANTUM: f32 = 0.01;   // Позиция: 1/100 единицы пространства
+pub(crate) const SIZE_QUANTUM: f32 = 0.01;       // Размер: 1/100 единицы
+
+// Версия раскладки массивов дельт (маски, квантование); меняется вместе с ними
+pub const DELTA_LAYOUT_VERSION: u16 = 1;
+
+// Биты маски изменившихся полей
+pub const DELTA_POSITION: u8 = 1;
+pub const DELTA_OPACITY: u8 = 1 << 1;
+pub const DELTA_SIZE: u8 = 1 << 2;
+
+// Квантованное состояние объекта и кадры последних изменений полей
+#[derive(Clone, Debug)]
+struct TrackedObject {
+    position: [i32; 3],
+    opacity: u8,
+    size: u16,
+    position_frame: u32,
+    opacity_frame: u32,
+    size_frame: u32,
+}
+
+impl TrackedObject {
+    fn quantize(data: &SpaceObjectData, frame: u32) -> Self {
+        Self {
+            position: [
+                (data.position.x / POSITION_QUANTUM).round() as i32,
+                (data.position.y / POSITION_QUANTUM).round() as i32,
+                (data.position.z / POSITION_QUANTUM).round() as i32,
+            ],
+            opacity: (data.opacity.clamp(0.0, 1.0) * 255.0).round() as u8,
+            size: (data.size.max(0.0) / SIZE_QUANTUM).round().min(u16::MAX as f32) as u16,
+            position_frame: frame,
+            opacity_frame: frame,
+            size_frame: frame,
+        }
+    }
+    
+    // Маска полей, изменившихся после кадра since_frame
+    fn changed_since(&self, since_frame: u32) -> u8 {
+        let mut mask = 0;
+        if self.position_frame > since_frame {
+            mask |= DELTA_POSITION;
+        }
+        if self.opacity_frame > since_frame {
+            mask |= DELTA_OPACITY;
+        }
+        if self.size_frame > since_frame {
+            mask |= DELTA_SIZE;
+        }
+        mask
+    }
+}
+
+/// Трекер изменений объектов системы
+#[derive(Default)]
+pub struct DeltaTracker {
+    objects: HashMap<usize, TrackedObject>,
+    
+    // Удаленные объекты и кадр удаления (хранятся не дольше KEYFRAME_INTERVAL кадров)
+    removed: Vec<(usize, u32)>,
+    
+    // Следующий запрос изменений получит полный снимок (mark_all_dirty)
+    keyframe_requested: bool,
+}
+
+impl DeltaTracker {
+    // Занятая память: (записей, емкость, байт занято, байт зарезервировано)
+    pub fn memory_usage(&self) -> (usize, usize, usize, usize) {
+        let entry = std::mem::size_of::<(usize, TrackedObject)>();
+        let removed = std::mem::size_of::<(usize, u32)>();
+        (
+            self.objects.len() + self.removed.len(),
+            self.objects.capacity() + self.removed.capacity(),
+            self.objects.len() * entry + self.removed.len() * removed,
+            self.objects.capacity() * entry + self.removed.capacity() * removed,
+        )
+    }
+    
+    // Запросить полный снимок при следующем сборе изменений
+    pub fn request_keyframe(&mut self) {
+        self.keyframe_requested = true;
+    }
+    
+    pub fn take_keyframe_request(&mut self) -> bool {
+        std::mem::take(&mut self.keyframe_requested)
+    }
+    
+    // Сравнить текущее состояние объектов с сохраненным и отметить изменения кадром frame
+    pub fn track<'a>(&mut self, frame: u32, objects: impl Iterator<Item = &'a SpaceObjectData>) {
+        let mut seen = Vec::new();
+        
+        for data in objects {
+            seen.push(data.id);
+            let current = TrackedObject::quantize(data, frame);
+            
+            match self.objects.get_mut(&data.id) {
+                Some(tracked) => {
+                    if tracked.position != current.position {
+                        tracked.position = current.position;
+                        tracked.position_frame = frame;
+                    }
+                    if tracked.opacity != current.opacity {
+                        tracked.opacity = current.opacity;
+                        tracked.opacity_frame = frame;
+                    }
+                    if tracked.size != current.size {
+                        tracked.size = current.size;
+                        tracked.size_frame = frame;
+                    }
+                }
+                None => {
+                    self.objects.insert(data.id, current);
+                }
+            }
+        }
+        
+        // Объекты, пропавшие из системы, переносим в список удаленных
+        if seen.len() != self.objects.len() {
+            seen.sort_unstable();
+            let removed = &mut self.removed;
+            self.objects.retain(|id, _| {
+                let alive = seen.binary_search(id).is_ok();
+                if !alive {
+                    removed.push((*id, frame));
+                }
+                alive
+            });
+        }
+        
+        self.removed.retain(|(_, removed_frame)| frame.saturating_sub(*removed_frame) <= KEYFRAME_INTERVAL);
+    }
+    
+    // Собрать изменения после кадра since_frame для текущего кадра frame
+    pub fn collect(&self, frame: u32, since_frame: u32) -> ObjectDeltas {
+        // Клиент без состояния или пропустивший границу ключевого кадра получает полный снимок
+        let last_keyframe = frame - frame % KEYFRAME_INTERVAL;
+        let keyframe = since_frame == 0 || since_frame > frame || since_frame < last_keyframe;
+        
+        let mut ids: Vec<usize> = self.objects.keys().copied().collect();
+        ids.sort_unstable();
+        
+        let mut deltas = ObjectDeltas {
+            frame,
+            keyframe,
+            ids: Vec::new(),
+            masks: Vec::new(),
+            positions: Vec::new(),
+            opacities: Vec::new(),
+            sizes: Vec::new(),
+            removed: Vec::new(),
+        };
+        
+        for id in ids {
+            let tracked = &self.objects[&id];
+            let mask = if keyframe {
+                DELTA_POSITION | DELTA_OPACITY | DELTA_SIZE
+            } else {
+                tracked.changed_since(since_frame)
+            };
+            
+            if mask == 0 {
+                continue;
+            }
+            
+            deltas.ids.push(id);
+            deltas.masks.push(mask);
+            if mask & DELTA_POSITION != 0 {
+                deltas.positions.extend_from_slice(&tracked.position);
+            }
+            if mask & DELTA_OPACITY != 0 {
+                deltas.opacities.push(tracked.opacity);
+            }
+            if mask & DELTA_SIZE != 0 {
+                deltas.sizes.push(tracked.size);
+            }
+        }
+        
+        if !keyframe {
+            deltas.removed = self.removed.iter()
+                .filter(|(_, removed_frame)| *removed_frame > since_frame)
+                .map(|(id, _)| *id)
+                .collect();
+        }
+        
+        deltas
+    }
+}
+
+/// Изменения объектов с указанного кадра.
+/// Для каждого ID в masks указано, какие поля присутствуют; значения полей
+/// лежат в своих массивах подряд в порядке ids (только для отмеченных объектов).
+/// Позиции и размеры квантованы (умножить на position_quantum / size_quantum),
+/// прозрачность - байт 0..255
+#[wasm_bindgen]
+pub struct ObjectDeltas {
+    pub(crate) frame: u32,
+    pub(crate) keyframe: bool,
+    pub(crate) ids: Vec<usize>,
+    pub(crate) masks: Vec<u8>,
+    pub(crate) positions: Vec<i32>,
+    pub(crate) opacities: Vec<u8>,
+    pub(crate) sizes: Vec<u16>,
+    pub(crate) removed: Vec<usize>,
+}
+
+#[wasm_bindgen]
+impl ObjectDeltas {
+    #[wasm_bindgen(getter)]
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn keyframe(&self) -> bool {
+        self.keyframe
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn ids(&self) -> Vec<usize> {
+        self.ids.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn masks(&self) -> Vec<u8> {
+        self.masks.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn positions(&self) -> Vec<i32> {
+        self.positions.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn opacities(&self) -> Vec<u8> {
+        self.opacities.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn sizes(&self) -> Vec<u16> {
+        self.sizes.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn removed(&self) -> Vec<usize> {
+        self.removed.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn position_quantum(&self) -> f32 {
+        POSITION_QUANTUM
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn size_quantum(&self) -> f32 {
+        SIZE_QUANTUM
+    }
+}
+
+// Изменения объектов системы после кадра since_frame (0 - полный снимок).
+// После mark_all_dirty первый запрос получает полный снимок независимо от since_frame
+#[wasm_bindgen]
+pub fn get_object_deltas(system_id: usize, since_frame: u32) -> Option<ObjectDeltas> {
+    let mut system = SPACE_OBJECT_SYSTEMS.get_mut(&system_id)?;
+    let since_frame = if system.deltas.take_keyframe_request() { 0 } else { since_frame };
+    Some(system.deltas.collect(system.frame, since_frame))
+}
+
+// Номер текущего кадра системы
+#[wasm_bindgen]
+pub fn get_system_frame(system_id: usize) -> Option<u32> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| system.frame)
+}
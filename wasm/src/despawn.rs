@@ -0,0 +1,135 @@
+/*
+ * despawn.rs
+ *
+ * Плавная очистка сцены (например, когда экран занимает модальное окно):
+ * despawn_all останавливает появление новых объектов и за заданное время
+ * гасит существующие, после чего удаляет их из системы и сообщает о
+ * завершении через очередь take_completed_despawns.
+ *
+ * Прозрачность объектов пересчитывается при каждом шаге, поэтому проход
+ * fade просто умножает ее на множитель угасания - без накопления.
+ * Появление возобновляется явным вызовом spawn_neon_comets.
+ */
+
+use wasm_bindgen::prelude::*;
+
+use crate::easing::EasingCurve;
+use crate::incremental_init::PendingInit;
+use crate::neon_comets::cancel_pending_spawns;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+#[derive(Clone, Copy, Debug)]
+struct ActiveFade {
+    id: u32,
+    duration: f32,
+    elapsed: f32,
+}
+
+/// Угасание сцены и остановка появлений
+#[derive(Clone, Debug, Default)]
+pub struct SceneFade {
+    active: Option<ActiveFade>,
+    spawning_stopped: bool,
+    completed: Vec<u32>,
+    next_id: u32,
+}
+
+impl SceneFade {
+    // Начать угасание; повторный вызов продолжает с текущего уровня за новое время
+    pub fn start(&mut self, fade_seconds: f32) -> u32 {
+        let duration = fade_seconds.max(0.0);
+        let elapsed = match self.active {
+            Some(fade) if fade.duration > 0.0 => (fade.elapsed / fade.duration).min(1.0) * duration,
+            _ => 0.0,
+        };
+
+        let id = self.active.map_or_else(|| self.allocate_id(), |fade| fade.id);
+        self.active = Some(ActiveFade { id, duration, elapsed });
+        self.spawning_stopped = true;
+        id
+    }
+
+    fn allocate_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        id
+    }
+
+    pub fn is_spawning_stopped(&self) -> bool {
+        self.spawning_stopped
+    }
+
+    pub fn resume_spawning(&mut self) {
+        self.spawning_stopped = false;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    pub fn progress(&self) -> Option<f32> {
+        self.active.map(|fade| if fade.duration > 0.0 { (fade.elapsed / fade.duration).min(1.0) } else { 1.0 })
+    }
+
+    // Множитель прозрачности объектов (1 - без угасания)
+    pub fn multiplier(&self) -> f32 {
+        self.progress().map_or(1.0, |p| 1.0 - EasingCurve::Smoothstep.apply(p))
+    }
+
+    // Продвинуть угасание; true - угасание завершилось на этом шаге
+    pub fn advance(&mut self, dt: f32) -> bool {
+        let Some(fade) = self.active.as_mut() else {
+            return false;
+        };
+
+        fade.elapsed += dt;
+        if fade.elapsed < fade.duration {
+            return false;
+        }
+
+        self.completed.push(fade.id);
+        self.active = None;
+        true
+    }
+
+    pub fn take_completed(&mut self) -> Vec<u32> {
+        std::mem::take(&mut self.completed)
+    }
+}
+
+// Остановить появление и погасить все объекты за fade_seconds секунд;
+// возвращает ID очистки для сопоставления с событием завершения
+#[wasm_bindgen]
+pub fn despawn_all(system_id: usize, fade_seconds: f32) -> Option<u32> {
+    if !fade_seconds.is_finite() || !SPACE_OBJECT_SYSTEMS.contains_key(&system_id) {
+        return None;
+    }
+
+    // Очередь появлений блокируется отдельно от системы; незавершенное
+    // наполнение (continue_init) отменяется вместе с ней
+    cancel_pending_spawns(system_id);
+
+    SPACE_OBJECT_SYSTEMS
+        .get_mut(&system_id)
+        .map(|mut system| {
+            system.pending_init = PendingInit::default();
+            system.fade.start(fade_seconds)
+        })
+}
+
+// Прогресс текущей очистки (0..1); None - очистки нет
+#[wasm_bindgen]
+pub fn get_despawn_progress(system_id: usize) -> Option<f32> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .and_then(|system| system.fade.progress())
+}
+
+// ID очисток, завершившихся с прошлого вызова (объекты уже удалены)
+#[wasm_bindgen]
+pub fn take_completed_despawns(system_id: usize) -> Vec<u32> {
+    SPACE_OBJECT_SYSTEMS
+        .get_mut(&system_id)
+        .map(|mut system| system.fade.take_completed())
+        .unwrap_or_default()
+}
@@ -0,0 +1,172 @@
+/*
+ * diagnostics.rs
+ *
+ * Диагностические сообщения горячего пути ("Slowed down fast comet" и т.п.)
+ * могут срабатывать каждый кадр для каждого объекта, и вывод в консоль
+ * браузера заметно просаживает производительность. Поэтому такие сообщения
+ * проходят через ограничитель: по каждому ключу сообщения выводится не чаще
+ * одного раза за min_interval секунд, а пропущенные сообщения считаются.
+ * Следующее выведенное сообщение сообщает, сколько похожих было пропущено.
+ *
+ * Текст сообщения формируется лениво - только если оно будет выведено.
+ * Режим диагностики можно выключить целиком (сообщения только считаются).
+ *
+ * Здесь же учитываются блокировки, восстановленные после паники
+ * (utils::lock_or_recover): о каждой сообщается один раз, а их имена
+ * доступны в get_diagnostics_stats.
+ */
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+use once_cell::sync::Lazy;
+use wasm_bindgen::prelude::*;
+
+use crate::errors::OpResult;
+use crate::platform;
+use crate::utils::lock_or_recover;
+
+// Интервал по умолчанию между сообщениями с одним ключом (секунды)
+const DEFAULT_MIN_INTERVAL_SECONDS: f64 = 2.0;
+
+#[derive(Clone, Copy, Debug, Default)]
+struct KeyState {
+    last_logged_ms: Option<f64>,
+    // Пропущено с момента последнего вывода
+    pending_suppressed: u32,
+    // Пропущено за все время
+    total_suppressed: u32,
+}
+
+struct Diagnostics {
+    enabled: bool,
+    min_interval_ms: f64,
+    keys: HashMap<&'static str, KeyState>,
+}
+
+static DIAGNOSTICS: Lazy<Mutex<Diagnostics>> = Lazy::new(|| {
+    Mutex::new(Diagnostics {
+        enabled: true,
+        min_interval_ms: DEFAULT_MIN_INTERVAL_SECONDS * 1000.0,
+        keys: HashMap::new(),
+    })
+});
+
+// Имена блокировок, восстановленных после паники. Сама эта блокировка
+// восстанавливается без учета, чтобы не зациклиться
+static RECOVERED_LOCKS: Lazy<Mutex<Vec<&'static str>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+// Отметить восстановление блокировки name; сообщение выводится один раз на имя
+pub(crate) fn record_lock_recovery(name: &'static str) {
+    let mut recovered = RECOVERED_LOCKS.lock().unwrap_or_else(PoisonError::into_inner);
+    if recovered.contains(&name) {
+        return;
+    }
+
+    recovered.push(name);
+    drop(recovered);
+    platform::log(&format!("Recovered poisoned lock {} after a panic; state may be incomplete", name));
+}
+
+// Вывести сообщение с ключом key, если с прошлого вывода этого ключа прошло
+// не меньше min_interval; иначе только посчитать его как пропущенное
+pub fn log_limited(key: &'static str, message: impl FnOnce() -> String) {
+    let mut diagnostics = lock_or_recover(&DIAGNOSTICS, "DIAGNOSTICS");
+    let enabled = diagnostics.enabled;
+    let min_interval_ms = diagnostics.min_interval_ms;
+    let state = diagnostics.keys.entry(key).or_default();
+
+    let now = platform::monotonic_ms();
+    let due = state.last_logged_ms.is_none_or(|last| now - last >= min_interval_ms);
+    if !enabled || !due {
+        state.pending_suppressed = state.pending_suppressed.saturating_add(1);
+        state.total_suppressed = state.total_suppressed.saturating_add(1);
+        return;
+    }
+
+    let suppressed = std::mem::take(&mut state.pending_suppressed);
+    state.last_logged_ms = Some(now);
+    drop(diagnostics);
+
+    if suppressed > 0 {
+        platform::log(&format!("{} ({} similar messages suppressed)", message(), suppressed));
+    } else {
+        platform::log(&message());
+    }
+}
+
+/// Счетчики пропущенных диагностических сообщений по ключам
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct DiagnosticsStats {
+    keys: Vec<String>,
+    suppressed: Vec<u32>,
+    recovered_locks: Vec<String>,
+}
+
+#[wasm_bindgen]
+impl DiagnosticsStats {
+    #[wasm_bindgen(getter)]
+    pub fn keys(&self) -> Vec<String> {
+        self.keys.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn suppressed(&self) -> Vec<u32> {
+        self.suppressed.clone()
+    }
+
+    // Блокировки, восстановленные после паники (в порядке восстановления)
+    #[wasm_bindgen(getter)]
+    pub fn recovered_locks(&self) -> Vec<String> {
+        self.recovered_locks.clone()
+    }
+
+    // Всего пропущено сообщений
+    #[wasm_bindgen(getter)]
+    pub fn total_suppressed(&self) -> u32 {
+        self.suppressed.iter().fold(0u32, |sum, count| sum.saturating_add(*count))
+    }
+}
+
+// Включить/выключить диагностический вывод и задать минимальный интервал
+// между сообщениями с одним ключом (секунды)
+#[wasm_bindgen]
+pub fn set_diagnostics_mode(enabled: bool, min_interval_seconds: f64) -> OpResult {
+    if !min_interval_seconds.is_finite() || min_interval_seconds < 0.0 {
+        return OpResult::invalid_argument("min_interval_seconds must be finite and non-negative");
+    }
+
+    let mut diagnostics = lock_or_recover(&DIAGNOSTICS, "DIAGNOSTICS");
+    diagnostics.enabled = enabled;
+    diagnostics.min_interval_ms = min_interval_seconds * 1000.0;
+    OpResult::success()
+}
+
+#[wasm_bindgen]
+pub fn get_diagnostics_stats() -> DiagnosticsStats {
+    let diagnostics = lock_or_recover(&DIAGNOSTICS, "DIAGNOSTICS");
+    let mut entries: Vec<(&'static str, u32)> = diagnostics
+        .keys
+        .iter()
+        .map(|(key, state)| (*key, state.total_suppressed))
+        .collect();
+    entries.sort_by_key(|entry| entry.0);
+
+    DiagnosticsStats {
+        keys: entries.iter().map(|entry| entry.0.to_string()).collect(),
+        suppressed: entries.iter().map(|entry| entry.1).collect(),
+        recovered_locks: RECOVERED_LOCKS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+    }
+}
+
+// Сбросить счетчики и интервалы (следующее сообщение каждого ключа выводится сразу)
+#[wasm_bindgen]
+pub fn reset_diagnostics() {
+    lock_or_recover(&DIAGNOSTICS, "DIAGNOSTICS").keys.clear();
+}
@@ -0,0 +1,99 @@
+/*
+ * distortion.rs
+ *
+ * Поле искажения пространства ("гравитационная линза") для пост-обработки.
+ * Каждая энергетическая сфера стягивает точки пространства к своему центру:
+ * смещение растет от нуля в центре до максимума на границе сферы и плавно
+ * гаснет к краю радиуса влияния. Вклады всех сфер складываются.
+ *
+ * sample_distortion возвращает смещение одной точки, а sample_distortion_grid -
+ * грубую сетку смещений по прямоугольнику видовой плоскости, которую шейдер
+ * загружает как текстуру искажения вместо того, чтобы считать поле сам.
+ * Черных дыр в сцене пока нет - при их появлении они добавят свой вклад сюда же.
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::{Vec2, Vec3};
+
+use crate::energy_spheres::EnergySphere;
+use crate::space_core::Vec3Wrapper;
+use crate::space_objects::{SpaceObjectSystem, SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+
+// Сила линзы: смещение на границе сферы в долях ее радиуса
+const LENS_STRENGTH: f32 = 0.35;
+
+// Доля силы, зависящая от пульсации (на пике пульса линза сильнее)
+const LENS_PULSE_SHARE: f32 = 0.4;
+
+// Радиус влияния сферы в ее радиусах; дальше вклад равен нулю
+const LENS_RANGE_FACTOR: f32 = 6.0;
+
+// Предел размера сетки по каждой оси
+pub const MAX_DISTORTION_GRID: usize = 128;
+
+// Смещение точки линзой одной сферы
+fn sphere_displacement(sphere: &EnergySphere, point: Vec3) -> Vec3 {
+    let radius = sphere.radius();
+    let offset = sphere.data.position - point;
+    let distance = offset.length();
+    let range = radius * LENS_RANGE_FACTOR;
+    if radius <= 0.0 || distance >= range {
+        return Vec3::ZERO;
+    }
+
+    let strength = LENS_STRENGTH * (1.0 - LENS_PULSE_SHARE + LENS_PULSE_SHARE * sphere.pulse()) * sphere.data.opacity;
+
+    // offset * r / (d² + r²): ноль в центре, strength * r / 2 на границе, ~ r² / d вдали
+    let lens = offset * radius / (distance * distance + radius * radius);
+    let falloff = 1.0 - (distance / range).powi(2);
+
+    lens * radius * strength * falloff * falloff
+}
+
+// Суммарное смещение точки от всех источников искажения системы
+pub fn distortion_at(system: &SpaceObjectSystem, point: Vec3) -> Vec3 {
+    system
+        .get_objects()
+        .get(&SpaceObjectType::EnergySphere)
+        .map(|objects| {
+            objects
+                .iter()
+                .filter_map(|obj| obj.as_any().downcast_ref::<EnergySphere>())
+                .map(|sphere| sphere_displacement(sphere, point))
+                .sum()
+        })
+        .unwrap_or(Vec3::ZERO)
+}
+
+// Смещение точки (x, y, z) полем искажения
+#[wasm_bindgen]
+pub fn sample_distortion(system_id: usize, x: f32, y: f32, z: f32) -> Option<Vec3Wrapper> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| Vec3Wrapper::from(distortion_at(&system, Vec3::new(x, y, z))))
+}
+
+// Сетка columns x rows смещений по видовой плоскости plane_index: ячейки
+// построчно от UV (0, 0), в каждой (dx, dy, dz) для центра ячейки.
+// None - нет системы или плоскости, либо недопустимый размер сетки
+#[wasm_bindgen]
+pub fn sample_distortion_grid(system_id: usize, plane_index: usize, columns: usize, rows: usize) -> Option<Vec<f32>> {
+    if columns == 0 || rows == 0 || columns > MAX_DISTORTION_GRID || rows > MAX_DISTORTION_GRID {
+        return None;
+    }
+
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let plane = system.viewing_planes.get(&system.space, plane_index)?;
+    let cells = Vec2::new(columns as f32, rows as f32);
+
+    let mut result = Vec::with_capacity(columns * rows * 3);
+    for row in 0..rows {
+        for column in 0..columns {
+            let uv = (Vec2::new(column as f32, row as f32) + Vec2::splat(0.5)) / cells;
+            let point = plane.center + ((uv - Vec2::splat(0.5)) * plane.size).extend(0.0);
+            result.extend_from_slice(&distortion_at(&system, point).to_array());
+        }
+    }
+
+    Some(result)
+}
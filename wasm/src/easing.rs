@@ -0,0 +1,164 @@
+/*
+ * easing.rs
+ * 
+ * Кривые сглаживания и профили прозрачности объектов.
+ * Вместо разбросанных по коду обновления формул каждая фаза жизни объекта
+ * (появление, полет, затухание после видовой плоскости) описывается
+ * кривой и параметрами, настраиваемыми отдельно для каждого типа объектов.
+ * Вместо встроенной кривой фаза может использовать кривую из реестра
+ * (curves.rs) - ту же, что и UI-анимации на стороне JS.
+ */
+
+use wasm_bindgen::prelude::*;
+
+use crate::curves::{curve, Curve};
+use crate::errors::OpResult;
+use crate::space_objects::{SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+
+/// Кривая сглаживания на отрезке 0..1
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EasingCurve {
+    Linear = 0,
+    EaseInQuad = 1,
+    EaseOutQuad = 2,
+    EaseInOutQuad = 3,
+    EaseInCubic = 4,
+    EaseOutCubic = 5,
+    Smoothstep = 6,
+}
+
+impl EasingCurve {
+    // Значение кривой в точке t (t ограничивается отрезком 0..1)
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        
+        match self {
+            EasingCurve::Linear => t,
+            EasingCurve::EaseInQuad => t * t,
+            EasingCurve::EaseOutQuad => t * (2.0 - t),
+            EasingCurve::EaseInOutQuad => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            EasingCurve::EaseInCubic => t * t * t,
+            EasingCurve::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+            EasingCurve::Smoothstep => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// Профиль прозрачности объекта: появление, полет и затухание за видовой плоскостью
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct OpacityProfile {
+    // Длительность появления (сек) и его кривая
+    pub fade_in_seconds: f32,
+    pub fade_in_curve: EasingCurve,
+    
+    // Кривая пересчета прозрачности по расстоянию и нижний предел во время полета
+    pub travel_curve: EasingCurve,
+    pub travel_min_opacity: f32,
+    
+    // Расстояние за видовой плоскостью, на котором объект полностью гаснет (0 - не гаснет)
+    pub fade_out_distance: f32,
+    pub fade_out_curve: EasingCurve,
+    
+    // Кривые из реестра, заменяющие встроенные кривые фаз
+    fade_in_custom: Option<Curve>,
+    travel_custom: Option<Curve>,
+    fade_out_custom: Option<Curve>,
+}
+
+impl Default for OpacityProfile {
+    // Линейное появление за секунду, без затухания за плоскостью
+    fn default() -> Self {
+        Self {
+            fade_in_seconds: 1.0,
+            fade_in_curve: EasingCurve::Linear,
+            travel_curve: EasingCurve::Linear,
+            travel_min_opacity: 0.3,
+            fade_out_distance: 0.0,
+            fade_out_curve: EasingCurve::Linear,
+            fade_in_custom: None,
+            travel_custom: None,
+            fade_out_custom: None,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl OpacityProfile {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    // Использовать кривую из реестра для появления; false - неизвестный дескриптор
+    pub fn set_fade_in_curve_handle(&mut self, handle: u32) -> bool {
+        curve(handle).map(|curve| self.fade_in_custom = Some(curve)).is_some()
+    }
+    
+    pub fn set_travel_curve_handle(&mut self, handle: u32) -> bool {
+        curve(handle).map(|curve| self.travel_custom = Some(curve)).is_some()
+    }
+    
+    pub fn set_fade_out_curve_handle(&mut self, handle: u32) -> bool {
+        curve(handle).map(|curve| self.fade_out_custom = Some(curve)).is_some()
+    }
+}
+
+// Кривая фазы: из реестра, если задана, иначе встроенная
+fn phase_value(custom: Option<Curve>, builtin: EasingCurve, t: f32) -> f32 {
+    match custom {
+        Some(curve) => curve.evaluate(t),
+        None => builtin.apply(t),
+    }
+}
+
+impl OpacityProfile {
+    // Рассчитать прозрачность объекта.
+    // distance_factor - прозрачность по расстоянию до наблюдателя (0..1),
+    // behind_plane - насколько объект ушел за видовую плоскость (<= 0 - еще не пересек)
+    pub fn opacity(&self, lifetime: f32, distance_factor: f32, behind_plane: f32) -> f32 {
+        let mut opacity = if lifetime < self.fade_in_seconds {
+            phase_value(self.fade_in_custom, self.fade_in_curve, lifetime / self.fade_in_seconds)
+        } else {
+            phase_value(self.travel_custom, self.travel_curve, distance_factor).max(self.travel_min_opacity)
+        };
+        
+        if self.fade_out_distance > 0.0 && behind_plane > 0.0 {
+            opacity *= 1.0 - phase_value(self.fade_out_custom, self.fade_out_curve, behind_plane / self.fade_out_distance);
+        }
+        
+        opacity
+    }
+}
+
+// Задать профиль прозрачности для типа объектов системы
+#[wasm_bindgen]
+pub fn set_opacity_profile(system_id: usize, object_type: SpaceObjectType, profile: &OpacityProfile) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.space.opacity_profiles.insert(object_type, *profile);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_opacity_profile(system_id: usize, object_type: SpaceObjectType) -> Option<OpacityProfile> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| system.space.opacity_profile(object_type))
+}
+
+// Значение кривой сглаживания - чтобы UI-анимации совпадали с симуляцией
+#[wasm_bindgen]
+pub fn evaluate_easing(curve: EasingCurve, t: f32) -> f32 {
+    curve.apply(t)
+}
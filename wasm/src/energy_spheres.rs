@@ -1,15 +1,477 @@
 /*
  * energy_spheres.rs
- * 
+ *
  * Модуль для реализации энергетических сфер - пульсирующих шаров энергии,
  * окруженных вихрями частиц, создающих эффект турбулентности.
- * 
+ *
  * Сферы излучают яркое свечение и могут взаимодействовать друг с другом,
  * образуя энергетические дуги между собой. При сближении с другими объектами,
  * они создают искажение пространства, как визуальная гравитационная линза.
- * 
+ *
  * Идеальная форма сферы может деформироваться под воздействием внешних сил,
  * создавая динамичные, постоянно меняющиеся формы.
- * 
- * TODO: Реализовать полную функциональность энергетических сфер
-*/
\ No newline at end of file
+ *
+ * Вихрь частиц - кольцо частиц на орбите вокруг сферы. Плоскость кольца
+ * наклонена к оси Z и прецессирует вокруг нее, а скорость обращения
+ * растет вместе с пульсацией сферы. Положения частиц вычисляются из
+ * состояния сферы при экспорте, поэтому частицы ничего не стоят в шаге
+ * симуляции.
+ *
+ * TODO: Энергетические дуги между сферами и деформация формы
+*/
+
+use wasm_bindgen::prelude::*;
+use glam::{Quat, Vec3};
+use rand::{Rng, rngs::StdRng};
+use std::any::Any;
+use std::f32::consts::TAU;
+
+use crate::errors::{ErrorCode, OpResult};
+use crate::palette;
+use crate::particle_budget::ParticleSubsystem;
+use crate::space_core::SpaceDefinition;
+use crate::object_bounds::object_bounds;
+use crate::protected_rect::reroll_protected;
+use crate::space_objects::{
+    SpaceObject, SpaceObjectData, SpaceObjectSystem, SpaceObjectType,
+    derive_seed, random_position_on_far_plane,
+    SPACE_OBJECT_SYSTEMS
+};
+
+// Константы для энергетических сфер
+const MIN_SPHERE_SIZE: f32 = 8.0;           // Минимальный диаметр сферы (единиц пространства)
+const MAX_SPHERE_SIZE: f32 = 20.0;          // Максимальный диаметр сферы
+const MIN_DRIFT_SPEED: f32 = 4.0;           // Сферы дрейфуют к наблюдателю медленнее комет
+const MAX_DRIFT_SPEED: f32 = 10.0;
+const MAX_SPHERE_LIFETIME: f32 = 120.0;     // Максимальное время жизни в секундах
+const MIN_PULSE_FREQUENCY: f32 = 0.3;       // Частота пульсации (Гц)
+const MAX_PULSE_FREQUENCY: f32 = 0.8;
+const PULSE_AMPLITUDE: f32 = 0.15;          // Доля изменения радиуса при пульсации
+const SPHERE_EMISSIVE: f32 = 1.5;           // Яркость свечения вне пульса
+
+// Больше сфер за один вызов spawn_energy_spheres не создается
+pub const MAX_SPHERES_PER_SPAWN: usize = 64;
+const SPHERE_PULSE_EMISSIVE: f32 = 1.0;     // Прирост яркости на пике пульса (доля базовой)
+
+// Вихрь частиц
+pub const VORTEX_PARTICLES: usize = 24;     // Частиц в кольце вихря
+const VORTEX_RADIUS_FACTOR: f32 = 1.6;      // Радиус орбиты относительно радиуса сферы
+const VORTEX_BASE_SPEED: f32 = 1.2;         // Угловая скорость вне пульса (рад/сек)
+const VORTEX_PULSE_SPEEDUP: f32 = 1.5;      // Прирост скорости на пике пульса (доля базовой)
+const MIN_VORTEX_INCLINATION: f32 = 0.2;    // Наклон плоскости кольца к оси Z (рад)
+const MAX_VORTEX_INCLINATION: f32 = 0.7;
+const MAX_PRECESSION_SPEED: f32 = 0.4;      // Скорость прецессии наклона (рад/сек)
+
+// Палитра энергетических сфер
+pub(crate) const SPHERE_PALETTE: [[f32; 3]; 4] = [
+    [0.3, 0.9, 1.0], // Ice blue
+    [1.0, 0.5, 0.1], // Plasma orange
+    [0.7, 0.3, 1.0], // Violet
+    [0.4, 1.0, 0.5], // Emerald
+];
+
+/// Энергетическая сфера с вихрем частиц
+#[derive(Clone, Debug)]
+pub struct EnergySphere {
+    // Основные данные объекта
+    pub data: SpaceObjectData,
+
+    // Цвет свечения сферы (RGB)
+    pub color: [f32; 3],
+
+    // Частота пульсации (Гц) и текущая фаза (рад)
+    pub pulse_frequency: f32,
+    pub pulse_phase: f32,
+
+    // Угол обращения кольца частиц (рад)
+    pub vortex_angle: f32,
+
+    // Наклон плоскости кольца к оси Z и его прецессия вокруг Z
+    pub vortex_inclination: f32,
+    pub precession_angle: f32,
+    pub precession_speed: f32,
+
+    // Детерминированный seed сферы (seed системы + ID)
+    pub seed: u64,
+
+    // Индекс цвета в палитре сфер
+    pub palette_index: usize,
+}
+
+impl EnergySphere {
+    pub fn new(id: usize) -> Self {
+        let data = SpaceObjectData {
+            id,
+            object_type: SpaceObjectType::EnergySphere,
+            position: Vec3::ZERO,
+            size: 0.0,
+            scale: 0.0,
+            opacity: 0.0,
+            rotation: Quat::IDENTITY,
+            velocity: Vec3::ZERO,
+            lifetime: 0.0,
+            max_lifetime: MAX_SPHERE_LIFETIME,
+            active: true,
+        };
+
+        Self {
+            data,
+            color: [0.0, 0.0, 0.0],
+            pulse_frequency: 0.0,
+            pulse_phase: 0.0,
+            vortex_angle: 0.0,
+            vortex_inclination: 0.0,
+            precession_angle: 0.0,
+            precession_speed: 0.0,
+            seed: id as u64,
+            palette_index: 0,
+        }
+    }
+
+    // Уровень пульсации (0..1)
+    pub fn pulse(&self) -> f32 {
+        0.5 + 0.5 * self.pulse_phase.sin()
+    }
+
+    // Сид цветовой вариации (сферы не респаунятся - постоянен всю жизнь)
+    pub fn color_seed(&self) -> u32 {
+        palette::color_seed(self.seed, 0)
+    }
+
+    // Яркость свечения для HDR (может превышать 1): растет на пике пульса
+    pub fn emissive_intensity(&self) -> f32 {
+        SPHERE_EMISSIVE * (1.0 + SPHERE_PULSE_EMISSIVE * self.pulse())
+    }
+
+    // Текущий радиус сферы в единицах пространства с учетом пульсации
+    pub fn radius(&self) -> f32 {
+        self.data.size * 0.5 * (1.0 + PULSE_AMPLITUDE * (self.pulse() * 2.0 - 1.0))
+    }
+
+    // Ориентация плоскости кольца: наклон вокруг X, затем прецессия вокруг Z
+    fn vortex_orientation(&self) -> Quat {
+        Quat::from_rotation_z(self.precession_angle) * Quat::from_rotation_x(self.vortex_inclination)
+    }
+
+    // Нормаль плоскости кольца вихря
+    pub fn vortex_normal(&self) -> Vec3 {
+        self.vortex_orientation() * Vec3::Z
+    }
+
+    // Радиус орбиты частиц вихря
+    pub fn vortex_orbit(&self) -> f32 {
+        self.radius() * VORTEX_RADIUS_FACTOR
+    }
+
+    // Мировые положения частиц вихря
+    // (count частиц, равномерно по кольцу)
+    pub fn vortex_particles(&self, count: usize) -> impl Iterator<Item = Vec3> + '_ {
+        let orientation = self.vortex_orientation();
+        let orbit = self.vortex_orbit();
+
+        (0..count).map(move |i| {
+            let angle = self.vortex_angle + TAU * i as f32 / count as f32;
+            self.data.position + orientation * Vec3::new(angle.cos() * orbit, angle.sin() * orbit, 0.0)
+        })
+    }
+}
+
+impl SpaceObject for EnergySphere {
+    fn get_data(&self) -> &SpaceObjectData {
+        &self.data
+    }
+
+    fn get_data_mut(&mut self) -> &mut SpaceObjectData {
+        &mut self.data
+    }
+
+    fn initialize_random(&mut self, rng: &mut StdRng, space: &SpaceDefinition) {
+        self.data.position = random_position_on_far_plane(rng, space);
+        self.data.size = rng.gen_range(MIN_SPHERE_SIZE..MAX_SPHERE_SIZE);
+        self.data.lifetime = 0.0;
+
+        // Медленный дрейф к случайной точке видового экрана
+        let viewport = space.get_viewport_dimensions();
+        let target = Vec3::new(
+            space.observer_position.x + rng.gen_range(-0.5..0.5) * viewport.x,
+            space.observer_position.y + rng.gen_range(-0.5..0.5) * viewport.y,
+            space.viewing_plane_z
+        );
+        let direction = (target - self.data.position).normalize_or_zero();
+        self.data.velocity = direction * rng.gen_range(MIN_DRIFT_SPEED..MAX_DRIFT_SPEED);
+
+        self.palette_index = rng.gen_range(0..SPHERE_PALETTE.len());
+        self.color = SPHERE_PALETTE[self.palette_index];
+        self.pulse_frequency = rng.gen_range(MIN_PULSE_FREQUENCY..MAX_PULSE_FREQUENCY);
+        self.pulse_phase = rng.gen_range(0.0..TAU);
+        self.vortex_angle = rng.gen_range(0.0..TAU);
+        self.vortex_inclination = rng.gen_range(MIN_VORTEX_INCLINATION..MAX_VORTEX_INCLINATION);
+        self.precession_angle = rng.gen_range(0.0..TAU);
+        self.precession_speed = rng.gen_range(-MAX_PRECESSION_SPEED..MAX_PRECESSION_SPEED);
+    }
+
+    fn update(&mut self, dt: f32, space: &SpaceDefinition) -> bool {
+        self.data.lifetime += dt;
+        if self.data.lifetime > self.data.max_lifetime {
+            return false;
+        }
+
+        self.data.position += self.data.velocity * dt;
+
+        // Сфера ушла за наблюдателя или за боковые границы - удаляем (респауна нет)
+        let dimensions = space.get_dimensions();
+        let pos = self.data.position;
+        if pos.z - space.observer_position.z < -30.0 || pos.x.abs() > dimensions.x || pos.y.abs() > dimensions.y {
+            return false;
+        }
+
+        // Пульсация и вихрь: на пике пульса частицы обращаются быстрее
+        self.pulse_phase = (self.pulse_phase + TAU * self.pulse_frequency * dt) % TAU;
+        let speed = VORTEX_BASE_SPEED * (1.0 + VORTEX_PULSE_SPEEDUP * self.pulse());
+        self.vortex_angle = (self.vortex_angle + speed * dt) % TAU;
+        self.precession_angle = (self.precession_angle + self.precession_speed * dt) % TAU;
+
+        self.data.scale = space.get_scale_factor(&self.data.position).powf(1.5) * self.data.size;
+
+        let profile = space.opacity_profile(self.data.object_type);
+        self.data.opacity = profile.opacity(
+            self.data.lifetime,
+            space.get_transparency_factor(&self.data.position),
+            space.viewing_plane_z - self.data.position.z
+        );
+
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Создать одну сферу на дальней плоскости и добавить ее в систему.
+// Возвращает ID новой сферы
+pub fn spawn_energy_sphere(system: &mut SpaceObjectSystem) -> usize {
+    let sphere_id = system.next_id;
+    system.next_id += 1;
+
+    let space_definition = system.space.clone();
+
+    let mut sphere = EnergySphere::new(sphere_id);
+    sphere.seed = derive_seed(system.seed(), sphere_id as u64, 0);
+    sphere.initialize_random(system.get_rng_mut(), &space_definition);
+    reroll_protected(&mut sphere, system.get_rng_mut(), &space_definition);
+    system.space.record_spawn(&sphere.data.position);
+
+    system.get_objects_mut()
+            .entry(SpaceObjectType::EnergySphere)
+            .or_default()
+            .push(Box::new(sphere));
+    system.population.record_spawn(SpaceObjectType::EnergySphere);
+    system.lifecycle.spawned(sphere_id, SpaceObjectType::EnergySphere);
+
+    sphere_id
+}
+
+// Создать count сфер сразу (сфер на сцене немного, очередь появлений не нужна)
+#[wasm_bindgen]
+pub fn spawn_energy_spheres(system_id: usize, count: usize) -> OpResult {
+    if count > MAX_SPHERES_PER_SPAWN {
+        return OpResult::invalid_argument(format!("count must not exceed {}", MAX_SPHERES_PER_SPAWN));
+    }
+
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            if system.fade.is_spawning_stopped() {
+                return OpResult::error(ErrorCode::SpawningStopped, "spawning is stopped by despawn_all");
+            }
+
+            for _ in 0..count {
+                spawn_energy_sphere(&mut system);
+            }
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+/// Данные энергетических сфер для рендеринга
+#[wasm_bindgen]
+pub struct EnergySphereDataArray {
+    ids: Vec<usize>,
+    positions: Vec<f32>,
+    radii: Vec<f32>,
+    scales: Vec<f32>,
+    opacities: Vec<f32>,
+    colors: Vec<f32>,
+    pulses: Vec<f32>,
+    vortex_positions: Vec<f32>,
+    vortex_particle_count: usize,
+    emissive: Vec<f32>,
+    color_seeds: Vec<u32>,
+    palette_indices: Vec<u8>,
+    motion_vectors: Vec<f32>,
+    bounds_min: Vec<f32>,
+    bounds_max: Vec<f32>,
+    bounding_spheres: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl EnergySphereDataArray {
+    #[wasm_bindgen(getter)]
+    pub fn ids(&self) -> Vec<usize> {
+        self.ids.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn positions(&self) -> Vec<f32> {
+        self.positions.clone()
+    }
+
+    // Радиус сферы в единицах пространства с учетом пульсации
+    #[wasm_bindgen(getter)]
+    pub fn radii(&self) -> Vec<f32> {
+        self.radii.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn scales(&self) -> Vec<f32> {
+        self.scales.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn opacities(&self) -> Vec<f32> {
+        self.opacities.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn colors(&self) -> Vec<f32> {
+        self.colors.clone()
+    }
+
+    // Уровень пульсации (0..1)
+    #[wasm_bindgen(getter)]
+    pub fn pulses(&self) -> Vec<f32> {
+        self.pulses.clone()
+    }
+
+    // Частицы вихрей: vortex_particle_count частиц (x, y, z) на сферу, сферы подряд
+    #[wasm_bindgen(getter)]
+    pub fn vortex_positions(&self) -> Vec<f32> {
+        self.vortex_positions.clone()
+    }
+
+    // Яркость свечения для HDR/bloom (множитель цвета, может превышать 1)
+    #[wasm_bindgen(getter)]
+    pub fn emissive(&self) -> Vec<f32> {
+        self.emissive.clone()
+    }
+
+    // Смещение проекции на окно за последний шаг (dx, dy в долях окна, y вниз)
+    #[wasm_bindgen(getter)]
+    pub fn motion_vectors(&self) -> Vec<f32> {
+        self.motion_vectors.clone()
+    }
+
+    // Мировой AABB (с кольцом вихря): min (x, y, z)
+    #[wasm_bindgen(getter)]
+    pub fn bounds_min(&self) -> Vec<f32> {
+        self.bounds_min.clone()
+    }
+
+    // Мировой AABB: max (x, y, z)
+    #[wasm_bindgen(getter)]
+    pub fn bounds_max(&self) -> Vec<f32> {
+        self.bounds_max.clone()
+    }
+
+    // Ограничивающая сфера объекта (cx, cy, cz, r)
+    #[wasm_bindgen(getter)]
+    pub fn bounding_spheres(&self) -> Vec<f32> {
+        self.bounding_spheres.clone()
+    }
+
+    // Постоянный сид цветовой вариации сферы
+    #[wasm_bindgen(getter)]
+    pub fn color_seeds(&self) -> Vec<u32> {
+        self.color_seeds.clone()
+    }
+
+    // Индекс цвета в палитре сфер (get_sphere_palette)
+    #[wasm_bindgen(getter)]
+    pub fn palette_indices(&self) -> Vec<u8> {
+        self.palette_indices.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn vortex_particle_count(&self) -> usize {
+        self.vortex_particle_count
+    }
+}
+
+// Собрать данные сфер системы
+pub fn collect_energy_spheres(system: &SpaceObjectSystem) -> EnergySphereDataArray {
+    let spheres: Vec<&EnergySphere> = system
+        .get_objects()
+        .get(&SpaceObjectType::EnergySphere)
+        .map(|objects| objects.iter().filter_map(|obj| obj.as_any().downcast_ref::<EnergySphere>()).collect())
+        .unwrap_or_default();
+
+    let count = spheres.len();
+    // В режиме покоя частиц вихря меньше; общий бюджет частиц делится между сферами
+    let mut particles = system.particle_count(VORTEX_PARTICLES);
+    if let Some(allocation) = system.particle_budget.allocation(ParticleSubsystem::Vortices) {
+        particles = particles.min(allocation / count.max(1));
+    }
+    let mut result = EnergySphereDataArray {
+        ids: Vec::with_capacity(count),
+        positions: Vec::with_capacity(count * 3),
+        radii: Vec::with_capacity(count),
+        scales: Vec::with_capacity(count),
+        opacities: Vec::with_capacity(count),
+        colors: Vec::with_capacity(count * 3),
+        pulses: Vec::with_capacity(count),
+        vortex_positions: Vec::with_capacity(count * particles * 3),
+        vortex_particle_count: particles,
+        emissive: Vec::with_capacity(count),
+        color_seeds: Vec::with_capacity(count),
+        palette_indices: Vec::with_capacity(count),
+        motion_vectors: Vec::with_capacity(count * 2),
+        bounds_min: Vec::with_capacity(count * 3),
+        bounds_max: Vec::with_capacity(count * 3),
+        bounding_spheres: Vec::with_capacity(count * 4),
+    };
+
+    for sphere in spheres {
+        result.ids.push(sphere.data.id);
+        result.positions.extend_from_slice(&sphere.data.position.to_array());
+        result.radii.push(sphere.radius());
+        result.scales.push(sphere.data.scale);
+        result.opacities.push(sphere.data.opacity);
+        result.colors.extend_from_slice(&system.export_color(sphere.color));
+        result.color_seeds.push(sphere.color_seed());
+        result.palette_indices.push(sphere.palette_index as u8);
+        result.pulses.push(sphere.pulse());
+        result.emissive.push(sphere.emissive_intensity());
+        result.motion_vectors.extend_from_slice(&system.motion.get(sphere.data.id).to_array());
+        let bounds = object_bounds(system, sphere);
+        result.bounds_min.extend_from_slice(&bounds.min.to_array());
+        result.bounds_max.extend_from_slice(&bounds.max.to_array());
+        result.bounding_spheres.extend_from_slice(&bounds.center.extend(bounds.radius).to_array());
+        for particle in sphere.vortex_particles(particles) {
+            result.vortex_positions.extend_from_slice(&particle.to_array());
+        }
+    }
+
+    result
+}
+
+#[wasm_bindgen]
+pub fn get_energy_spheres(system_id: usize) -> Option<EnergySphereDataArray> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| collect_energy_spheres(&system))
+}
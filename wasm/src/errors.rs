@@ -0,0 +1,83 @@
+/*
+ * errors.rs
+ *
+ * Результаты изменяющих вызовов для JS: флаг успеха, код ошибки и текст.
+ * Раньше такие функции возвращали bool, и хост не мог отличить отсутствующую
+ * систему от недопустимого аргумента или отсутствующего объекта.
+ *
+ * Отравленные блокировки не приводят к ошибке: данные восстанавливаются
+ * (utils::lock_or_recover), а факт восстановления попадает в диагностику.
+ *
+ * Ошибки не выбрасываются исключениями: вызовы делаются из цикла кадра, где
+ * исключение прервало бы весь кадр. Функции-запросы по-прежнему возвращают
+ * undefined (Option), если системы нет.
+ */
+
+use wasm_bindgen::prelude::*;
+
+/// Код ошибки вызова (Ok - успех)
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCode {
+    Ok = 0,
+    SystemNotFound = 1,   // Нет системы (или физического мира) с таким ID
+    NotFound = 2,         // Нет объекта, плоскости, модификатора или обработчика
+    InvalidArgument = 3,  // NaN, бесконечность или значение вне допустимого диапазона
+    // 4 - бывший LockPoisoned (блокировки теперь восстанавливаются); не переиспользовать
+    SpawningStopped = 5,  // Появление остановлено очисткой сцены (despawn_all)
+}
+
+/// Результат изменяющего вызова
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpResult {
+    code: ErrorCode,
+    message: String,
+}
+
+impl OpResult {
+    pub fn success() -> Self {
+        OpResult {
+            code: ErrorCode::Ok,
+            message: String::new(),
+        }
+    }
+
+    pub fn error(code: ErrorCode, message: impl Into<String>) -> Self {
+        OpResult {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn system_not_found(system_id: usize) -> Self {
+        Self::error(ErrorCode::SystemNotFound, format!("space object system {} not found", system_id))
+    }
+
+    pub fn invalid_argument(message: impl Into<String>) -> Self {
+        Self::error(ErrorCode::InvalidArgument, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::error(ErrorCode::NotFound, message)
+    }
+}
+
+#[wasm_bindgen]
+impl OpResult {
+    #[wasm_bindgen(getter)]
+    pub fn ok(&self) -> bool {
+        self.code == ErrorCode::Ok
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    // Текст для логов; пустой при успехе
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+}
@@ -0,0 +1,69 @@
+/*
+ * fallback.rs
+ *
+ * Восстановление после потери WebGL-контекста. Пока контекст не вернулся,
+ * хост может рисовать упрощенную сцену (например, на Canvas 2D) по
+ * минимальному состоянию: ID, позиция и масштаб видимых объектов в одном
+ * плотном буфере. После восстановления контекста mark_all_dirty заставляет
+ * следующий запрос дельт вернуть полный снимок, чтобы пересоздать все
+ * буферы рендера с нуля.
+ */
+
+use wasm_bindgen::prelude::*;
+
+use crate::errors::OpResult;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+/// Минимальное состояние для запасного рендера:
+/// objects - по 4 float на объект (x, y, z, scale) в порядке ids
+#[wasm_bindgen]
+pub struct MinimalState {
+    ids: Vec<u32>,
+    objects: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl MinimalState {
+    #[wasm_bindgen(getter)]
+    pub fn ids(&self) -> Vec<u32> {
+        self.ids.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn objects(&self) -> Vec<f32> {
+        self.objects.clone()
+    }
+}
+
+// Видимые объекты системы в минимальном виде (ожидающие респауна пропускаются)
+#[wasm_bindgen]
+pub fn get_minimal_state(system_id: usize) -> Option<MinimalState> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let mut state = MinimalState {
+        ids: Vec::new(),
+        objects: Vec::new(),
+    };
+
+    for obj in system.get_objects().values().flat_map(|objects| objects.iter()) {
+        if obj.is_waiting_for_respawn() {
+            continue;
+        }
+        let data = obj.get_data();
+        state.ids.push(data.id as u32);
+        state.objects.extend_from_slice(&[data.position.x, data.position.y, data.position.z, data.scale]);
+    }
+
+    Some(state)
+}
+
+// Пересоздать кэши рендера: следующий get_object_deltas вернет полный снимок
+#[wasm_bindgen]
+pub fn mark_all_dirty(system_id: usize) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.deltas.request_keyframe();
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
@@ -0,0 +1,172 @@
+/*
+ * frame_driver.rs
+ *
+ * Внутренний цикл кадров. Обычно хост сам вызывает requestAnimationFrame,
+ * update_space_object_system и экспорт данных для каждой системы - это
+ * несколько переходов JS <-> WASM на кадр. В режиме драйвера крейт сам
+ * регистрирует requestAnimationFrame (через часы platform.rs), обрабатывает
+ * очередь появления комет, обновляет перечисленные системы и вызывает один
+ * обработчик рендера:
+ *
+ *   render(dt: number, comets: Array<CometDataArray | undefined>)
+ *
+ * где comets[i] - данные видимых комет системы system_ids[i].
+ *
+ * Замыкание rAF создается один раз и не удаляется: остановка драйвера
+ * (в том числе из самого обработчика рендера) только отменяет следующий
+ * кадр, поэтому замыкание никогда не освобождается во время вызова.
+ * В нативной сборке окна нет, и драйвер не запускается.
+ */
+
+use std::cell::{OnceCell, RefCell};
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::errors::OpResult;
+use crate::neon_comets::{collect_visible_neon_comets, process_neon_comet_spawns};
+use crate::platform;
+use crate::space_objects::{update_space_object_system, SPACE_OBJECT_SYSTEMS};
+
+/// Шаг кадра по меткам времени requestAnimationFrame
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FramePacer {
+    last_timestamp: Option<f64>,
+    frames: u64,
+}
+
+impl FramePacer {
+    // Шаг в секундах до метки timestamp_ms; первый кадр после старта - нулевой,
+    // метки "назад во времени" дают нулевой шаг
+    pub fn tick(&mut self, timestamp_ms: f64) -> f32 {
+        let dt = match self.last_timestamp {
+            Some(last) => ((timestamp_ms - last) / 1000.0).max(0.0) as f32,
+            None => 0.0,
+        };
+        self.last_timestamp = Some(timestamp_ms);
+        self.frames += 1;
+        dt
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+}
+
+struct FrameDriver {
+    system_ids: Vec<usize>,
+    render: js_sys::Function,
+    pacer: FramePacer,
+    request_id: Option<i32>,
+}
+
+thread_local! {
+    static DRIVER: RefCell<Option<FrameDriver>> = const { RefCell::new(None) };
+    static TICK: OnceCell<Closure<dyn FnMut(f64)>> = const { OnceCell::new() };
+}
+
+// Запросить следующий кадр; None - окна нет (нативная сборка, воркер без rAF)
+fn request_frame() -> Option<i32> {
+    TICK.with(|tick| {
+        let tick = tick.get_or_init(|| Closure::<dyn FnMut(f64)>::new(on_animation_frame));
+        platform::request_animation_frame(tick.as_ref().unchecked_ref())
+    })
+}
+
+// Один шаг драйвера к метке timestamp_ms: очередь появления комет и обновление
+// систем system_ids, как это делает хост в своем цикле. Возвращает шаг в секундах
+pub fn drive_frame(pacer: &mut FramePacer, system_ids: &[usize], timestamp_ms: f64) -> f32 {
+    let dt = pacer.tick(timestamp_ms);
+    process_neon_comet_spawns(dt);
+    for &system_id in system_ids {
+        let _ = update_space_object_system(system_id, dt);
+    }
+    dt
+}
+
+fn on_animation_frame(timestamp_ms: f64) {
+    // Данные кадра копируются, чтобы обработчик мог остановить или перезапустить драйвер
+    let Some((system_ids, render, mut pacer)) = DRIVER.with(|driver| {
+        let mut driver = driver.borrow_mut();
+        let driver = driver.as_mut()?;
+        driver.request_id = None;
+        Some((driver.system_ids.clone(), driver.render.clone(), driver.pacer))
+    }) else {
+        return;
+    };
+
+    let dt = drive_frame(&mut pacer, &system_ids, timestamp_ms);
+    // Шаг сохраняется, только если драйвер не перезапустили во время обновления
+    DRIVER.with(|driver| {
+        if let Some(driver) = driver.borrow_mut().as_mut() {
+            if driver.pacer.frames() + 1 == pacer.frames() {
+                driver.pacer = pacer;
+            }
+        }
+    });
+
+    let comets = js_sys::Array::new();
+    for &system_id in &system_ids {
+        let data = SPACE_OBJECT_SYSTEMS
+            .get(&system_id)
+            .and_then(|system| collect_visible_neon_comets(&system));
+        comets.push(&data.map_or(JsValue::UNDEFINED, JsValue::from));
+    }
+    let _ = render.call2(&JsValue::NULL, &JsValue::from(dt), &comets);
+
+    // Следующий кадр - только если драйвер все еще работает и кадр не запрошен заново
+    DRIVER.with(|driver| {
+        if let Some(driver) = driver.borrow_mut().as_mut() {
+            if driver.request_id.is_none() {
+                driver.request_id = request_frame();
+            }
+        }
+    });
+}
+
+// Запустить внутренний цикл кадров для систем system_ids с обработчиком
+// render(dt, comets). Повторный запуск заменяет системы и обработчик
+#[wasm_bindgen]
+pub fn start_frame_driver(system_ids: Vec<usize>, render: js_sys::Function) -> OpResult {
+    if let Some(missing) = system_ids.iter().find(|id| !SPACE_OBJECT_SYSTEMS.contains_key(id)) {
+        return OpResult::system_not_found(*missing);
+    }
+
+    stop_frame_driver();
+    let Some(request_id) = request_frame() else {
+        return OpResult::not_found("requestAnimationFrame is not available");
+    };
+
+    DRIVER.with(|driver| {
+        *driver.borrow_mut() = Some(FrameDriver {
+            system_ids,
+            render,
+            pacer: FramePacer::default(),
+            request_id: Some(request_id),
+        });
+    });
+    OpResult::success()
+}
+
+#[wasm_bindgen]
+pub fn stop_frame_driver() -> OpResult {
+    let Some(driver) = DRIVER.with(|driver| driver.borrow_mut().take()) else {
+        return OpResult::not_found("frame driver is not running");
+    };
+
+    if let Some(request_id) = driver.request_id {
+        platform::cancel_animation_frame(request_id);
+    }
+    OpResult::success()
+}
+
+#[wasm_bindgen]
+pub fn is_frame_driver_running() -> bool {
+    DRIVER.with(|driver| driver.borrow().is_some())
+}
+
+// Кадров, выполненных драйвером с последнего запуска
+#[wasm_bindgen]
+pub fn get_frame_driver_frames() -> f64 {
+    DRIVER.with(|driver| driver.borrow().as_ref().map_or(0.0, |driver| driver.pacer.frames() as f64))
+}
@@ -0,0 +1,169 @@
+/*
+ * frame_graph.rs
+ *
+ * Порядок проходов обновления, вычисляемый по объявленным зависимостям.
+ * Каждый проход перечисляет ресурсы, которые он читает и пишет. Ресурс
+ * записывается как "имя@версия": проход, пишущий objects@2, порождает
+ * новую версию objects из objects@1. Отсюда следуют правила порядка:
+ *
+ * - читатель name@v выполняется после писателя name@v;
+ * - писатель name@v выполняется после писателя name@(v-1) и после всех
+ *   читателей name@(v-1) - иначе они увидели бы уже измененные данные.
+ *
+ * Версия 0 - состояние на начало кадра, у нее нет писателя. Порядок
+ * объявления проходов влияет только на выбор среди независимых проходов.
+ */
+
+use std::collections::HashMap;
+
+/// Проход обновления с объявленными зависимостями
+pub struct Pass<F> {
+    pub name: &'static str,
+    pub reads: &'static [&'static str],
+    pub writes: &'static [&'static str],
+    pub run: F,
+}
+
+/// Граф проходов с вычисленным порядком выполнения
+pub struct FrameGraph<F> {
+    passes: Vec<Pass<F>>,
+    edges: Vec<(usize, usize)>,
+    order: Vec<usize>,
+}
+
+// Разобрать "имя@версия" (без версии - версия 0)
+fn parse_resource(resource: &str) -> Result<(&str, u32), String> {
+    match resource.split_once('@') {
+        Some((name, version)) => version
+            .parse()
+            .map(|version| (name, version))
+            .map_err(|_| format!("Invalid resource version in '{}'", resource)),
+        None => Ok((resource, 0)),
+    }
+}
+
+impl<F> FrameGraph<F> {
+    // Построить граф и вычислить порядок; ошибка при неоднозначных писателях,
+    // чтении несуществующей версии или циклической зависимости
+    pub fn new(passes: Vec<Pass<F>>) -> Result<Self, String> {
+        // Писатель каждой версии ресурса
+        let mut writers: HashMap<(&str, u32), usize> = HashMap::new();
+        for (index, pass) in passes.iter().enumerate() {
+            for resource in pass.writes {
+                let key = parse_resource(resource)?;
+                if key.1 == 0 {
+                    return Err(format!("Pass '{}' writes initial version of '{}'", pass.name, key.0));
+                }
+                if let Some(other) = writers.insert(key, index) {
+                    return Err(format!("'{}' is written by both '{}' and '{}'", resource, passes[other].name, pass.name));
+                }
+            }
+        }
+
+        // Читатели каждой версии ресурса
+        let mut readers: HashMap<(&str, u32), Vec<usize>> = HashMap::new();
+        for (index, pass) in passes.iter().enumerate() {
+            for resource in pass.reads {
+                let key = parse_resource(resource)?;
+                if key.1 > 0 && !writers.contains_key(&key) {
+                    return Err(format!("Pass '{}' reads '{}' which nobody writes", pass.name, resource));
+                }
+                readers.entry(key).or_default().push(index);
+            }
+        }
+
+        let mut edges = Vec::new();
+        for (&(name, version), &writer) in &writers {
+            // Читатели этой версии - после писателя
+            for &reader in readers.get(&(name, version)).into_iter().flatten() {
+                if reader != writer {
+                    edges.push((writer, reader));
+                }
+            }
+
+            // Писатель - после писателя и читателей предыдущей версии
+            let previous = (name, version - 1);
+            if let Some(&previous_writer) = writers.get(&previous) {
+                edges.push((previous_writer, writer));
+            }
+            for &reader in readers.get(&previous).into_iter().flatten() {
+                if reader != writer {
+                    edges.push((reader, writer));
+                }
+            }
+        }
+        edges.sort_unstable();
+        edges.dedup();
+
+        // Топологическая сортировка (Кан); среди готовых проходов - первый объявленный
+        let mut incoming = vec![0usize; passes.len()];
+        for &(_, to) in &edges {
+            incoming[to] += 1;
+        }
+
+        let mut order = Vec::with_capacity(passes.len());
+        let mut ready: Vec<usize> = (0..passes.len()).filter(|&i| incoming[i] == 0).collect();
+        while let Some(position) = ready.iter().enumerate().min_by_key(|(_, &i)| i).map(|(p, _)| p) {
+            let current = ready.swap_remove(position);
+            order.push(current);
+
+            for &(from, to) in &edges {
+                if from == current {
+                    incoming[to] -= 1;
+                    if incoming[to] == 0 {
+                        ready.push(to);
+                    }
+                }
+            }
+        }
+
+        if order.len() != passes.len() {
+            return Err("Update passes have a dependency cycle".to_string());
+        }
+
+        Ok(Self { passes, edges, order })
+    }
+
+    // Проходы в порядке выполнения
+    pub fn ordered(&self) -> impl Iterator<Item = &Pass<F>> {
+        self.order.iter().map(|&index| &self.passes[index])
+    }
+
+    // Имена проходов в порядке выполнения
+    pub fn order_names(&self) -> Vec<String> {
+        self.ordered().map(|pass| pass.name.to_string()).collect()
+    }
+
+    // Граф в формате Graphviz DOT: ребра подписаны ресурсами, через которые связаны проходы
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph update {\n    rankdir=LR;\n");
+
+        for (step, pass) in self.ordered().enumerate() {
+            dot.push_str(&format!("    \"{}\" [label=\"{}. {}\"];\n", pass.name, step + 1, pass.name));
+        }
+
+        for &(from, to) in &self.edges {
+            let from_pass = &self.passes[from];
+            let to_pass = &self.passes[to];
+
+            // Ресурсы, общие для двух проходов (по имени, без версии)
+            let names = |pass: &Pass<F>| -> Vec<&'static str> {
+                pass.reads.iter().chain(pass.writes.iter())
+                    .map(|r| r.split('@').next().unwrap_or(r))
+                    .collect()
+            };
+            let to_names = names(to_pass);
+            let mut shared: Vec<&str> = names(from_pass).into_iter().filter(|n| to_names.contains(n)).collect();
+            shared.sort_unstable();
+            shared.dedup();
+
+            dot.push_str(&format!(
+                "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                from_pass.name, to_pass.name, shared.join(", ")
+            ));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
@@ -0,0 +1,134 @@
+/*
+ * frame_hook.rs
+ *
+ * Пользовательский JS-обработчик кадра. Если он задан, то вызывается после
+ * внутреннего обновления системы (update_space_object_system) и получает
+ * легкий прокси FrameHookView, через который можно подправить отдельные
+ * объекты (скорость, цвет) до экспорта данных кадра - без пересборки Rust.
+ *
+ * Обработчик вызывается, когда блокировка системы уже снята: каждый метод
+ * прокси берет ее заново, поэтому повторный вход из JS безопасен.
+ * js_sys::Function не Send, поэтому обработчики хранятся в thread_local
+ * (WASM однопоточный).
+ */
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+
+use crate::errors::OpResult;
+use crate::neon_comets::NeonComet;
+use crate::diagnostics;
+use crate::space_core::Vec3Wrapper;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+thread_local! {
+    static FRAME_HOOKS: RefCell<HashMap<usize, js_sys::Function>> = RefCell::new(HashMap::new());
+}
+
+/// Прокси системы, передаваемый обработчику кадра
+#[wasm_bindgen]
+pub struct FrameHookView {
+    system_id: usize,
+}
+
+#[wasm_bindgen]
+impl FrameHookView {
+    #[wasm_bindgen(getter)]
+    pub fn system_id(&self) -> usize {
+        self.system_id
+    }
+
+    // ID активных объектов системы
+    pub fn ids(&self) -> Vec<usize> {
+        let Some(system) = SPACE_OBJECT_SYSTEMS.get(&self.system_id) else {
+            return Vec::new();
+        };
+
+        let mut ids: Vec<usize> = system
+            .get_objects()
+            .values()
+            .flat_map(|objects| objects.iter())
+            .filter(|obj| !obj.is_waiting_for_respawn())
+            .map(|obj| obj.get_data().id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    pub fn get_velocity(&self, object_id: usize) -> Option<Vec3Wrapper> {
+        let system = SPACE_OBJECT_SYSTEMS.get(&self.system_id)?;
+        system
+            .find_object(object_id)
+            .map(|obj| Vec3Wrapper::from(obj.get_data().velocity))
+    }
+
+    pub fn set_velocity(&self, object_id: usize, x: f32, y: f32, z: f32) -> bool {
+        let velocity = Vec3::new(x, y, z);
+        if !velocity.is_finite() {
+            return false;
+        }
+
+        let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&self.system_id) else {
+            return false;
+        };
+        match system.find_object_mut(object_id) {
+            Some(obj) => {
+                obj.get_data_mut().velocity = velocity;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Цвет RGB (0..1); поддерживается только объектами с цветом (неоновые кометы)
+    pub fn set_color(&self, object_id: usize, r: f32, g: f32, b: f32) -> bool {
+        let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&self.system_id) else {
+            return false;
+        };
+        let Some(comet) = system
+            .find_object_mut(object_id)
+            .and_then(|obj| obj.as_any_mut().downcast_mut::<NeonComet>())
+        else {
+            return false;
+        };
+
+        comet.color = [r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0)];
+        true
+    }
+}
+
+// Задать обработчик кадра системы: hook(view: FrameHookView)
+#[wasm_bindgen]
+pub fn set_frame_hook(system_id: usize, hook: js_sys::Function) -> OpResult {
+    if !SPACE_OBJECT_SYSTEMS.contains_key(&system_id) {
+        return OpResult::system_not_found(system_id);
+    }
+
+    FRAME_HOOKS.with(|hooks| hooks.borrow_mut().insert(system_id, hook));
+    OpResult::success()
+}
+
+#[wasm_bindgen]
+pub fn clear_frame_hook(system_id: usize) -> OpResult {
+    match FRAME_HOOKS.with(|hooks| hooks.borrow_mut().remove(&system_id)) {
+        Some(_) => OpResult::success(),
+        None => OpResult::not_found(format!("no frame hook for system {}", system_id)),
+    }
+}
+
+// Вызвать обработчик кадра системы, если он задан.
+// Блокировка системы к этому моменту должна быть снята
+pub(crate) fn run_frame_hook(system_id: usize) {
+    // Клонируем функцию, чтобы обработчик мог сам заменить или снять себя
+    let Some(hook) = FRAME_HOOKS.with(|hooks| hooks.borrow().get(&system_id).cloned()) else {
+        return;
+    };
+
+    let view = FrameHookView { system_id };
+    if let Err(error) = hook.call1(&JsValue::NULL, &JsValue::from(view)) {
+        diagnostics::log_limited("frame_hook_failed", || format!("Frame hook of system {} failed: {:?}", system_id, error));
+    }
+}
@@ -0,0 +1,250 @@
+/*
+ * gltf_export.rs
+ *
+ * Экспорт снимка сцены в glTF 2.0 (бинарный контейнер GLB), чтобы сцену
+ * можно было открыть во внешних 3D-инструментах или использовать как
+ * статический ассет.
+ *
+ * В снимок попадают видимые объекты системы (облако точек по типам)
+ * и, если передан, спроецированный гиперкуб: треугольная сетка из 24
+ * квадратных граней и набор ребер. Кристаллы пока не имеют сгенерированных
+ * оболочек, поэтому экспортируются только их позиции, как и остальные объекты.
+ *
+ * JSON собирается вручную: формат фиксирован и мал, а отдельная
+ * зависимость ради него не нужна.
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+
+use crate::hypercube::Hypercube;
+use crate::space_core::Vec3Wrapper;
+use crate::space_objects::{SpaceObjectSystem, SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+
+// Константы формата GLB и glTF
+const GLB_MAGIC: u32 = 0x4654_6C67;       // "glTF"
+pub(crate) const GLB_VERSION: u32 = 2;
+const CHUNK_JSON: u32 = 0x4E4F_534A;      // "JSON"
+const CHUNK_BIN: u32 = 0x004E_4942;       // "BIN\0"
+const COMPONENT_FLOAT: u32 = 5126;
+const COMPONENT_UNSIGNED_SHORT: u32 = 5123;
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const MODE_POINTS: u32 = 0;
+const MODE_LINES: u32 = 1;
+const MODE_TRIANGLES: u32 = 4;
+
+// Сборщик glTF: общий бинарный буфер и JSON-описания его частей
+#[derive(Default)]
+struct GltfBuilder {
+    bin: Vec<u8>,
+    buffer_views: Vec<String>,
+    accessors: Vec<String>,
+    meshes: Vec<String>,
+    nodes: Vec<String>,
+}
+
+impl GltfBuilder {
+    // Добавить участок буфера с выравниванием на 4 байта
+    fn push_view(&mut self, bytes: &[u8], target: u32) -> usize {
+        while !self.bin.len().is_multiple_of(4) {
+            self.bin.push(0);
+        }
+
+        self.buffer_views.push(format!(
+            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":{}}}"#,
+            self.bin.len(), bytes.len(), target
+        ));
+        self.bin.extend_from_slice(bytes);
+        self.buffer_views.len() - 1
+    }
+
+    // Добавить массив позиций (VEC3 float) с обязательными для POSITION min/max
+    fn add_positions(&mut self, positions: &[Vec3]) -> usize {
+        let bytes: Vec<u8> = positions.iter()
+            .flat_map(|p| [p.x, p.y, p.z])
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let view = self.push_view(&bytes, TARGET_ARRAY_BUFFER);
+
+        let min = positions.iter().fold(Vec3::splat(f32::MAX), |acc, p| acc.min(*p));
+        let max = positions.iter().fold(Vec3::splat(f32::MIN), |acc, p| acc.max(*p));
+
+        self.accessors.push(format!(
+            r#"{{"bufferView":{},"componentType":{},"count":{},"type":"VEC3","min":[{},{},{}],"max":[{},{},{}]}}"#,
+            view, COMPONENT_FLOAT, positions.len(), min.x, min.y, min.z, max.x, max.y, max.z
+        ));
+        self.accessors.len() - 1
+    }
+
+    // Добавить массив индексов (SCALAR unsigned short)
+    fn add_indices(&mut self, indices: &[u16]) -> usize {
+        let bytes: Vec<u8> = indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+        let view = self.push_view(&bytes, TARGET_ELEMENT_ARRAY_BUFFER);
+
+        self.accessors.push(format!(
+            r#"{{"bufferView":{},"componentType":{},"count":{},"type":"SCALAR"}}"#,
+            view, COMPONENT_UNSIGNED_SHORT, indices.len()
+        ));
+        self.accessors.len() - 1
+    }
+
+    // Добавить сетку из готовых JSON-примитивов и узел, который на нее ссылается
+    fn add_mesh(&mut self, name: &str, primitives: &[String]) {
+        self.meshes.push(format!(r#"{{"name":"{}","primitives":[{}]}}"#, name, primitives.join(",")));
+        self.nodes.push(format!(r#"{{"name":"{}","mesh":{}}}"#, name, self.meshes.len() - 1));
+    }
+
+    // Облако точек
+    fn add_point_cloud(&mut self, name: &str, positions: &[Vec3]) {
+        if positions.is_empty() {
+            return;
+        }
+
+        let accessor = self.add_positions(positions);
+        let primitive = format!(r#"{{"attributes":{{"POSITION":{}}},"mode":{}}}"#, accessor, MODE_POINTS);
+        self.add_mesh(name, &[primitive]);
+    }
+
+    // Собрать GLB: заголовок, JSON-чанк (дополнен пробелами) и BIN-чанк (дополнен нулями).
+    // Схема glTF запрещает пустые массивы и буфер нулевой длины, поэтому у пустой
+    // сцены они опускаются вместе с BIN-чанком
+    fn finish(mut self) -> Vec<u8> {
+        while !self.bin.len().is_multiple_of(4) {
+            self.bin.push(0);
+        }
+
+        let mut fields = vec![
+            r#""asset":{"version":"2.0","generator":"hypercube-wasm"}"#.to_string(),
+            r#""scene":0"#.to_string(),
+        ];
+        if self.nodes.is_empty() {
+            fields.push(r#""scenes":[{}]"#.to_string());
+        } else {
+            let node_ids: Vec<String> = (0..self.nodes.len()).map(|i| i.to_string()).collect();
+            fields.push(format!(r#""scenes":[{{"nodes":[{}]}}]"#, node_ids.join(",")));
+        }
+        for (name, items) in [
+            ("nodes", &self.nodes),
+            ("meshes", &self.meshes),
+            ("accessors", &self.accessors),
+            ("bufferViews", &self.buffer_views),
+        ] {
+            if !items.is_empty() {
+                fields.push(format!(r#""{}":[{}]"#, name, items.join(",")));
+            }
+        }
+        if !self.bin.is_empty() {
+            fields.push(format!(r#""buffers":[{{"byteLength":{}}}]"#, self.bin.len()));
+        }
+        let mut json = format!("{{{}}}", fields.join(",")).into_bytes();
+        while !json.len().is_multiple_of(4) {
+            json.push(b' ');
+        }
+
+        let bin_chunk = if self.bin.is_empty() { 0 } else { 8 + self.bin.len() };
+        let total = 12 + 8 + json.len() + bin_chunk;
+        let mut glb = Vec::with_capacity(total);
+        for value in [GLB_MAGIC, GLB_VERSION, total as u32, json.len() as u32, CHUNK_JSON] {
+            glb.extend_from_slice(&value.to_le_bytes());
+        }
+        glb.extend_from_slice(&json);
+        if bin_chunk > 0 {
+            glb.extend_from_slice(&(self.bin.len() as u32).to_le_bytes());
+            glb.extend_from_slice(&CHUNK_BIN.to_le_bytes());
+            glb.extend_from_slice(&self.bin);
+        }
+        glb
+    }
+}
+
+// Добавить видимые объекты системы: по облаку точек на каждый тип
+fn add_system_objects(builder: &mut GltfBuilder, system: &SpaceObjectSystem) {
+    let types = [
+        (SpaceObjectType::NeonComet, "NeonComets"),
+        (SpaceObjectType::EnergySphere, "EnergySpheres"),
+        (SpaceObjectType::PolygonalCrystal, "PolygonalCrystals"),
+    ];
+
+    for (object_type, name) in types {
+        let Some(objects) = system.get_objects().get(&object_type) else {
+            continue;
+        };
+
+        let positions: Vec<Vec3> = objects.iter()
+            .filter(|obj| !obj.is_waiting_for_respawn())
+            .map(|obj| obj.get_data().position)
+            .collect();
+        builder.add_point_cloud(name, &positions);
+    }
+}
+
+// Добавить спроецированный гиперкуб: грани треугольниками и ребра линиями
+fn add_hypercube(builder: &mut GltfBuilder, hypercube: &Hypercube, w_camera: f64, center: Vec3, scale: f32) {
+    let positions: Vec<Vec3> = hypercube.get_projected_vertices(w_camera)
+        .chunks_exact(3)
+        .map(|p| center + Vec3::new(p[0] as f32, p[1] as f32, p[2] as f32) * scale)
+        .collect();
+    if positions.is_empty() {
+        return;
+    }
+
+    let position_accessor = builder.add_positions(&positions);
+    let mut primitives = Vec::new();
+
+    // Свет не важен для геометрии - берем индексы граней из затенения
+    if let Some(shading) = hypercube.compute_shading(&[0.0, 0.0, 0.0, 1.0], w_camera) {
+        let triangles: Vec<u16> = shading.face_indices()
+            .chunks_exact(4)
+            .flat_map(|q| [q[0], q[1], q[2], q[0], q[2], q[3]])
+            .map(|i| i as u16)
+            .collect();
+        let accessor = builder.add_indices(&triangles);
+        primitives.push(format!(
+            r#"{{"attributes":{{"POSITION":{}}},"indices":{},"mode":{}}}"#,
+            position_accessor, accessor, MODE_TRIANGLES
+        ));
+    }
+
+    let edges: Vec<u16> = hypercube.get_edges().iter().map(|&i| i as u16).collect();
+    let accessor = builder.add_indices(&edges);
+    primitives.push(format!(
+        r#"{{"attributes":{{"POSITION":{}}},"indices":{},"mode":{}}}"#,
+        position_accessor, accessor, MODE_LINES
+    ));
+
+    builder.add_mesh("Hypercube", &primitives);
+}
+
+// Снимок объектов системы в формате GLB
+pub fn build_gltf(system: &SpaceObjectSystem, hypercube: Option<(&Hypercube, f64, Vec3, f32)>) -> Vec<u8> {
+    let mut builder = GltfBuilder::default();
+    add_system_objects(&mut builder, system);
+
+    if let Some((hypercube, w_camera, center, scale)) = hypercube {
+        add_hypercube(&mut builder, hypercube, w_camera, center, scale);
+    }
+
+    builder.finish()
+}
+
+// Экспорт текущей сцены системы в GLB (Uint8Array в JS)
+#[wasm_bindgen]
+pub fn export_gltf(system_id: usize) -> Option<Vec<u8>> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| build_gltf(&system, None))
+}
+
+// Экспорт сцены вместе с гиперкубом, размещенным в ней так же, как в track_hypercube_crossings
+#[wasm_bindgen]
+pub fn export_gltf_with_hypercube(
+    system_id: usize,
+    hypercube: &Hypercube,
+    w_camera: f64,
+    center: &Vec3Wrapper,
+    scale: f32,
+) -> Option<Vec<u8>> {
+    let center: Vec3 = center.clone().into();
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| build_gltf(&system, Some((hypercube, w_camera, center, scale))))
+}
@@ -0,0 +1,64 @@
+/*
+ * golden.rs
+ * 
+ * Регрессионная проверка "золотого" состояния: детерминированная сцена
+ * с фиксированным seed прогоняется N шагов, после чего состояние объектов
+ * (квантованные позиции, размеры, прозрачность) сворачивается в хеш.
+ * Хеши фиксируются в tests/golden.rs, поэтому любое изменение математики
+ * обновления, меняющее поведение, сразу становится заметным.
+ */
+
+use crate::neon_comets::spawn_neon_comet;
+use crate::space_objects::SpaceObjectSystem;
+
+// Шаг квантования (1/1000 единицы пространства) сглаживает шум последних бит f32
+const QUANTIZATION: f32 = 1000.0;
+
+// FNV-1a: простой и стабильный между платформами хеш
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+fn fnv_mix(hash: u64, value: i64) -> u64 {
+    value.to_le_bytes().iter().fold(hash, |h, byte| (h ^ *byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+fn quantize(value: f32) -> i64 {
+    (value * QUANTIZATION).round() as i64
+}
+
+// Создать детерминированную сцену с заданным количеством комет
+pub fn seeded_scene(seed: u64, comet_count: usize) -> SpaceObjectSystem {
+    let mut system = SpaceObjectSystem::with_seed(seed);
+    for _ in 0..comet_count {
+        spawn_neon_comet(&mut system);
+    }
+    system
+}
+
+// Прогнать steps шагов симуляции с фиксированным dt
+pub fn run_steps(system: &mut SpaceObjectSystem, steps: usize, dt: f32) {
+    for _ in 0..steps {
+        system.update(dt);
+    }
+}
+
+// Свернуть состояние системы в хеш. Объекты сортируются по ID,
+// чтобы порядок обхода HashMap не влиял на результат
+pub fn state_hash(system: &SpaceObjectSystem) -> u64 {
+    let mut objects: Vec<_> = system
+        .get_objects()
+        .values()
+        .flat_map(|objects| objects.iter())
+        .map(|obj| obj.get_data())
+        .collect();
+    objects.sort_by_key(|data| data.id);
+    
+    objects.iter().fold(FNV_OFFSET, |hash, data| {
+        let hash = fnv_mix(hash, data.id as i64);
+        let hash = fnv_mix(hash, quantize(data.position.x));
+        let hash = fnv_mix(hash, quantize(data.position.y));
+        let hash = fnv_mix(hash, quantize(data.position.z));
+        let hash = fnv_mix(hash, quantize(data.size));
+        fnv_mix(hash, quantize(data.opacity))
+    })
+}
@@ -0,0 +1,106 @@
+/*
+ * hit_test.rs
+ *
+ * Поиск объектов под курсором для подсветки при наведении и подсказок.
+ * Центр объекта проецируется из наблюдателя на видовую плоскость и далее
+ * в пиксели окна (SpaceDefinition::project_to_viewport), а след объекта -
+ * круг радиусом в половину его масштаба, пересчитанный в пиксели на
+ * глубине объекта. Попаданием считается пересечение следа с кругом
+ * курсора радиусом tolerance_px.
+ *
+ * Размер окна в пикселях задается set_screen_size (CSS-пиксели, как у
+ * координат событий мыши).
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::Vec2;
+
+use crate::errors::OpResult;
+use crate::space_objects::{SpaceObjectSystem, SPACE_OBJECT_SYSTEMS};
+
+/// Объекты под курсором, от ближнего к дальнему
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct HitTestResult {
+    ids: Vec<usize>,
+    depths: Vec<f32>,
+    distances_px: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl HitTestResult {
+    #[wasm_bindgen(getter)]
+    pub fn ids(&self) -> Vec<usize> {
+        self.ids.clone()
+    }
+
+    // Расстояние от наблюдателя
+    #[wasm_bindgen(getter)]
+    pub fn depths(&self) -> Vec<f32> {
+        self.depths.clone()
+    }
+
+    // Расстояние от курсора до центра объекта на экране
+    #[wasm_bindgen(getter)]
+    pub fn distances_px(&self) -> Vec<f32> {
+        self.distances_px.clone()
+    }
+}
+
+// Объекты системы, след которых на экране пересекает круг курсора
+pub fn hit_test_system(system: &SpaceObjectSystem, screen_x: f32, screen_y: f32, tolerance_px: f32) -> HitTestResult {
+    let space = &system.space;
+    if space.screen_size.x <= 0.0 || space.screen_size.y <= 0.0 {
+        return HitTestResult::default();
+    }
+
+    let cursor = Vec2::new(screen_x, screen_y);
+    let tolerance = tolerance_px.max(0.0);
+
+    let mut hits: Vec<(usize, f32, f32)> = system
+        .get_objects()
+        .values()
+        .flat_map(|objects| objects.iter())
+        .filter(|obj| !obj.is_waiting_for_respawn())
+        .map(|obj| obj.get_data())
+        .filter_map(|data| {
+            let screen = space.project_to_viewport(&data.position)? * space.screen_size;
+            let depth = data.position.z - space.observer_position.z;
+            let radius = data.scale * 0.5 * space.pixels_per_unit(depth);
+
+            let distance = screen.distance(cursor);
+            (distance <= radius + tolerance)
+                .then(|| (data.id, data.position.distance(space.observer_position), distance))
+        })
+        .collect();
+    hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    HitTestResult {
+        ids: hits.iter().map(|hit| hit.0).collect(),
+        depths: hits.iter().map(|hit| hit.1).collect(),
+        distances_px: hits.iter().map(|hit| hit.2).collect(),
+    }
+}
+
+// Размер окна в CSS-пикселях
+#[wasm_bindgen]
+pub fn set_screen_size(system_id: usize, width_px: f32, height_px: f32) -> OpResult {
+    if !(width_px.is_finite() && height_px.is_finite()) {
+        return OpResult::invalid_argument("screen size must be finite");
+    }
+
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.space.screen_size = Vec2::new(width_px.max(0.0), height_px.max(0.0));
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn hit_test(system_id: usize, screen_x: f32, screen_y: f32, tolerance_px: f32) -> Option<HitTestResult> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| hit_test_system(&system, screen_x, screen_y, tolerance_px))
+}
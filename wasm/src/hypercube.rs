@@ -40,11 +40,73 @@ impl Point4D {
     }
 }
 
+// Положение точки обзора для диаграммы Шлегеля относительно центра выбранной ячейки.
+// При 2.0 внутренний (противоположный) куб получается в 3 раза меньше внешнего
+const SCHLEGEL_EYE_FACTOR: f64 = 2.0;
+
+// Наибольшее число отрезков на ребро при подразбиении: 32 ребра по 257 точек
+// уже неотличимы от гладких дуг, а значение приходит из JS без ограничений
+pub const MAX_EDGE_SEGMENTS: usize = 256;
+
+// Параметры вращения гиперкуба перетаскиванием указателя
+const DRAG_SENSITIVITY: f64 = 0.01;      // Радиан на пиксель перетаскивания
+const DRAG_INERTIA_GAIN: f64 = 8.0;      // Перевод угла перетаскивания в угловую скорость инерции
+const DRAG_INERTIA_DAMPING: f64 = 3.0;   // Скорость затухания инерции (1/сек)
+const MIN_INERTIA_SPEED: f64 = 1e-4;     // Ниже этой скорости инерция останавливается
+
+// Индексы плоскостей вращения в массиве угловой скорости
+const PLANE_XY: usize = 0;
+const PLANE_XZ: usize = 1;
+const PLANE_XW: usize = 2;
+const PLANE_YW: usize = 4;
+
+// Доля фонового освещения при 4D затенении
+const SHADING_AMBIENT: f64 = 0.25;
+
+/// Результат 4D затенения гиперкуба в виде плоских массивов
+#[wasm_bindgen]
+pub struct HypercubeShading {
+    vertex_intensities: Vec<f32>,
+    vertex_fog: Vec<f32>,
+    face_indices: Vec<u32>,
+    face_intensities: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl HypercubeShading {
+    // Интенсивность освещения каждой из 16 вершин (0.0 - 1.0)
+    #[wasm_bindgen(getter)]
+    pub fn vertex_intensities(&self) -> Vec<f32> {
+        self.vertex_intensities.clone()
+    }
+    
+    // Коэффициент тумана по глубине W для каждой вершины (0 - ближняя, 1 - дальняя)
+    #[wasm_bindgen(getter)]
+    pub fn vertex_fog(&self) -> Vec<f32> {
+        self.vertex_fog.clone()
+    }
+    
+    // Индексы вершин 24 квадратных граней (по 4 на грань)
+    #[wasm_bindgen(getter)]
+    pub fn face_indices(&self) -> Vec<u32> {
+        self.face_indices.clone()
+    }
+    
+    // Интенсивность освещения каждой грани по ее 4D нормали
+    #[wasm_bindgen(getter)]
+    pub fn face_intensities(&self) -> Vec<f32> {
+        self.face_intensities.clone()
+    }
+}
+
 // Структура, представляющая Гиперкуб
 #[wasm_bindgen]
+#[derive(Clone, Debug)]
 pub struct Hypercube {
     vertices: Vec<Point4D>,
     edges: Vec<(usize, usize)>,
+    // Угловая скорость инерции вращения по плоскостям XY, XZ, XW, YZ, YW, ZW (рад/сек)
+    angular_velocity: [f64; 6],
 }
 
 #[wasm_bindgen]
@@ -77,7 +139,7 @@ impl Hypercube {
             }
         }
         
-        Self { vertices, edges }
+        Self { vertices, edges, angular_velocity: [0.0; 6] }
     }
     
     // Применяем вращение к гиперкубу в разных плоскостях
@@ -150,6 +212,51 @@ impl Hypercube {
         self.vertices = rotated_vertices;
     }
     
+    // Вращение перетаскиванием указателя: без модификатора горизонтальное движение
+    // вращает в плоскости XZ, вертикальное - в XY; с модификатором - в XW и YW,
+    // "выворачивая" гиперкуб через четвертое измерение. Перетаскивание также
+    // задает инерцию, которая затухает в update_inertia
+    pub fn apply_drag_rotation(&mut self, dx: f64, dy: f64, modifier: bool) {
+        let (plane_x, plane_y) = if modifier {
+            (PLANE_XW, PLANE_YW)
+        } else {
+            (PLANE_XZ, PLANE_XY)
+        };
+        
+        let mut angles = [0.0; 6];
+        angles[plane_x] = dx * DRAG_SENSITIVITY;
+        angles[plane_y] = dy * DRAG_SENSITIVITY;
+        self.rotate_planes(&angles);
+        
+        // Новое перетаскивание заменяет инерцию в затронутых плоскостях
+        self.angular_velocity[plane_x] = angles[plane_x] * DRAG_INERTIA_GAIN;
+        self.angular_velocity[plane_y] = angles[plane_y] * DRAG_INERTIA_GAIN;
+    }
+    
+    // Продолжить вращение по инерции после перетаскивания.
+    // Возвращает true, пока гиперкуб еще вращается
+    pub fn update_inertia(&mut self, dt: f64) -> bool {
+        if self.angular_velocity.iter().all(|v| v.abs() < MIN_INERTIA_SPEED) {
+            self.angular_velocity = [0.0; 6];
+            return false;
+        }
+        
+        let angles = self.angular_velocity.map(|v| v * dt);
+        self.rotate_planes(&angles);
+        
+        let damping = (-DRAG_INERTIA_DAMPING * dt).exp();
+        for velocity in self.angular_velocity.iter_mut() {
+            *velocity *= damping;
+        }
+        
+        true
+    }
+    
+    // Остановить вращение по инерции
+    pub fn stop_inertia(&mut self) {
+        self.angular_velocity = [0.0; 6];
+    }
+    
     // Получение координат вершин после проецирования в 3D пространство
     pub fn get_projected_vertices(&self, w_camera: f64) -> Vec<f64> {
         let mut result = Vec::new();
@@ -162,6 +269,96 @@ impl Hypercube {
         result
     }
     
+    // Получение рёбер в виде ломаных: каждое 4D ребро делится на segments частей
+    // до проецирования, поэтому вблизи w_camera рёбра изгибаются корректными дугами.
+    // Для каждого ребра возвращается (segments + 1) точек по 3 координаты подряд,
+    // segments ограничено MAX_EDGE_SEGMENTS
+    pub fn get_projected_edges_subdivided(&self, segments: usize, w_camera: f64) -> Vec<f64> {
+        let segments = segments.clamp(1, MAX_EDGE_SEGMENTS);
+        let mut result = Vec::with_capacity(self.edges.len() * (segments + 1) * 3);
+        
+        for (start, end) in &self.edges {
+            let a = &self.vertices[*start];
+            let b = &self.vertices[*end];
+            
+            for i in 0..=segments {
+                let t = i as f64 / segments as f64;
+                let point = Point4D::new(
+                    a.x + (b.x - a.x) * t,
+                    a.y + (b.y - a.y) * t,
+                    a.z + (b.z - a.z) * t,
+                    a.w + (b.w - a.w) * t,
+                );
+                result.extend(point.project_to_3d(w_camera));
+            }
+        }
+        
+        result
+    }
+    
+    // Расчет 4D освещения: интенсивность вершин и граней по их 4D нормалям
+    // относительно направления света light_dir4 (x, y, z, w) и туман по глубине W
+    pub fn compute_shading(&self, light_dir4: &[f64], w_camera: f64) -> Option<HypercubeShading> {
+        if light_dir4.len() != 4 {
+            return None;
+        }
+        
+        let light = na::Vector4::new(light_dir4[0], light_dir4[1], light_dir4[2], light_dir4[3]);
+        if light.norm() <= f64::EPSILON {
+            return None;
+        }
+        let light = light.normalize();
+        
+        let to_vector = |p: &Point4D| na::Vector4::new(p.x, p.y, p.z, p.w);
+        let lambert = |normal: na::Vector4<f64>| {
+            let diffuse = normal.normalize().dot(&light).max(0.0);
+            (SHADING_AMBIENT + (1.0 - SHADING_AMBIENT) * diffuse) as f32
+        };
+        
+        // Нормаль вершины гиперкуба, центрированного в начале координат, - направление на вершину
+        let vertex_intensities = self.vertices.iter().map(|v| lambert(to_vector(v))).collect();
+        
+        // Туман согласован с перспективой: отношение масштаба вершины к масштабу ближайшей вершины
+        let max_w = self.vertices.iter().map(|v| v.w).fold(f64::MIN, f64::max);
+        let near_distance = w_camera - max_w;
+        let vertex_fog = self.vertices.iter()
+            .map(|v| {
+                let distance = w_camera - v.w;
+                if distance <= f64::EPSILON || near_distance <= f64::EPSILON {
+                    0.0
+                } else {
+                    (1.0 - near_distance / distance).clamp(0.0, 1.0) as f32
+                }
+            })
+            .collect();
+        
+        // 24 грани: для каждой пары осей (a, b) и каждой фиксации двух оставшихся осей
+        let mut face_indices = Vec::with_capacity(24 * 4);
+        let mut face_intensities = Vec::with_capacity(24);
+        for a in 0..4 {
+            for b in (a + 1)..4 {
+                let others: Vec<usize> = (0..4).filter(|&i| i != a && i != b).collect();
+                for combo in 0..4 {
+                    let base = ((combo & 1) << others[0]) | (((combo >> 1) & 1) << others[1]);
+                    let quad = [base, base | (1 << a), base | (1 << a) | (1 << b), base | (1 << b)];
+                    
+                    let center = quad.iter()
+                        .fold(na::Vector4::zeros(), |acc, &i| acc + to_vector(&self.vertices[i])) / 4.0;
+                    
+                    face_indices.extend(quad.iter().map(|&i| i as u32));
+                    face_intensities.push(lambert(center));
+                }
+            }
+        }
+        
+        Some(HypercubeShading {
+            vertex_intensities,
+            vertex_fog,
+            face_indices,
+            face_intensities,
+        })
+    }
+    
     // Получение индексов рёбер
     pub fn get_edges(&self) -> Vec<u32> {
         let mut result = Vec::new();
@@ -173,4 +370,95 @@ impl Hypercube {
         
         result
     }
-} 
\ No newline at end of file
+    
+    // Проекция гиперкуба в виде диаграммы Шлегеля через центр одной из 8 кубических ячеек.
+    // Ячейка cell_index задается осью (cell_index / 2) и знаком (нечетный индекс - "+").
+    // Выбранная ячейка становится внешним кубом, остальные оказываются внутри него.
+    // Ячейки определяются топологией вершин, поэтому раскладка стабильна при вращении
+    pub fn project_schlegel(&self, cell_index: usize) -> Option<Vec<f64>> {
+        if cell_index >= 8 {
+            return None;
+        }
+        
+        let axis = cell_index / 2;
+        let positive = cell_index % 2 == 1;
+        let to_vector = |p: &Point4D| na::Vector4::new(p.x, p.y, p.z, p.w);
+        
+        // Центр выбранной ячейки - среднее ее 8 вершин
+        let cell_center = self.vertices.iter().enumerate()
+            .filter(|(i, _)| ((i >> axis) & 1 == 1) == positive)
+            .fold(na::Vector4::zeros(), |acc, (_, v)| acc + to_vector(v)) / 8.0;
+        
+        let normal = cell_center.normalize();
+        let eye = cell_center * SCHLEGEL_EYE_FACTOR;
+        
+        // Базис гиперплоскости ячейки - направления ребер вдоль трех других осей
+        let basis: Vec<na::Vector4<f64>> = (0..4)
+            .filter(|&a| a != axis)
+            .map(|a| (to_vector(&self.vertices[1 << a]) - to_vector(&self.vertices[0])).normalize())
+            .collect();
+        
+        let plane_depth = (cell_center - eye).dot(&normal);
+        let mut result = Vec::with_capacity(self.vertices.len() * 3);
+        
+        for vertex in &self.vertices {
+            let ray = to_vector(vertex) - eye;
+            
+            // Пересечение луча из точки обзора с гиперплоскостью выбранной ячейки
+            let projected = eye + ray * (plane_depth / ray.dot(&normal));
+            let local = projected - cell_center;
+            
+            result.extend(basis.iter().map(|b| local.dot(b)));
+        }
+        
+        Some(result)
+    }
+    
+    // Объемы 8 кубических ячеек после перспективной проекции в 3D.
+    // Каждая ячейка разбивается на 6 тетраэдров вокруг главной диагонали
+    pub fn get_projected_cell_volumes(&self, w_camera: f64) -> Vec<f64> {
+        let projected: Vec<na::Vector3<f64>> = self.vertices.iter()
+            .map(|v| {
+                let p = v.project_to_3d(w_camera);
+                na::Vector3::new(p[0], p[1], p[2])
+            })
+            .collect();
+        
+        // Пути от вершины 0 к вершине 7 локального куба через соседние вершины
+        const TETRA_PATHS: [[usize; 2]; 6] = [[1, 3], [1, 5], [2, 3], [2, 6], [4, 5], [4, 6]];
+        
+        let mut volumes = Vec::with_capacity(8);
+        for cell in 0..8 {
+            let axis = cell / 2;
+            let fixed_bit = (cell % 2) << axis;
+            
+            // Глобальный индекс вершины по локальному 3-битному индексу внутри ячейки
+            let vertex = |local: usize| {
+                let mut index = fixed_bit;
+                for (bit, a) in (0..4).filter(|&a| a != axis).enumerate() {
+                    if (local >> bit) & 1 == 1 {
+                        index |= 1 << a;
+                    }
+                }
+                projected[index]
+            };
+            
+            let origin = vertex(0);
+            let diagonal = vertex(7) - origin;
+            let volume: f64 = TETRA_PATHS.iter()
+                .map(|[a, b]| (vertex(*a) - origin).cross(&(vertex(*b) - origin)).dot(&diagonal).abs() / 6.0)
+                .sum();
+            
+            volumes.push(volume);
+        }
+        
+        volumes
+    }
+}
+
+impl Hypercube {
+    // Вращение по массиву углов в порядке плоскостей XY, XZ, XW, YZ, YW, ZW
+    fn rotate_planes(&mut self, angles: &[f64; 6]) {
+        self.rotate(angles[0], angles[1], angles[2], angles[3], angles[4], angles[5]);
+    }
+}
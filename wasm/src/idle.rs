@@ -0,0 +1,85 @@
+/*
+ * idle.rs
+ *
+ * Экономия батареи в фоновых сценах. Пока пользователь бездействует
+ * (notify_user_idle(true)), система обновляется с частотой IDLE_UPDATE_HZ:
+ * шаги кадров копятся и применяются одним шагом, а число частиц эффектов
+ * плавно уменьшается вдвое. При взаимодействии частота обновления сразу
+ * возвращается к полной, а частицы плавно восстанавливаются за
+ * IDLE_BLEND_SECONDS.
+ *
+ * Накопленное время не теряется, поэтому траектории в режиме покоя
+ * совпадают с полными с точностью до шага интегрирования.
+ */
+
+use wasm_bindgen::prelude::*;
+
+use crate::errors::OpResult;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+// Частота обновления в режиме покоя (Гц)
+pub const IDLE_UPDATE_HZ: f32 = 20.0;
+
+const IDLE_PARTICLE_SCALE: f32 = 0.5;   // Доля частиц в режиме покоя
+const IDLE_BLEND_SECONDS: f32 = 0.5;    // Время перехода числа частиц
+
+/// Состояние режима покоя системы
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IdleThrottle {
+    idle: bool,
+    // Уровень покоя 0..1 (плавный переход числа частиц)
+    level: f32,
+    // Время, накопленное с последнего шага в режиме покоя
+    pending: f32,
+}
+
+impl IdleThrottle {
+    pub fn set_idle(&mut self, idle: bool) {
+        self.idle = idle;
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+
+    // Шаг, который нужно применить в этом кадре; None - кадр пропускается
+    pub fn throttle(&mut self, dt: f32) -> Option<f32> {
+        let blend = if dt.is_finite() { dt.max(0.0) / IDLE_BLEND_SECONDS } else { 0.0 };
+        let target = if self.idle { 1.0 } else { 0.0 };
+        self.level += (target - self.level).clamp(-blend, blend);
+
+        if !self.idle {
+            // Выход из покоя: отдаем накопленное время с первым же кадром
+            let pending = std::mem::take(&mut self.pending);
+            return Some(dt + pending);
+        }
+
+        self.pending += if dt.is_finite() { dt } else { 0.0 };
+        if self.pending < 1.0 / IDLE_UPDATE_HZ {
+            return None;
+        }
+        Some(std::mem::take(&mut self.pending))
+    }
+
+    // Множитель числа частиц эффектов (1 - полное число)
+    pub fn particle_scale(&self) -> f32 {
+        1.0 - (1.0 - IDLE_PARTICLE_SCALE) * self.level
+    }
+}
+
+// Сообщить о бездействии пользователя (true) или о взаимодействии (false)
+#[wasm_bindgen]
+pub fn notify_user_idle(system_id: usize, idle: bool) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.idle.set_idle(idle);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn is_user_idle(system_id: usize) -> Option<bool> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| system.idle.is_idle())
+}
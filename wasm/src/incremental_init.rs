@@ -0,0 +1,104 @@
+/*
+ * incremental_init.rs
+ *
+ * Пошаговое наполнение системы при старте. Создание системы с большим числом
+ * объектов целиком (RNG, выделения памяти) занимает главный поток на
+ * несколько кадров. create_system_async сразу возвращает пустую систему и
+ * запоминает, сколько объектов нужно создать, а continue_init создает их
+ * порциями в пределах бюджета времени за вызов и сообщает прогресс.
+ *
+ * Каждый вызов создает хотя бы один объект, поэтому наполнение завершается
+ * даже при нулевом бюджете; нечисловой бюджет считается нулевым. Число
+ * объектов ограничено, как и в остальных API появления. Очистка сцены
+ * (despawn_all) отменяет незавершенное наполнение.
+ */
+
+use wasm_bindgen::prelude::*;
+
+use crate::energy_spheres::spawn_energy_sphere;
+use crate::neon_comets::spawn_neon_comet;
+use crate::platform;
+use crate::space_objects::{create_space_object_system, SPACE_OBJECT_SYSTEMS};
+
+// Предел объектов каждого типа для одного наполнения
+const MAX_INIT_COMETS: usize = 1024;
+const MAX_INIT_SPHERES: usize = 256;
+
+/// Объекты, которые еще предстоит создать
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PendingInit {
+    comets: usize,
+    spheres: usize,
+    total: usize,
+}
+
+impl PendingInit {
+    pub fn new(comets: usize, spheres: usize) -> Self {
+        let comets = comets.min(MAX_INIT_COMETS);
+        let spheres = spheres.min(MAX_INIT_SPHERES);
+        PendingInit {
+            comets,
+            spheres,
+            total: comets.saturating_add(spheres),
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.comets.saturating_add(self.spheres)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    // Доля созданных объектов (1 - наполнение завершено или не начиналось)
+    pub fn progress(&self) -> f32 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        1.0 - self.remaining() as f32 / self.total as f32
+    }
+}
+
+// Создать пустую систему и запланировать ее наполнение через continue_init
+#[wasm_bindgen]
+pub fn create_system_async(viewport_size_percent: f32, fov_degrees: f32, comet_count: usize, sphere_count: usize) -> usize {
+    let system_id = create_space_object_system(viewport_size_percent, fov_degrees);
+    if let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        system.pending_init = PendingInit::new(comet_count, sphere_count);
+    }
+    system_id
+}
+
+// Создать очередную порцию объектов за budget_ms миллисекунд; возвращает
+// прогресс наполнения (1 - завершено) или None, если системы нет
+#[wasm_bindgen]
+pub fn continue_init(system_id: usize, budget_ms: f64) -> Option<f32> {
+    let mut system = SPACE_OBJECT_SYSTEMS.get_mut(&system_id)?;
+    // NaN никогда не исчерпался бы, и все наполнение прошло бы за один вызов
+    let budget_ms = if budget_ms.is_finite() { budget_ms } else { 0.0 };
+    let start = platform::monotonic_ms();
+
+    while !system.pending_init.is_done() {
+        if system.pending_init.comets > 0 {
+            system.pending_init.comets -= 1;
+            spawn_neon_comet(&mut system);
+        } else {
+            system.pending_init.spheres -= 1;
+            spawn_energy_sphere(&mut system);
+        }
+
+        if platform::monotonic_ms() - start >= budget_ms {
+            break;
+        }
+    }
+
+    Some(system.pending_init.progress())
+}
+
+#[wasm_bindgen]
+pub fn get_init_progress(system_id: usize) -> Option<f32> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| system.pending_init.progress())
+}
@@ -1,8 +1,8 @@
 use wasm_bindgen::prelude::*;
-use web_sys::console;
 
 // Модули
 mod utils;
+mod platform;
 mod physics;
 mod hypercube;
 mod space_core;
@@ -10,11 +10,135 @@ mod space_objects;
 mod neon_comets;
 mod energy_spheres;
 mod polygonal_crystals;
+mod noise4d;
+mod scene_modulation;
+mod visibility;
+mod easing;
+mod deltas;
+mod state_stream;
+mod gltf_export;
+mod query;
+mod frame_graph;
+mod memory_report;
+mod parallax;
+mod stereo;
+mod frame_hook;
+mod modifiers;
+mod comet_effects;
+mod curves;
+mod warp;
+mod transitions;
+mod hit_test;
+mod selection;
+mod population;
+mod diagnostics;
+mod portal_glow;
+mod batch_queries;
+mod planes;
+mod despawn;
+mod distortion;
+mod lifecycle;
+mod errors;
+mod crash;
+mod module_info;
+mod incremental_init;
+mod fallback;
+mod debris;
+mod plane_physics;
+mod sleeping;
+mod packed_export;
+mod morton;
+mod frame_driver;
+mod idle;
+mod power;
+mod color_space;
+mod palette;
+mod tail_particles;
+mod particle_budget;
+mod near_miss;
+mod viewpoints;
+mod scene_bounds;
+mod svg_export;
+mod scene_sync;
+mod commands;
+mod occupancy;
+mod spawn_spacing;
+mod spawn_density;
+mod protected_rect;
+mod motion_vectors;
+mod object_bounds;
+mod crossing_stats;
+mod analytics;
+mod subsystems;
+#[cfg(feature = "golden")]
+pub mod golden;
 
 // Реэкспорт публичных функций и типов
 pub use space_core::*;
+pub use hypercube::*;
 pub use space_objects::*;
 pub use neon_comets::*;
+pub use energy_spheres::*;
+pub use noise4d::*;
+pub use scene_modulation::*;
+pub use visibility::*;
+pub use easing::*;
+pub use deltas::*;
+pub use state_stream::*;
+pub use gltf_export::*;
+pub use query::*;
+pub use memory_report::*;
+pub use parallax::*;
+pub use stereo::*;
+pub use frame_hook::*;
+pub use modifiers::*;
+pub use comet_effects::*;
+pub use curves::*;
+pub use warp::*;
+pub use transitions::*;
+pub use hit_test::*;
+pub use selection::*;
+pub use population::*;
+pub use diagnostics::*;
+pub use portal_glow::*;
+pub use batch_queries::*;
+pub use planes::*;
+pub use despawn::*;
+pub use distortion::*;
+pub use lifecycle::*;
+pub use errors::*;
+pub use crash::*;
+pub use module_info::*;
+pub use incremental_init::*;
+pub use fallback::*;
+pub use debris::*;
+pub use plane_physics::*;
+pub use sleeping::*;
+pub use packed_export::*;
+pub use morton::*;
+pub use frame_driver::*;
+pub use idle::*;
+pub use power::*;
+pub use color_space::*;
+pub use palette::*;
+pub use tail_particles::*;
+pub use particle_budget::*;
+pub use near_miss::*;
+pub use viewpoints::*;
+pub use scene_bounds::*;
+pub use svg_export::*;
+pub use scene_sync::*;
+pub use commands::*;
+pub use occupancy::*;
+pub use spawn_spacing::*;
+pub use spawn_density::*;
+pub use protected_rect::*;
+pub use object_bounds::*;
+pub use crossing_stats::*;
+pub use analytics::*;
+pub use subsystems::*;
+pub use platform::{set_clock, Clock};
+pub use physics::{init_world, step_simulation};
 
 #[wasm_bindgen]
 pub fn init() {
@@ -24,5 +148,5 @@ pub fn init() {
 
 #[wasm_bindgen]
 pub fn log_message(message: &str) {
-    console::log_1(&JsValue::from_str(message));
+    platform::log(message);
 }
\ No newline at end of file
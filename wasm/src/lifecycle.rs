@@ -0,0 +1,190 @@
+/*
+ * lifecycle.rs
+ *
+ * События жизненного цикла объектов для хоста: появление, активация (первый
+ * шаг или возвращение после респауна), пересечение видовой плоскости и
+ * исчезновение с причиной. Хост забирает накопленные события вызовом
+ * take_lifecycle_events и вешает на них звуки или аналитику, не сравнивая
+ * снимки сцены между кадрами.
+ *
+ * Очередь ограничена: если хост не забирает события, самые старые
+ * отбрасываются, а их число отдается вместе со следующей порцией.
+ */
+
+use std::collections::VecDeque;
+
+use wasm_bindgen::prelude::*;
+
+use crate::population::DespawnReason;
+use crate::space_core::CrossingType;
+use crate::space_objects::{SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+
+// Предел очереди событий одной системы
+pub const MAX_LIFECYCLE_EVENTS: usize = 4096;
+
+/// Вид события жизненного цикла
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LifecycleEventKind {
+    Spawned = 0,
+    Activated = 1,
+    CrossedPlane = 2,
+    Despawned = 3,
+}
+
+/// Уточнение события: направление пересечения, причина исчезновения
+/// или респаун для активации
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LifecycleReason {
+    None = 0,
+    Respawn = 1,
+    Entry = 2,
+    Exit = 3,
+    OutOfBounds = 4,
+    Lifetime = 5,
+    Opacity = 6,
+    Crossing = 7,
+}
+
+impl From<DespawnReason> for LifecycleReason {
+    fn from(reason: DespawnReason) -> Self {
+        match reason {
+            DespawnReason::OutOfBounds => LifecycleReason::OutOfBounds,
+            DespawnReason::Lifetime => LifecycleReason::Lifetime,
+            DespawnReason::Opacity => LifecycleReason::Opacity,
+            DespawnReason::Crossing => LifecycleReason::Crossing,
+        }
+    }
+}
+
+impl From<CrossingType> for LifecycleReason {
+    fn from(crossing_type: CrossingType) -> Self {
+        match crossing_type {
+            CrossingType::Entry => LifecycleReason::Entry,
+            CrossingType::Exit => LifecycleReason::Exit,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LifecycleEvent {
+    pub kind: LifecycleEventKind,
+    pub object_id: usize,
+    pub object_type: SpaceObjectType,
+    pub reason: LifecycleReason,
+}
+
+/// Очередь событий жизненного цикла системы
+#[derive(Clone, Debug, Default)]
+pub struct LifecycleEvents {
+    events: VecDeque<LifecycleEvent>,
+    dropped: u32,
+}
+
+impl LifecycleEvents {
+    pub fn push(&mut self, kind: LifecycleEventKind, object_id: usize, object_type: SpaceObjectType, reason: LifecycleReason) {
+        if self.events.len() >= MAX_LIFECYCLE_EVENTS {
+            self.events.pop_front();
+            self.dropped = self.dropped.saturating_add(1);
+        }
+        self.events.push_back(LifecycleEvent { kind, object_id, object_type, reason });
+    }
+
+    pub fn spawned(&mut self, object_id: usize, object_type: SpaceObjectType) {
+        self.push(LifecycleEventKind::Spawned, object_id, object_type, LifecycleReason::None);
+    }
+
+    pub fn activated(&mut self, object_id: usize, object_type: SpaceObjectType, respawn: bool) {
+        let reason = if respawn { LifecycleReason::Respawn } else { LifecycleReason::None };
+        self.push(LifecycleEventKind::Activated, object_id, object_type, reason);
+    }
+
+    pub fn crossed_plane(&mut self, object_id: usize, object_type: SpaceObjectType, crossing_type: CrossingType) {
+        self.push(LifecycleEventKind::CrossedPlane, object_id, object_type, crossing_type.into());
+    }
+
+    pub fn despawned(&mut self, object_id: usize, object_type: SpaceObjectType, reason: DespawnReason) {
+        self.push(LifecycleEventKind::Despawned, object_id, object_type, reason.into());
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn drain(&mut self) -> LifecycleEventArray {
+        let mut result = LifecycleEventArray {
+            kinds: Vec::with_capacity(self.events.len()),
+            ids: Vec::with_capacity(self.events.len()),
+            types: Vec::with_capacity(self.events.len()),
+            reasons: Vec::with_capacity(self.events.len()),
+            dropped: std::mem::take(&mut self.dropped),
+        };
+
+        for event in self.events.drain(..) {
+            result.kinds.push(event.kind);
+            result.ids.push(event.object_id);
+            result.types.push(event.object_type);
+            result.reasons.push(event.reason);
+        }
+
+        result
+    }
+
+    // Занятая и зарезервированная память очереди событий (байты)
+    pub fn memory_usage(&self) -> (usize, usize, usize, usize) {
+        let element = std::mem::size_of::<LifecycleEvent>();
+        (self.events.len(), self.events.capacity(), self.events.len() * element, self.events.capacity() * element)
+    }
+}
+
+/// События жизненного цикла в порядке возникновения
+#[wasm_bindgen]
+pub struct LifecycleEventArray {
+    kinds: Vec<LifecycleEventKind>,
+    ids: Vec<usize>,
+    types: Vec<SpaceObjectType>,
+    reasons: Vec<LifecycleReason>,
+    dropped: u32,
+}
+
+#[wasm_bindgen]
+impl LifecycleEventArray {
+    #[wasm_bindgen(getter)]
+    pub fn kinds(&self) -> Vec<LifecycleEventKind> {
+        self.kinds.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn ids(&self) -> Vec<usize> {
+        self.ids.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn types(&self) -> Vec<SpaceObjectType> {
+        self.types.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn reasons(&self) -> Vec<LifecycleReason> {
+        self.reasons.clone()
+    }
+
+    // Сколько событий отброшено из-за переполнения очереди с прошлого вызова
+    #[wasm_bindgen(getter)]
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+// Забрать накопленные события системы
+#[wasm_bindgen]
+pub fn take_lifecycle_events(system_id: usize) -> Option<LifecycleEventArray> {
+    SPACE_OBJECT_SYSTEMS
+        .get_mut(&system_id)
+        .map(|mut system| system.lifecycle.drain())
+}
@@ -0,0 +1,151 @@
+/*
+ * memory_report.rs
+ * 
+ * Отчет об использовании памяти хранилищами крейта: для каждого
+ * хранилища - число элементов и емкость, а также байты занятые (len)
+ * и зарезервированные (capacity). Так становятся заметны утечки вроде
+ * неосвобожденных систем или бесконечно растущей очереди появлений.
+ * 
+ * Размеры считаются по самим контейнерам и элементам; память, на которую
+ * ссылаются элементы (строки, вложенные векторы), не учитывается.
+ */
+
+use wasm_bindgen::prelude::*;
+use std::mem::size_of;
+use glam::Vec3;
+
+use crate::neon_comets::spawn_queue_memory;
+use crate::space_objects::{SpaceObject, SpaceObjectSystem, SPACE_OBJECT_SYSTEMS};
+
+/// Отчет о памяти: параллельные массивы по хранилищам
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct MemoryReport {
+    names: Vec<String>,
+    lens: Vec<usize>,
+    capacities: Vec<usize>,
+    used_bytes: Vec<usize>,
+    reserved_bytes: Vec<usize>,
+}
+
+impl MemoryReport {
+    fn push(&mut self, name: String, len: usize, capacity: usize, used: usize, reserved: usize) {
+        self.names.push(name);
+        self.lens.push(len);
+        self.capacities.push(capacity);
+        self.used_bytes.push(used);
+        self.reserved_bytes.push(reserved);
+    }
+    
+    fn push_vec<T>(&mut self, name: String, values: &Vec<T>) {
+        let element = size_of::<T>();
+        self.push(name, values.len(), values.capacity(), values.len() * element, values.capacity() * element);
+    }
+    
+    // Добавить группы объектов: слоты Box в векторах плюс сами объекты в куче
+    fn push_objects<'a>(&mut self, name: String, groups: impl Iterator<Item = &'a Vec<Box<dyn SpaceObject>>>) {
+        let (mut len, mut capacity, mut used, mut reserved) = (0, 0, 0, 0);
+        for objects in groups {
+            let heap: usize = objects.iter().map(|obj| size_of_val::<dyn SpaceObject>(obj.as_ref())).sum();
+            let slot = size_of::<Box<dyn SpaceObject>>();
+            len += objects.len();
+            capacity += objects.capacity();
+            used += objects.len() * slot + heap;
+            reserved += objects.capacity() * slot + heap;
+        }
+        self.push(name, len, capacity, used, reserved);
+    }
+    
+    // Добавить хранилища одной системы объектов (новое растущее хранилище
+    // SpaceObjectSystem должно попасть сюда и в список теста memory_report_covers_all_system_stores)
+    fn add_system(&mut self, system_id: usize, system: &SpaceObjectSystem) {
+        self.push_objects(format!("system:{}:objects", system_id), system.get_objects().values());
+        self.push_objects(format!("system:{}:parked_objects", system_id), system.subsystems.parked().values());
+        
+        self.push_vec(format!("system:{}:crossings", system_id), &system.crossings);
+        self.push_vec(format!("system:{}:hypercube_vertices", system_id), &system.hypercube_vertices);
+        self.push_vec(format!("system:{}:hypercube_crossings", system_id), &system.hypercube_crossings);
+        
+        let snapshot = &system.position_snapshot;
+        let entry = size_of::<(usize, Vec3)>();
+        self.push(
+            format!("system:{}:position_snapshot", system_id),
+            snapshot.len(), snapshot.capacity(), snapshot.len() * entry, snapshot.capacity() * entry,
+        );
+        
+        let stores = [
+            ("deltas", system.deltas.memory_usage()),
+            ("effects", system.effects.memory_usage()),
+            ("tail_particles", system.tail_particles.memory_usage()),
+            ("lifecycle", system.lifecycle.memory_usage()),
+            ("debris", system.debris.memory_usage()),
+            ("commands", system.commands.memory_usage()),
+            ("crossing_stats", system.crossing_stats.memory_usage()),
+            ("analytics", system.analytics.memory_usage()),
+            ("near_misses", system.near_misses.memory_usage()),
+        ];
+        for (store, (len, capacity, used, reserved)) in stores {
+            self.push(format!("system:{}:{}", system_id, store), len, capacity, used, reserved);
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl MemoryReport {
+    #[wasm_bindgen(getter)]
+    pub fn names(&self) -> Vec<String> {
+        self.names.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn lens(&self) -> Vec<usize> {
+        self.lens.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn capacities(&self) -> Vec<usize> {
+        self.capacities.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn used_bytes(&self) -> Vec<usize> {
+        self.used_bytes.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn reserved_bytes(&self) -> Vec<usize> {
+        self.reserved_bytes.clone()
+    }
+    
+    // Всего зарезервировано байт по всем хранилищам
+    #[wasm_bindgen(getter)]
+    pub fn total_reserved_bytes(&self) -> usize {
+        self.reserved_bytes.iter().sum()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn system_count(&self) -> usize {
+        self.names.iter().filter(|name| name.ends_with(":objects")).count()
+    }
+}
+
+// Отчет о памяти всех систем объектов и общей очереди появлений
+#[wasm_bindgen]
+pub fn get_memory_report() -> MemoryReport {
+    let mut report = MemoryReport::default();
+    
+    let (len, capacity, element) = spawn_queue_memory();
+    report.push("spawn_queue".to_string(), len, capacity, len * element, capacity * element);
+    
+    // Системы по возрастанию ID - стабильный порядок для сравнения отчетов
+    let mut system_ids: Vec<usize> = SPACE_OBJECT_SYSTEMS.iter().map(|entry| *entry.key()).collect();
+    system_ids.sort_unstable();
+    
+    for system_id in system_ids {
+        if let Some(system) = SPACE_OBJECT_SYSTEMS.get(&system_id) {
+            report.add_system(system_id, &system);
+        }
+    }
+    
+    report
+}
@@ -0,0 +1,186 @@
+/*
+ * modifiers.rs
+ *
+ * Стек модификаторов поведения системы. Временные эффекты ("замедление,
+ * пока открыто модальное окно") включаются push и выключаются pop, без
+ * ручного сохранения и восстановления параметров.
+ *
+ * Модификаторы применяются в порядке добавления:
+ * - множитель скорости масштабирует шаг времени объектов (проход integrate);
+ * - дрожание и ограничение размера - в проходе modifiers после integrate;
+ * - оттенок цвета - при экспорте цветов, исходные цвета объектов не меняются.
+ *
+ * Ограничение размера действует на масштаб (scale), который объекты
+ * пересчитывают каждый кадр, поэтому после pop размеры возвращаются сами.
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+
+use crate::errors::OpResult;
+use crate::noise4d::simulation_noise;
+use crate::space_objects::{SpaceObject, SPACE_OBJECT_SYSTEMS};
+
+// Частота кадров, по которой номер кадра переводится во время шума дрожания
+const JITTER_TIME_RATE: f32 = 1.0 / 60.0;
+
+/// Модификатор поведения объектов системы
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Modifier {
+    // Множитель шага времени объектов (0.5 - замедление вдвое)
+    SpeedMultiplier(f32),
+    // Смешивание цвета с оттенком: цвет, доля (0..1)
+    ColorTint([f32; 3], f32),
+    // Смещение по шуму: амплитуда (единиц в секунду), частота шума
+    NoiseJitter(f32, f32),
+    // Ограничение масштаба объектов
+    SizeClamp(f32, f32),
+}
+
+/// Стек модификаторов с дескрипторами для удаления из середины
+#[derive(Clone, Debug, Default)]
+pub struct ModifierStack {
+    modifiers: Vec<(u32, Modifier)>,
+    next_handle: u32,
+}
+
+impl ModifierStack {
+    pub fn push(&mut self, modifier: Modifier) -> u32 {
+        let handle = self.next_handle;
+        self.next_handle = self.next_handle.wrapping_add(1);
+        self.modifiers.push((handle, modifier));
+        handle
+    }
+
+    pub fn pop(&mut self) -> Option<Modifier> {
+        self.modifiers.pop().map(|(_, modifier)| modifier)
+    }
+
+    pub fn remove(&mut self, handle: u32) -> bool {
+        let before = self.modifiers.len();
+        self.modifiers.retain(|(h, _)| *h != handle);
+        self.modifiers.len() != before
+    }
+
+    pub fn len(&self) -> usize {
+        self.modifiers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modifiers.is_empty()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &Modifier> {
+        self.modifiers.iter().map(|(_, modifier)| modifier)
+    }
+
+    // Итоговый множитель шага времени
+    pub fn time_scale(&self) -> f32 {
+        self.iter()
+            .filter_map(|modifier| match modifier {
+                Modifier::SpeedMultiplier(factor) => Some(*factor),
+                _ => None,
+            })
+            .product()
+    }
+
+    // Применить дрожание и ограничение размера к объекту после шага
+    pub fn apply(&self, obj: &mut dyn SpaceObject, dt: f32, frame: u32) {
+        if obj.is_waiting_for_respawn() {
+            return;
+        }
+
+        let data = obj.get_data_mut();
+        for modifier in self.iter() {
+            match *modifier {
+                Modifier::NoiseJitter(amplitude, frequency) => {
+                    let time = frame as f32 * JITTER_TIME_RATE;
+                    let offset = simulation_noise().turbulence(data.position, time, frequency);
+                    data.position += offset * amplitude * dt;
+                }
+                Modifier::SizeClamp(min, max) => {
+                    data.scale = data.scale.clamp(min, max);
+                }
+                Modifier::SpeedMultiplier(_) | Modifier::ColorTint(..) => {}
+            }
+        }
+    }
+
+    // Цвет с учетом оттенков
+    pub fn tint(&self, color: [f32; 3]) -> [f32; 3] {
+        self.iter().fold(color, |color, modifier| match *modifier {
+            Modifier::ColorTint(tint, amount) => {
+                let mixed = Vec3::from(color).lerp(Vec3::from(tint), amount);
+                mixed.to_array()
+            }
+            _ => color,
+        })
+    }
+}
+
+// Добавить модификатор в стек системы; возвращает его дескриптор
+fn push_modifier(system_id: usize, modifier: Modifier) -> Option<u32> {
+    SPACE_OBJECT_SYSTEMS
+        .get_mut(&system_id)
+        .map(|mut system| system.modifiers.push(modifier))
+}
+
+#[wasm_bindgen]
+pub fn push_speed_modifier(system_id: usize, multiplier: f32) -> Option<u32> {
+    if !multiplier.is_finite() {
+        return None;
+    }
+    push_modifier(system_id, Modifier::SpeedMultiplier(multiplier.max(0.0)))
+}
+
+#[wasm_bindgen]
+pub fn push_color_tint_modifier(system_id: usize, r: f32, g: f32, b: f32, amount: f32) -> Option<u32> {
+    let tint = [r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0)];
+    push_modifier(system_id, Modifier::ColorTint(tint, amount.clamp(0.0, 1.0)))
+}
+
+#[wasm_bindgen]
+pub fn push_noise_jitter_modifier(system_id: usize, amplitude: f32, frequency: f32) -> Option<u32> {
+    if !amplitude.is_finite() || !frequency.is_finite() {
+        return None;
+    }
+    push_modifier(system_id, Modifier::NoiseJitter(amplitude.max(0.0), frequency.max(0.0)))
+}
+
+#[wasm_bindgen]
+pub fn push_size_clamp_modifier(system_id: usize, min_scale: f32, max_scale: f32) -> Option<u32> {
+    if !min_scale.is_finite() || !max_scale.is_finite() || min_scale > max_scale {
+        return None;
+    }
+    push_modifier(system_id, Modifier::SizeClamp(min_scale, max_scale))
+}
+
+// Снять верхний модификатор
+#[wasm_bindgen]
+pub fn pop_modifier(system_id: usize) -> OpResult {
+    let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) else {
+        return OpResult::system_not_found(system_id);
+    };
+    match system.modifiers.pop() {
+        Some(_) => OpResult::success(),
+        None => OpResult::not_found("modifier stack is empty"),
+    }
+}
+
+// Снять модификатор по дескриптору (не обязательно верхний)
+#[wasm_bindgen]
+pub fn remove_modifier(system_id: usize, handle: u32) -> OpResult {
+    let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) else {
+        return OpResult::system_not_found(system_id);
+    };
+    if !system.modifiers.remove(handle) {
+        return OpResult::not_found(format!("modifier {} not found", handle));
+    }
+
+    OpResult::success()
+}
+
+#[wasm_bindgen]
+pub fn get_modifier_count(system_id: usize) -> Option<usize> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| system.modifiers.len())
+}
@@ -0,0 +1,96 @@
+/*
+ * module_info.rs
+ *
+ * Рукопожатие между JS-оберткой и бинарником WASM. Загрузчик может получить
+ * из кэша бинарник другой сборки, чем сгенерированная обертка; get_module_info
+ * сообщает версию крейта, включенные возможности, версии раскладок буферов и
+ * уровень API, чтобы загрузчик обнаружил несовпадение до первого кадра.
+ *
+ * API_LEVEL увеличивается при несовместимом изменении экспортов (сигнатуры,
+ * значения перечислений); версии раскладок - при изменении двоичных форматов.
+ */
+
+use wasm_bindgen::prelude::*;
+
+use crate::deltas::DELTA_LAYOUT_VERSION;
+use crate::gltf_export::GLB_VERSION;
+use crate::packed_export::{PACKED_LAYOUT, PACKED_LAYOUT_VERSION};
+use crate::state_stream::STATE_STREAM_VERSION;
+
+// Уровень API экспортируемых функций
+pub const API_LEVEL: u32 = 1;
+
+/// Версии и возможности модуля
+#[wasm_bindgen]
+pub struct ModuleInfo {
+    features: Vec<String>,
+}
+
+#[wasm_bindgen]
+impl ModuleInfo {
+    #[wasm_bindgen(getter)]
+    pub fn crate_version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn api_level(&self) -> u32 {
+        API_LEVEL
+    }
+
+    // Включенные cargo-возможности сборки
+    #[wasm_bindgen(getter)]
+    pub fn features(&self) -> Vec<String> {
+        self.features.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn state_stream_version(&self) -> u16 {
+        STATE_STREAM_VERSION
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn delta_layout_version(&self) -> u16 {
+        DELTA_LAYOUT_VERSION
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn gltf_version(&self) -> u32 {
+        GLB_VERSION
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn packed_layout_version(&self) -> u16 {
+        PACKED_LAYOUT_VERSION
+    }
+
+    // Раскладка упакованного экспорта (get_packed_neon_comets): "поле:формат,..."
+    #[wasm_bindgen(getter)]
+    pub fn packed_layout(&self) -> String {
+        PACKED_LAYOUT.to_string()
+    }
+
+    // Поддерживает ли модуль уровень API, под который собрана обертка
+    pub fn supports_api_level(&self, level: u32) -> bool {
+        level <= API_LEVEL
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_module_info() -> ModuleInfo {
+    let features = [
+        ("console_error_panic_hook", cfg!(feature = "console_error_panic_hook")),
+        ("web", cfg!(feature = "web")),
+        ("native", cfg!(feature = "native")),
+        ("headless", cfg!(feature = "headless")),
+        ("golden", cfg!(feature = "golden")),
+    ];
+
+    ModuleInfo {
+        features: features
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| name.to_string())
+            .collect(),
+    }
+}
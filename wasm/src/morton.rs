@@ -0,0 +1,133 @@
+/*
+ * morton.rs
+ *
+ * Упорядочивание объектов по коду Мортона (Z-кривой) их позиций. Соседние в
+ * пространстве объекты оказываются рядом в массиве, поэтому проходы и
+ * запросы по окрестности (пересечения, притяжение, выборки) читают память
+ * подряд. Объекты движутся, порядок постепенно портится - сортировка
+ * повторяется раз в interval кадров.
+ *
+ * Сортировка устойчивая и зависит только от позиций, поэтому симуляция
+ * остается воспроизводимой; стоимость каждой сортировки измеряется и
+ * отдается хосту (get_morton_stats), чтобы он мог решить, окупается ли она.
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+
+use crate::errors::OpResult;
+use crate::platform;
+use crate::space_core::SpaceDefinition;
+use crate::space_objects::{SpaceObject, SPACE_OBJECT_SYSTEMS};
+
+// Массивы меньше этого размера не сортируются: выигрыша нет
+pub const MORTON_MIN_OBJECTS: usize = 32;
+
+const MORTON_BITS: u32 = 10;           // Бит на ось (код - 30 бит)
+const DEFAULT_MORTON_INTERVAL: u32 = 30;
+
+// Раздвинуть 10 младших бит так, чтобы между ними было по два нулевых
+fn spread_bits(mut value: u32) -> u32 {
+    value &= 0x3ff;
+    value = (value | (value << 16)) & 0x0300_00ff;
+    value = (value | (value << 8)) & 0x0300_f00f;
+    value = (value | (value << 4)) & 0x030c_30c3;
+    value = (value | (value << 2)) & 0x0924_9249;
+    value
+}
+
+// Код Мортона позиции внутри границ пространства
+pub fn morton_code(position: Vec3, space: &SpaceDefinition) -> u32 {
+    let max = ((1 << MORTON_BITS) - 1) as f32;
+    let quantize = |value: f32, min: f32, max_bound: f32| {
+        let t = (value - min) / (max_bound - min).max(f32::EPSILON);
+        // NaN превращается в 0 при приведении
+        (t.clamp(0.0, 1.0) * max) as u32
+    };
+
+    spread_bits(quantize(position.x, space.min_x, space.max_x))
+        | spread_bits(quantize(position.y, space.min_y, space.max_y)) << 1
+        | spread_bits(quantize(position.z, space.min_z, space.max_z)) << 2
+}
+
+/// Настройки и измерения упорядочивания по Мортону
+#[derive(Debug, Default)]
+pub struct MortonOrdering {
+    enabled: bool,
+    interval: u32,
+    frames_since: u32,
+    stats: MortonStats,
+}
+
+/// Статистика сортировок по Мортону
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MortonStats {
+    // Выполненных сортировок
+    pub reorders: u32,
+
+    // Отсортированных объектов в последней сортировке
+    pub last_objects: usize,
+
+    // Время последней сортировки (мс)
+    pub last_cost_ms: f64,
+
+    // Суммарное время всех сортировок (мс)
+    pub total_cost_ms: f64,
+}
+
+impl MortonOrdering {
+    pub fn configure(&mut self, enabled: bool, interval: u32) {
+        self.enabled = enabled;
+        self.interval = if interval == 0 { DEFAULT_MORTON_INTERVAL } else { interval };
+        // Первая сортировка - на ближайшем кадре
+        self.frames_since = self.interval;
+    }
+
+    pub fn stats(&self) -> MortonStats {
+        self.stats
+    }
+
+    // Отсортировать массивы объектов, если подошел срок
+    pub fn update<'a>(&mut self, arrays: impl Iterator<Item = &'a mut Vec<Box<dyn SpaceObject>>>, space: &SpaceDefinition) {
+        if !self.enabled {
+            return;
+        }
+        self.frames_since += 1;
+        if self.frames_since < self.interval {
+            return;
+        }
+        self.frames_since = 0;
+
+        let start = platform::monotonic_ms();
+        let mut sorted = 0;
+        for objects in arrays.filter(|objects| objects.len() >= MORTON_MIN_OBJECTS) {
+            objects.sort_by_cached_key(|obj| morton_code(obj.get_data().position, space));
+            sorted += objects.len();
+        }
+        let cost = platform::monotonic_ms() - start;
+
+        self.stats.reorders += 1;
+        self.stats.last_objects = sorted;
+        self.stats.last_cost_ms = cost;
+        self.stats.total_cost_ms += cost;
+    }
+}
+
+// Включить сортировку объектов по коду Мортона раз в interval кадров
+// (0 - интервал по умолчанию)
+#[wasm_bindgen]
+pub fn set_morton_ordering(system_id: usize, enabled: bool, interval: u32) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.morton.configure(enabled, interval);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_morton_stats(system_id: usize) -> Option<MortonStats> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| system.morton.stats())
+}
@@ -0,0 +1,64 @@
+/*
+ * motion_vectors.rs
+ *
+ * Векторы движения для размытия в движении. После шага симуляции для
+ * каждого объекта запоминается смещение его проекции на окно за этот шаг
+ * (текущая проекция минус проекция позиции прошлого кадра), и экспорт
+ * данных рендера (кометы, сферы) отдает его рядом с позицией. Так
+ * размытие рендерера совпадает с реальным движением объекта, включая
+ * разгон, модификаторы и варп, а не угадывается по скорости.
+ *
+ * Смещение - в долях окна (UV, y вниз, как project_to_viewport); обе
+ * проекции берутся с текущей камерой, поэтому собственное движение
+ * наблюдателя в вектор не входит (его рендерер размывает камерой целиком).
+ * Объекты без прошлой позиции (только что появились или вернулись после
+ * респауна) получают нулевой вектор - скачок на дальнюю границу не
+ * размывается.
+ */
+
+use std::collections::HashMap;
+
+use glam::{Vec2, Vec3};
+
+use crate::space_core::SpaceDefinition;
+use crate::space_objects::SpaceObject;
+
+/// Смещения проекций объектов за последний шаг
+#[derive(Clone, Debug, Default)]
+pub struct MotionVectors {
+    screen: HashMap<usize, Vec2>,
+}
+
+impl MotionVectors {
+    // Пересчитать смещения по позициям прошлого кадра
+    pub fn update<'a>(
+        &mut self,
+        objects: impl Iterator<Item = &'a Box<dyn SpaceObject>>,
+        previous_positions: &HashMap<usize, Vec3>,
+        space: &SpaceDefinition,
+    ) {
+        self.screen.clear();
+        for obj in objects {
+            let data = obj.get_data();
+            let Some(previous) = previous_positions.get(&data.id) else {
+                continue;
+            };
+            if let (Some(from), Some(to)) = (space.project_to_viewport(previous), space.project_to_viewport(&data.position)) {
+                self.screen.insert(data.id, to - from);
+            }
+        }
+    }
+
+    // Смещение проекции объекта за последний шаг (UV); 0 - нет данных
+    pub fn get(&self, object_id: usize) -> Vec2 {
+        self.screen.get(&object_id).copied().unwrap_or(Vec2::ZERO)
+    }
+
+    pub fn len(&self) -> usize {
+        self.screen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.screen.is_empty()
+    }
+}
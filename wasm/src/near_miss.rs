@@ -0,0 +1,240 @@
+/*
+ * near_miss.rs
+ *
+ * События пролета комет мимо наблюдателя. Комета, которая прошла ближе
+ * заданного расстояния от наблюдателя, но не пересекла видовую плоскость,
+ * не дает ни удара, ни события пересечения - а для звука и вибрации это
+ * самый заметный момент. Каждый кадр для отрезка движения кометы
+ * относительно наблюдателя (позиция до шага минус наблюдатель прошлого кадра
+ * -> позиция после шага минус текущий наблюдатель) ищется ближайшая точка;
+ * если минимум расстояния пришелся на этот кадр и меньше порога, в очередь
+ * попадает событие с точкой наибольшего сближения, расстоянием и
+ * относительной скоростью. Так летящий навстречу комете наблюдатель тоже
+ * получает событие, а его собственное движение не сдвигает момент сближения.
+ *
+ * Минимум расстояния приходится ровно на один кадр, поэтому событие
+ * выдается один раз за пролет. Кометы, пересекшие плоскость, запоминаются до
+ * респауна: сближение после удара о плоскость - не пролет мимо, даже если
+ * оно случилось через несколько кадров после пересечения. По умолчанию
+ * события выключены; порог задает хост (set_near_miss_distance).
+ */
+
+use std::collections::{HashSet, VecDeque};
+
+use glam::Vec3;
+use wasm_bindgen::prelude::*;
+
+use crate::errors::OpResult;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+// Предел очереди событий одной системы
+pub const MAX_NEAR_MISS_EVENTS: usize = 1024;
+
+// Ближайшая к point точка отрезка from -> to, если расстояние до point
+// достигает минимума на этом отрезке (не в его начале): (доля отрезка, точка)
+pub fn closest_approach(from: Vec3, to: Vec3, point: Vec3) -> Option<(f32, Vec3)> {
+    let segment = to - from;
+    let length_squared = segment.length_squared();
+    if length_squared <= f32::EPSILON {
+        return None;
+    }
+
+    // t <= 0 - объект уже удаляется, t > 1 - еще приближается
+    let t = (point - from).dot(segment) / length_squared;
+    (t > 0.0 && t <= 1.0).then(|| (t, from + segment * t))
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct NearMissEvent {
+    pub comet_id: usize,
+    pub point: Vec3,
+    pub distance: f32,
+    pub relative_speed: f32,
+    // Смещение момента сближения от конца кадра (<= 0), как у пересечений
+    pub time_offset: f32,
+}
+
+/// Порог пролета и очередь событий системы
+#[derive(Clone, Debug, Default)]
+pub struct NearMisses {
+    distance: Option<f32>,
+    events: VecDeque<NearMissEvent>,
+    dropped: u32,
+    // Кометы, пересекшие плоскость в текущем пролете (до респауна)
+    crossed: HashSet<usize>,
+    // Положение наблюдателя в конце прошлого кадра
+    observer: Option<Vec3>,
+}
+
+impl NearMisses {
+    pub fn set_distance(&mut self, distance: Option<f32>) {
+        self.distance = distance;
+        if distance.is_none() {
+            self.events.clear();
+            self.crossed.clear();
+        }
+    }
+
+    pub fn distance(&self) -> Option<f32> {
+        self.distance
+    }
+
+    // Запомнить наблюдателя этого кадра; возвращает положение прошлого кадра
+    pub fn begin_frame(&mut self, observer: Vec3) -> Vec3 {
+        self.observer.replace(observer).unwrap_or(observer)
+    }
+
+    // Комета пересекла плоскость: до респауна событий пролета от нее нет
+    pub fn mark_crossed(&mut self, comet_id: usize) {
+        self.crossed.insert(comet_id);
+    }
+
+    // Новый пролет кометы (респаун) или ее удаление
+    pub fn forget(&mut self, comet_id: usize) {
+        self.crossed.remove(&comet_id);
+    }
+
+    pub fn retain_crossed(&mut self, alive: impl Fn(usize) -> bool) {
+        self.crossed.retain(|&comet_id| alive(comet_id));
+    }
+
+    // Проверить отрезок движения кометы за кадр длительностью dt относительно
+    // наблюдателя, сместившегося за кадр из previous_observer в observer
+    pub fn check(&mut self, comet_id: usize, from: Vec3, to: Vec3, dt: f32, previous_observer: Vec3, observer: Vec3) {
+        let Some(threshold) = self.distance else {
+            return;
+        };
+        if self.crossed.contains(&comet_id) {
+            return;
+        }
+        let (relative_from, relative_to) = (from - previous_observer, to - observer);
+        let Some((t, relative_point)) = closest_approach(relative_from, relative_to, Vec3::ZERO) else {
+            return;
+        };
+        let distance = relative_point.length();
+        if distance > threshold {
+            return;
+        }
+
+        // Скорость по фактическому смещению: учитывает разгон и модификаторы
+        let relative_velocity = if dt > 0.0 { (relative_to - relative_from) / dt } else { Vec3::ZERO };
+        if self.events.len() >= MAX_NEAR_MISS_EVENTS {
+            self.events.pop_front();
+            self.dropped = self.dropped.saturating_add(1);
+        }
+        self.events.push_back(NearMissEvent {
+            comet_id,
+            point: from.lerp(to, t),
+            distance,
+            relative_speed: relative_velocity.length(),
+            time_offset: -(1.0 - t) * dt,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn drain(&mut self) -> NearMissEventArray {
+        let mut result = NearMissEventArray {
+            comet_ids: Vec::with_capacity(self.events.len()),
+            points: Vec::with_capacity(self.events.len() * 3),
+            distances: Vec::with_capacity(self.events.len()),
+            relative_speeds: Vec::with_capacity(self.events.len()),
+            time_offsets: Vec::with_capacity(self.events.len()),
+            dropped: std::mem::take(&mut self.dropped),
+        };
+
+        for event in self.events.drain(..) {
+            result.comet_ids.push(event.comet_id);
+            result.points.extend_from_slice(&event.point.to_array());
+            result.distances.push(event.distance);
+            result.relative_speeds.push(event.relative_speed);
+            result.time_offsets.push(event.time_offset);
+        }
+
+        result
+    }
+
+    // Занятая и зарезервированная память очереди событий (байты)
+    pub fn memory_usage(&self) -> (usize, usize, usize, usize) {
+        let element = std::mem::size_of::<NearMissEvent>();
+        (self.events.len(), self.events.capacity(), self.events.len() * element, self.events.capacity() * element)
+    }
+}
+
+/// События пролета в порядке возникновения
+#[wasm_bindgen]
+pub struct NearMissEventArray {
+    comet_ids: Vec<usize>,
+    points: Vec<f32>,
+    distances: Vec<f32>,
+    relative_speeds: Vec<f32>,
+    time_offsets: Vec<f32>,
+    dropped: u32,
+}
+
+#[wasm_bindgen]
+impl NearMissEventArray {
+    #[wasm_bindgen(getter)]
+    pub fn comet_ids(&self) -> Vec<usize> {
+        self.comet_ids.clone()
+    }
+
+    // Точки наибольшего сближения (x, y, z)
+    #[wasm_bindgen(getter)]
+    pub fn points(&self) -> Vec<f32> {
+        self.points.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn distances(&self) -> Vec<f32> {
+        self.distances.clone()
+    }
+
+    // Скорость кометы относительно наблюдателя, единиц в секунду
+    #[wasm_bindgen(getter)]
+    pub fn relative_speeds(&self) -> Vec<f32> {
+        self.relative_speeds.clone()
+    }
+
+    // Смещение момента сближения от конца кадра, секунды (<= 0)
+    #[wasm_bindgen(getter)]
+    pub fn time_offsets(&self) -> Vec<f32> {
+        self.time_offsets.clone()
+    }
+
+    // Сколько событий отброшено из-за переполнения очереди с прошлого вызова
+    #[wasm_bindgen(getter)]
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+// Порог пролета; undefined - события пролета выключены
+#[wasm_bindgen]
+pub fn set_near_miss_distance(system_id: usize, distance: Option<f32>) -> OpResult {
+    if distance.is_some_and(|distance| !distance.is_finite() || distance <= 0.0) {
+        return OpResult::invalid_argument("near miss distance must be positive and finite");
+    }
+
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.near_misses.set_distance(distance);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Забрать накопленные события пролета системы
+#[wasm_bindgen]
+pub fn take_near_miss_events(system_id: usize) -> Option<NearMissEventArray> {
+    SPACE_OBJECT_SYSTEMS
+        .get_mut(&system_id)
+        .map(|mut system| system.near_misses.drain())
+}
@@ -4,12 +4,20 @@ use rand::{Rng, rngs::StdRng, SeedableRng, thread_rng};
 use std::sync::Mutex;
 use once_cell::sync::Lazy;
 use std::any::Any;
-use web_sys::console;
 
+use crate::noise4d::simulation_noise;
+use crate::palette;
+use crate::platform;
+use crate::diagnostics;
+use crate::errors::OpResult;
+use crate::utils::lock_or_recover;
+use crate::visibility;
 use crate::space_core::SpaceDefinition;
+use crate::object_bounds::object_bounds;
+use crate::protected_rect::reroll_protected;
 use crate::space_objects::{
-    SpaceObject, SpaceObjectData, SpaceObjectType,
-    random_position_on_far_plane,
+    SpaceObject, SpaceObjectData, SpaceObjectSystem, SpaceObjectType,
+    derive_seed, random_position_on_far_plane,
     SPACE_OBJECT_SYSTEMS
 };
 
@@ -25,6 +33,18 @@ const MIN_ACCELERATION: f32 = 0.05;        // Минимальное ускор
 const MAX_ACCELERATION: f32 = 0.3;         // Максимальное ускорение
 const MAX_LATERAL_SPEED: f32 = 40.0;       // Уменьшаем максимальную боковую скорость с 60.0 до 40.0
 const MIN_VISIBILITY_TIME: f32 = 0.5;      // Минимальное время, в течение которого комета должна быть видна (сек)
+const TURBULENCE_STRENGTH: f32 = 1.5;      // Сила турбулентного смещения кометы (единиц в секунду)
+const TURBULENCE_FREQUENCY: f32 = 0.02;    // Пространственная частота шума турбулентности
+const HIGHLIGHT_EMISSIVE_BOOST: f32 = 1.0;  // Прирост яркости свечения выделенной кометы на пике подсветки
+
+// Палитра неоновых комет
+pub(crate) const COMET_PALETTE: [[f32; 3]; 5] = [
+    [0.0, 1.0, 0.8], // Cyan
+    [1.0, 0.2, 0.8], // Pink
+    [0.2, 0.4, 1.0], // Blue
+    [1.0, 0.8, 0.0], // Yellow
+    [0.6, 0.0, 1.0], // Purple
+];
 
 /// Структура данных неоновой кометы
 #[derive(Clone, Debug)]
@@ -73,6 +93,15 @@ pub struct NeonComet {
     
     // Максимальная длина хвоста кометы
     pub max_trail_length: f32,
+    
+    // Множитель турбулентности частиц хвоста (к полю хвостов системы)
+    pub tail_turbulence: f32,
+    
+    // Индекс базового цвета кометы в палитре
+    pub palette_index: usize,
+    
+    // Детерминированный seed кометы (seed системы + ID); респауны выводят из него свои seed-ы
+    pub seed: u64,
 }
 
 impl NeonComet {
@@ -108,6 +137,9 @@ impl NeonComet {
             respawn_count: 0,
             random_offset: 0.0,
             max_trail_length: 0.0,
+            tail_turbulence: 1.0,
+            palette_index: 0,
+            seed: id as u64,
         }
     }
     
@@ -125,6 +157,100 @@ impl NeonComet {
     pub fn get_glow_intensity(&self) -> f32 {
         self.glow_intensity
     }
+    
+    // Сид цветовой вариации: постоянен до респауна
+    pub fn color_seed(&self) -> u32 {
+        palette::color_seed(self.seed, self.respawn_count as u64)
+    }
+    
+    // Яркость свечения для HDR (может превышать 1): свечение кометы,
+    // усиленное подсветкой выделения (highlight 0..1)
+    pub fn emissive_intensity(&self, highlight: f32) -> f32 {
+        self.glow_intensity * (1.0 + HIGHLIGHT_EMISSIVE_BOOST * highlight)
+    }
+    
+    // Сдвинуть цвет кометы к следующему цвету палитры (t от 0.0 до 1.0)
+    pub fn shift_palette(&mut self, t: f32) {
+        let base = COMET_PALETTE[self.palette_index];
+        let next = COMET_PALETTE[(self.palette_index + 1) % COMET_PALETTE.len()];
+        for i in 0..3 {
+            self.color[i] = base[i] + (next[i] - base[i]) * t;
+        }
+    }
+    
+    // Рассчитать новую скорость кометы с учетом ускорения и ограничений боковой скорости.
+    // Возвращает новую скорость и исходное время пересечения экрана, если комету пришлось замедлить
+    fn accelerated_velocity(&self, position: Vec3, velocity: Vec3, dt: f32, space: &SpaceDefinition) -> (Vec3, Option<f32>) {
+        let mut velocity = velocity;
+        let mut slowed_crossing_time = None;
+        let current_speed = velocity.length();
+        
+        // Применяем адаптивное ускорение
+        let mut acceleration_factor = 1.0;
+        
+        // Рассчитываем вектор от наблюдателя к объекту
+        let to_object = position - space.observer_position;
+        
+        // Проверяем, движется ли объект в направлении наблюдателя (по Z)
+        if to_object.z > 0.0 && velocity.z < 0.0 {
+            // Рассчитываем расстояние до наблюдателя
+            let distance = to_object.length();
+            
+            // Добавляем дополнительное ускорение по мере приближения
+            if distance < 50.0 {
+                // Усиление ускорения от 1.0 до 1.5 по мере приближения (снижено с 2.0)
+                acceleration_factor = 1.0 + (1.0 - distance / 50.0) * 0.5;
+            }
+        }
+        
+        // Новая скорость по модели ускорения системы с ограничением по максимуму
+        let new_speed = space.acceleration.next_speed(
+            current_speed,
+            self.acceleration,
+            acceleration_factor,
+            space.plane_progress(&position),
+            self.max_speed,
+            dt
+        );
+        
+        // Сохраняем направление, но меняем величину скорости
+        if current_speed > 0.0001 {
+            let direction = velocity / current_speed;
+            velocity = direction * new_speed;
+            
+            // Строгое ограничение боковой скорости для предотвращения "мерцания" комет
+            let lateral_speed = (velocity.x * velocity.x + velocity.y * velocity.y).sqrt();
+            
+            if lateral_speed > MAX_LATERAL_SPEED {
+                let lateral_dir = Vec3::new(velocity.x, velocity.y, 0.0).normalize();
+                
+                // Уменьшаем только X и Y компоненты, сохраняя Z-компоненту скорости
+                velocity.x = lateral_dir.x * MAX_LATERAL_SPEED;
+                velocity.y = lateral_dir.y * MAX_LATERAL_SPEED;
+            }
+            
+            // Проверяем, сколько времени потребуется комете, чтобы пересечь поле зрения
+            // Если время слишком мало, снижаем скорость
+            let viewport = space.get_viewport_dimensions();
+            let screen_width = viewport.x * 2.0; // Удвоенная ширина, чтобы учесть весь экран
+            
+            if lateral_speed > 0.0 {
+                let crossing_time = screen_width / lateral_speed;
+                
+                // Если время пересечения экрана меньше минимального, снижаем скорость
+                if crossing_time < MIN_VISIBILITY_TIME {
+                    let adjusted_speed = screen_width / MIN_VISIBILITY_TIME;
+                    let speed_ratio = adjusted_speed / lateral_speed;
+                    
+                    velocity.x *= speed_ratio;
+                    velocity.y *= speed_ratio;
+                    slowed_crossing_time = Some(crossing_time);
+                }
+            }
+        }
+        
+        (velocity, slowed_crossing_time)
+    }
 }
 
 impl SpaceObject for NeonComet {
@@ -218,8 +344,8 @@ impl SpaceObject for NeonComet {
             // Увеличиваем z-компонент для компенсации общей скорости
             self.data.velocity.z *= 1.2;
             
-            console::log_1(&format!("Adjusted high lateral speed for comet {}: {:.2} -> {:.2}", 
-                           self.data.id, lateral_speed, lateral_speed * reduction_factor).into());
+            diagnostics::log_limited("comet_lateral_speed", || format!("Adjusted high lateral speed for comet {}: {:.2} -> {:.2}", 
+                           self.data.id, lateral_speed, lateral_speed * reduction_factor));
         }
         
         // Ограничиваем максимальную скорость
@@ -231,16 +357,10 @@ impl SpaceObject for NeonComet {
         
         // Изменяем выбор цвета в зависимости от ID и количества респаунов
         let color_seed = (self.data.id as u32).wrapping_add(self.respawn_count * 7);
-        let color_choice = color_seed % 5;
+        self.palette_index = color_seed as usize % COMET_PALETTE.len();
         
         // Используем разные цвета для неоновых комет
-        self.color = match color_choice {
-            0 => [0.0, 1.0, 0.8], // Cyan
-            1 => [1.0, 0.2, 0.8], // Pink
-            2 => [0.2, 0.4, 1.0], // Blue
-            3 => [1.0, 0.8, 0.0], // Yellow
-            _ => [0.6, 0.0, 1.0], // Purple
-        };
+        self.color = COMET_PALETTE[self.palette_index];
         
         // Устанавливаем яркость свечения
         self.glow_intensity = rng.gen_range(1.0..2.2);
@@ -266,18 +386,19 @@ impl SpaceObject for NeonComet {
                 // Увеличиваем счетчик респаунов для уникальности
                 self.respawn_count += 1;
                 
-                // Создаем по-настоящему случайный seed, используя id, счетчик респаунов, и текущее время
-                let time_seed = (js_sys::Date::now() as u64) & 0xFFFFFFFF;
-                let seed = (self.data.id as u64)
-                    .wrapping_mul(42)
-                    .wrapping_add(self.respawn_count as u64)
-                    .wrapping_add(time_seed);
+                // Seed респауна выводится из seed-а кометы и номера респауна;
+                // время подмешивается только в режиме энтропии
+                let mut seed = derive_seed(self.seed, self.respawn_count as u64, 0);
+                if space.entropy_respawns {
+                    seed = derive_seed(seed, platform::now_ms() as u64, 0);
+                }
                 let mut local_rng = StdRng::seed_from_u64(seed);
                 
                 // Генерируем новый случайный сдвиг для разнообразия
                 self.random_offset = local_rng.gen_range(-50.0..50.0);
                 
                 self.initialize_random(&mut local_rng, space);
+                reroll_protected(self, &mut local_rng, space);
                 // console::log_1(&format!("Comet {} respawned for the {} time with offset {}", 
                 //     self.data.id, self.respawn_count, self.random_offset).into());
             }
@@ -294,75 +415,23 @@ impl SpaceObject for NeonComet {
         }
         
         // Применяем постепенное ускорение с ограничением максимальной скорости
-        let current_speed = self.data.velocity.length();
-        
-        // Применяем адаптивное ускорение
-        let mut acceleration_factor = 1.0;
-        
-        // Рассчитываем вектор от наблюдателя к объекту
-        let to_object = self.data.position - space.observer_position;
+        let (velocity, slowed_crossing_time) =
+            self.accelerated_velocity(self.data.position, self.data.velocity, dt, space);
+        self.data.velocity = velocity;
         
-        // Проверяем, движется ли объект в направлении наблюдателя (по Z)
-        if to_object.z > 0.0 && self.data.velocity.z < 0.0 {
-            // Рассчитываем расстояние до наблюдателя
-            let distance = to_object.length();
-            
-            // Добавляем дополнительное ускорение по мере приближения
-            if distance < 50.0 {
-                // Усиление ускорения от 1.0 до 1.5 по мере приближения (снижено с 2.0)
-                acceleration_factor = 1.0 + (1.0 - distance / 50.0) * 0.5;
-            }
-        }
-        
-        // Рассчитываем прирост скорости с учетом коэффициента ускорения
-        let speed_increase = self.acceleration * dt * acceleration_factor;
-        
-        // Новая скорость с ограничением по максимуму
-        let new_speed = (current_speed + speed_increase).min(self.max_speed);
-        
-        // Сохраняем направление, но меняем величину скорости
-        if current_speed > 0.0001 {
-            let direction = self.data.velocity / current_speed;
-            self.data.velocity = direction * new_speed;
-            
-            // Строгое ограничение боковой скорости для предотвращения "мерцания" комет
-            let lateral_speed = (self.data.velocity.x * self.data.velocity.x + 
-                                self.data.velocity.y * self.data.velocity.y).sqrt();
-            
-            if lateral_speed > MAX_LATERAL_SPEED {
-                let lateral_dir = Vec3::new(self.data.velocity.x, self.data.velocity.y, 0.0).normalize();
-                
-                // Уменьшаем только X и Y компоненты, сохраняя Z-компоненту скорости
-                self.data.velocity.x = lateral_dir.x * MAX_LATERAL_SPEED;
-                self.data.velocity.y = lateral_dir.y * MAX_LATERAL_SPEED;
-            }
-            
-            // Проверяем, сколько времени потребуется комете, чтобы пересечь поле зрения
-            // Если время слишком мало, снижаем скорость
-            let viewport = space.get_viewport_dimensions();
-            let screen_width = viewport.x * 2.0; // Удвоенная ширина, чтобы учесть весь экран
-            
-            if lateral_speed > 0.0 {
-                let crossing_time = screen_width / lateral_speed;
-                
-                // Если время пересечения экрана меньше минимального, снижаем скорость
-                if crossing_time < MIN_VISIBILITY_TIME {
-                    let adjusted_speed = screen_width / MIN_VISIBILITY_TIME;
-                    let speed_ratio = adjusted_speed / lateral_speed;
-                    
-                    self.data.velocity.x *= speed_ratio;
-                    self.data.velocity.y *= speed_ratio;
-                    
-                    // Логируем информацию о замедлении слишком быстрых комет
-                    console::log_1(&format!("Slowed down fast comet {}: crossing time {:.2}s -> {:.2}s", 
-                                          self.data.id, crossing_time, MIN_VISIBILITY_TIME).into());
-                }
-            }
+        // Логируем информацию о замедлении слишком быстрых комет
+        if let Some(crossing_time) = slowed_crossing_time {
+            diagnostics::log_limited("comet_slowed_down", || format!("Slowed down fast comet {}: crossing time {:.2}s -> {:.2}s", 
+                                  self.data.id, crossing_time, MIN_VISIBILITY_TIME));
         }
         
         // Обновляем позицию на основе скорости
         self.data.position += self.data.velocity * dt;
         
+        // Турбулентное "дрожание" траектории от 4D шума (четвертая координата - время жизни)
+        let turbulence = simulation_noise().turbulence(self.data.position, self.data.lifetime, TURBULENCE_FREQUENCY);
+        self.data.position += turbulence * TURBULENCE_STRENGTH * dt;
+        
         // Проверяем, вышла ли комета за пределы пространства
         let space_dims = space.get_dimensions();
         let pos = self.data.position;
@@ -375,9 +444,10 @@ impl SpaceObject for NeonComet {
         if to_comet.z < -30.0 || pos.x.abs() > space_dims.x || pos.y.abs() > space_dims.y {
             // Устанавливаем в режим ожидания респауна
             self.waiting_for_respawn = true;
-            self.respawn_delay = rand::thread_rng().gen_range(MIN_SPAWN_DELAY..MAX_SPAWN_DELAY);
-            console::log_1(&format!("Comet {} went out of bounds, will respawn in {} seconds", 
-                                   self.data.id, self.respawn_delay).into());
+            let mut delay_rng = StdRng::seed_from_u64(derive_seed(self.seed, self.respawn_count as u64, 1));
+            self.respawn_delay = delay_rng.gen_range(MIN_SPAWN_DELAY..MAX_SPAWN_DELAY);
+            diagnostics::log_limited("comet_out_of_bounds", || format!("Comet {} went out of bounds, will respawn in {} seconds", 
+                                   self.data.id, self.respawn_delay));
             return true; // Объект остаётся активным, но ждет респауна
         }
         
@@ -387,7 +457,7 @@ impl SpaceObject for NeonComet {
             glam::EulerRot::XYZ,
             rot_speed, rot_speed * 0.7, rot_speed * 0.3
         );
-        self.data.rotation = self.data.rotation * rotation_delta;
+        self.data.rotation *= rotation_delta;
         
         // Постепенно увеличиваем размер кометы от начального до целевого
         if self.data.size < self.target_size {
@@ -406,15 +476,13 @@ impl SpaceObject for NeonComet {
             self.data.scale = 0.01;
         }
         
-        // Обновляем прозрачность: постепенное появление в начале жизни
-        if self.data.lifetime < 1.0 {
-            // First second - gradual appearance
-            self.data.opacity = self.data.lifetime;
-        } else {
-            // Normal distance-based transparency, without special handling for camera proximity
-            let base_opacity = space.get_transparency_factor(&self.data.position);
-            self.data.opacity = base_opacity.max(0.3);
-        }
+        // Обновляем прозрачность по профилю типа: появление, полет и затухание за плоскостью
+        let profile = space.opacity_profile(self.data.object_type);
+        self.data.opacity = profile.opacity(
+            self.data.lifetime,
+            space.get_transparency_factor(&self.data.position),
+            space.viewing_plane_z - self.data.position.z
+        );
         
         // Предотвращаем прямую зависимость от прохождения через наблюдателя/камеру
         // Вместо этого увеличиваем яркость на основе пройденного расстояния
@@ -431,12 +499,20 @@ impl SpaceObject for NeonComet {
         
         // Яркость свечения пульсирует со временем
         let pulse_factor = (self.data.lifetime * 2.0).sin() * 0.2 + 0.8;
-        self.glow_intensity = self.glow_intensity * pulse_factor;
+        self.glow_intensity *= pulse_factor;
         
         // Объект остается активным
         true
     }
     
+    fn predict_velocity(&self, position: Vec3, velocity: Vec3, dt: f32, space: &SpaceDefinition) -> Vec3 {
+        self.accelerated_velocity(position, velocity, dt, space).0
+    }
+    
+    fn is_waiting_for_respawn(&self) -> bool {
+        self.waiting_for_respawn
+    }
+    
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -446,20 +522,132 @@ impl SpaceObject for NeonComet {
     }
 }
 
+// Создать одну комету на дальней плоскости и добавить ее в систему.
+// Возвращает ID новой кометы
+pub fn spawn_neon_comet(system: &mut SpaceObjectSystem) -> usize {
+    // Получаем следующий ID
+    let comet_id = system.next_id;
+    system.next_id += 1;
+    
+    // Клонируем определение пространства, чтобы избежать конфликтов заимствования
+    let space_definition = system.space.clone();
+    
+    // Создаем новую комету
+    let mut comet = NeonComet::new(comet_id);
+    comet.seed = derive_seed(system.seed(), comet_id as u64, 0);
+    
+    // Инициализируем комету со случайными свойствами
+    comet.initialize_random(system.get_rng_mut(), &space_definition);
+    reroll_protected(&mut comet, system.get_rng_mut(), &space_definition);
+    
+    // Палитра сцены может быть смещена гиперкубом
+    comet.shift_palette(system.modulation.palette_t);
+    system.space.record_spawn(&comet.data.position);
+    
+    // Добавляем комету в систему
+    system.get_objects_mut()
+            .entry(SpaceObjectType::NeonComet)
+            .or_default()
+            .push(Box::new(comet));
+    system.population.record_spawn(SpaceObjectType::NeonComet);
+    system.lifecycle.spawned(comet_id, SpaceObjectType::NeonComet);
+    
+    comet_id
+}
+
+// Параметры планировщика появлений
+const MAX_PENDING_SPAWNS: usize = 128;           // Глобальный лимит очереди появлений (все системы)
+const AUTO_SPAWN_QUOTA_PER_SYSTEM: usize = 6;    // Квота автоматических появлений в очереди на систему
+const AUTO_SPAWN_MIN_ACTIVE: usize = 5;          // Ниже этого числа активных комет система пополняется
+
+// Планировщик отложенного создания комет.
+// Очередь общая для всех систем, но каждая система получает свою квоту
+// автоподпитки, а обход систем идет по кругу, чтобы при упоре в глобальный
+// лимит не выигрывали всегда одни и те же системы
+struct SpawnScheduler {
+    pending: Vec<(usize, f32)>,     // (ID системы, оставшаяся задержка)
+    cursor: usize,                  // С какой системы начинать следующий обход
+    spawned_total: u64,             // Всего создано комет через очередь
+    rejected_total: u64,            // Отклонено заявок из-за глобального лимита
+    deferred_last_frame: usize,     // Готовых заявок, отложенных на следующий кадр
+}
+
+impl SpawnScheduler {
+    fn new() -> Self {
+        SpawnScheduler {
+            pending: Vec::new(),
+            cursor: 0,
+            spawned_total: 0,
+            rejected_total: 0,
+            deferred_last_frame: 0,
+        }
+    }
+    
+    // Количество заявок в очереди для системы
+    fn pending_for(&self, system_id: usize) -> usize {
+        self.pending.iter().filter(|(id, _)| *id == system_id).count()
+    }
+    
+    // Добавить заявку с учетом глобального лимита
+    fn push(&mut self, system_id: usize, delay: f32) -> bool {
+        if self.pending.len() >= MAX_PENDING_SPAWNS {
+            self.rejected_total += 1;
+            return false;
+        }
+        
+        self.pending.push((system_id, delay));
+        true
+    }
+    
+    // Уменьшить задержки и забрать готовые заявки, не больше
+    // MAX_SIMULTANEOUS_SPAWNS на систему за кадр (остальные ждут следующего кадра).
+    // Если указан only_system, обрабатываются только заявки этой системы.
+    // Возвращает готовые заявки и число отложенных
+    fn take_ready(&mut self, dt: f32, only_system: Option<usize>) -> (Vec<usize>, usize) {
+        let mut ready: Vec<usize> = Vec::new();
+        let mut deferred = 0;
+        
+        self.pending.retain_mut(|(system_id, delay)| {
+            if only_system.is_some_and(|id| id != *system_id) {
+                return true;
+            }
+            
+            *delay -= dt;
+            if *delay > 0.0 {
+                return true;
+            }
+            
+            let already = ready.iter().filter(|id| **id == *system_id).count();
+            if already < MAX_SIMULTANEOUS_SPAWNS {
+                ready.push(*system_id);
+                false
+            } else {
+                deferred += 1;
+                true
+            }
+        });
+        
+        (ready, deferred)
+    }
+}
+
 // Хранилище для отложенного создания комет
-static PENDING_COMETS: Lazy<Mutex<Vec<(usize, f32)>>> = Lazy::new(|| Mutex::new(Vec::new()));
+static SPAWN_SCHEDULER: Lazy<Mutex<SpawnScheduler>> = Lazy::new(|| Mutex::new(SpawnScheduler::new()));
 
-#[allow(unused_variables)]
 #[wasm_bindgen]
-pub fn spawn_neon_comets(system_id: usize, count: usize) -> bool {
+pub fn spawn_neon_comets(system_id: usize, count: usize) -> OpResult {
     // Проверяем наличие системы объектов, используя DashMap API
-    if let Some(system_ref) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+    if let Some(mut system_ref) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        // Явный запрос появления снимает остановку после despawn_all
+        system_ref.fade.resume_spawning();
+        
         let mut rng = thread_rng();
-        let mut pending = PENDING_COMETS.lock().unwrap();
+        let mut scheduler = lock_or_recover(&SPAWN_SCHEDULER, "SPAWN_SCHEDULER");
         
         // Распределяем появление комет по группам (по 1-3 кометы)
         let mut remaining = count;
         let mut current_delay = 0.0;
+        let mut scheduled = 0;
         
         while remaining > 0 {
             // Определяем количество комет в текущей группе (1-3 или оставшиеся)
@@ -470,7 +658,9 @@ pub fn spawn_neon_comets(system_id: usize, count: usize) -> bool {
             
             // Создаем задержку для группы
             for _ in 0..group_size {
-                pending.push((system_id, current_delay));
+                if scheduler.push(system_id, current_delay) {
+                    scheduled += 1;
+                }
             }
             
             // Уменьшаем оставшееся количество
@@ -482,103 +672,229 @@ pub fn spawn_neon_comets(system_id: usize, count: usize) -> bool {
             }
         }
         
-        console::log_1(&format!("Scheduled spawning of {} comets with staggered delays", count).into());
-        true
+        platform::log(&format!("Scheduled spawning of {} of {} comets with staggered delays", scheduled, count));
+        OpResult::success()
     } else {
-        false
+        OpResult::system_not_found(system_id)
     }
 }
 
 #[wasm_bindgen]
 pub fn process_neon_comet_spawns(dt: f32) -> usize {
+    // Пока вкладка скрыта (или сразу после возвращения) шаг определяет политика догоняния
+    let Some(dt) = visibility::spawn_queue_dt(dt) else {
+        return 0;
+    };
+    
     let mut spawned = 0;
-    let mut pending = PENDING_COMETS.lock().unwrap();
+    let mut scheduler = lock_or_recover(&SPAWN_SCHEDULER, "SPAWN_SCHEDULER");
     
     // Обрабатываем задержки и собираем ID систем, нуждающихся в новых кометах
-    let mut systems_to_spawn: Vec<usize> = Vec::new();
-    
-    pending.retain_mut(|(system_id, delay)| {
-        *delay -= dt;
-        
-        if *delay <= 0.0 {
-            systems_to_spawn.push(*system_id);
-            false // Удаляем из списка ожидания
-        } else {
-            true // Оставляем в списке ожидания
-        }
-    });
+    let (systems_to_spawn, deferred) = scheduler.take_ready(dt, None);
+    scheduler.deferred_last_frame = deferred;
     
     // Теперь создаем кометы для каждой системы
     for system_id in systems_to_spawn {
         // Получаем доступ к системе объектов через DashMap
         if let Some(mut system_ref) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
-            // Получаем следующий ID
-            let comet_id = system_ref.next_id;
-            system_ref.next_id += 1;
-            
-            // Клонируем определение пространства, чтобы избежать конфликтов заимствования
-            let space_definition = system_ref.space.clone();
-            
-            // Создаем новую комету
-            let mut comet = NeonComet::new(comet_id);
-            
-            // Инициализируем комету со случайными свойствами
-            comet.initialize_random(system_ref.get_rng_mut(), &space_definition);
-            
-            // Добавляем комету в систему
-            system_ref.get_objects_mut()
-                    .entry(SpaceObjectType::NeonComet)
-                    .or_insert_with(Vec::new)
-                    .push(Box::new(comet));
+            if system_ref.fade.is_spawning_stopped() {
+                continue;
+            }
             
+            let comet_id = spawn_neon_comet(&mut system_ref);
             spawned += 1;
             
             // Выводим отладочную информацию
-            console::log_1(&format!("Created comet with ID: {} at far plane", comet_id).into());
+            diagnostics::log_limited("comet_created", || format!("Created comet with ID: {} at far plane", comet_id));
         }
     }
+    scheduler.spawned_total += spawned as u64;
+    
+    // Автоматическое "воскрешение" систем с малым числом активных комет.
+    // Системы обходятся по кругу начиная с курсора, каждая ограничена своей квотой
+    let mut system_ids: Vec<usize> = SPACE_OBJECT_SYSTEMS.iter().map(|entry| *entry.key()).collect();
+    system_ids.sort_unstable();
+    
+    if !system_ids.is_empty() {
+        let start = scheduler.cursor % system_ids.len();
+        system_ids.rotate_left(start);
+        scheduler.cursor = start + 1;
+    }
     
-    // Добавим случайное создание новых комет для "воскрешения" системы,
-    // если в очереди мало комет и общее количество активных комет мало
-    if pending.len() < 3 {
-        // Проверяем количество активных комет во всех системах
-        // let mut total_active_comets = 0;
+    let mut rng = thread_rng();
+    for system_id in system_ids {
+        let Some(system) = SPACE_OBJECT_SYSTEMS.get(&system_id) else {
+            continue;
+        };
         
-        // Используем итератор DashMap для доступа к системам
-        for system_ref in SPACE_OBJECT_SYSTEMS.iter() {
-            let system_id = *system_ref.key();
-            let system = system_ref.value();
-            
-            let objects = system.get_objects();
-            if let Some(comets) = objects.get(&SpaceObjectType::NeonComet) {
-                let active_comets = comets.iter()
-                    .filter(|c| !c.as_any().downcast_ref::<NeonComet>().unwrap().waiting_for_respawn)
-                    .count();
-                
-               // total_active_comets += active_comets;
-                
-                // Если в системе мало активных комет, добавляем новые
-                if active_comets < 5 {
-                    let mut rng = thread_rng();
-                    let new_comets = rng.gen_range(1..=MAX_SIMULTANEOUS_SPAWNS);
-                    let delay = rng.gen_range(0.5..2.0);
-                    
-                    // Добавляем в очередь появления
-                    for _ in 0..new_comets {
-                        pending.push((system_id, delay));
-                    }
-                    
-                    // console::log_1(&format!("Auto-scheduling {} new comets for system {}", new_comets, system_id).into());
-                }
-            }
+        // Подпитываем только системы, в которых уже есть кометы и появление не остановлено
+        if system.fade.is_spawning_stopped() {
+            continue;
+        }
+        let Some(comets) = system.get_objects().get(&SpaceObjectType::NeonComet) else {
+            continue;
+        };
+        let active_comets = comets.iter().filter(|c| !c.is_waiting_for_respawn()).count();
+        
+        if active_comets >= AUTO_SPAWN_MIN_ACTIVE {
+            continue;
         }
         
-        // console::log_1(&format!("Total active comets: {}", total_active_comets).into());
+        let quota_left = AUTO_SPAWN_QUOTA_PER_SYSTEM.saturating_sub(scheduler.pending_for(system_id));
+        let new_comets = rng.gen_range(1..=MAX_SIMULTANEOUS_SPAWNS).min(quota_left);
+        // Частота появления модулируется гиперкубом
+        let delay = rng.gen_range(0.5..2.0) / system.modulation.spawn_rate.max(0.1);
+        
+        // Добавляем в очередь появления
+        for _ in 0..new_comets {
+            scheduler.push(system_id, delay);
+        }
     }
     
     spawned
 }
 
+// Количество заявок в очереди появлений для системы
+pub(crate) fn pending_spawns_for(system_id: usize) -> usize {
+    lock_or_recover(&SPAWN_SCHEDULER, "SPAWN_SCHEDULER").pending_for(system_id)
+}
+
+// Число заявок без ожидания блокировки очереди (для отчета о панике)
+pub(crate) fn try_pending_spawns() -> Option<usize> {
+    SPAWN_SCHEDULER.try_lock().ok().map(|scheduler| scheduler.pending.len())
+}
+
+// Отменить заявки системы в очереди появлений; возвращает число отмененных
+pub(crate) fn cancel_pending_spawns(system_id: usize) -> usize {
+    let mut scheduler = lock_or_recover(&SPAWN_SCHEDULER, "SPAWN_SCHEDULER");
+    let before = scheduler.pending.len();
+    scheduler.pending.retain(|(id, _)| *id != system_id);
+    before - scheduler.pending.len()
+}
+
+// Память очереди появлений: (заявок, емкость, размер заявки в байтах)
+pub(crate) fn spawn_queue_memory() -> (usize, usize, usize) {
+    let scheduler = lock_or_recover(&SPAWN_SCHEDULER, "SPAWN_SCHEDULER");
+    (scheduler.pending.len(), scheduler.pending.capacity(), std::mem::size_of::<(usize, f32)>())
+}
+
+// Статистика очереди появлений для отладки и мониторинга
+#[wasm_bindgen]
+pub struct SpawnQueueStats {
+    total_pending: usize,
+    system_ids: Vec<usize>,
+    pending_per_system: Vec<usize>,
+    spawned_total: f64,
+    rejected_total: f64,
+    deferred_last_frame: usize,
+    global_cap: usize,
+    per_system_quota: usize,
+}
+
+#[wasm_bindgen]
+impl SpawnQueueStats {
+    #[wasm_bindgen(getter)]
+    pub fn total_pending(&self) -> usize {
+        self.total_pending
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn system_ids(&self) -> Vec<usize> {
+        self.system_ids.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn pending_per_system(&self) -> Vec<usize> {
+        self.pending_per_system.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn spawned_total(&self) -> f64 {
+        self.spawned_total
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn rejected_total(&self) -> f64 {
+        self.rejected_total
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn deferred_last_frame(&self) -> usize {
+        self.deferred_last_frame
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn global_cap(&self) -> usize {
+        self.global_cap
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn per_system_quota(&self) -> usize {
+        self.per_system_quota
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_spawn_queue_stats() -> SpawnQueueStats {
+    let scheduler = lock_or_recover(&SPAWN_SCHEDULER, "SPAWN_SCHEDULER");
+    
+    let mut system_ids: Vec<usize> = scheduler.pending.iter().map(|(id, _)| *id).collect();
+    system_ids.sort_unstable();
+    system_ids.dedup();
+    
+    let pending_per_system = system_ids.iter()
+        .map(|id| scheduler.pending_for(*id))
+        .collect();
+    
+    SpawnQueueStats {
+        total_pending: scheduler.pending.len(),
+        system_ids,
+        pending_per_system,
+        spawned_total: scheduler.spawned_total as f64,
+        rejected_total: scheduler.rejected_total as f64,
+        deferred_last_frame: scheduler.deferred_last_frame,
+        global_cap: MAX_PENDING_SPAWNS,
+        per_system_quota: AUTO_SPAWN_QUOTA_PER_SYSTEM,
+    }
+}
+
+// Параметры предварительной симуляции ("прогрева") системы
+const PRESIMULATION_STEP: f32 = 1.0 / 30.0;     // Шаг предварительной симуляции (сек)
+const MAX_PRESIMULATION_SECONDS: f32 = 10.0;    // Максимум симулируемого времени за один вызов
+
+// Прогреть систему: продвинуть ее на seconds секунд (не больше MAX_PRESIMULATION_SECONDS
+// за вызов) вместе с отложенными появлениями комет этой системы, чтобы первый
+// отрисованный кадр уже показывал заполненное небо.
+// Возвращает фактически просимулированное время
+#[wasm_bindgen]
+pub fn presimulate(system_id: usize, seconds: f32) -> f32 {
+    let budget = seconds.clamp(0.0, MAX_PRESIMULATION_SECONDS);
+    let mut simulated = 0.0;
+    
+    while simulated < budget {
+        let dt = PRESIMULATION_STEP.min(budget - simulated);
+        
+        // Порядок блокировок как в process_neon_comet_spawns: сначала очередь, затем система
+        let mut scheduler = lock_or_recover(&SPAWN_SCHEDULER, "SPAWN_SCHEDULER");
+        let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) else {
+            break;
+        };
+        
+        // Обрабатываем только отложенные появления этой системы
+        // Отложенные при прогреве заявки не попадают в статистику кадров
+        let (ready, _) = scheduler.take_ready(dt, Some(system_id));
+        for _ in &ready {
+            spawn_neon_comet(&mut system);
+        }
+        scheduler.spawned_total += ready.len() as u64;
+        
+        system.update(dt);
+        simulated += dt;
+    }
+    
+    simulated
+}
+
 #[wasm_bindgen]
 pub fn get_active_neon_comets_count(system_id: usize) -> usize {
     // Получаем доступ к системе через DashMap API
@@ -595,14 +911,24 @@ pub fn get_active_neon_comets_count(system_id: usize) -> usize {
 // Структура для передачи данных о нескольких кометах в JavaScript
 #[wasm_bindgen]
 pub struct CometDataArray {
-    ids: Vec<usize>,
-    positions: Vec<f32>,
-    scales: Vec<f32>,
+    pub(crate) ids: Vec<usize>,
+    pub(crate) positions: Vec<f32>,
+    pub(crate) scales: Vec<f32>,
     rotations: Vec<f32>,
-    opacities: Vec<f32>,
-    colors: Vec<f32>,
+    pub(crate) opacities: Vec<f32>,
+    pub(crate) colors: Vec<f32>,
     tail_lengths: Vec<f32>,
     glow_intensities: Vec<f32>,
+    occluded: Vec<u8>,
+    selected: Vec<u8>,
+    highlights: Vec<f32>,
+    emissive: Vec<f32>,
+    color_seeds: Vec<u32>,
+    palette_indices: Vec<u8>,
+    motion_vectors: Vec<f32>,
+    bounds_min: Vec<f32>,
+    bounds_max: Vec<f32>,
+    bounding_spheres: Vec<f32>,
 }
 
 #[wasm_bindgen]
@@ -647,91 +973,188 @@ impl CometDataArray {
     pub fn glow_intensities(&self) -> Vec<f32> {
         self.glow_intensities.clone()
     }
+    
+    // 1 - комета внутри области перекрытия (хвост не строится)
+    #[wasm_bindgen(getter)]
+    pub fn occluded(&self) -> Vec<u8> {
+        self.occluded.clone()
+    }
+    
+    // 1 - комета выделена
+    #[wasm_bindgen(getter)]
+    pub fn selected(&self) -> Vec<u8> {
+        self.selected.clone()
+    }
+    
+    // Пульсирующая подсветка выделенной кометы (0..1)
+    #[wasm_bindgen(getter)]
+    pub fn highlights(&self) -> Vec<f32> {
+        self.highlights.clone()
+    }
+    
+    // Яркость свечения для HDR/bloom (множитель цвета, может превышать 1)
+    #[wasm_bindgen(getter)]
+    pub fn emissive(&self) -> Vec<f32> {
+        self.emissive.clone()
+    }
+    
+    // Смещение проекции на окно за последний шаг (dx, dy в долях окна, y вниз)
+    #[wasm_bindgen(getter)]
+    pub fn motion_vectors(&self) -> Vec<f32> {
+        self.motion_vectors.clone()
+    }
+    
+    // Мировой AABB (с хвостом): min (x, y, z)
+    #[wasm_bindgen(getter)]
+    pub fn bounds_min(&self) -> Vec<f32> {
+        self.bounds_min.clone()
+    }
+    
+    // Мировой AABB: max (x, y, z)
+    #[wasm_bindgen(getter)]
+    pub fn bounds_max(&self) -> Vec<f32> {
+        self.bounds_max.clone()
+    }
+    
+    // Ограничивающая сфера объекта (cx, cy, cz, r)
+    #[wasm_bindgen(getter)]
+    pub fn bounding_spheres(&self) -> Vec<f32> {
+        self.bounding_spheres.clone()
+    }
+    
+    // Постоянный сид цветовой вариации (до респауна кометы)
+    #[wasm_bindgen(getter)]
+    pub fn color_seeds(&self) -> Vec<u32> {
+        self.color_seeds.clone()
+    }
+    
+    // Индекс цвета в палитре комет (get_comet_palette)
+    #[wasm_bindgen(getter)]
+    pub fn palette_indices(&self) -> Vec<u8> {
+        self.palette_indices.clone()
+    }
 }
 
-#[wasm_bindgen]
-pub fn get_visible_neon_comets(system_id: usize) -> Option<CometDataArray> {
-    // Получаем доступ к системе через DashMap API
-    if let Some(system_ref) = SPACE_OBJECT_SYSTEMS.get(&system_id) {
-        let objects = system_ref.get_objects();
-        if let Some(comets) = objects.get(&SpaceObjectType::NeonComet) {
-            let mut data = CometDataArray {
-                ids: Vec::with_capacity(comets.len()),
-                positions: Vec::with_capacity(comets.len() * 3),
-                scales: Vec::with_capacity(comets.len()),
-                rotations: Vec::with_capacity(comets.len() * 4),
-                opacities: Vec::with_capacity(comets.len()),
-                colors: Vec::with_capacity(comets.len() * 3),
-                tail_lengths: Vec::with_capacity(comets.len()),
-                glow_intensities: Vec::with_capacity(comets.len()),
-            };
+// Собрать данные видимых комет системы (чистая Rust-функция без JS-обвязки).
+// Возвращает None, если в системе еще нет комет
+pub fn collect_visible_neon_comets(system: &SpaceObjectSystem) -> Option<CometDataArray> {
+    let objects = system.get_objects();
+    let comets = objects.get(&SpaceObjectType::NeonComet)?;
+    
+    let mut data = CometDataArray {
+        ids: Vec::with_capacity(comets.len()),
+        positions: Vec::with_capacity(comets.len() * 3),
+        scales: Vec::with_capacity(comets.len()),
+        rotations: Vec::with_capacity(comets.len() * 4),
+        opacities: Vec::with_capacity(comets.len()),
+        colors: Vec::with_capacity(comets.len() * 3),
+        tail_lengths: Vec::with_capacity(comets.len()),
+        glow_intensities: Vec::with_capacity(comets.len()),
+        occluded: Vec::with_capacity(comets.len()),
+        selected: Vec::with_capacity(comets.len()),
+        highlights: Vec::with_capacity(comets.len()),
+        emissive: Vec::with_capacity(comets.len()),
+        color_seeds: Vec::with_capacity(comets.len()),
+        palette_indices: Vec::with_capacity(comets.len()),
+        motion_vectors: Vec::with_capacity(comets.len() * 2),
+        bounds_min: Vec::with_capacity(comets.len() * 3),
+        bounds_max: Vec::with_capacity(comets.len() * 3),
+        bounding_spheres: Vec::with_capacity(comets.len() * 4),
+    };
+    
+    // Хвосты укорачиваются профилем питания
+    let tail_scale = system.power_profile.budget().tail_scale;
+    
+    // let mut visible_count = 0;
+    
+    for comet in comets.iter() {
+        // Получаем доступ к специфичным для кометы данным
+        let neon_comet = comet.as_any().downcast_ref::<NeonComet>().unwrap();
+        
+        // Пропускаем кометы, ожидающие респауна
+        if neon_comet.waiting_for_respawn {
+            continue;
+        }
+        
+        // Проверяем видимость кометы
+        #[cfg(debug_assertions)]
+        let is_visible = true;
+        
+        // В релизной версии используем обычную проверку видимости
+        #[cfg(not(debug_assertions))]
+        let is_visible = comet.is_visible(&system.space);
+        
+        if is_visible {
+            let comet_data = comet.get_data();
+            // visible_count += 1;
             
-            // let mut visible_count = 0;
+            // ID
+            data.ids.push(comet_data.id);
             
-            for comet in comets.iter() {
-                // Получаем доступ к специфичным для кометы данным
-                let neon_comet = comet.as_any().downcast_ref::<NeonComet>().unwrap();
-                
-                // Пропускаем кометы, ожидающие респауна
-                if neon_comet.waiting_for_respawn {
-                    continue;
-                }
-                
-                // Проверяем видимость кометы
-                #[cfg(debug_assertions)]
-                let is_visible = true;
-                
-                // В релизной версии используем обычную проверку видимости
-                #[cfg(not(debug_assertions))]
-                let is_visible = comet.is_visible(&system_ref.space);
-                
-                if is_visible {
-                    let comet_data = comet.get_data();
-                    // visible_count += 1;
-                    
-                    // ID
-                    data.ids.push(comet_data.id);
-                    
-                    // Позиция
-                    data.positions.push(comet_data.position.x);
-                    data.positions.push(comet_data.position.y);
-                    data.positions.push(comet_data.position.z);
-                    
-                    // Масштаб
-                    data.scales.push(comet_data.scale);
-                    
-                    // Поворот (как кватернион)
-                    data.rotations.push(comet_data.rotation.x);
-                    data.rotations.push(comet_data.rotation.y);
-                    data.rotations.push(comet_data.rotation.z);
-                    data.rotations.push(comet_data.rotation.w);
-                    
-                    // Прозрачность
-                    data.opacities.push(comet_data.opacity);
-                    
-                    // Цвет
-                    data.colors.extend_from_slice(&neon_comet.color);
-                    
-                    // Длина хвоста
-                    data.tail_lengths.push(neon_comet.tail_length);
-                    
-                    // Интенсивность свечения
-                    data.glow_intensities.push(neon_comet.glow_intensity);
-                }
-            }
+            // Позиция
+            data.positions.push(comet_data.position.x);
+            data.positions.push(comet_data.position.y);
+            data.positions.push(comet_data.position.z);
             
-            // Выводим количество видимых комет для отладки
-            // console::log_1(&format!("Found {} visible comets out of {} total", visible_count, comets.len()).into());
+            // Масштаб
+            data.scales.push(comet_data.scale);
             
-            // Даже если нет видимых комет, все равно возвращаем пустую структуру массива,
-            // чтобы избежать проблем с нулевыми указателями в JavaScript
-            return Some(data);
-        } else {
-            console::log_1(&"No comet objects found in the system".into());
+            // Поворот (как кватернион)
+            data.rotations.push(comet_data.rotation.x);
+            data.rotations.push(comet_data.rotation.y);
+            data.rotations.push(comet_data.rotation.z);
+            data.rotations.push(comet_data.rotation.w);
+            
+            // Прозрачность
+            data.opacities.push(comet_data.opacity);
+            
+            // Цвет
+            data.colors.extend_from_slice(&system.export_color(neon_comet.color));
+            data.color_seeds.push(neon_comet.color_seed());
+            data.palette_indices.push(neon_comet.palette_index as u8);
+            
+            // Длина хвоста: растягивается разгоном, за областью перекрытия не строится
+            let occluded = system.space.is_occluded(&comet_data.position);
+            let tail_length = neon_comet.tail_length * system.warp.multiplier() * tail_scale;
+            data.tail_lengths.push(if occluded { 0.0 } else { tail_length });
+            data.occluded.push(occluded as u8);
+            
+            // Интенсивность свечения
+            data.glow_intensities.push(neon_comet.glow_intensity);
+            
+            // Выделение
+            data.selected.push(system.selection.is_selected(comet_data.id) as u8);
+            let highlight = system.selection.highlight(comet_data.id);
+            data.highlights.push(highlight);
+            data.emissive.push(neon_comet.emissive_intensity(highlight));
+            data.motion_vectors.extend_from_slice(&system.motion.get(comet_data.id).to_array());
+            let bounds = object_bounds(system, comet.as_ref());
+            data.bounds_min.extend_from_slice(&bounds.min.to_array());
+            data.bounds_max.extend_from_slice(&bounds.max.to_array());
+            data.bounding_spheres.extend_from_slice(&bounds.center.extend(bounds.radius).to_array());
         }
+    }
+    
+    // Выводим количество видимых комет для отладки
+    // console::log_1(&format!("Found {} visible comets out of {} total", visible_count, comets.len()).into());
+    
+    // Даже если нет видимых комет, все равно возвращаем пустую структуру массива,
+    // чтобы избежать проблем с нулевыми указателями в JavaScript
+    Some(data)
+}
+
+#[wasm_bindgen]
+pub fn get_visible_neon_comets(system_id: usize) -> Option<CometDataArray> {
+    // Получаем доступ к системе через DashMap API
+    if let Some(system_ref) = SPACE_OBJECT_SYSTEMS.get(&system_id) {
+        let data = collect_visible_neon_comets(&system_ref);
+        if data.is_none() {
+            diagnostics::log_limited("no_comets", || "No comet objects found in the system".to_string());
+        }
+        return data;
     } else {
-        console::log_1(&format!("System with ID {} not found", system_id).into());
+        diagnostics::log_limited("system_not_found", || format!("System with ID {} not found", system_id));
     }
     
     None
-}
\ No newline at end of file
+}
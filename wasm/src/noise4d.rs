@@ -0,0 +1,130 @@
+/*
+ * noise4d.rs
+ * 
+ * Генератор четырехмерного шума (OpenSimplex). Четвертая координата
+ * обычно используется как время, что дает плавно "текущий" 3D шум.
+ * 
+ * Шум используется внутри симуляции для турбулентности комет и плотности
+ * туманности, а также экспортируется в JS, чтобы шейдеры можно было
+ * инициализировать тем же seed и получать согласованную картинку.
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+use noise::{NoiseFn, OpenSimplex};
+use once_cell::sync::Lazy;
+
+// Seed шума, которым пользуется сама симуляция
+pub const SIMULATION_NOISE_SEED: u32 = 0x4E30_4D44;
+
+// Количество октав для фрактального шума плотности туманности
+const NEBULA_OCTAVES: usize = 4;
+
+// Наибольшее число октав фрактального шума: дальше вклад октав меньше точности
+// результата, а число выборок растет линейно
+pub const MAX_FRACTAL_OCTAVES: usize = 8;
+
+// Смещения по W для независимых компонент вектора турбулентности
+const TURBULENCE_W_OFFSETS: [f64; 3] = [0.0, 31.4, 62.8];
+
+// Общий генератор шума симуляции
+static SIMULATION_NOISE: Lazy<Noise4D> = Lazy::new(|| Noise4D::new(SIMULATION_NOISE_SEED));
+
+/// Генератор 4D шума
+#[wasm_bindgen]
+pub struct Noise4D {
+    seed: u32,
+    generator: OpenSimplex,
+}
+
+#[wasm_bindgen]
+impl Noise4D {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u32) -> Self {
+        Self {
+            seed,
+            generator: OpenSimplex::new(seed),
+        }
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+    
+    // Значение шума в точке (примерно в диапазоне -1..1)
+    pub fn sample(&self, x: f64, y: f64, z: f64, w: f64) -> f64 {
+        self.generator.get([x, y, z, w])
+    }
+    
+    // Пакетная выборка: points содержит координаты x, y, z, w подряд
+    pub fn sample_batch(&self, points: &[f64]) -> Vec<f64> {
+        points
+            .chunks_exact(4)
+            .map(|p| self.sample(p[0], p[1], p[2], p[3]))
+            .collect()
+    }
+    
+    // Фрактальный шум (сумма октав, не больше MAX_FRACTAL_OCTAVES), нормированный в диапазон 0..1
+    pub fn fractal(&self, x: f64, y: f64, z: f64, w: f64, octaves: usize) -> f64 {
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut total = 0.0;
+        let mut norm = 0.0;
+        
+        for _ in 0..octaves.clamp(1, MAX_FRACTAL_OCTAVES) {
+            total += self.sample(x * frequency, y * frequency, z * frequency, w * frequency) * amplitude;
+            norm += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        
+        (total / norm * 0.5 + 0.5).clamp(0.0, 1.0)
+    }
+}
+
+impl Noise4D {
+    // Вектор турбулентности в точке: три независимых канала шума (-1..1)
+    pub fn turbulence(&self, position: Vec3, time: f32, frequency: f32) -> Vec3 {
+        let p = position * frequency;
+        let w = time as f64;
+        let channel = |offset: f64| {
+            self.sample(p.x as f64, p.y as f64, p.z as f64, w + offset) as f32
+        };
+        
+        Vec3::new(
+            channel(TURBULENCE_W_OFFSETS[0]),
+            channel(TURBULENCE_W_OFFSETS[1]),
+            channel(TURBULENCE_W_OFFSETS[2])
+        )
+    }
+}
+
+// Получить общий генератор шума симуляции
+pub fn simulation_noise() -> &'static Noise4D {
+    &SIMULATION_NOISE
+}
+
+// Seed шума симуляции - чтобы шейдеры на стороне JS использовали тот же шум
+#[wasm_bindgen]
+pub fn get_noise_seed() -> u32 {
+    SIMULATION_NOISE_SEED
+}
+
+// Значение шума симуляции в точке
+#[wasm_bindgen]
+pub fn noise4d_sample(x: f64, y: f64, z: f64, w: f64) -> f64 {
+    SIMULATION_NOISE.sample(x, y, z, w)
+}
+
+// Пакетная выборка шума симуляции (x, y, z, w подряд)
+#[wasm_bindgen]
+pub fn noise4d_sample_batch(points: &[f64]) -> Vec<f64> {
+    SIMULATION_NOISE.sample_batch(points)
+}
+
+// Плотность туманности в точке пространства в момент времени (0..1)
+#[wasm_bindgen]
+pub fn nebula_density(x: f64, y: f64, z: f64, time: f64) -> f64 {
+    SIMULATION_NOISE.fractal(x * 0.01, y * 0.01, z * 0.01, time * 0.05, NEBULA_OCTAVES)
+}
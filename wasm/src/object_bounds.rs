@@ -0,0 +1,86 @@
+/*
+ * object_bounds.rs
+ *
+ * Габариты отдельных объектов для отсечения и выделения в рендерере:
+ * мировой AABB и ограничивающая сфера. Одного size для этого мало - хвост
+ * кометы тянется далеко за голову, а кольцо вихря сферы шире ее тела.
+ *
+ * - комета: капсула от головы до конца хвоста (хвост против скорости,
+ *   длина с учетом разгона и профиля питания, как в экспорте), радиус -
+ *   половина масштаба головы (как след в hit_test);
+ * - энергетическая сфера: тело радиуса radius() и кольцо вихря; AABB кольца
+ *   точный - по оси i кольцо радиуса R с нормалью n выступает на
+ *   R * sqrt(1 - n_i^2);
+ * - остальные объекты (в том числе кристаллы, пока у них нет своей
+ *   геометрии) - сфера радиусом в половину масштаба.
+ *
+ * Сфера строится по форме объекта, а не описывается вокруг AABB, поэтому
+ * для вытянутых комет она заметно теснее.
+ */
+
+use glam::Vec3;
+
+use crate::energy_spheres::EnergySphere;
+use crate::neon_comets::NeonComet;
+use crate::space_objects::{SpaceObject, SpaceObjectSystem};
+
+/// Мировые габариты объекта
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ObjectBounds {
+    pub min: Vec3,
+    pub max: Vec3,
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl ObjectBounds {
+    pub fn sphere(center: Vec3, radius: f32) -> Self {
+        let radius = radius.max(0.0);
+        Self {
+            min: center - Vec3::splat(radius),
+            max: center + Vec3::splat(radius),
+            center,
+            radius,
+        }
+    }
+
+    // Капсула вокруг отрезка a-b
+    pub fn capsule(a: Vec3, b: Vec3, radius: f32) -> Self {
+        let radius = radius.max(0.0);
+        Self {
+            min: a.min(b) - Vec3::splat(radius),
+            max: a.max(b) + Vec3::splat(radius),
+            center: (a + b) * 0.5,
+            radius: a.distance(b) * 0.5 + radius,
+        }
+    }
+
+    // Шар радиуса body с кольцом радиуса orbit в плоскости с нормалью normal
+    pub fn ringed(center: Vec3, body: f32, normal: Vec3, orbit: f32) -> Self {
+        let normal = normal.normalize_or_zero();
+        let ring = (Vec3::ONE - normal * normal).max(Vec3::ZERO);
+        let extent = (Vec3::new(ring.x.sqrt(), ring.y.sqrt(), ring.z.sqrt()) * orbit.max(0.0)).max(Vec3::splat(body.max(0.0)));
+        Self {
+            min: center - extent,
+            max: center + extent,
+            center,
+            radius: body.max(orbit).max(0.0),
+        }
+    }
+}
+
+// Габариты объекта системы
+pub fn object_bounds(system: &SpaceObjectSystem, obj: &dyn SpaceObject) -> ObjectBounds {
+    let data = obj.get_data();
+    let head_radius = data.scale * 0.5;
+
+    if let Some(comet) = obj.as_any().downcast_ref::<NeonComet>() {
+        let length = comet.tail_length * system.warp.multiplier() * system.power_profile.budget().tail_scale;
+        let tail_end = data.position - data.velocity.normalize_or_zero() * length;
+        return ObjectBounds::capsule(data.position, tail_end, head_radius);
+    }
+    if let Some(sphere) = obj.as_any().downcast_ref::<EnergySphere>() {
+        return ObjectBounds::ringed(data.position, sphere.radius(), sphere.vortex_normal(), sphere.vortex_orbit());
+    }
+    ObjectBounds::sphere(data.position, head_radius)
+}
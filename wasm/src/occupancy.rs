@@ -0,0 +1,144 @@
+/*
+ * occupancy.rs
+ *
+ * Заполненность видимого пространства: сколько активных объектов сейчас
+ * находится в объеме перед видовой плоскостью и как они распределены по
+ * октантам этого объема. Адаптивное появление может по этим данным целиться
+ * в недонаселенные области, а не сыпать объекты равномерно.
+ *
+ * Объем плоскости - параллелепипед над ее прямоугольником от самой плоскости
+ * до дальней границы пространства (max_z), откуда объекты летят к
+ * наблюдателю. Октанты делят его пополам по каждой оси; индекс октанта -
+ * биты (x >= центра) | (y >= центра) << 1 | (z >= центра) << 2, то есть
+ * октанты 4..7 - дальняя половина объема.
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+
+use crate::planes::ViewingPlane;
+use crate::space_objects::{SpaceObjectSystem, SPACE_OBJECT_SYSTEMS};
+
+/// Заполненность объема перед видовой плоскостью
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Occupancy {
+    pub min: Vec3,
+    pub max: Vec3,
+    pub count: u32,
+    pub octants: [u32; 8],
+}
+
+impl Occupancy {
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    // Объем одного октанта
+    pub fn octant_volume(&self) -> f32 {
+        let size = (self.max - self.min).max(Vec3::ZERO);
+        size.x * size.y * size.z / 8.0
+    }
+
+    // Объектов на единицу объема в каждом октанте
+    pub fn octant_densities(&self) -> [f32; 8] {
+        let volume = self.octant_volume();
+        self.octants.map(|count| if volume > 0.0 { count as f32 / volume } else { 0.0 })
+    }
+
+    // Наименее заполненный октант (при равенстве - с меньшим индексом)
+    pub fn least_occupied_octant(&self) -> usize {
+        (0..8).min_by_key(|&octant| self.octants[octant]).unwrap_or(0)
+    }
+}
+
+// Индекс октанта точки внутри объема с центром center
+pub fn octant_index(point: Vec3, center: Vec3) -> usize {
+    (point.x >= center.x) as usize | ((point.y >= center.y) as usize) << 1 | ((point.z >= center.z) as usize) << 2
+}
+
+// Подсчитать активные объекты в объеме перед плоскостью
+pub fn plane_occupancy(system: &SpaceObjectSystem, plane: &ViewingPlane) -> Occupancy {
+    let half = plane.size * 0.5;
+    let min = plane.center - half.extend(0.0);
+    let max = (plane.center.truncate() + half).extend(system.space.max_z.max(plane.center.z));
+    let mut occupancy = Occupancy { min, max, count: 0, octants: [0; 8] };
+    let center = occupancy.center();
+
+    let positions = system
+        .get_objects()
+        .values()
+        .flatten()
+        .filter(|obj| !obj.is_waiting_for_respawn())
+        .map(|obj| obj.get_data().position)
+        .filter(|position| position.cmpge(min).all() && position.cmple(max).all());
+    for position in positions {
+        occupancy.count += 1;
+        occupancy.octants[octant_index(position, center)] += 1;
+    }
+
+    occupancy
+}
+
+/// Заполненность объема плоскости по октантам
+#[wasm_bindgen]
+pub struct PlaneOccupancy {
+    count: u32,
+    octant_counts: Vec<u32>,
+    octant_densities: Vec<f32>,
+    least_occupied_octant: usize,
+    min: Vec<f32>,
+    max: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl PlaneOccupancy {
+    // Активных объектов внутри объема
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    // Объектов в каждом из 8 октантов
+    #[wasm_bindgen(getter)]
+    pub fn octant_counts(&self) -> Vec<u32> {
+        self.octant_counts.clone()
+    }
+
+    // Объектов на единицу объема в каждом октанте
+    #[wasm_bindgen(getter)]
+    pub fn octant_densities(&self) -> Vec<f32> {
+        self.octant_densities.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn least_occupied_octant(&self) -> usize {
+        self.least_occupied_octant
+    }
+
+    // Границы объема (x, y, z)
+    #[wasm_bindgen(getter)]
+    pub fn min(&self) -> Vec<f32> {
+        self.min.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max(&self) -> Vec<f32> {
+        self.max.clone()
+    }
+}
+
+// Заполненность объема перед плоскостью plane_index; None - нет системы или плоскости
+#[wasm_bindgen]
+pub fn get_plane_occupancy(system_id: usize, plane_index: usize) -> Option<PlaneOccupancy> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let plane = system.viewing_planes.get(&system.space, plane_index)?;
+    let occupancy = plane_occupancy(&system, &plane);
+    Some(PlaneOccupancy {
+        count: occupancy.count,
+        octant_counts: occupancy.octants.to_vec(),
+        octant_densities: occupancy.octant_densities().to_vec(),
+        least_occupied_octant: occupancy.least_occupied_octant(),
+        min: occupancy.min.to_array().to_vec(),
+        max: occupancy.max.to_array().to_vec(),
+    })
+}
@@ -0,0 +1,174 @@
+/*
+ * packed_export.rs
+ *
+ * Упакованный экспорт комет для больших сцен. Полные массивы f32 четырехкратно
+ * превышают то, что нужно шейдеру для позиций в пределах пространства и
+ * цветов; get_packed_neon_comets отдает те же данные, что
+ * get_visible_neon_comets, но в половинной точности:
+ *
+ *   positions  - f16 x3 (Uint16Array, биты IEEE 754 binary16)
+ *   scales     - f16
+ *   opacities  - unorm8 (0..255)
+ *   colors     - f16 x3 (PackedColorFormat::Half, HDR-цвета сохраняются)
+ *                или unorm8 x3 (PackedColorFormat::Unorm8, цвет обрезается до 0..1)
+ *
+ * Раскладка описана в get_module_info (packed_layout, packed_layout_version);
+ * версия меняется при любом изменении набора или формата полей. Точность f16
+ * на границе пространства (|x| = 100) - около 0.06 единицы.
+ */
+
+use wasm_bindgen::prelude::*;
+
+use crate::neon_comets::collect_visible_neon_comets;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+// Версия раскладки упакованного экспорта
+pub const PACKED_LAYOUT_VERSION: u16 = 1;
+
+// Описание раскладки для get_module_info: поле:формат, через запятую
+pub const PACKED_LAYOUT: &str = "positions:f16x3,scales:f16,opacities:unorm8,colors:f16x3|unorm8x3";
+
+/// Формат цветов упакованного экспорта
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PackedColorFormat {
+    Half = 0,
+    Unorm8 = 1,
+}
+
+// f32 -> биты binary16 с округлением к ближайшему четному;
+// переполнение дает бесконечность, NaN остается NaN
+pub fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    if exponent == 0xff {
+        let nan = if mantissa != 0 { 0x0200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    if half_exponent <= 0 {
+        // Денормализованные числа binary16 (или ноль)
+        if half_exponent < -10 {
+            return sign;
+        }
+        let mantissa = mantissa | 0x0080_0000;
+        let shift = (14 - half_exponent) as u32;
+        let half_mantissa = mantissa >> shift;
+        let remainder = mantissa & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        let round = remainder > halfway || (remainder == halfway && half_mantissa & 1 == 1);
+        return sign | (half_mantissa as u16 + round as u16);
+    }
+
+    let half = ((half_exponent as u32) << 10) | (mantissa >> 13);
+    let remainder = mantissa & 0x1fff;
+    let round = remainder > 0x1000 || (remainder == 0x1000 && half & 1 == 1);
+    // Перенос округления в порядок корректен и может дать бесконечность
+    sign | (half + round as u32) as u16
+}
+
+// Биты binary16 -> f32 (для проверок и нативных потребителей)
+pub fn f16_to_f32(half: u16) -> f32 {
+    let sign = ((half & 0x8000) as u32) << 16;
+    let exponent = ((half >> 10) & 0x1f) as u32;
+    let mantissa = (half & 0x03ff) as u32;
+
+    let bits = match exponent {
+        0 if mantissa == 0 => sign,
+        0 => {
+            // Денормализованное: нормализуем мантиссу
+            let shift = mantissa.leading_zeros() - 21;
+            let mantissa = (mantissa << shift) & 0x03ff;
+            sign | ((127 - 15 + 1 - shift) << 23) | (mantissa << 13)
+        }
+        0x1f => sign | 0x7f80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 127 - 15) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(bits)
+}
+
+// 0..1 -> 0..255 с округлением; значения вне диапазона обрезаются
+pub fn to_unorm8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Видимые кометы в упакованном виде (раскладка - в заголовке модуля)
+#[wasm_bindgen]
+pub struct PackedCometArray {
+    ids: Vec<usize>,
+    positions: Vec<u16>,
+    scales: Vec<u16>,
+    opacities: Vec<u8>,
+    colors_half: Vec<u16>,
+    colors_unorm8: Vec<u8>,
+    color_format: PackedColorFormat,
+}
+
+#[wasm_bindgen]
+impl PackedCometArray {
+    #[wasm_bindgen(getter)]
+    pub fn ids(&self) -> Vec<usize> {
+        self.ids.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn positions(&self) -> Vec<u16> {
+        self.positions.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn scales(&self) -> Vec<u16> {
+        self.scales.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn opacities(&self) -> Vec<u8> {
+        self.opacities.clone()
+    }
+
+    // Цвета в f16 (пусто для PackedColorFormat::Unorm8)
+    #[wasm_bindgen(getter)]
+    pub fn colors_half(&self) -> Vec<u16> {
+        self.colors_half.clone()
+    }
+
+    // Цвета в unorm8 (пусто для PackedColorFormat::Half)
+    #[wasm_bindgen(getter)]
+    pub fn colors_unorm8(&self) -> Vec<u8> {
+        self.colors_unorm8.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn color_format(&self) -> PackedColorFormat {
+        self.color_format
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_packed_neon_comets(system_id: usize, color_format: PackedColorFormat) -> Option<PackedCometArray> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let data = collect_visible_neon_comets(&system)?;
+
+    let (colors_half, colors_unorm8) = match color_format {
+        PackedColorFormat::Half => (data.colors.iter().map(|&c| f32_to_f16(c)).collect(), Vec::new()),
+        PackedColorFormat::Unorm8 => (Vec::new(), data.colors.iter().map(|&c| to_unorm8(c)).collect()),
+    };
+
+    Some(PackedCometArray {
+        positions: data.positions.iter().map(|&p| f32_to_f16(p)).collect(),
+        scales: data.scales.iter().map(|&s| f32_to_f16(s)).collect(),
+        opacities: data.opacities.iter().map(|&o| to_unorm8(o)).collect(),
+        ids: data.ids,
+        colors_half,
+        colors_unorm8,
+        color_format,
+    })
+}
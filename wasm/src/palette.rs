@@ -0,0 +1,44 @@
+/*
+ * palette.rs
+ *
+ * Палитры и сиды цветовых вариаций для рендереров с атласами текстур и
+ * градиентными LUT. Цвет объекта в экспорте - float RGB, который меняется от
+ * кадра к кадру (оттенки модификаторов, сдвиг палитры), поэтому выбирать по
+ * нему вариацию ненадежно. Вместо этого вместе с цветом экспортируются
+ * индекс палитры и color_seed - 32-битный сид, постоянный все время жизни
+ * объекта (у кометы - до респауна, после которого меняется и ее цвет).
+ *
+ * Палитры отдаются целиком (get_comet_palette, get_sphere_palette), чтобы
+ * рендерер построил LUT один раз.
+ */
+
+use wasm_bindgen::prelude::*;
+
+use crate::energy_spheres::SPHERE_PALETTE;
+use crate::neon_comets::COMET_PALETTE;
+use crate::space_objects::derive_seed;
+
+// Соль сида вариаций: не совпадает с сидами траекторий и респаунов
+const COLOR_SEED_SALT: u64 = 0xC010_5EED;
+
+// Сид цветовой вариации объекта с seed object_seed в поколении generation
+// (номер респауна; у объектов без респауна - 0)
+pub fn color_seed(object_seed: u64, generation: u64) -> u32 {
+    derive_seed(object_seed, generation, COLOR_SEED_SALT) as u32
+}
+
+fn flatten(palette: &[[f32; 3]]) -> Vec<f32> {
+    palette.iter().flatten().copied().collect()
+}
+
+// Палитра комет: [r, g, b] подряд, индекс - palette_indices комет (sRGB)
+#[wasm_bindgen]
+pub fn get_comet_palette() -> Vec<f32> {
+    flatten(&COMET_PALETTE)
+}
+
+// Палитра энергетических сфер (sRGB)
+#[wasm_bindgen]
+pub fn get_sphere_palette() -> Vec<f32> {
+    flatten(&SPHERE_PALETTE)
+}
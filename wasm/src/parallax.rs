@@ -0,0 +1,107 @@
+/*
+ * parallax.rs
+ *
+ * Слои параллакса, привязанные к движению наблюдателя. Слой задается
+ * коэффициентом глубины: 0 - видовая плоскость, 1 - дальняя граница
+ * пространства. Смещение слоя считается по той же модели масштаба, что
+ * и размеры объектов (SpaceDefinition::get_scale_factor), поэтому фоновые
+ * HTML/CSS-слои двигаются согласованно с кометами на той же глубине.
+ *
+ * Наблюдатель смещается либо реально (set_observer_position), либо
+ * синтетически - смещением от мыши или гироскопа (set_parallax_input).
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::{Vec2, Vec3};
+
+use crate::errors::OpResult;
+use crate::space_core::SpaceDefinition;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+/// Слои параллакса системы
+#[derive(Clone, Debug)]
+pub struct ParallaxLayers {
+    // Коэффициенты глубины слоев (0 - видовая плоскость, 1 - дальняя граница)
+    depth_factors: Vec<f32>,
+
+    // Синтетическое смещение наблюдателя (мышь, гироскоп) в единицах пространства
+    input_offset: Vec2,
+
+    // Положение наблюдателя, относительно которого считается смещение
+    rest_observer: Vec3,
+}
+
+impl ParallaxLayers {
+    pub fn new(rest_observer: Vec3) -> Self {
+        Self {
+            depth_factors: Vec::new(),
+            input_offset: Vec2::ZERO,
+            rest_observer,
+        }
+    }
+
+    // Запомнить текущее положение наблюдателя как исходное
+    pub fn set_rest_observer(&mut self, observer: Vec3) {
+        self.rest_observer = observer;
+    }
+
+    // Смещения слоев (x, y на слой): слои сдвигаются против движения наблюдателя,
+    // ближние - сильнее, в пропорции масштаба объектов на их глубине
+    pub fn offsets(&self, space: &SpaceDefinition) -> Vec<f32> {
+        let observer_shift = (space.observer_position - self.rest_observer).truncate() + self.input_offset;
+
+        self.depth_factors.iter()
+            .flat_map(|&factor| {
+                let z = space.viewing_plane_z + factor * (space.max_z - space.viewing_plane_z);
+                let layer_point = Vec3::new(space.observer_position.x, space.observer_position.y, z);
+                let offset = -observer_shift * space.get_scale_factor(&layer_point);
+                [offset.x, offset.y]
+            })
+            .collect()
+    }
+}
+
+impl Default for ParallaxLayers {
+    fn default() -> Self {
+        Self::new(SpaceDefinition::new().observer_position)
+    }
+}
+
+// Добавить слой; возвращает его индекс
+#[wasm_bindgen]
+pub fn add_parallax_layer(system_id: usize, depth_factor: f32) -> Option<usize> {
+    let mut system = SPACE_OBJECT_SYSTEMS.get_mut(&system_id)?;
+    system.parallax.depth_factors.push(depth_factor.clamp(0.0, 1.0));
+    Some(system.parallax.depth_factors.len() - 1)
+}
+
+#[wasm_bindgen]
+pub fn clear_parallax_layers(system_id: usize) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.parallax.depth_factors.clear();
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Синтетическое смещение наблюдателя (например, от положения мыши) в единицах пространства
+#[wasm_bindgen]
+pub fn set_parallax_input(system_id: usize, offset_x: f32, offset_y: f32) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.parallax.input_offset = Vec2::new(offset_x, offset_y);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Смещения всех слоев в единицах пространства: [x0, y0, x1, y1, ...]
+#[wasm_bindgen]
+pub fn get_parallax_offsets(system_id: usize) -> Option<Vec<f32>> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| system.parallax.offsets(&system.space))
+}
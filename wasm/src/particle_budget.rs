@@ -0,0 +1,210 @@
+/*
+ * particle_budget.rs
+ *
+ * Общий бюджет частиц системы. Частицы порождают несколько подсистем: хвосты
+ * комет (tail_particles.rs), вихри энергетических сфер, эффекты ударов и
+ * обломки в физическом мире. Каждая ограничена сама по себе, но в худшем
+ * случае (много комет, частые удары) их сумма не ограничена ничем. Если
+ * хост задает общий бюджет, он каждый кадр делится между подсистемами:
+ *
+ * - спрос подсистемы - сколько частиц ей нужно в этом кадре (хвосты -
+ *   только для комет в пирамиде видимости; эффектам и обломкам - с запасом
+ *   на новые удары);
+ * - бюджет делится пропорционально приоритетам; подсистема, которой нужно
+ *   меньше ее доли, получает сколько нужно, а остаток перераспределяется
+ *   между остальными.
+ *
+ * Эффект удара считается как EFFECT_PARTICLE_COST частиц (рендерер рисует
+ * его набором частиц), обломок - как одна. Без бюджета (по умолчанию)
+ * подсистемы работают со своими собственными пределами.
+ */
+
+use wasm_bindgen::prelude::*;
+
+use crate::comet_effects::MAX_COMET_EFFECTS;
+use crate::debris::MAX_DEBRIS_CHUNKS;
+use crate::energy_spheres::VORTEX_PARTICLES;
+use crate::errors::OpResult;
+use crate::neon_comets::NeonComet;
+use crate::space_objects::{SpaceObject, SpaceObjectSystem, SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+use crate::tail_particles::TAIL_PARTICLES_PER_COMET;
+
+// Частиц на один эффект удара
+pub const EFFECT_PARTICLE_COST: usize = 32;
+
+// Запас спроса на новые эффекты и обломки кадра
+const EFFECT_HEADROOM: usize = 6;
+const DEBRIS_HEADROOM: usize = 8;
+
+const SUBSYSTEM_COUNT: usize = 4;
+
+/// Подсистема, получающая долю бюджета частиц
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticleSubsystem {
+    Tails = 0,
+    Vortices = 1,
+    Effects = 2,
+    Debris = 3,
+}
+
+/// Бюджет частиц системы и его распределение на последнем кадре
+#[derive(Clone, Copy, Debug)]
+pub struct ParticleBudget {
+    total: Option<usize>,
+    priorities: [f32; SUBSYSTEM_COUNT],
+    demands: [usize; SUBSYSTEM_COUNT],
+    allocations: [usize; SUBSYSTEM_COUNT],
+}
+
+impl Default for ParticleBudget {
+    fn default() -> Self {
+        ParticleBudget {
+            total: None,
+            // Хвосты и эффекты ударов заметнее вихрей и обломков
+            priorities: [2.0, 1.0, 2.0, 1.0],
+            demands: [0; SUBSYSTEM_COUNT],
+            allocations: [0; SUBSYSTEM_COUNT],
+        }
+    }
+}
+
+// Разделить total между спросами demands пропорционально весам weights:
+// удовлетворенный спрос выбывает, его неиспользованная доля перераспределяется
+pub fn arbitrate(total: usize, demands: &[usize], weights: &[f32]) -> Vec<usize> {
+    let mut allocations = vec![0; demands.len()];
+    let mut active: Vec<usize> = (0..demands.len()).filter(|&i| demands[i] > 0 && weights[i] > 0.0).collect();
+    let mut remaining = total;
+
+    while !active.is_empty() {
+        let weight_sum: f32 = active.iter().map(|&i| weights[i]).sum();
+        let share = |i: usize| (remaining as f64 * weights[i] as f64 / weight_sum as f64) as usize;
+
+        let satisfied: Vec<usize> = active.iter().copied().filter(|&i| demands[i] <= share(i)).collect();
+        if satisfied.is_empty() {
+            for &i in &active {
+                allocations[i] = share(i);
+            }
+            break;
+        }
+        for i in satisfied {
+            allocations[i] = demands[i];
+            remaining -= demands[i];
+            active.retain(|&other| other != i);
+        }
+    }
+
+    allocations
+}
+
+impl ParticleBudget {
+    pub fn set_total(&mut self, total: Option<usize>) {
+        self.total = total;
+    }
+
+    pub fn set_priority(&mut self, subsystem: ParticleSubsystem, priority: f32) {
+        self.priorities[subsystem as usize] = priority.max(0.0);
+    }
+
+    // Доля подсистемы; None - бюджет не задан
+    pub fn allocation(&self, subsystem: ParticleSubsystem) -> Option<usize> {
+        self.total.map(|_| self.allocations[subsystem as usize])
+    }
+
+    // Оценить спрос подсистем и распределить бюджет
+    pub fn update(&mut self, system: &SpaceObjectSystem) {
+        if self.total.is_none() {
+            return;
+        }
+
+        let objects = system.get_objects();
+        let count = |object_type| objects.get(&object_type).map_or(0, |objects| objects.len());
+        let visible_comets = objects
+            .get(&SpaceObjectType::NeonComet)
+            .into_iter()
+            .flatten()
+            .filter_map(|obj| obj.as_any().downcast_ref::<NeonComet>())
+            .filter(|comet| !comet.waiting_for_respawn && comet.is_visible(&system.space))
+            .count();
+
+        let tails = (visible_comets * system.particle_count(TAIL_PARTICLES_PER_COMET)).min(system.tail_particles.budget());
+        let vortices = count(SpaceObjectType::EnergySphere) * system.particle_count(VORTEX_PARTICLES);
+        let effects = (system.effects.len() + EFFECT_HEADROOM).min(MAX_COMET_EFFECTS) * EFFECT_PARTICLE_COST;
+        let debris = if system.debris.is_enabled() {
+            (system.debris.len() + DEBRIS_HEADROOM).min(MAX_DEBRIS_CHUNKS)
+        } else {
+            0
+        };
+
+        self.demands = [tails, vortices, effects, debris];
+        let allocations = arbitrate(self.total.unwrap_or(0), &self.demands, &self.priorities);
+        self.allocations.copy_from_slice(&allocations);
+    }
+}
+
+/// Распределение бюджета частиц по подсистемам (индекс - ParticleSubsystem)
+#[wasm_bindgen]
+pub struct ParticleBudgetStats {
+    total: Option<u32>,
+    demands: Vec<u32>,
+    allocations: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl ParticleBudgetStats {
+    // Общий бюджет; undefined - не задан
+    #[wasm_bindgen(getter)]
+    pub fn total(&self) -> Option<u32> {
+        self.total
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn demands(&self) -> Vec<u32> {
+        self.demands.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn allocations(&self) -> Vec<u32> {
+        self.allocations.clone()
+    }
+}
+
+// Общий бюджет частиц системы; undefined - без общего бюджета
+#[wasm_bindgen]
+pub fn set_particle_budget(system_id: usize, total: Option<u32>) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.particle_budget.set_total(total.map(|total| total as usize));
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Приоритет (вес доли) подсистемы; 0 - подсистема не получает частиц при заданном бюджете
+#[wasm_bindgen]
+pub fn set_particle_priority(system_id: usize, subsystem: ParticleSubsystem, priority: f32) -> OpResult {
+    if !priority.is_finite() {
+        return OpResult::invalid_argument("priority must be finite");
+    }
+
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.particle_budget.set_priority(subsystem, priority);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_particle_budget(system_id: usize) -> Option<ParticleBudgetStats> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let budget = &system.particle_budget;
+    let to_u32 = |values: &[usize]| values.iter().map(|&value| value as u32).collect();
+    Some(ParticleBudgetStats {
+        total: budget.total.map(|total| total as u32),
+        demands: to_u32(&budget.demands),
+        allocations: to_u32(&budget.allocations),
+    })
+}
@@ -1,10 +1,21 @@
 use wasm_bindgen::prelude::*;
 use rapier3d::prelude::*;
 use std::collections::HashMap;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
 
-// Global storage for physics worlds
-static mut PHYSICS_WORLDS: Option<HashMap<usize, PhysicsWorld>> = None;
-static mut NEXT_WORLD_ID: usize = 0;
+use crate::errors::OpResult;
+use crate::utils::lock_or_recover;
+
+// Global storage for physics worlds. Миры трогают и из-под блокировки систем
+// (обломки, синхронизация плоскостей), поэтому доступ только через мьютекс
+static PHYSICS_WORLDS: Lazy<Mutex<PhysicsWorlds>> = Lazy::new(|| Mutex::new(PhysicsWorlds::default()));
+
+#[derive(Default)]
+struct PhysicsWorlds {
+    worlds: HashMap<usize, PhysicsWorld>,
+    next_id: usize,
+}
 
 pub struct PhysicsWorld {
     pub rigid_body_set: RigidBodySet,
@@ -23,6 +34,26 @@ pub struct PhysicsWorld {
     pub events: ()
 }
 
+// Доступ к миру из других модулей (обломки ударов и т.п.); None - мира нет.
+// Блокировка удерживается на время f, поэтому f не должна снова вызывать with_world
+pub(crate) fn with_world<R>(world_id: usize, f: impl FnOnce(&mut PhysicsWorld) -> R) -> Option<R> {
+    lock_or_recover(&PHYSICS_WORLDS, "PHYSICS_WORLDS").worlds.get_mut(&world_id).map(f)
+}
+
+impl PhysicsWorld {
+    // Удалить тело вместе с его коллайдерами
+    pub fn remove_body(&mut self, handle: RigidBodyHandle) {
+        self.rigid_body_set.remove(
+            handle,
+            &mut self.island_manager,
+            &mut self.collider_set,
+            &mut self.impulse_joint_set,
+            &mut self.multibody_joint_set,
+            true,
+        );
+    }
+}
+
 // Initialize physics world
 #[wasm_bindgen]
 pub fn init_world() -> usize {
@@ -59,60 +90,47 @@ pub fn init_world() -> usize {
     };
 
     // Save the world in global storage
-    unsafe {
-        let raw_ptr = &raw const PHYSICS_WORLDS;
-        if (*raw_ptr).is_none() {
-            PHYSICS_WORLDS = Some(HashMap::new());
-        }
-        
-        let id = NEXT_WORLD_ID;
-        NEXT_WORLD_ID += 1;
-        
-        if let Some(worlds) = &mut *(&raw mut PHYSICS_WORLDS) {
-            worlds.insert(id, world);
-        }
-        
-        id
-    }
+    let mut storage = lock_or_recover(&PHYSICS_WORLDS, "PHYSICS_WORLDS");
+    let id = storage.next_id;
+    storage.next_id += 1;
+    storage.worlds.insert(id, world);
+    id
 }
 
 // Function for simulation step
 #[wasm_bindgen]
-pub fn step_simulation(world_id: usize, dt: f32) -> bool {
-    unsafe {
-        if let Some(worlds) = &mut *(&raw mut PHYSICS_WORLDS) {
-            if let Some(world) = worlds.get_mut(&world_id) {
-                world.integration_parameters.dt = dt;
-                
-                // Update query_pipeline before simulation step
-                world.query_pipeline.update(&world.rigid_body_set, &world.collider_set);
-                
-                world.physics_pipeline.step(
-                    &world.gravity,
-                    &world.integration_parameters,
-                    &mut world.island_manager,
-                    &mut world.broad_phase,
-                    &mut world.narrow_phase,
-                    &mut world.rigid_body_set,
-                    &mut world.collider_set,
-                    &mut world.impulse_joint_set,
-                    &mut world.multibody_joint_set,
-                    &mut world.ccd_solver,
-                    None,
-                    &world.hooks,
-                    &world.events,
-                );
-
-                // Update the query pipeline
-                world.query_pipeline.update(
-                    &world.rigid_body_set,
-                    &world.collider_set,
-                );
-                
-                return true;
-            }
-        }
+pub fn step_simulation(world_id: usize, dt: f32) -> OpResult {
+    let stepped = with_world(world_id, |world| {
+        world.integration_parameters.dt = dt;
+        
+        // Update query_pipeline before simulation step
+        world.query_pipeline.update(&world.rigid_body_set, &world.collider_set);
         
-        false
+        world.physics_pipeline.step(
+            &world.gravity,
+            &world.integration_parameters,
+            &mut world.island_manager,
+            &mut world.broad_phase,
+            &mut world.narrow_phase,
+            &mut world.rigid_body_set,
+            &mut world.collider_set,
+            &mut world.impulse_joint_set,
+            &mut world.multibody_joint_set,
+            &mut world.ccd_solver,
+            None,
+            &world.hooks,
+            &world.events,
+        );
+
+        // Update the query pipeline
+        world.query_pipeline.update(
+            &world.rigid_body_set,
+            &world.collider_set,
+        );
+    });
+    
+    match stepped {
+        Some(()) => OpResult::success(),
+        None => OpResult::not_found(format!("physics world {} not found", world_id)),
     }
-}
\ No newline at end of file
+}
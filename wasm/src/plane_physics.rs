@@ -0,0 +1,179 @@
+/*
+ * plane_physics.rs
+ *
+ * Коллайдеры видовых плоскостей в физическом мире rapier. Пересечения и их
+ * эффекты считаются по видовым плоскостям, поэтому физические тела хоста
+ * должны сталкиваться с той же геометрией: sync_plane_to_physics создает
+ * тонкий коллайдер-параллелепипед по прямоугольнику плоскости и каждый кадр
+ * приводит его в соответствие с плоскостью (основная плоскость следует за
+ * наблюдателем, дополнительные можно удалить).
+ *
+ * Статический коллайдер переносится мгновенно; кинематический движется к
+ * новому положению за шаг мира, передавая скорость телам, которых касается.
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+use rapier3d::prelude::*;
+
+use crate::errors::OpResult;
+use crate::physics::with_world;
+use crate::planes::ViewingPlane;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+// Полутолщина коллайдера плоскости
+const PLANE_COLLIDER_HALF_THICKNESS: f32 = 0.05;
+
+/// Тип тела коллайдера плоскости
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PhysicsSyncMode {
+    Static = 0,
+    Kinematic = 1,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct PlaneCollider {
+    plane_index: usize,
+    world_id: usize,
+    body: RigidBodyHandle,
+    collider: ColliderHandle,
+}
+
+/// Коллайдеры плоскостей системы в физических мирах
+#[derive(Debug, Default)]
+pub struct PlaneColliders {
+    colliders: Vec<PlaneCollider>,
+}
+
+fn plane_shape(plane: &ViewingPlane) -> SharedShape {
+    let half = plane.size * 0.5;
+    SharedShape::cuboid(half.x, half.y, PLANE_COLLIDER_HALF_THICKNESS)
+}
+
+fn plane_translation(plane: &ViewingPlane) -> Vector<Real> {
+    vector![plane.center.x, plane.center.y, plane.center.z]
+}
+
+impl PlaneColliders {
+    pub fn is_empty(&self) -> bool {
+        self.colliders.is_empty()
+    }
+
+    // Создать (или пересоздать с новым режимом) коллайдер плоскости; false - мира нет,
+    // прежний коллайдер плоскости тогда остается на месте
+    pub fn sync(&mut self, plane_index: usize, plane: &ViewingPlane, world_id: usize, mode: PhysicsSyncMode) -> bool {
+        let body = match mode {
+            PhysicsSyncMode::Static => RigidBodyBuilder::fixed(),
+            PhysicsSyncMode::Kinematic => RigidBodyBuilder::kinematic_position_based(),
+        }
+        .translation(plane_translation(plane))
+        .build();
+
+        let handles = with_world(world_id, |world| {
+            let body = world.rigid_body_set.insert(body);
+            let collider = ColliderBuilder::new(plane_shape(plane)).build();
+            let collider = world.collider_set.insert_with_parent(collider, body, &mut world.rigid_body_set);
+            (body, collider)
+        });
+        let Some((body, collider)) = handles else {
+            return false;
+        };
+
+        self.unsync(plane_index);
+        self.colliders.push(PlaneCollider { plane_index, world_id, body, collider });
+        true
+    }
+
+    // Удалить коллайдер плоскости из мира; false - плоскость не синхронизировалась
+    pub fn unsync(&mut self, plane_index: usize) -> bool {
+        let Some(position) = self.colliders.iter().position(|entry| entry.plane_index == plane_index) else {
+            return false;
+        };
+
+        let entry = self.colliders.remove(position);
+        with_world(entry.world_id, |world| world.remove_body(entry.body));
+        true
+    }
+
+    // Привести коллайдеры к текущей геометрии плоскостей; плоскости, которых
+    // больше нет (или чей мир удален), перестают синхронизироваться
+    pub fn update(&mut self, plane: impl Fn(usize) -> Option<ViewingPlane>) {
+        self.colliders.retain(|entry| {
+            let Some(plane) = plane(entry.plane_index) else {
+                with_world(entry.world_id, |world| world.remove_body(entry.body));
+                return false;
+            };
+
+            with_world(entry.world_id, |world| {
+                if let Some(body) = world.rigid_body_set.get_mut(entry.body) {
+                    let translation = plane_translation(&plane);
+                    if body.is_kinematic() {
+                        body.set_next_kinematic_translation(translation);
+                    } else if *body.translation() != translation {
+                        body.set_translation(translation, true);
+                    }
+                }
+                if let Some(collider) = world.collider_set.get_mut(entry.collider) {
+                    let half = plane.size * 0.5;
+                    let current = collider.shape().as_cuboid().map(|cuboid| cuboid.half_extents);
+                    if current != Some(vector![half.x, half.y, PLANE_COLLIDER_HALF_THICKNESS]) {
+                        collider.set_shape(plane_shape(&plane));
+                    }
+                }
+            })
+            .is_some()
+        });
+    }
+
+    // Центр коллайдера плоскости в мире (для отладки и проверки синхронизации)
+    pub fn collider_center(&self, plane_index: usize) -> Option<Vec3> {
+        let entry = self.colliders.iter().find(|entry| entry.plane_index == plane_index)?;
+        with_world(entry.world_id, |world| {
+            world
+                .rigid_body_set
+                .get(entry.body)
+                .map(|body| {
+                    let translation = body.next_position().translation.vector;
+                    Vec3::new(translation.x, translation.y, translation.z)
+                })
+        })
+        .flatten()
+    }
+}
+
+// Создать коллайдер видовой плоскости plane_index в мире world_id и
+// обновлять его каждый кадр
+#[wasm_bindgen]
+pub fn sync_plane_to_physics(system_id: usize, plane_index: usize, world_id: usize, mode: PhysicsSyncMode) -> OpResult {
+    let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) else {
+        return OpResult::system_not_found(system_id);
+    };
+    let Some(plane) = system.viewing_planes.get(&system.space, plane_index) else {
+        return OpResult::not_found(format!("viewing plane {} not found", plane_index));
+    };
+    if !system.plane_colliders.sync(plane_index, &plane, world_id, mode) {
+        return OpResult::not_found(format!("physics world {} not found", world_id));
+    }
+
+    OpResult::success()
+}
+
+#[wasm_bindgen]
+pub fn unsync_plane_from_physics(system_id: usize, plane_index: usize) -> OpResult {
+    let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) else {
+        return OpResult::system_not_found(system_id);
+    };
+    if !system.plane_colliders.unsync(plane_index) {
+        return OpResult::not_found(format!("viewing plane {} is not synced to physics", plane_index));
+    }
+
+    OpResult::success()
+}
+
+// Центр коллайдера плоскости в физическом мире [x, y, z]
+#[wasm_bindgen]
+pub fn get_plane_collider_center(system_id: usize, plane_index: usize) -> Option<Vec<f32>> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    system.plane_colliders.collider_center(plane_index).map(|center| center.to_array().to_vec())
+}
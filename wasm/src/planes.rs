@@ -0,0 +1,411 @@
+/*
+ * planes.rs
+ *
+ * Видовые плоскости системы. Основная плоскость (индекс 0) задается
+ * пространством (SpaceDefinition::viewing_plane_z и размер видового экрана),
+ * дополнительные - прямоугольники на своей глубине для раскладок из
+ * нескольких панелей или разделенного экрана. Все плоскости активны
+ * одновременно; если за кадр объект пересекает несколько плоскостей,
+ * засчитывается ближайшая по ходу движения, а при совпадении - плоскость
+ * с большим приоритетом.
+ *
+ * Для рендерера экспортируются углы плоскостей в мировых координатах,
+ * чтобы квад плоскости строился на стороне JS без повторного вывода ее
+ * положения и размеров.
+ *
+ * Стиль плоскостей (цвет, свечение края, пульсация, сдвиг градиента при
+ * прокрутке) анимируется здесь же, вместе с симуляцией: плоскость "дышит"
+ * в такт сцене и усиливает свечение края вместе с модуляцией гиперкуба.
+ */
+
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+
+use wasm_bindgen::prelude::*;
+use glam::{Vec2, Vec3};
+
+use crate::errors::OpResult;
+use crate::space_core::SpaceDefinition;
+use crate::space_objects::{SpaceObjectSystem, SPACE_OBJECT_SYSTEMS};
+
+// Индекс основной видовой плоскости
+pub const MAIN_PLANE_INDEX: usize = 0;
+
+// Ограничение числа дополнительных плоскостей системы
+pub const MAX_VIEWING_PLANES: usize = 16;
+
+// Доли кадра, которые считаются одновременным пересечением
+const SIMULTANEOUS_CROSSING_EPSILON: f32 = 1e-5;
+
+/// Видовая плоскость: прямоугольник, параллельный плоскости XY
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewingPlane {
+    // Центр прямоугольника; z - глубина плоскости
+    pub center: Vec3,
+    pub size: Vec2,
+    pub priority: i32,
+}
+
+impl ViewingPlane {
+    // Основная плоскость пространства
+    pub fn main(space: &SpaceDefinition, priority: i32) -> Self {
+        ViewingPlane {
+            center: space.observer_position.truncate().extend(space.viewing_plane_z),
+            size: space.get_viewport_dimensions(),
+            priority,
+        }
+    }
+
+    // Углы в порядке UV (0,0), (1,0), (1,1), (0,1):
+    // левый нижний, правый нижний, правый верхний, левый верхний
+    pub fn corners(&self) -> [Vec3; 4] {
+        let half = self.size * 0.5;
+        [
+            Vec2::new(-half.x, -half.y),
+            Vec2::new(half.x, -half.y),
+            Vec2::new(half.x, half.y),
+            Vec2::new(-half.x, half.y),
+        ]
+        .map(|offset| self.center + offset.extend(0.0))
+    }
+
+    // UV-координаты точки плоскости (0..1 внутри прямоугольника)
+    pub fn uv(&self, point: &Vec3) -> Vec2 {
+        (point.truncate() - self.center.truncate()) / self.size + Vec2::splat(0.5)
+    }
+
+    // Пересечение отрезка с прямоугольником плоскости в любом направлении:
+    // доля отрезка t (0..1) и точка пересечения
+    pub fn crossing(&self, from: &Vec3, to: &Vec3) -> Option<(f32, Vec3)> {
+        let depth = self.center.z;
+        if (from.z > depth) == (to.z > depth) {
+            return None;
+        }
+
+        let t = (from.z - depth) / (from.z - to.z);
+        let point = from.lerp(*to, t);
+        let offset = (point.truncate() - self.center.truncate()).abs();
+        (offset.x <= self.size.x * 0.5 && offset.y <= self.size.y * 0.5).then_some((t, point))
+    }
+}
+
+/// Набор видовых плоскостей системы
+#[derive(Clone, Debug)]
+pub struct ViewingPlanes {
+    main_priority: i32,
+    extra: Vec<(usize, ViewingPlane)>,
+    next_index: usize,
+}
+
+impl Default for ViewingPlanes {
+    fn default() -> Self {
+        ViewingPlanes {
+            main_priority: 0,
+            extra: Vec::new(),
+            next_index: MAIN_PLANE_INDEX + 1,
+        }
+    }
+}
+
+impl ViewingPlanes {
+    // Добавить плоскость; None - достигнут предел числа плоскостей
+    pub fn add(&mut self, plane: ViewingPlane) -> Option<usize> {
+        if self.extra.len() >= MAX_VIEWING_PLANES {
+            return None;
+        }
+
+        let index = self.next_index;
+        self.next_index += 1;
+        self.extra.push((index, plane));
+        Some(index)
+    }
+
+    // Удалить дополнительную плоскость (основную удалить нельзя)
+    pub fn remove(&mut self, index: usize) -> bool {
+        let before = self.extra.len();
+        self.extra.retain(|(plane_index, _)| *plane_index != index);
+        self.extra.len() != before
+    }
+
+    pub fn set_priority(&mut self, index: usize, priority: i32) -> bool {
+        if index == MAIN_PLANE_INDEX {
+            self.main_priority = priority;
+            return true;
+        }
+
+        match self.extra.iter_mut().find(|(plane_index, _)| *plane_index == index) {
+            Some((_, plane)) => {
+                plane.priority = priority;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get(&self, space: &SpaceDefinition, index: usize) -> Option<ViewingPlane> {
+        if index == MAIN_PLANE_INDEX {
+            return Some(ViewingPlane::main(space, self.main_priority));
+        }
+        self.extra.iter().find(|(plane_index, _)| *plane_index == index).map(|(_, plane)| *plane)
+    }
+
+    // Все плоскости, включая основную
+    pub fn iter<'a>(&'a self, space: &SpaceDefinition) -> impl Iterator<Item = (usize, ViewingPlane)> + 'a {
+        std::iter::once((MAIN_PLANE_INDEX, ViewingPlane::main(space, self.main_priority)))
+            .chain(self.extra.iter().copied())
+    }
+
+    // Ближайшая по ходу движения пересеченная плоскость: индекс, доля отрезка и точка
+    pub fn nearest_crossing(&self, space: &SpaceDefinition, from: &Vec3, to: &Vec3) -> Option<(usize, f32, Vec3)> {
+        let mut nearest: Option<(usize, f32, Vec3, i32)> = None;
+
+        for (index, plane) in self.iter(space) {
+            let Some((t, point)) = plane.crossing(from, to) else {
+                continue;
+            };
+
+            let better = match nearest {
+                None => true,
+                Some((_, best_t, _, best_priority)) => {
+                    t < best_t - SIMULTANEOUS_CROSSING_EPSILON
+                        || (t <= best_t + SIMULTANEOUS_CROSSING_EPSILON && plane.priority > best_priority)
+                }
+            };
+            if better {
+                nearest = Some((index, t, point, plane.priority));
+            }
+        }
+
+        nearest.map(|(index, t, point, _)| (index, t, point))
+    }
+}
+
+// Существует ли плоскость с таким индексом
+fn plane_exists(system: &SpaceObjectSystem, plane_index: usize) -> bool {
+    system.viewing_planes.get(&system.space, plane_index).is_some()
+}
+
+// Добавить видовую плоскость размером width x height с центром (center_x, center_y)
+// на глубине z; возвращает индекс плоскости
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn add_viewing_plane(
+    system_id: usize,
+    center_x: f32,
+    center_y: f32,
+    z: f32,
+    width: f32,
+    height: f32,
+    priority: i32
+) -> Option<usize> {
+    let center = Vec3::new(center_x, center_y, z);
+    let size = Vec2::new(width, height);
+    if !center.is_finite() || !size.is_finite() || size.min_element() <= 0.0 {
+        return None;
+    }
+
+    let mut system = SPACE_OBJECT_SYSTEMS.get_mut(&system_id)?;
+    system.viewing_planes.add(ViewingPlane { center, size, priority })
+}
+
+#[wasm_bindgen]
+pub fn remove_viewing_plane(system_id: usize, plane_index: usize) -> OpResult {
+    let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) else {
+        return OpResult::system_not_found(system_id);
+    };
+    if !system.viewing_planes.remove(plane_index) {
+        return OpResult::not_found(format!("viewing plane {} not found or is the main plane", plane_index));
+    }
+
+    system.plane_styles.remove(plane_index);
+    OpResult::success()
+}
+
+#[wasm_bindgen]
+pub fn set_viewing_plane_priority(system_id: usize, plane_index: usize, priority: i32) -> OpResult {
+    let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) else {
+        return OpResult::system_not_found(system_id);
+    };
+    if !system.viewing_planes.set_priority(plane_index, priority) {
+        return OpResult::not_found(format!("viewing plane {} not found", plane_index));
+    }
+
+    OpResult::success()
+}
+
+// Индексы всех плоскостей системы по убыванию приоритета
+#[wasm_bindgen]
+pub fn get_viewing_plane_indices(system_id: usize) -> Vec<usize> {
+    let Some(system) = SPACE_OBJECT_SYSTEMS.get(&system_id) else {
+        return Vec::new();
+    };
+
+    let mut planes: Vec<(usize, i32)> = system
+        .viewing_planes
+        .iter(&system.space)
+        .map(|(index, plane)| (index, plane.priority))
+        .collect();
+    planes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    planes.into_iter().map(|(index, _)| index).collect()
+}
+
+// Четыре угла плоскости (x, y, z подряд) в порядке UV (0,0), (1,0), (1,1), (0,1);
+// None - нет системы или плоскости с таким индексом
+#[wasm_bindgen]
+pub fn get_plane_corners(system_id: usize, plane_index: usize) -> Option<Vec<f32>> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let plane = system.viewing_planes.get(&system.space, plane_index)?;
+    Some(plane.corners().iter().flat_map(|corner| corner.to_array()).collect())
+}
+
+// Глубина "дыхания" плоскости: доля колебания прозрачности и свечения края
+const PLANE_PULSE_DEPTH: f32 = 0.25;
+
+/// Стиль видовой плоскости: базовый цвет, свечение края, частота пульсации
+/// и сдвиг градиента на единицу прокрутки страницы
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlaneStyle {
+    pub base_color: [f32; 4],
+    pub edge_glow: f32,
+    pub pulse_frequency: f32,
+    pub gradient_scroll_factor: f32,
+}
+
+impl Default for PlaneStyle {
+    fn default() -> Self {
+        PlaneStyle {
+            base_color: [0.4, 0.8, 1.0, 0.15],
+            edge_glow: 0.5,
+            pulse_frequency: 0.25,
+            gradient_scroll_factor: 1.0,
+        }
+    }
+}
+
+/// Стили плоскостей системы и общее для них время анимации
+#[derive(Clone, Debug, Default)]
+pub struct PlaneStyles {
+    styles: HashMap<usize, PlaneStyle>,
+    elapsed: f32,
+    scroll_offset: f32,
+}
+
+impl PlaneStyles {
+    pub fn set(&mut self, plane_index: usize, style: PlaneStyle) {
+        self.styles.insert(plane_index, style);
+    }
+
+    pub fn get(&self, plane_index: usize) -> PlaneStyle {
+        self.styles.get(&plane_index).copied().unwrap_or_default()
+    }
+
+    pub fn remove(&mut self, plane_index: usize) {
+        self.styles.remove(&plane_index);
+    }
+
+    pub fn set_scroll_offset(&mut self, offset: f32) {
+        self.scroll_offset = offset;
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        self.elapsed += dt;
+    }
+
+    // Анимированное состояние стиля; plane_glow - свечение, модулируемое гиперкубом
+    pub fn animated(&self, plane_index: usize, plane_glow: f32) -> PlaneStyleState {
+        let style = self.get(plane_index);
+        let pulse = (TAU * style.pulse_frequency * self.elapsed).sin();
+        let breath = 1.0 + PLANE_PULSE_DEPTH * pulse;
+        let [r, g, b, a] = style.base_color;
+
+        PlaneStyleState {
+            r,
+            g,
+            b,
+            a: (a * breath).clamp(0.0, 1.0),
+            edge_glow: style.edge_glow * breath * (1.0 + plane_glow),
+            pulse,
+            gradient_offset: (self.scroll_offset * style.gradient_scroll_factor).rem_euclid(1.0),
+        }
+    }
+}
+
+/// Стиль плоскости на текущем кадре
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlaneStyleState {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+
+    // Свечение края с учетом пульсации и модуляции сцены
+    pub edge_glow: f32,
+
+    // Фаза пульсации (-1..1)
+    pub pulse: f32,
+
+    // Сдвиг градиента по прокрутке страницы (0..1)
+    pub gradient_offset: f32,
+}
+
+// Задать стиль плоскости
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn set_plane_style(
+    system_id: usize,
+    plane_index: usize,
+    r: f32,
+    g: f32,
+    b: f32,
+    a: f32,
+    edge_glow: f32,
+    pulse_frequency: f32,
+    gradient_scroll_factor: f32
+) -> OpResult {
+    let values = [r, g, b, a, edge_glow, pulse_frequency, gradient_scroll_factor];
+    if values.iter().any(|value| !value.is_finite()) {
+        return OpResult::invalid_argument("plane style values must be finite");
+    }
+
+    let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) else {
+        return OpResult::system_not_found(system_id);
+    };
+    if !plane_exists(&system, plane_index) {
+        return OpResult::not_found(format!("viewing plane {} not found", plane_index));
+    }
+
+    system.plane_styles.set(plane_index, PlaneStyle {
+        base_color: [r, g, b, a].map(|channel| channel.clamp(0.0, 1.0)),
+        edge_glow: edge_glow.max(0.0),
+        pulse_frequency: pulse_frequency.max(0.0),
+        gradient_scroll_factor,
+    });
+    OpResult::success()
+}
+
+// Прокрутка страницы, сдвигающая градиент плоскостей (в единицах страницы)
+#[wasm_bindgen]
+pub fn set_scroll_offset(system_id: usize, offset: f32) -> OpResult {
+    if !offset.is_finite() {
+        return OpResult::invalid_argument("scroll offset must be finite");
+    }
+
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.plane_styles.set_scroll_offset(offset);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_plane_style(system_id: usize, plane_index: usize) -> Option<PlaneStyleState> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    if !plane_exists(&system, plane_index) {
+        return None;
+    }
+
+    Some(system.plane_styles.animated(plane_index, system.modulation.plane_glow))
+}
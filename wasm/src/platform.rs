@@ -0,0 +1,145 @@
+/*
+ * platform.rs
+ *
+ * Слой абстракции над временем, кадрами анимации и логированием. Все
+ * обращения к `web_sys::window()` и `js_sys::Date` собраны здесь, за трейтом
+ * Clock: в браузере используется BrowserClock (Date.now, performance.now,
+ * requestAnimationFrame), а в нативной сборке (тесты, бенчмарки, WASI,
+ * серверный процесс на Node) - SystemClock на стандартной библиотеке.
+ * Остальной крейт вызывает только функции этого модуля.
+ *
+ * Нативная реализация выбирается автоматически для не-wasm целей и без фичи
+ * `web`, а фича `native` включает ее принудительно (например, для
+ * wasm32-wasi). Сборка без web-sys:
+ *
+ *   cargo build --no-default-features --features headless
+ *
+ * Встраивающий крейт может подменить часы (set_clock), например, чтобы
+ * сервер вел симуляцию по своему расписанию кадров.
+ */
+
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+
+/// Источник времени и кадров анимации
+pub trait Clock: Send + Sync {
+    // Текущее время в миллисекундах с начала эпохи Unix
+    fn now_ms(&self) -> f64;
+
+    // Монотонное время высокого разрешения в миллисекундах
+    fn monotonic_ms(&self) -> f64;
+
+    // Запросить вызов callback(timestamp_ms) к следующему кадру; None - кадров
+    // анимации нет (нативная сборка, воркер без requestAnimationFrame)
+    fn request_animation_frame(&self, _callback: &js_sys::Function) -> Option<i32> {
+        None
+    }
+
+    fn cancel_animation_frame(&self, _request_id: i32) {}
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "web", not(feature = "native")))]
+mod backend {
+    use web_sys::console;
+
+    /// Часы браузера: Date.now, performance.now и requestAnimationFrame окна
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct BrowserClock;
+
+    impl super::Clock for BrowserClock {
+        fn now_ms(&self) -> f64 {
+            js_sys::Date::now()
+        }
+
+        fn monotonic_ms(&self) -> f64 {
+            web_sys::window()
+                .and_then(|window| window.performance())
+                .map(|performance| performance.now())
+                .unwrap_or_else(js_sys::Date::now)
+        }
+
+        fn request_animation_frame(&self, callback: &js_sys::Function) -> Option<i32> {
+            web_sys::window()?.request_animation_frame(callback).ok()
+        }
+
+        fn cancel_animation_frame(&self, request_id: i32) {
+            if let Some(window) = web_sys::window() {
+                let _ = window.cancel_animation_frame(request_id);
+            }
+        }
+    }
+
+    pub type DefaultClock = BrowserClock;
+
+    // Вывести сообщение в консоль браузера
+    pub fn log(message: &str) {
+        console::log_1(&message.into());
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", feature = "web", not(feature = "native"))))]
+mod backend {
+    use once_cell::sync::Lazy;
+    use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+    // Точка отсчета для монотонного времени
+    static START: Lazy<Instant> = Lazy::new(Instant::now);
+
+    /// Часы стандартной библиотеки; кадров анимации нет
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct SystemClock;
+
+    impl super::Clock for SystemClock {
+        fn now_ms(&self) -> f64 {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs_f64() * 1000.0)
+                .unwrap_or(0.0)
+        }
+
+        fn monotonic_ms(&self) -> f64 {
+            START.elapsed().as_secs_f64() * 1000.0
+        }
+    }
+
+    pub type DefaultClock = SystemClock;
+
+    // Вывести сообщение в стандартный вывод
+    pub fn log(message: &str) {
+        println!("{}", message);
+    }
+}
+
+pub use backend::*;
+
+// Текущие часы крейта
+static CLOCK: Lazy<RwLock<Box<dyn Clock>>> = Lazy::new(|| RwLock::new(Box::new(DefaultClock::default())));
+
+fn with_clock<R>(f: impl FnOnce(&dyn Clock) -> R) -> R {
+    let clock = CLOCK.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(clock.as_ref())
+}
+
+// Подменить часы крейта (встраивание в нативный хост)
+pub fn set_clock(clock: Box<dyn Clock>) {
+    *CLOCK.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = clock;
+}
+
+// Текущее время в миллисекундах с начала эпохи Unix
+pub fn now_ms() -> f64 {
+    with_clock(|clock| clock.now_ms())
+}
+
+// Монотонное время высокого разрешения в миллисекундах
+pub fn monotonic_ms() -> f64 {
+    with_clock(|clock| clock.monotonic_ms())
+}
+
+pub fn request_animation_frame(callback: &js_sys::Function) -> Option<i32> {
+    with_clock(|clock| clock.request_animation_frame(callback))
+}
+
+pub fn cancel_animation_frame(request_id: i32) {
+    with_clock(|clock| clock.cancel_animation_frame(request_id))
+}
@@ -0,0 +1,197 @@
+/*
+ * population.rs
+ *
+ * Счетчики жизненного цикла объектов по типам: сколько создано, сколько
+ * исчезло и по какой причине, сколько возродилось. Помогают понять, почему
+ * сцена иногда выглядит пустой (например, кометы массово уходят за границы
+ * раньше, чем долетают до плоскости).
+ *
+ * Исчезновения фиксирует проход integrate по изменению состояния объекта:
+ * удаление из системы или переход в ожидание респауна.
+ *
+ * Готовность сцены (get_scene_readiness) - доля "ожившых" объектов среди
+ * запрошенных: заявки, еще ждущие в очереди появлений, и только что
+ * созданные объекты, не успевшие сделать ни одного шага, сцену не оживляют.
+ */
+
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::neon_comets::pending_spawns_for;
+use crate::space_objects::{SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+
+/// Причина исчезновения объекта
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DespawnReason {
+    OutOfBounds, // Вышел за границы пространства, не пересекая видовую плоскость
+    Lifetime,    // Истекло время жизни
+    Opacity,     // Полностью погас
+    Crossing,    // Ушел из пространства после пересечения видовой плоскости
+}
+
+/// Счетчики одного типа объектов
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TypeCounters {
+    pub spawned: u32,
+    pub respawned: u32,
+    pub despawned_out_of_bounds: u32,
+    pub despawned_lifetime: u32,
+    pub despawned_opacity: u32,
+    pub despawned_crossing: u32,
+}
+
+/// Счетчики жизненного цикла системы
+#[derive(Clone, Debug, Default)]
+pub struct PopulationCounters {
+    counters: HashMap<SpaceObjectType, TypeCounters>,
+}
+
+impl PopulationCounters {
+    fn entry(&mut self, object_type: SpaceObjectType) -> &mut TypeCounters {
+        self.counters.entry(object_type).or_default()
+    }
+
+    pub fn record_spawn(&mut self, object_type: SpaceObjectType) {
+        let counters = self.entry(object_type);
+        counters.spawned = counters.spawned.saturating_add(1);
+    }
+
+    pub fn record_respawn(&mut self, object_type: SpaceObjectType) {
+        let counters = self.entry(object_type);
+        counters.respawned = counters.respawned.saturating_add(1);
+    }
+
+    pub fn record_despawn(&mut self, object_type: SpaceObjectType, reason: DespawnReason) {
+        let counters = self.entry(object_type);
+        let counter = match reason {
+            DespawnReason::OutOfBounds => &mut counters.despawned_out_of_bounds,
+            DespawnReason::Lifetime => &mut counters.despawned_lifetime,
+            DespawnReason::Opacity => &mut counters.despawned_opacity,
+            DespawnReason::Crossing => &mut counters.despawned_crossing,
+        };
+        *counter = counter.saturating_add(1);
+    }
+
+    pub fn get(&self, object_type: SpaceObjectType) -> TypeCounters {
+        self.counters.get(&object_type).copied().unwrap_or_default()
+    }
+}
+
+/// Статистика населения системы: параллельные массивы по типам объектов
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct PopulationStats {
+    types: Vec<SpaceObjectType>,
+    active: Vec<u32>,
+    waiting: Vec<u32>,
+    spawned: Vec<u32>,
+    respawned: Vec<u32>,
+    despawned_out_of_bounds: Vec<u32>,
+    despawned_lifetime: Vec<u32>,
+    despawned_opacity: Vec<u32>,
+    despawned_crossing: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl PopulationStats {
+    #[wasm_bindgen(getter)]
+    pub fn types(&self) -> Vec<SpaceObjectType> {
+        self.types.clone()
+    }
+
+    // Текущее число видимых (не ожидающих респауна) объектов
+    #[wasm_bindgen(getter)]
+    pub fn active(&self) -> Vec<u32> {
+        self.active.clone()
+    }
+
+    // Текущее число объектов, ожидающих респауна
+    #[wasm_bindgen(getter)]
+    pub fn waiting(&self) -> Vec<u32> {
+        self.waiting.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn spawned(&self) -> Vec<u32> {
+        self.spawned.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn respawned(&self) -> Vec<u32> {
+        self.respawned.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn despawned_out_of_bounds(&self) -> Vec<u32> {
+        self.despawned_out_of_bounds.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn despawned_lifetime(&self) -> Vec<u32> {
+        self.despawned_lifetime.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn despawned_opacity(&self) -> Vec<u32> {
+        self.despawned_opacity.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn despawned_crossing(&self) -> Vec<u32> {
+        self.despawned_crossing.clone()
+    }
+}
+
+const ALL_TYPES: [SpaceObjectType; 4] = [
+    SpaceObjectType::NeonComet,
+    SpaceObjectType::EnergySphere,
+    SpaceObjectType::PolygonalCrystal,
+    SpaceObjectType::Hypercube,
+];
+
+#[wasm_bindgen]
+pub fn get_population_stats(system_id: usize) -> Option<PopulationStats> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let mut stats = PopulationStats::default();
+
+    for object_type in ALL_TYPES {
+        let objects = system.get_objects().get(&object_type);
+        let total = objects.map_or(0, |objects| objects.len());
+        let waiting = objects.map_or(0, |objects| objects.iter().filter(|obj| obj.is_waiting_for_respawn()).count());
+        let counters = system.population.get(object_type);
+
+        stats.types.push(object_type);
+        stats.active.push((total - waiting) as u32);
+        stats.waiting.push(waiting as u32);
+        stats.spawned.push(counters.spawned);
+        stats.respawned.push(counters.respawned);
+        stats.despawned_out_of_bounds.push(counters.despawned_out_of_bounds);
+        stats.despawned_lifetime.push(counters.despawned_lifetime);
+        stats.despawned_opacity.push(counters.despawned_opacity);
+        stats.despawned_crossing.push(counters.despawned_crossing);
+    }
+
+    Some(stats)
+}
+
+// Готовность сцены для экрана загрузки (0..1): доля активировавшихся объектов
+// (с положительным возрастом или уже улетевших и ждущих респауна) среди
+// существующих объектов и заявок в очереди появлений. Без объектов и заявок - 1
+#[wasm_bindgen]
+pub fn get_scene_readiness(system_id: usize) -> Option<f32> {
+    let pending = pending_spawns_for(system_id);
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+
+    let objects = system.get_objects().values().flat_map(|objects| objects.iter());
+    let (total, activated) = objects.fold((0usize, 0usize), |(total, activated), obj| {
+        let alive = obj.is_waiting_for_respawn() || obj.get_data().lifetime > 0.0;
+        (total + 1, activated + alive as usize)
+    });
+
+    let target = total + pending;
+    if target == 0 {
+        return Some(1.0);
+    }
+    Some(activated as f32 / target as f32)
+}
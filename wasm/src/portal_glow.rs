@@ -0,0 +1,115 @@
+/*
+ * portal_glow.rs
+ *
+ * "Свечение портала": энергия ударов о видовую плоскость накапливается в
+ * небольшой сетке ячеек плоскости и экспоненциально затухает. Наружу
+ * отдаются только агрегаты - суммарная энергия и UV самой "горячей" ячейки,
+ * чтобы одна uniform-переменная шейдера управляла общим свечением плоскости
+ * без передачи всей сетки каждый кадр.
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::Vec2;
+
+use crate::errors::OpResult;
+use crate::space_core::SpaceDefinition;
+use crate::space_objects::{PlaneCrossing, SPACE_OBJECT_SYSTEMS};
+
+// Размер сетки накопления (ячеек по каждой оси плоскости)
+pub const PORTAL_GLOW_GRID: usize = 8;
+
+// Скорость экспоненциального затухания энергии (1/сек)
+const PORTAL_GLOW_DECAY: f32 = 1.5;
+
+// Предел энергии одной ячейки, чтобы серия ударов не "слепила" плоскость
+const MAX_CELL_ENERGY: f32 = 4.0;
+
+/// Сетка энергии пересечений одной видовой плоскости
+#[derive(Clone, Debug)]
+pub struct PortalGlow {
+    cells: [f32; PORTAL_GLOW_GRID * PORTAL_GLOW_GRID],
+}
+
+impl Default for PortalGlow {
+    fn default() -> Self {
+        PortalGlow {
+            cells: [0.0; PORTAL_GLOW_GRID * PORTAL_GLOW_GRID],
+        }
+    }
+}
+
+impl PortalGlow {
+    // Добавить энергию удара в ячейку точки пересечения
+    pub fn add_crossing(&mut self, crossing: &PlaneCrossing, space: &SpaceDefinition) {
+        let uv = space.plane_uv(&crossing.position).clamp(Vec2::ZERO, Vec2::ONE);
+        let column = ((uv.x * PORTAL_GLOW_GRID as f32) as usize).min(PORTAL_GLOW_GRID - 1);
+        let row = ((uv.y * PORTAL_GLOW_GRID as f32) as usize).min(PORTAL_GLOW_GRID - 1);
+
+        let cell = &mut self.cells[row * PORTAL_GLOW_GRID + column];
+        *cell = (*cell + crossing.impact_strength()).min(MAX_CELL_ENERGY);
+    }
+
+    pub fn decay(&mut self, dt: f32) {
+        let factor = (-PORTAL_GLOW_DECAY * dt).exp();
+        for cell in &mut self.cells {
+            *cell *= factor;
+        }
+    }
+
+    pub fn total_energy(&self) -> f32 {
+        self.cells.iter().sum()
+    }
+
+    // UV центра самой "горячей" ячейки и ее энергия
+    pub fn hottest_cell(&self) -> (Vec2, f32) {
+        let (index, energy) = self
+            .cells
+            .iter()
+            .copied()
+            .enumerate()
+            .fold((0, 0.0f32), |best, (index, energy)| if energy > best.1 { (index, energy) } else { best });
+
+        let cell = Vec2::new((index % PORTAL_GLOW_GRID) as f32, (index / PORTAL_GLOW_GRID) as f32);
+        ((cell + Vec2::splat(0.5)) / PORTAL_GLOW_GRID as f32, energy)
+    }
+}
+
+/// Агрегаты свечения плоскости для uniform-переменных шейдера
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PortalGlowSummary {
+    // Суммарная энергия по всем ячейкам
+    pub total_energy: f32,
+
+    // Энергия самой "горячей" ячейки
+    pub peak_energy: f32,
+
+    // UV центра самой "горячей" ячейки (0.5, 0.5 при пустой сетке)
+    pub hottest_u: f32,
+    pub hottest_v: f32,
+}
+
+#[wasm_bindgen]
+pub fn get_portal_glow(system_id: usize) -> Option<PortalGlowSummary> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let (uv, peak_energy) = system.portal_glow.hottest_cell();
+    let uv = if peak_energy > 0.0 { uv } else { Vec2::splat(0.5) };
+
+    Some(PortalGlowSummary {
+        total_energy: system.portal_glow.total_energy(),
+        peak_energy,
+        hottest_u: uv.x,
+        hottest_v: uv.y,
+    })
+}
+
+#[wasm_bindgen]
+pub fn clear_portal_glow(system_id: usize) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.portal_glow = PortalGlow::default();
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
@@ -0,0 +1,75 @@
+/*
+ * power.rs
+ *
+ * Профили энергопотребления. Хост, следящий за зарядом батареи или нагревом
+ * (Battery Status API, Compute Pressure), переключает профиль одним вызовом,
+ * а профиль задает конкретные бюджеты симуляции:
+ *
+ *   Performance - два подшага интегрирования на кадр (плавнее траектории
+ *                 быстрых объектов), полное число частиц и длина хвостов;
+ *   Balanced    - поведение по умолчанию;
+ *   Saver       - половина частиц эффектов и укороченные хвосты комет.
+ *
+ * Бюджеты частиц перемножаются с режимом покоя (idle.rs).
+ */
+
+use wasm_bindgen::prelude::*;
+
+use crate::errors::OpResult;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+/// Профиль энергопотребления
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PowerProfile {
+    Performance = 0,
+    #[default]
+    Balanced = 1,
+    Saver = 2,
+}
+
+/// Бюджеты симуляции профиля
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PowerBudget {
+    // Доля частиц эффектов (1 - полное число)
+    pub particle_scale: f32,
+
+    // Подшагов интегрирования объектов на кадр
+    pub substeps: u32,
+
+    // Множитель длины хвостов комет
+    pub tail_scale: f32,
+}
+
+impl PowerProfile {
+    pub fn budget(self) -> PowerBudget {
+        match self {
+            PowerProfile::Performance => PowerBudget { particle_scale: 1.0, substeps: 2, tail_scale: 1.0 },
+            PowerProfile::Balanced => PowerBudget { particle_scale: 1.0, substeps: 1, tail_scale: 1.0 },
+            PowerProfile::Saver => PowerBudget { particle_scale: 0.5, substeps: 1, tail_scale: 0.6 },
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_power_profile(system_id: usize, profile: PowerProfile) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.power_profile = profile;
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_power_profile(system_id: usize) -> Option<PowerProfile> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| system.power_profile)
+}
+
+// Бюджеты текущего профиля системы
+#[wasm_bindgen]
+pub fn get_power_budget(system_id: usize) -> Option<PowerBudget> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| system.power_profile.budget())
+}
@@ -0,0 +1,167 @@
+/*
+ * protected_rect.rs
+ *
+ * Защищенная область экрана: прямоугольник окна (например, заголовок или
+ * кнопка призыва), который объекты не должны перекрывать. При появлении и
+ * респауне траектория объекта проверяется, и если ее проекция проходит
+ * через область на заданном диапазоне глубин, траектория перебрасывается
+ * (повторный initialize_random с тем же генератором, до MAX_PROTECTED_REROLLS
+ * раз - после этого остается последняя попытка, чтобы появление не
+ * зацикливалось при области во весь экран).
+ *
+ * Траектория считается прямой (позиция + скорость * t): ускорение меняет в
+ * основном величину скорости, а не направление. Проекция отрезка прямой на
+ * экран - тоже отрезок, поэтому проверка точная для этой модели: концы
+ * участка с глубиной в [near, far] проецируются на окно, и отрезок
+ * пересекается с прямоугольником (Лианг-Барски). Глубина отсчитывается от
+ * наблюдателя вдоль Z. Вдали все траектории сходятся к центру окна, поэтому
+ * для центральной области диапазон обычно ограничивают ближними глубинами,
+ * где объекты крупные и действительно заслоняют интерфейс.
+ */
+
+use rand::rngs::StdRng;
+use wasm_bindgen::prelude::*;
+use glam::{Vec2, Vec3};
+
+use crate::errors::OpResult;
+use crate::space_core::SpaceDefinition;
+use crate::space_objects::{SpaceObject, SPACE_OBJECT_SYSTEMS};
+
+// Попыток перебросить траекторию
+pub const MAX_PROTECTED_REROLLS: usize = 8;
+
+// Ближе этой глубины проекция вырождается
+const MIN_PROTECTED_DEPTH: f32 = 1e-3;
+
+/// Защищенный прямоугольник окна (UV 0..1, y вниз) и диапазон глубин проверки
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProtectedRect {
+    pub min: Vec2,
+    pub max: Vec2,
+    pub near: f32,
+    pub far: f32,
+}
+
+// Пересекает ли отрезок a-b прямоугольник [min, max] (Лианг-Барски)
+pub fn segment_hits_rect(a: Vec2, b: Vec2, min: Vec2, max: Vec2) -> bool {
+    let delta = b - a;
+    let (mut t0, mut t1) = (0.0f32, 1.0f32);
+    for (p, q) in [
+        (-delta.x, a.x - min.x),
+        (delta.x, max.x - a.x),
+        (-delta.y, a.y - min.y),
+        (delta.y, max.y - a.y),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return false;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+            if t0 > t1 {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+impl SpaceDefinition {
+    // Проходит ли прямая траектория из position со скоростью velocity через
+    // защищенную область на диапазоне глубин
+    pub fn obstructs_protected_rect(&self, position: &Vec3, velocity: &Vec3) -> bool {
+        let Some(rect) = self.protected_rect else {
+            return false;
+        };
+
+        // Участок траектории (t >= 0), где глубина в [near, far]; дальше дальней
+        // границы пространства объект не бывает
+        let depth = position.z - self.observer_position.z;
+        let near = rect.near.max(MIN_PROTECTED_DEPTH);
+        let far = rect.far.min(depth.max(self.max_z - self.observer_position.z));
+        let (t_start, t_end) = if velocity.z.abs() < 1e-6 {
+            if depth < near || depth > far {
+                return false;
+            }
+            // Без движения по глубине - до выхода за боковые границы пространства
+            let dims = self.get_dimensions();
+            (0.0, (dims.x + dims.y) / velocity.truncate().length().max(1e-6))
+        } else {
+            let (t_near, t_far) = ((near - depth) / velocity.z, (far - depth) / velocity.z);
+            (t_near.min(t_far).max(0.0), t_near.max(t_far))
+        };
+        if t_end < t_start {
+            return false;
+        }
+
+        let start = *position + *velocity * t_start;
+        let end = *position + *velocity * t_end;
+        match (self.project_to_viewport(&start), self.project_to_viewport(&end)) {
+            (Some(a), Some(b)) => segment_hits_rect(a, b, rect.min, rect.max),
+            _ => false,
+        }
+    }
+}
+
+// Перебросить траекторию объекта, пока она проходит через защищенную область
+pub fn reroll_protected(obj: &mut dyn SpaceObject, rng: &mut StdRng, space: &SpaceDefinition) {
+    for _ in 0..MAX_PROTECTED_REROLLS {
+        let data = obj.get_data();
+        if !space.obstructs_protected_rect(&data.position, &data.velocity) {
+            return;
+        }
+        obj.initialize_random(rng, space);
+    }
+}
+
+// Защищенная область окна: x, y - левый верхний угол, w, h - размер в долях
+// окна (0..1). Траектории, проходящие через нее на глубинах near..far от
+// наблюдателя, перебрасываются при появлении; far = Infinity - вся глубина
+#[wasm_bindgen]
+pub fn set_protected_screen_rect(system_id: usize, x: f32, y: f32, w: f32, h: f32, near: f32, far: f32) -> OpResult {
+    if ![x, y, w, h, near].iter().all(|value| value.is_finite()) || far.is_nan() || w <= 0.0 || h <= 0.0 || near < 0.0 || far < near {
+        return OpResult::invalid_argument("protected rect requires a positive size and 0 <= near <= far");
+    }
+
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.space.protected_rect = Some(ProtectedRect {
+                min: Vec2::new(x, y),
+                max: Vec2::new(x + w, y + h),
+                near,
+                far,
+            });
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn clear_protected_screen_rect(system_id: usize) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.space.protected_rect = None;
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Проходит ли текущая траектория объекта через защищенную область
+#[wasm_bindgen]
+pub fn crosses_protected_rect(system_id: usize, object_id: usize) -> Option<bool> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let data = system
+        .get_objects()
+        .values()
+        .flatten()
+        .map(|obj| obj.get_data())
+        .find(|data| data.id == object_id)?;
+    Some(system.space.obstructs_protected_rect(&data.position, &data.velocity))
+}
@@ -0,0 +1,193 @@
+/*
+ * query.rs
+ *
+ * Запросы к объектам системы по маске компонентов и области пространства.
+ * Хост может сам собирать нужные ему данные (например, "все объекты рядом
+ * с видовой плоскостью") без новой Rust-функции под каждый случай.
+ *
+ * Компоненты - это поля общих данных объекта (SpaceObjectData); маска
+ * определяет, какие из них попадут в ответ. Объекты, ожидающие респауна,
+ * в выборку не попадают. Размытие и туман - производные компоненты,
+ * вычисляемые по модели глубины пространства (SpaceDefinition::depth_effects).
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+
+use crate::space_objects::{SpaceObjectSystem, SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+
+// Биты маски компонентов
+pub const COMPONENT_POSITION: u32 = 1;
+pub const COMPONENT_VELOCITY: u32 = 1 << 1;
+pub const COMPONENT_SIZE: u32 = 1 << 2;
+pub const COMPONENT_OPACITY: u32 = 1 << 3;
+pub const COMPONENT_LIFETIME: u32 = 1 << 4;
+pub const COMPONENT_ROTATION: u32 = 1 << 5;
+pub const COMPONENT_BLUR: u32 = 1 << 6;
+pub const COMPONENT_FOG: u32 = 1 << 7;
+pub const COMPONENT_HIGHLIGHT: u32 = 1 << 8;
+
+/// Результат запроса: ID и типы всех найденных объектов, а также
+/// данные только тех компонентов, которые были запрошены маской
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct EntityQueryResult {
+    mask: u32,
+    ids: Vec<usize>,
+    types: Vec<SpaceObjectType>,
+    positions: Vec<f32>,
+    velocities: Vec<f32>,
+    sizes: Vec<f32>,
+    opacities: Vec<f32>,
+    lifetimes: Vec<f32>,
+    rotations: Vec<f32>,
+    blurs: Vec<f32>,
+    fogs: Vec<f32>,
+    highlights: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl EntityQueryResult {
+    #[wasm_bindgen(getter)]
+    pub fn mask(&self) -> u32 {
+        self.mask
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn ids(&self) -> Vec<usize> {
+        self.ids.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn types(&self) -> Vec<SpaceObjectType> {
+        self.types.clone()
+    }
+
+    // x, y, z на объект
+    #[wasm_bindgen(getter)]
+    pub fn positions(&self) -> Vec<f32> {
+        self.positions.clone()
+    }
+
+    // x, y, z на объект
+    #[wasm_bindgen(getter)]
+    pub fn velocities(&self) -> Vec<f32> {
+        self.velocities.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sizes(&self) -> Vec<f32> {
+        self.sizes.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn opacities(&self) -> Vec<f32> {
+        self.opacities.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn lifetimes(&self) -> Vec<f32> {
+        self.lifetimes.clone()
+    }
+
+    // Кватернион x, y, z, w на объект
+    #[wasm_bindgen(getter)]
+    pub fn rotations(&self) -> Vec<f32> {
+        self.rotations.clone()
+    }
+
+    // Степень размытия глубины резкости (0..1)
+    #[wasm_bindgen(getter)]
+    pub fn blurs(&self) -> Vec<f32> {
+        self.blurs.clone()
+    }
+
+    // Доля тумана (0..1)
+    #[wasm_bindgen(getter)]
+    pub fn fogs(&self) -> Vec<f32> {
+        self.fogs.clone()
+    }
+
+    // Подсветка выделения (0 - объект не выделен)
+    #[wasm_bindgen(getter)]
+    pub fn highlights(&self) -> Vec<f32> {
+        self.highlights.clone()
+    }
+}
+
+// Выбрать объекты системы внутри области (min, max) и собрать запрошенные компоненты
+pub fn query_system(system: &SpaceObjectSystem, mask: u32, region: Option<(Vec3, Vec3)>) -> EntityQueryResult {
+    let mut objects: Vec<_> = system
+        .get_objects()
+        .values()
+        .flat_map(|objects| objects.iter())
+        .filter(|obj| !obj.is_waiting_for_respawn())
+        .map(|obj| obj.get_data())
+        .filter(|data| match region {
+            Some((min, max)) => data.position.cmpge(min).all() && data.position.cmple(max).all(),
+            None => true,
+        })
+        .collect();
+    objects.sort_by_key(|data| data.id);
+
+    let mut result = EntityQueryResult {
+        mask,
+        ..Default::default()
+    };
+
+    for data in objects {
+        result.ids.push(data.id);
+        result.types.push(data.object_type);
+
+        if mask & COMPONENT_POSITION != 0 {
+            result.positions.extend_from_slice(&data.position.to_array());
+        }
+        if mask & COMPONENT_VELOCITY != 0 {
+            result.velocities.extend_from_slice(&data.velocity.to_array());
+        }
+        if mask & COMPONENT_SIZE != 0 {
+            result.sizes.push(data.size);
+        }
+        if mask & COMPONENT_OPACITY != 0 {
+            result.opacities.push(data.opacity);
+        }
+        if mask & COMPONENT_LIFETIME != 0 {
+            result.lifetimes.push(data.lifetime);
+        }
+        if mask & COMPONENT_ROTATION != 0 {
+            result.rotations.extend_from_slice(&data.rotation.to_array());
+        }
+        if mask & (COMPONENT_BLUR | COMPONENT_FOG) != 0 {
+            let (blur, fog) = system.space.get_depth_effects(&data.position);
+            if mask & COMPONENT_BLUR != 0 {
+                result.blurs.push(blur);
+            }
+            if mask & COMPONENT_FOG != 0 {
+                result.fogs.push(fog);
+            }
+        }
+        if mask & COMPONENT_HIGHLIGHT != 0 {
+            result.highlights.push(system.selection.highlight(data.id));
+        }
+    }
+
+    result
+}
+
+// Запрос объектов по маске компонентов.
+// region - необязательная область [min_x, min_y, min_z, max_x, max_y, max_z]
+#[wasm_bindgen]
+pub fn query_entities(system_id: usize, component_mask: u32, region: Option<Vec<f32>>) -> Option<EntityQueryResult> {
+    let region = match region {
+        Some(bounds) if bounds.len() == 6 => Some((
+            Vec3::new(bounds[0], bounds[1], bounds[2]),
+            Vec3::new(bounds[3], bounds[4], bounds[5]),
+        )),
+        Some(_) => return None,
+        None => None,
+    };
+
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| query_system(&system, component_mask, region))
+}
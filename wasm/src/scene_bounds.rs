@@ -0,0 +1,193 @@
+/*
+ * scene_bounds.rs
+ *
+ * Габариты сцены и автоматическое кадрирование. get_scene_bounds отдает
+ * AABB активных видимых объектов (не ожидающих респауна, с ненулевой
+ * прозрачностью), frame_scene - положение наблюдателя и угол обзора, при
+ * которых все эти объекты попадают в кадр: для скриншотов, превью и
+ * вступительных планов без ручной подгонки камеры.
+ *
+ * Кадрирование сохраняет направление взгляда вдоль +Z: наблюдатель ставится
+ * напротив центра сцены на таком расстоянии, чтобы описанная сфера AABB
+ * (с запасом padding) вписалась в пирамиду видимости. Если для этого
+ * пришлось бы выйти за ближнюю границу пространства, наблюдатель остается
+ * на границе, а угол обзора расширяется (не больше MAX_FRAMING_FOV).
+ */
+
+use std::f32::consts::PI;
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+
+use crate::space_core::SpaceDefinition;
+use crate::space_objects::{SpaceObjectSystem, SPACE_OBJECT_SYSTEMS};
+
+// Предел угла обзора, до которого расширяется кадр
+const MAX_FRAMING_FOV: f32 = PI * 2.0 / 3.0;
+
+// Наименьший радиус кадрируемой сцены (одиночный объект)
+const MIN_FRAMING_RADIUS: f32 = 1.0;
+
+/// AABB активных видимых объектов
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub min: Vec3,
+    pub max: Vec3,
+    pub count: usize,
+}
+
+impl Bounds {
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    // Радиус описанной сферы
+    pub fn radius(&self) -> f32 {
+        (self.max - self.min).length() * 0.5
+    }
+}
+
+// AABB позиций активных видимых объектов системы; None - таких нет
+pub fn scene_bounds(system: &SpaceObjectSystem) -> Option<Bounds> {
+    system
+        .get_objects()
+        .values()
+        .flatten()
+        .filter(|obj| !obj.is_waiting_for_respawn() && obj.get_data().opacity > 0.0)
+        .map(|obj| obj.get_data().position)
+        .fold(None, |bounds: Option<Bounds>, position| {
+            Some(match bounds {
+                Some(bounds) => Bounds {
+                    min: bounds.min.min(position),
+                    max: bounds.max.max(position),
+                    count: bounds.count + 1,
+                },
+                None => Bounds { min: position, max: position, count: 1 },
+            })
+        })
+}
+
+// Положение наблюдателя и угол обзора, при которых bounds с запасом padding
+// (доля радиуса) целиком видны при взгляде вдоль +Z
+pub fn frame_bounds(bounds: &Bounds, padding: f32, space: &SpaceDefinition) -> (Vec3, f32) {
+    let radius = (bounds.radius() * (1.0 + padding.max(0.0))).max(MIN_FRAMING_RADIUS);
+    let center = bounds.center();
+
+    // Угол обзора - вертикальный; по горизонтали пирамида шире или уже по пропорциям окна
+    let aspect = if space.screen_size.x > 0.0 && space.screen_size.y > 0.0 {
+        space.screen_size.x / space.screen_size.y
+    } else {
+        1.0
+    };
+    let half_angle = |fov: f32| {
+        let half = fov * 0.5;
+        half.min((half.tan() * aspect).atan())
+    };
+
+    let distance = radius / half_angle(space.field_of_view).sin();
+    let observer_z = center.z - distance;
+    if observer_z >= space.min_z {
+        return (Vec3::new(center.x, center.y, observer_z), space.field_of_view);
+    }
+
+    // Упираемся в ближнюю границу: расширяем угол, пока сфера не впишется
+    // (сфера, которая не помещается целиком перед наблюдателем, - предельный угол)
+    let ratio = (radius / (center.z - space.min_z).max(radius)).min(0.999);
+    let needed_tan = ratio / (1.0 - ratio * ratio).sqrt();
+    let fov = 2.0 * (needed_tan / aspect.min(1.0)).atan();
+    (Vec3::new(center.x, center.y, space.min_z), fov.clamp(space.field_of_view, MAX_FRAMING_FOV))
+}
+
+/// Габариты сцены
+#[wasm_bindgen]
+pub struct SceneBounds {
+    min: Vec<f32>,
+    max: Vec<f32>,
+    center: Vec<f32>,
+    radius: f32,
+    count: usize,
+}
+
+#[wasm_bindgen]
+impl SceneBounds {
+    #[wasm_bindgen(getter)]
+    pub fn min(&self) -> Vec<f32> {
+        self.min.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max(&self) -> Vec<f32> {
+        self.max.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn center(&self) -> Vec<f32> {
+        self.center.clone()
+    }
+
+    // Радиус описанной сферы AABB
+    #[wasm_bindgen(getter)]
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    // Число объектов внутри габаритов
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+/// Кадр, вмещающий сцену
+#[wasm_bindgen]
+pub struct SceneFraming {
+    position: Vec<f32>,
+    field_of_view: f32,
+}
+
+#[wasm_bindgen]
+impl SceneFraming {
+    // Положение наблюдателя (x, y, z)
+    #[wasm_bindgen(getter)]
+    pub fn position(&self) -> Vec<f32> {
+        self.position.clone()
+    }
+
+    // Угол обзора, радианы
+    #[wasm_bindgen(getter)]
+    pub fn field_of_view(&self) -> f32 {
+        self.field_of_view
+    }
+}
+
+// AABB активных видимых объектов; undefined - нет системы или объектов
+#[wasm_bindgen]
+pub fn get_scene_bounds(system_id: usize) -> Option<SceneBounds> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let bounds = scene_bounds(&system)?;
+    Some(SceneBounds {
+        min: bounds.min.to_array().to_vec(),
+        max: bounds.max.to_array().to_vec(),
+        center: bounds.center().to_array().to_vec(),
+        radius: bounds.radius(),
+        count: bounds.count,
+    })
+}
+
+// Положение наблюдателя и угол обзора, вмещающие сцену с запасом padding
+// (0.1 - 10% радиуса). Наблюдатель не перемещается: кадр применяет хост
+// (set_observer_position и угол обзора камеры рендерера)
+#[wasm_bindgen]
+pub fn frame_scene(system_id: usize, padding: f32) -> Option<SceneFraming> {
+    if !padding.is_finite() {
+        return None;
+    }
+
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let bounds = scene_bounds(&system)?;
+    let (position, field_of_view) = frame_bounds(&bounds, padding, &system.space);
+    Some(SceneFraming {
+        position: position.to_array().to_vec(),
+        field_of_view,
+    })
+}
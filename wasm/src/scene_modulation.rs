@@ -0,0 +1,255 @@
+/*
+ * scene_modulation.rs
+ * 
+ * Связь гиперкуба со сценой: объемы спроецированных ячеек тессеракта
+ * модулируют частоту появления комет, смещение палитры и свечение
+ * видовой плоскости. Так 4D объект становится "сердцем" живой сцены,
+ * а не отдельным виджетом.
+ * 
+ * Привязка хранит снимок: гиперкуб принадлежит JS, и система не держит
+ * на него ссылку. Модуляция последнего bind_hypercube_to_scene
+ * переприменяется в начале каждого шага, пока ее не снимут, а смещение
+ * палитры доходит до уже живущих и респаунящихся комет. Чтобы поворот
+ * гиперкуба доходил до сцены, bind_hypercube_to_scene вызывается заново
+ * в каждом кадре, где гиперкуб повернулся.
+ *
+ * Вместе со снимком хранится копия привязанного гиперкуба. Ее поворачивает
+ * команда очереди {"type": "rotate"} (см. commands.rs) - так поворот
+ * становится частью воспроизводимого журнала команд; повернутую копию
+ * рендерер забирает через get_bound_hypercube.
+ * 
+ * Кроме того, вершины спроецированного гиперкуба, размещенного в сцене,
+ * порождают такие же события пересечения видовой плоскости, как и
+ * космические объекты, - и запускают тот же конвейер эффектов.
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+
+use crate::errors::OpResult;
+use crate::hypercube::Hypercube;
+use crate::neon_comets::NeonComet;
+use crate::space_core::Vec3Wrapper;
+use crate::space_objects::{PlaneCrossing, SpaceObjectSystem, SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+
+// Диапазон множителя частоты появления комет
+const MIN_SPAWN_RATE: f32 = 0.5;
+const MAX_SPAWN_RATE: f32 = 1.5;
+
+// Базовое свечение видовой плоскости при равномерных ячейках
+const BASE_PLANE_GLOW: f32 = 0.2;
+
+// Наибольшее число пересечений вершин гиперкуба, ожидающих шага системы
+const MAX_PENDING_HYPERCUBE_CROSSINGS: usize = 128;
+
+/// Параметры сцены, модулируемые гиперкубом
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct SceneModulation {
+    // Множитель частоты появления комет
+    pub spawn_rate: f32,
+    
+    // Смещение палитры комет к следующему цвету (0.0 - 1.0)
+    pub palette_t: f32,
+    
+    // Интенсивность свечения видовой плоскости (0.0 - 1.0)
+    pub plane_glow: f32,
+}
+
+impl Default for SceneModulation {
+    // Нейтральная модуляция - сцена ведет себя как без гиперкуба
+    fn default() -> Self {
+        Self {
+            spawn_rate: 1.0,
+            palette_t: 0.0,
+            plane_glow: 0.0,
+        }
+    }
+}
+
+impl SceneModulation {
+    // Рассчитать модуляцию по объемам 8 спроецированных ячеек
+    pub fn from_cell_volumes(volumes: &[f64]) -> Self {
+        let total: f64 = volumes.iter().sum();
+        if volumes.is_empty() || total <= f64::EPSILON || !total.is_finite() {
+            return Self::default();
+        }
+        
+        let uniform = 1.0 / volumes.len() as f64;
+        let fractions: Vec<f64> = volumes.iter().map(|v| v / total).collect();
+        
+        // Насколько одна ячейка "доминирует" над остальными (0 - все равны, 1 - одна ячейка)
+        let max_fraction = fractions.iter().cloned().fold(0.0, f64::max);
+        let dominance = ((max_fraction - uniform) / (1.0 - uniform)).clamp(0.0, 1.0) as f32;
+        
+        // Фаза палитры - взвешенный по объемам индекс ячейки
+        let weighted_index: f64 = fractions.iter().enumerate().map(|(i, f)| i as f64 * f).sum();
+        let palette_t = (weighted_index / (volumes.len() - 1).max(1) as f64) as f32;
+        
+        Self {
+            spawn_rate: MIN_SPAWN_RATE + (MAX_SPAWN_RATE - MIN_SPAWN_RATE) * dominance,
+            palette_t: palette_t.clamp(0.0, 1.0),
+            plane_glow: BASE_PLANE_GLOW + (1.0 - BASE_PLANE_GLOW) * dominance,
+        }
+    }
+}
+
+/// Привязка гиперкуба к сцене системы
+#[derive(Clone, Debug, Default)]
+pub struct SceneBinding {
+    // Модуляция последнего bind_hypercube_to_scene; None - гиперкуб не привязан
+    bound: Option<SceneModulation>,
+    
+    // Копия привязанного гиперкуба и положение камеры по W для команды rotate
+    hypercube: Option<(Hypercube, f64)>,
+    
+    // Смещение палитры, уже примененное к живущим кометам
+    applied_palette_t: f32,
+}
+
+impl SpaceObjectSystem {
+    // Переприменить привязку гиперкуба: пока она есть, модуляцию сцены задает
+    // гиперкуб (в том числе поверх команды palette). Изменившееся смещение
+    // палитры сдвигает цвет уже живущих комет; респаунящиеся получают его
+    // при активации, новые - при появлении
+    pub fn apply_scene_modulation(&mut self) {
+        if let Some(modulation) = self.scene_binding.bound {
+            self.modulation = modulation;
+        }
+        
+        let palette_t = self.modulation.palette_t;
+        if palette_t == self.scene_binding.applied_palette_t {
+            return;
+        }
+        let comets = self.get_objects_mut().get_mut(&SpaceObjectType::NeonComet);
+        for obj in comets.into_iter().flatten() {
+            if let Some(comet) = obj.as_any_mut().downcast_mut::<NeonComet>() {
+                comet.shift_palette(palette_t);
+            }
+        }
+        self.scene_binding.applied_palette_t = palette_t;
+    }
+    
+    // Повернуть копию привязанного гиперкуба по плоскостям XY, XZ, XW, YZ, YW, ZW
+    // и пересчитать модуляцию; false - гиперкуб не привязан
+    pub fn rotate_bound_hypercube(&mut self, angles: [f64; 6]) -> bool {
+        let Some((hypercube, w_camera)) = self.scene_binding.hypercube.as_mut() else {
+            return false;
+        };
+        
+        let [xy, xz, xw, yz, yw, zw] = angles;
+        hypercube.rotate(xy, xz, xw, yz, yw, zw);
+        let volumes = hypercube.get_projected_cell_volumes(*w_camera);
+        self.scene_binding.bound = Some(SceneModulation::from_cell_volumes(&volumes));
+        true
+    }
+}
+
+// Привязать текущее состояние гиперкуба к сцене системы объектов.
+// Сохраняется снимок модуляции, а не ссылка на гиперкуб: после поворота
+// гиперкуба функцию нужно вызвать снова (раз в кадр, до update_space_object_system).
+// Снимок действует до следующего вызова или unbind_hypercube_from_scene, влияя на
+// появление комет и их палитру
+#[wasm_bindgen]
+pub fn bind_hypercube_to_scene(system_id: usize, hypercube: &Hypercube, w_camera: f64) -> OpResult {
+    if let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        let volumes = hypercube.get_projected_cell_volumes(w_camera);
+        system.scene_binding.bound = Some(SceneModulation::from_cell_volumes(&volumes));
+        system.scene_binding.hypercube = Some((hypercube.clone(), w_camera));
+        system.apply_scene_modulation();
+        OpResult::success()
+    } else {
+        OpResult::system_not_found(system_id)
+    }
+}
+
+// Снять привязку и сбросить модуляцию сцены к нейтральной
+#[wasm_bindgen]
+pub fn unbind_hypercube_from_scene(system_id: usize) -> OpResult {
+    if let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        system.scene_binding.bound = None;
+        system.scene_binding.hypercube = None;
+        system.modulation = SceneModulation::default();
+        system.apply_scene_modulation();
+        OpResult::success()
+    } else {
+        OpResult::system_not_found(system_id)
+    }
+}
+
+// Копия привязанного гиперкуба с поворотами команд rotate; undefined - не привязан
+#[wasm_bindgen]
+pub fn get_bound_hypercube(system_id: usize) -> Option<Hypercube> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    system.scene_binding.hypercube.as_ref().map(|(hypercube, _)| hypercube.clone())
+}
+
+// Получить текущую модуляцию сцены (в том числе свечение плоскости для рендерера)
+#[wasm_bindgen]
+pub fn get_scene_modulation(system_id: usize) -> Option<SceneModulation> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| system.modulation)
+}
+
+// Отследить пересечения видовой плоскости вершинами гиперкуба, размещенного в сцене
+// с центром center и масштабом scale. Положения вершин сравниваются с прошлым
+// вызовом (object_id пересечения - индекс вершины). Найденные пересечения ждут
+// ближайшего update_space_object_system и проходят через его проход пересечений,
+// поэтому их, как и пересечения объектов, видят все потребители кадра.
+// Вызывается один раз на кадр (до или после обновления системы).
+// Возвращает количество новых пересечений
+#[wasm_bindgen]
+pub fn track_hypercube_crossings(
+    system_id: usize,
+    hypercube: &Hypercube,
+    w_camera: f64,
+    center: &Vec3Wrapper,
+    scale: f32,
+    dt: f32,
+) -> usize {
+    let Some(mut system_ref) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) else {
+        return 0;
+    };
+    let system = &mut *system_ref;
+    
+    let center: Vec3 = center.clone().into();
+    let projected = hypercube.get_projected_vertices(w_camera);
+    let vertices: Vec<Vec3> = projected
+        .chunks_exact(3)
+        .map(|p| center + Vec3::new(p[0] as f32, p[1] as f32, p[2] as f32) * scale)
+        .collect();
+    
+    let mut new_crossings = 0;
+    
+    // Сравниваем только при неизменной топологии и корректном шаге времени
+    if system.hypercube_vertices.len() == vertices.len() && dt > 0.0 {
+        for (index, (previous, current)) in system.hypercube_vertices.iter().zip(&vertices).enumerate() {
+            // Без шагов системы очередь не растет бесконечно
+            if system.hypercube_crossings.len() >= MAX_PENDING_HYPERCUBE_CROSSINGS {
+                break;
+            }
+            if let Some((plane, t, point)) = system.viewing_planes.nearest_crossing(&system.space, previous, current) {
+                let Some(id) = system.crossing_dedup.accept(SpaceObjectType::Hypercube, index, plane) else {
+                    continue;
+                };
+                let (crossing_type, normal) = system.space.crossing_orientation(&(*current - *previous));
+                let crossing = PlaneCrossing {
+                    id,
+                    object_id: index,
+                    object_type: SpaceObjectType::Hypercube,
+                    plane,
+                    position: point,
+                    velocity: (*current - *previous) / dt,
+                    size: scale,
+                    t,
+                    crossing_type,
+                    normal,
+                };
+                system.hypercube_crossings.push(crossing);
+                new_crossings += 1;
+            }
+        }
+    }
+    
+    system.hypercube_vertices = vertices;
+    new_crossings
+}
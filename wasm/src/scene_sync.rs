@@ -0,0 +1,529 @@
+/*
+ * scene_sync.rs
+ *
+ * Синхронизация сцены между вкладками и устройствами (хост/зеркало).
+ * Хост ведет симуляцию как обычно и отдает компактные пакеты
+ * (encode_sync_packet) на основе дельта-трекера: только поля, изменившиеся
+ * после кадра base_frame. Зеркало не симулирует ничего само: оно
+ * восстанавливает состояние из пакетов и показывает его с задержкой
+ * delay - этот запас и есть буфер джиттера, поэтому неровно пришедшие
+ * пакеты не дают рывков, а положения между кадрами хоста интерполируются.
+ *
+ * Пакет (little-endian), заголовок 32 байта:
+ *   0..4   магия b"HCSP"
+ *   4..6   версия формата (u16), сейчас SYNC_PACKET_VERSION
+ *   6..8   размер заголовка (u16)
+ *   8..12  кадр хоста (u32)
+ *   12..16 базовый кадр дельты (u32); 0 - полный снимок
+ *   16..24 время симуляции хоста, секунды (f64)
+ *   24..28 записей объектов (u32)
+ *   28..32 удаленных объектов (u32)
+ * Запись объекта: ID (u32), тип (u8), маска полей (u8, биты DELTA_*), затем
+ * присутствующие поля: позиция 3 x i32 (кванты position_quantum),
+ * прозрачность u8, размер u16 (кванты size_quantum). После записей - ID
+ * удаленных объектов (u32).
+ *
+ * Пакет применяется, если его базовый кадр не новее последнего примененного;
+ * пакеты "из будущего" ждут в очереди, устаревшие отбрасываются. Если очередь
+ * переполнена (пакет потерян), зеркало просит полный снимок (needs_keyframe).
+ */
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use once_cell::sync::Lazy;
+use dashmap::DashMap;
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+
+use crate::deltas::{DELTA_OPACITY, DELTA_POSITION, DELTA_SIZE, POSITION_QUANTUM, SIZE_QUANTUM};
+use crate::errors::OpResult;
+use crate::space_objects::{SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+use crate::state_stream::object_type_from_u8;
+
+pub const SYNC_PACKET_MAGIC: &[u8; 4] = b"HCSP";
+pub const SYNC_PACKET_VERSION: u16 = 1;
+const SYNC_HEADER_SIZE: usize = 32;
+
+// Задержка показа зеркала по умолчанию (запас на джиттер сети), секунды
+pub const DEFAULT_MIRROR_DELAY: f32 = 0.1;
+
+// Пакетов, ожидающих пропущенный предыдущий, до запроса полного снимка
+const MAX_PENDING_PACKETS: usize = 32;
+
+// Состояний хоста в буфере зеркала
+const MAX_MIRROR_SNAPSHOTS: usize = 64;
+
+// Скорость подтягивания часов зеркала к времени хоста (1/с)
+const MIRROR_CLOCK_CORRECTION: f64 = 2.0;
+
+// Пакет хоста для зеркал: изменения после кадра since_frame (0 - полный снимок)
+pub fn encode_packet(system_id: usize, since_frame: u32) -> Option<Vec<u8>> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let deltas = system.deltas.collect(system.frame, since_frame);
+    let types: HashMap<usize, SpaceObjectType> = system
+        .get_objects()
+        .values()
+        .flatten()
+        .map(|obj| (obj.get_data().id, obj.get_data().object_type))
+        .collect();
+    let base_frame = if deltas.keyframe { 0 } else { since_frame };
+
+    let mut bytes = Vec::with_capacity(SYNC_HEADER_SIZE + deltas.ids.len() * 20 + deltas.removed.len() * 4);
+    bytes.extend_from_slice(SYNC_PACKET_MAGIC);
+    bytes.extend_from_slice(&SYNC_PACKET_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(SYNC_HEADER_SIZE as u16).to_le_bytes());
+    bytes.extend_from_slice(&deltas.frame.to_le_bytes());
+    bytes.extend_from_slice(&base_frame.to_le_bytes());
+    bytes.extend_from_slice(&system.sim_time.to_le_bytes());
+    bytes.extend_from_slice(&(deltas.ids.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&(deltas.removed.len() as u32).to_le_bytes());
+
+    let (mut positions, mut opacities, mut sizes) = (deltas.positions.iter(), deltas.opacities.iter(), deltas.sizes.iter());
+    for (&id, &mask) in deltas.ids.iter().zip(&deltas.masks) {
+        let object_type = types.get(&id).copied().unwrap_or(SpaceObjectType::NeonComet);
+        bytes.extend_from_slice(&(id as u32).to_le_bytes());
+        bytes.push(object_type as u8);
+        bytes.push(mask);
+        if mask & DELTA_POSITION != 0 {
+            for value in positions.by_ref().take(3) {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        if mask & DELTA_OPACITY != 0 {
+            bytes.push(opacities.next().copied().unwrap_or(0));
+        }
+        if mask & DELTA_SIZE != 0 {
+            bytes.extend_from_slice(&sizes.next().copied().unwrap_or(0).to_le_bytes());
+        }
+    }
+    for &id in &deltas.removed {
+        bytes.extend_from_slice(&(id as u32).to_le_bytes());
+    }
+
+    Some(bytes)
+}
+
+/// Объект в состоянии зеркала
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MirrorObject {
+    pub object_type: SpaceObjectType,
+    pub position: Vec3,
+    pub opacity: f32,
+    pub size: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct ObjectUpdate {
+    id: u32,
+    object_type: Option<SpaceObjectType>,
+    mask: u8,
+    position: Vec3,
+    opacity: f32,
+    size: f32,
+}
+
+/// Разобранный пакет хоста
+#[derive(Clone, Debug)]
+pub struct SyncPacket {
+    pub frame: u32,
+    pub base_frame: u32,
+    pub host_time: f64,
+    updates: Vec<ObjectUpdate>,
+    removed: Vec<u32>,
+}
+
+struct PacketReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl PacketReader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let chunk = self
+            .bytes
+            .get(self.offset..self.offset + N)
+            .ok_or_else(|| "Truncated sync packet".to_string())?;
+        self.offset += N;
+        Ok(chunk.try_into().unwrap_or([0; N]))
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_le_bytes(self.take()?))
+    }
+
+    fn f64(&mut self) -> Result<f64, String> {
+        Ok(f64::from_le_bytes(self.take()?))
+    }
+}
+
+// Разобрать пакет хоста
+pub fn decode_packet(bytes: &[u8]) -> Result<SyncPacket, String> {
+    if bytes.len() < SYNC_HEADER_SIZE || &bytes[0..4] != SYNC_PACKET_MAGIC {
+        return Err("Not a hypercube sync packet".to_string());
+    }
+
+    let mut reader = PacketReader { bytes, offset: 4 };
+    let version = reader.u16()?;
+    if version != SYNC_PACKET_VERSION {
+        return Err(format!("Unsupported sync packet version {}", version));
+    }
+    let header_size = reader.u16()? as usize;
+    let frame = reader.u32()?;
+    let base_frame = reader.u32()?;
+    let host_time = reader.f64()?;
+    let count = reader.u32()? as usize;
+    let removed_count = reader.u32()? as usize;
+    if header_size < SYNC_HEADER_SIZE {
+        return Err("Invalid sync packet header".to_string());
+    }
+    reader.offset = header_size;
+
+    let mut updates = Vec::with_capacity(count.min(bytes.len()));
+    for _ in 0..count {
+        let mut update = ObjectUpdate { id: reader.u32()?, ..Default::default() };
+        let object_type = reader.u8()?;
+        update.object_type = Some(object_type_from_u8(object_type).ok_or_else(|| format!("Unknown object type {}", object_type))?);
+        update.mask = reader.u8()?;
+        if update.mask & DELTA_POSITION != 0 {
+            let quantized = [reader.i32()?, reader.i32()?, reader.i32()?];
+            update.position = Vec3::from(quantized.map(|value| value as f32 * POSITION_QUANTUM));
+        }
+        if update.mask & DELTA_OPACITY != 0 {
+            update.opacity = reader.u8()? as f32 / 255.0;
+        }
+        if update.mask & DELTA_SIZE != 0 {
+            update.size = reader.u16()? as f32 * SIZE_QUANTUM;
+        }
+        updates.push(update);
+    }
+
+    let removed = (0..removed_count).map(|_| reader.u32()).collect::<Result<_, _>>()?;
+    Ok(SyncPacket { frame, base_frame, host_time, updates, removed })
+}
+
+#[derive(Clone, Debug)]
+struct MirrorSnapshot {
+    host_time: f64,
+    objects: HashMap<u32, MirrorObject>,
+}
+
+/// Зеркало сцены: восстановленные состояния хоста и часы показа
+#[derive(Clone, Debug)]
+pub struct SceneMirror {
+    delay: f32,
+    snapshots: VecDeque<MirrorSnapshot>,
+    // Пакеты, ожидающие пропущенный предыдущий, по кадру хоста
+    pending: BTreeMap<u32, SyncPacket>,
+    last_frame: Option<u32>,
+    playback_time: Option<f64>,
+    needs_keyframe: bool,
+    dropped: u32,
+}
+
+impl SceneMirror {
+    pub fn new(delay: f32) -> Self {
+        SceneMirror {
+            delay: delay.max(0.0),
+            snapshots: VecDeque::new(),
+            pending: BTreeMap::new(),
+            last_frame: None,
+            playback_time: None,
+            needs_keyframe: true,
+            dropped: 0,
+        }
+    }
+
+    pub fn needs_keyframe(&self) -> bool {
+        self.needs_keyframe
+    }
+
+    pub fn last_frame(&self) -> Option<u32> {
+        self.last_frame
+    }
+
+    // Принять пакет: применить сразу, отложить до предыдущего или отбросить устаревший
+    pub fn receive(&mut self, packet: SyncPacket) {
+        if packet.base_frame == 0 {
+            self.needs_keyframe = false;
+        } else if self.last_frame.is_none_or(|last| packet.base_frame > last) {
+            if self.pending.len() >= MAX_PENDING_PACKETS {
+                self.pending.clear();
+                self.needs_keyframe = true;
+            }
+            self.pending.insert(packet.frame, packet);
+            return;
+        }
+
+        if !self.apply(packet) {
+            self.dropped = self.dropped.saturating_add(1);
+        }
+
+        // Применить отложенные пакеты, которые теперь стали применимыми
+        while let Some(entry) = self.pending.first_entry() {
+            let last = self.last_frame.unwrap_or(0);
+            if entry.get().frame <= last {
+                entry.remove();
+                self.dropped = self.dropped.saturating_add(1);
+            } else if entry.get().base_frame <= last {
+                let packet = entry.remove();
+                self.apply(packet);
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn apply(&mut self, packet: SyncPacket) -> bool {
+        if self.last_frame.is_some_and(|last| packet.frame <= last) {
+            return false;
+        }
+
+        let mut objects = match (packet.base_frame, self.snapshots.back()) {
+            (0, _) | (_, None) => HashMap::new(),
+            (_, Some(previous)) => previous.objects.clone(),
+        };
+        for update in &packet.updates {
+            let object = objects.entry(update.id).or_insert(MirrorObject {
+                object_type: update.object_type.unwrap_or(SpaceObjectType::NeonComet),
+                position: update.position,
+                opacity: update.opacity,
+                size: update.size,
+            });
+            if let Some(object_type) = update.object_type {
+                object.object_type = object_type;
+            }
+            if update.mask & DELTA_POSITION != 0 {
+                object.position = update.position;
+            }
+            if update.mask & DELTA_OPACITY != 0 {
+                object.opacity = update.opacity;
+            }
+            if update.mask & DELTA_SIZE != 0 {
+                object.size = update.size;
+            }
+        }
+        for id in &packet.removed {
+            objects.remove(id);
+        }
+
+        // Полный снимок после разрыва или первый пакет - часы показа заново
+        if packet.base_frame == 0 && self.snapshots.back().is_none_or(|last| packet.host_time < last.host_time) {
+            self.snapshots.clear();
+            self.playback_time = None;
+        }
+        self.snapshots.push_back(MirrorSnapshot { host_time: packet.host_time, objects });
+        while self.snapshots.len() > MAX_MIRROR_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+        self.last_frame = Some(packet.frame);
+        self.playback_time.get_or_insert(packet.host_time - self.delay as f64);
+        true
+    }
+
+    // Продвинуть часы показа на dt: они идут вместе с локальным временем и
+    // плавно подтягиваются к последнему времени хоста минус задержка
+    pub fn advance(&mut self, dt: f32) {
+        let (Some(playback), Some(latest)) = (self.playback_time, self.snapshots.back()) else {
+            return;
+        };
+
+        let dt = dt.max(0.0) as f64;
+        let target = latest.host_time - self.delay as f64;
+        let mut playback = playback + dt;
+        playback += (target - playback) * (MIRROR_CLOCK_CORRECTION * dt).min(1.0);
+        self.playback_time = Some(playback.min(latest.host_time));
+
+        // Снимки старше предыдущего для текущего момента больше не нужны
+        while self.snapshots.len() > 2 && self.snapshots[1].host_time <= playback {
+            self.snapshots.pop_front();
+        }
+    }
+
+    // Состояние в момент показа: положения интерполируются между соседними снимками.
+    // Новые объекты появляются с момента своего снимка, удаленные исчезают в момент удаления
+    pub fn sample(&self) -> Vec<(u32, MirrorObject)> {
+        let Some(playback) = self.playback_time else {
+            return Vec::new();
+        };
+
+        let next_index = self.snapshots.iter().position(|snapshot| snapshot.host_time > playback);
+        let (from, to) = match next_index {
+            Some(0) => (&self.snapshots[0], None),
+            Some(index) => (&self.snapshots[index - 1], Some(&self.snapshots[index])),
+            None => match self.snapshots.back() {
+                Some(last) => (last, None),
+                None => return Vec::new(),
+            },
+        };
+
+        let mut objects: Vec<(u32, MirrorObject)> = from
+            .objects
+            .iter()
+            .map(|(&id, &object)| {
+                let Some((to, next)) = to.and_then(|to| Some((to, to.objects.get(&id)?))) else {
+                    return (id, object);
+                };
+                let span = to.host_time - from.host_time;
+                let t = if span > 0.0 { ((playback - from.host_time) / span) as f32 } else { 1.0 };
+                (id, MirrorObject {
+                    object_type: object.object_type,
+                    position: object.position.lerp(next.position, t),
+                    opacity: object.opacity + (next.opacity - object.opacity) * t,
+                    size: object.size + (next.size - object.size) * t,
+                })
+            })
+            .collect();
+        objects.sort_unstable_by_key(|(id, _)| *id);
+        objects
+    }
+}
+
+// Зеркала по ID: зеркало не привязано к системе объектов
+static SCENE_MIRRORS: Lazy<DashMap<usize, SceneMirror>> = Lazy::new(DashMap::new);
+static NEXT_MIRROR_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Состояние зеркала в момент показа
+#[wasm_bindgen]
+pub struct MirrorState {
+    ids: Vec<u32>,
+    types: Vec<SpaceObjectType>,
+    positions: Vec<f32>,
+    opacities: Vec<f32>,
+    sizes: Vec<f32>,
+    playback_time: Option<f64>,
+    needs_keyframe: bool,
+}
+
+#[wasm_bindgen]
+impl MirrorState {
+    #[wasm_bindgen(getter)]
+    pub fn ids(&self) -> Vec<u32> {
+        self.ids.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn types(&self) -> Vec<SpaceObjectType> {
+        self.types.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn positions(&self) -> Vec<f32> {
+        self.positions.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn opacities(&self) -> Vec<f32> {
+        self.opacities.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sizes(&self) -> Vec<f32> {
+        self.sizes.clone()
+    }
+
+    // Время хоста, которое показывает зеркало; undefined - пакетов еще не было
+    #[wasm_bindgen(getter)]
+    pub fn playback_time(&self) -> Option<f64> {
+        self.playback_time
+    }
+
+    // Зеркалу нужен полный снимок (encode_sync_packet с since_frame = 0)
+    #[wasm_bindgen(getter)]
+    pub fn needs_keyframe(&self) -> bool {
+        self.needs_keyframe
+    }
+}
+
+// Пакет хоста для зеркал: изменения после кадра since_frame (0 - полный снимок).
+// Обычно since_frame - кадр предыдущего разосланного пакета
+#[wasm_bindgen]
+pub fn encode_sync_packet(system_id: usize, since_frame: u32) -> Option<Vec<u8>> {
+    encode_packet(system_id, since_frame)
+}
+
+// Создать зеркало с задержкой показа delay секунд (запас на джиттер)
+#[wasm_bindgen]
+pub fn create_scene_mirror(delay: f32) -> usize {
+    let delay = if delay.is_finite() { delay } else { DEFAULT_MIRROR_DELAY };
+    let id = NEXT_MIRROR_ID.fetch_add(1, Ordering::SeqCst);
+    SCENE_MIRRORS.insert(id, SceneMirror::new(delay));
+    id
+}
+
+#[wasm_bindgen]
+pub fn destroy_scene_mirror(mirror_id: usize) -> OpResult {
+    match SCENE_MIRRORS.remove(&mirror_id) {
+        Some(_) => OpResult::success(),
+        None => OpResult::not_found(format!("scene mirror {} not found", mirror_id)),
+    }
+}
+
+// Передать зеркалу пакет хоста
+#[wasm_bindgen]
+pub fn apply_sync_packet(mirror_id: usize, packet: &[u8]) -> OpResult {
+    let Some(mut mirror) = SCENE_MIRRORS.get_mut(&mirror_id) else {
+        return OpResult::not_found(format!("scene mirror {} not found", mirror_id));
+    };
+
+    match decode_packet(packet) {
+        Ok(packet) => {
+            mirror.receive(packet);
+            OpResult::success()
+        }
+        Err(message) => OpResult::invalid_argument(message),
+    }
+}
+
+// Продвинуть часы показа зеркала на dt секунд (вызывается каждый кадр рендера)
+#[wasm_bindgen]
+pub fn update_scene_mirror(mirror_id: usize, dt: f32) -> OpResult {
+    match SCENE_MIRRORS.get_mut(&mirror_id) {
+        Some(mut mirror) => {
+            mirror.advance(if dt.is_finite() { dt } else { 0.0 });
+            OpResult::success()
+        }
+        None => OpResult::not_found(format!("scene mirror {} not found", mirror_id)),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_scene_mirror_state(mirror_id: usize) -> Option<MirrorState> {
+    let mirror = SCENE_MIRRORS.get(&mirror_id)?;
+    let objects = mirror.sample();
+    let mut state = MirrorState {
+        ids: Vec::with_capacity(objects.len()),
+        types: Vec::with_capacity(objects.len()),
+        positions: Vec::with_capacity(objects.len() * 3),
+        opacities: Vec::with_capacity(objects.len()),
+        sizes: Vec::with_capacity(objects.len()),
+        playback_time: mirror.playback_time,
+        needs_keyframe: mirror.needs_keyframe(),
+    };
+    for (id, object) in objects {
+        state.ids.push(id);
+        state.types.push(object.object_type);
+        state.positions.extend_from_slice(&object.position.to_array());
+        state.opacities.push(object.opacity);
+        state.sizes.push(object.size);
+    }
+    Some(state)
+}
+
+// Последний примененный кадр хоста - since_frame для адресного пакета этому зеркалу
+#[wasm_bindgen]
+pub fn get_scene_mirror_frame(mirror_id: usize) -> Option<u32> {
+    SCENE_MIRRORS.get(&mirror_id).and_then(|mirror| mirror.last_frame())
+}
@@ -0,0 +1,94 @@
+/*
+ * selection.rs
+ *
+ * Выделение объекта и его подсветка. Состояние хранится в системе, чтобы
+ * визуальное выделение в рендерере совпадало с симуляцией: выделенный
+ * объект получает флаг is_selected и пульсирующий коэффициент подсветки
+ * highlight_t (0..1) в экспортируемых данных.
+ */
+
+use std::f32::consts::TAU;
+
+use wasm_bindgen::prelude::*;
+
+use crate::errors::OpResult;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+// Частота пульсации подсветки (Гц)
+const HIGHLIGHT_PULSE_HZ: f32 = 1.5;
+
+/// Выделенный объект системы и фаза пульсации подсветки
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Selection {
+    selected: Option<usize>,
+    elapsed: f32,
+}
+
+impl Selection {
+    pub fn select(&mut self, object_id: usize) {
+        if self.selected != Some(object_id) {
+            self.selected = Some(object_id);
+            self.elapsed = 0.0;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.selected = None;
+        self.elapsed = 0.0;
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    pub fn is_selected(&self, object_id: usize) -> bool {
+        self.selected == Some(object_id)
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        if self.selected.is_some() {
+            self.elapsed += dt;
+        }
+    }
+
+    // Подсветка объекта: пульсация от 0 в момент выделения; у невыделенных - 0
+    pub fn highlight(&self, object_id: usize) -> f32 {
+        if !self.is_selected(object_id) {
+            return 0.0;
+        }
+
+        0.5 - 0.5 * (TAU * HIGHLIGHT_PULSE_HZ * self.elapsed).cos()
+    }
+}
+
+// Выделить объект; false - нет системы или объекта с таким ID
+#[wasm_bindgen]
+pub fn select_object(system_id: usize, object_id: usize) -> OpResult {
+    let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) else {
+        return OpResult::system_not_found(system_id);
+    };
+    if system.find_object(object_id).is_none() {
+        return OpResult::not_found(format!("object {} not found", object_id));
+    }
+
+    system.selection.select(object_id);
+    OpResult::success()
+}
+
+#[wasm_bindgen]
+pub fn clear_selection(system_id: usize) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.selection.clear();
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_selected_object(system_id: usize) -> Option<usize> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .and_then(|system| system.selection.selected())
+}
@@ -0,0 +1,124 @@
+/*
+ * sleeping.rs
+ *
+ * "Сон" далеких объектов. Объект дальше sleep_distance от наблюдателя и вне
+ * пирамиды видимости ни с чем не взаимодействует (пересечения, удары и
+ * подсветка происходят только рядом с видовой плоскостью), поэтому полный
+ * шаг для него выполняется лишь раз в interval кадров. В промежуточных
+ * кадрах позиция экстраполируется по скорости, а пропущенное время
+ * копится и отдается объекту при следующем полном шаге - время жизни и
+ * задержки респауна не отстают.
+ *
+ * Кадр пробуждения объекта выбирается по его ID, поэтому полные шаги
+ * спящих объектов распределены по кадрам равномерно. По умолчанию сон
+ * выключен, и симуляция совпадает с полной.
+ */
+
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::errors::OpResult;
+use crate::space_core::SpaceDefinition;
+use crate::space_objects::{SpaceObject, SPACE_OBJECT_SYSTEMS};
+
+// Ограничения интервала полных шагов спящих объектов (кадров)
+const MIN_SLEEP_INTERVAL: u32 = 2;
+const MAX_SLEEP_INTERVAL: u32 = 16;
+
+/// Настройки и состояние сна далеких объектов
+#[derive(Debug, Default)]
+pub struct ObjectSleeping {
+    enabled: bool,
+    interval: u32,
+    distance: f32,
+    // Время, пропущенное спящими объектами с их последнего полного шага
+    pending: HashMap<usize, f32>,
+    // Пропущенные полные шаги за все время
+    skipped_total: u64,
+}
+
+/// Результат шага объекта с учетом сна
+pub enum SleepStep {
+    // Выполнить полный шаг с указанным dt
+    Update(f32),
+    // Шаг пропущен, позиция экстраполирована
+    Skipped,
+}
+
+impl ObjectSleeping {
+    pub fn configure(&mut self, enabled: bool, interval: u32, distance: f32) {
+        self.enabled = enabled;
+        self.interval = interval.clamp(MIN_SLEEP_INTERVAL, MAX_SLEEP_INTERVAL);
+        self.distance = distance.max(0.0);
+        if !enabled {
+            self.pending.clear();
+        }
+    }
+
+    pub fn skipped_total(&self) -> u64 {
+        self.skipped_total
+    }
+
+    // Решить, выполнять ли полный шаг объекта в этом кадре
+    pub fn step(&mut self, obj: &mut dyn SpaceObject, dt: f32, frame: u32, space: &SpaceDefinition) -> SleepStep {
+        let id = obj.get_data().id;
+        if !self.enabled {
+            return SleepStep::Update(dt);
+        }
+
+        let position = obj.get_data().position;
+        let asleep = (position - space.observer_position).length() >= self.distance && !obj.is_visible(space);
+        let wake_frame = (frame as usize).wrapping_add(id).is_multiple_of(self.interval as usize);
+        if asleep && !wake_frame {
+            if !obj.is_waiting_for_respawn() {
+                let data = obj.get_data_mut();
+                data.position += data.velocity * dt;
+            }
+            *self.pending.entry(id).or_insert(0.0) += dt;
+            self.skipped_total += 1;
+            return SleepStep::Skipped;
+        }
+
+        // Полный шаг: возвращаем накопленное время. Позиция уже сдвинута
+        // экстраполяцией, поэтому движущемуся объекту отдаем только время жизни
+        let Some(pending) = self.pending.remove(&id) else {
+            return SleepStep::Update(dt);
+        };
+        if obj.is_waiting_for_respawn() {
+            return SleepStep::Update(dt + pending);
+        }
+        obj.get_data_mut().lifetime += pending;
+        SleepStep::Update(dt)
+    }
+
+    // Забыть удаленные объекты
+    pub fn forget(&mut self, id: usize) {
+        self.pending.remove(&id);
+    }
+}
+
+// Включить сон объектов дальше distance от наблюдателя и вне видимости:
+// полный шаг раз в interval кадров (2..16), в остальных - экстраполяция
+#[wasm_bindgen]
+pub fn set_object_sleeping(system_id: usize, enabled: bool, interval: u32, distance: f32) -> OpResult {
+    if !distance.is_finite() {
+        return OpResult::invalid_argument("distance must be finite");
+    }
+
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.sleeping.configure(enabled, interval, distance);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Пропущенные спящими объектами полные шаги за все время
+#[wasm_bindgen]
+pub fn get_sleep_skipped_total(system_id: usize) -> Option<f64> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| system.sleeping.skipped_total() as f64)
+}
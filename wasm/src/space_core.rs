@@ -1,6 +1,14 @@
 use wasm_bindgen::prelude::*;
-use glam::{Vec3, Vec2};
+use glam::{Quat, Vec3, Vec2};
 use std::f32::consts::PI;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::easing::OpacityProfile;
+use crate::space_objects::SpaceObjectType;
+use crate::protected_rect::ProtectedRect;
+use crate::spawn_density::SpawnDensityMap;
+use crate::spawn_spacing::SpawnSpacing;
 
 // JS-compatible wrapper for Vec3
 #[wasm_bindgen]
@@ -99,6 +107,163 @@ impl From<Vec2Wrapper> for Vec2 {
     }
 }
 
+// Профиль разгона объектов на пути к видовой плоскости
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccelerationProfile {
+    ConstantVelocity = 0, // Скорость не меняется
+    Linear = 1,           // Равномерный прирост скорости с усилением вблизи наблюдателя
+    Exponential = 2,      // Скорость растет экспоненциально
+    EaseInOut = 3,        // Разгон в первой половине пути к плоскости, торможение во второй
+}
+
+// Направление пересечения видовой плоскости
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrossingType {
+    Entry = 0, // С дальней стороны к наблюдателю
+    Exit = 1,  // Со стороны наблюдателя обратно в глубину
+}
+
+// Наклон устройства: ограничение, нейтральный наклон телефона в руке и влияние на появление объектов
+const MAX_DEVICE_TILT: f32 = PI / 9.0;          // 20 градусов
+const NEUTRAL_DEVICE_BETA: f32 = 45.0;          // Градусы наклона "вперед" при обычном удержании
+const SPAWN_TILT_BIAS: f32 = 0.5;               // Доля смещения точки появления в сторону взгляда
+
+// Свободный полет наблюдателя: скорость, отклик сглаживания, поворот взгляда и отступ от видовой плоскости
+const NAVIGATION_MAX_SPEED: f32 = 30.0;         // Единиц в секунду при полном отклонении
+const NAVIGATION_RESPONSE: f32 = 6.0;           // 1/с: за 1/6 с скорость проходит ~63% пути к цели
+const NAVIGATION_LOOK_SPEED: f32 = PI / 2.0;    // Радиан в секунду при полном отклонении
+const NAVIGATION_MAX_LOOK: f32 = PI / 3.0;      // 60 градусов по каждой оси: взгляд не отворачивается от плоскости
+const NAVIGATION_PLANE_MARGIN: f32 = 1.0;       // Наблюдатель не подлетает к плоскости ближе
+
+// Доля максимальной скорости, ниже которой не тормозит профиль EaseInOut
+const EASE_IN_OUT_MIN_SPEED_FRACTION: f32 = 0.2;
+
+// Модель ускорения: профиль и множитель силы разгона
+#[derive(Clone, Copy, Debug)]
+pub struct AccelerationModel {
+    pub profile: AccelerationProfile,
+    pub strength: f32,
+}
+
+impl Default for AccelerationModel {
+    fn default() -> Self {
+        Self {
+            profile: AccelerationProfile::Linear,
+            strength: 1.0,
+        }
+    }
+}
+
+impl AccelerationModel {
+    // Рассчитать новую величину скорости объекта.
+    // acceleration - собственное ускорение объекта, proximity_factor - усиление
+    // вблизи наблюдателя, plane_progress - пройденная доля пути от дальней
+    // границы до видовой плоскости (0..1)
+    pub fn next_speed(
+        &self,
+        current_speed: f32,
+        acceleration: f32,
+        proximity_factor: f32,
+        plane_progress: f32,
+        max_speed: f32,
+        dt: f32
+    ) -> f32 {
+        let acceleration = acceleration * self.strength;
+        
+        match self.profile {
+            AccelerationProfile::ConstantVelocity => current_speed,
+            AccelerationProfile::Linear => {
+                (current_speed + acceleration * dt * proximity_factor).min(max_speed)
+            }
+            AccelerationProfile::Exponential => {
+                (current_speed * (acceleration * dt).exp()).min(max_speed)
+            }
+            AccelerationProfile::EaseInOut => {
+                // Ускорение положительно до середины пути и отрицательно после
+                let direction = 1.0 - 2.0 * plane_progress.clamp(0.0, 1.0);
+                let speed = current_speed + acceleration * max_speed * direction * dt;
+                speed.clamp(max_speed * EASE_IN_OUT_MIN_SPEED_FRACTION, max_speed)
+            }
+        }
+    }
+}
+
+// Глубина резкости и туман. aperture = 0 и fog_density = 0 отключают эффекты
+#[derive(Clone, Copy, Debug)]
+pub struct DepthEffectsModel {
+    // Расстояние от наблюдателя до плоскости резкости
+    pub focus_distance: f32,
+    // Сила размытия: кружок нерезкости на бесконечности относительно фокуса
+    pub aperture: f32,
+    // Плотность экспоненциального тумана (на единицу расстояния)
+    pub fog_density: f32,
+}
+
+impl Default for DepthEffectsModel {
+    fn default() -> Self {
+        Self {
+            focus_distance: 25.0, // Расстояние от наблюдателя по умолчанию до видовой плоскости
+            aperture: 0.0,
+            fog_density: 0.0,
+        }
+    }
+}
+
+impl DepthEffectsModel {
+    // Степень размытия (0..1) по модели тонкой линзы: |d - f| / d
+    pub fn blur_amount(&self, distance: f32) -> f32 {
+        if distance <= 0.0 {
+            return 0.0;
+        }
+        
+        (self.aperture * (distance - self.focus_distance).abs() / distance).clamp(0.0, 1.0)
+    }
+    
+    // Доля тумана (0 - чисто, 1 - полностью скрыт туманом)
+    pub fn fog_factor(&self, distance: f32) -> f32 {
+        (1.0 - (-self.fog_density * distance.max(0.0)).exp()).clamp(0.0, 1.0)
+    }
+}
+
+// Свободный полет наблюдателя. Ввод (move - смещение, look - поворот) приходит
+// от геймпада, клавиатуры или сенсора в диапазоне -1..1 по осям; длина ввода
+// ограничивается единицей, поэтому диагональ клавиатуры не быстрее стика.
+// Скорость и поворот плавно догоняют ввод, так что у всех устройств один отклик
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NavigationModel {
+    // Текущая скорость наблюдателя
+    pub velocity: Vec3,
+    // Угловая скорость взгляда: x - наклон вверх/вниз, y - поворот влево/вправо
+    pub look_velocity: Vec2,
+    // Накопленный поворот взгляда (радианы), в тех же осях, что и device_tilt
+    pub look: Vec2,
+}
+
+impl NavigationModel {
+    // Доля пути к цели за шаг dt при экспоненциальном сглаживании
+    fn blend(dt: f32) -> f32 {
+        1.0 - (-NAVIGATION_RESPONSE * dt).exp()
+    }
+    
+    // Продвинуть скорость и взгляд к вводу; возвращает смещение наблюдателя за шаг
+    pub fn step(&mut self, move_input: Vec3, look_input: Vec2, dt: f32) -> Vec3 {
+        let blend = Self::blend(dt);
+        
+        // Смещение - в осях взгляда: "вперед" ведет туда, куда смотрит наблюдатель
+        let rotation = Quat::from_rotation_y(self.look.y) * Quat::from_rotation_x(-self.look.x);
+        let target_velocity = rotation * move_input.clamp_length_max(1.0) * NAVIGATION_MAX_SPEED;
+        self.velocity += (target_velocity - self.velocity) * blend;
+        
+        let target_look_velocity = look_input.clamp_length_max(1.0) * NAVIGATION_LOOK_SPEED;
+        self.look_velocity += (target_look_velocity - self.look_velocity) * blend;
+        self.look = (self.look + self.look_velocity * dt).clamp(Vec2::splat(-NAVIGATION_MAX_LOOK), Vec2::splat(NAVIGATION_MAX_LOOK));
+        
+        self.velocity * dt
+    }
+}
+
 /// Определяет размеры и характеристики трехмерного пространства
 #[derive(Clone, Debug)]
 pub struct SpaceDefinition {
@@ -118,6 +283,45 @@ pub struct SpaceDefinition {
     
     // Угол обзора (в радианах)
     pub field_of_view: f32,
+    
+    // Положение видовой плоскости ("страницы") по оси Z.
+    // Плоскость имеет размеры видового экрана и центрирована по наблюдателю
+    pub viewing_plane_z: f32,
+    
+    // Модель разгона объектов к видовой плоскости
+    pub acceleration: AccelerationModel,
+    
+    // Профили прозрачности по типам объектов (для отсутствующих - профиль по умолчанию)
+    pub opacity_profiles: HashMap<SpaceObjectType, OpacityProfile>,
+    
+    // Подмешивать текущее время в seed респаунов (невоспроизводимая случайность)
+    pub entropy_respawns: bool,
+    
+    // Наклон взгляда наблюдателя от ориентации устройства (радианы):
+    // x - вверх/вниз (beta), y - влево/вправо (gamma)
+    pub device_tilt: Vec2,
+    
+    // Глубина резкости и туман по расстоянию от наблюдателя
+    pub depth_effects: DepthEffectsModel,
+    
+    // Непрозрачные области (min, max), за которыми объекты считаются скрытыми
+    // (например, панель страницы перед сценой)
+    pub occlusion_boxes: Vec<(Vec3, Vec3)>,
+    
+    // Размер окна в пикселях (для проекции на экран); 0 - не задан
+    pub screen_size: Vec2,
+    
+    // Свободный полет наблюдателя (скорость и поворот взгляда)
+    pub navigation: NavigationModel,
+    
+    // Минимальное расстояние между появлениями на дальней плоскости
+    pub spawn_spacing: SpawnSpacing,
+    
+    // Карта плотности появлений; None - встроенное распределение
+    pub spawn_density: Option<Arc<SpawnDensityMap>>,
+    
+    // Область окна, через которую не проходят траектории объектов
+    pub protected_rect: Option<ProtectedRect>,
 }
 
 // Add a wasm-bindgen wrapper for SpaceDefinition
@@ -204,6 +408,18 @@ impl SpaceDefinitionWrapper {
     }
 }
 
+impl Default for SpaceDefinitionWrapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for SpaceDefinition {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SpaceDefinition {
     pub fn new() -> Self {
         // Создаем пространство от -100 до 100 по всем осям
@@ -217,6 +433,18 @@ impl SpaceDefinition {
             viewport_size_percent: 25.0, // Видовой экран занимает 25% пространства
             observer_position: Vec3::new(0.0, 0.0, -25.0), // Обновляем позицию наблюдателя в соответствии с настройками камеры в React
             field_of_view: PI / 3.0, // 60 градусов
+            viewing_plane_z: 0.0, // Видовая плоскость проходит через центр пространства
+            acceleration: AccelerationModel::default(),
+            opacity_profiles: HashMap::new(),
+            entropy_respawns: false,
+            device_tilt: Vec2::ZERO,
+            depth_effects: DepthEffectsModel::default(),
+            occlusion_boxes: Vec::new(),
+            screen_size: Vec2::ZERO,
+            navigation: NavigationModel::default(),
+            spawn_spacing: SpawnSpacing::default(),
+            spawn_density: None,
+            protected_rect: None,
         }
     }
     
@@ -346,6 +574,179 @@ impl SpaceDefinition {
         
         // На дальних дистанциях (более 150 единиц) объект постепенно исчезает
         let fade_factor = (1.0 - normalized_distance) * 4.0; // Плавное исчезновение
-        return fade_factor.max(0.0).min(1.0);
+        fade_factor.clamp(0.0, 1.0)
+    }
+    
+    // Размытие и туман объекта: та же дистанция до наблюдателя, что и в
+    // get_scale_factor / get_transparency_factor
+    pub fn get_depth_effects(&self, position: &Vec3) -> (f32, f32) {
+        let distance = (*position - self.observer_position).length();
+        (self.depth_effects.blur_amount(distance), self.depth_effects.fog_factor(distance))
+    }
+    
+    // Находится ли точка внутри одной из областей перекрытия
+    pub fn is_occluded(&self, point: &Vec3) -> bool {
+        self.occlusion_boxes
+            .iter()
+            .any(|(min, max)| point.cmpge(*min).all() && point.cmple(*max).all())
+    }
+    
+    // Находится ли точка внутри границ пространства
+    pub fn contains(&self, point: &Vec3) -> bool {
+        (self.min_x..=self.max_x).contains(&point.x)
+            && (self.min_y..=self.max_y).contains(&point.y)
+            && (self.min_z..=self.max_z).contains(&point.z)
+    }
+    
+    // Расстояние от точки до видовой плоскости со знаком:
+    // положительное - за плоскостью (дальняя сторона), отрицательное - перед ней
+    pub fn distance_to_viewing_plane(&self, point: &Vec3) -> f32 {
+        point.z - self.viewing_plane_z
+    }
+    
+    // Нормаль видовой плоскости, ориентированная к наблюдателю
+    pub fn viewing_plane_normal(&self) -> Vec3 {
+        Vec3::NEG_Z
+    }
+    
+    // Тип пересечения и нормаль грани плоскости, через которую прошел объект
+    // (направлена навстречу движению), по фактическому смещению за кадр
+    pub fn crossing_orientation(&self, travel: &Vec3) -> (CrossingType, Vec3) {
+        let normal = self.viewing_plane_normal();
+        if travel.dot(normal) >= 0.0 {
+            (CrossingType::Entry, -normal)
+        } else {
+            (CrossingType::Exit, normal)
+        }
+    }
+    
+    // Найти пересечение отрезка движения объекта с видовой плоскостью.
+    // Учитываются только пересечения в направлении наблюдателя (с дальней стороны на ближнюю)
+    // в пределах прямоугольника видового экрана.
+    // Возвращает долю отрезка t (0..1) и точку пересечения
+    pub fn segment_plane_crossing(&self, from: &Vec3, to: &Vec3) -> Option<(f32, Vec3)> {
+        if from.z <= self.viewing_plane_z {
+            return None;
+        }
+        self.any_plane_crossing(from, to)
+    }
+    
+    // То же, что segment_plane_crossing, но в обоих направлениях
+    pub fn any_plane_crossing(&self, from: &Vec3, to: &Vec3) -> Option<(f32, Vec3)> {
+        if (from.z > self.viewing_plane_z) == (to.z > self.viewing_plane_z) {
+            return None;
+        }
+        
+        let t = (from.z - self.viewing_plane_z) / (from.z - to.z);
+        let point = from.lerp(*to, t);
+        
+        // Проверяем попадание в прямоугольник видового экрана
+        let viewport = self.get_viewport_dimensions();
+        let dx = (point.x - self.observer_position.x).abs();
+        let dy = (point.y - self.observer_position.y).abs();
+        if dx > viewport.x * 0.5 || dy > viewport.y * 0.5 {
+            return None;
+        }
+        
+        Some((t, point))
+    }
+    
+    // Перевести точку на видовой плоскости в UV-координаты плоскости (0..1).
+    // U растет слева направо по X, V - снизу вверх по Y
+    pub fn plane_uv(&self, point: &Vec3) -> Vec2 {
+        let viewport = self.get_viewport_dimensions();
+        Vec2::new(
+            (point.x - self.observer_position.x) / viewport.x + 0.5,
+            (point.y - self.observer_position.y) / viewport.y + 0.5
+        )
+    }
+    
+    // Перевести точку видовой плоскости в нормированные координаты окна (0..1).
+    // В отличие от UV плоскости ось Y направлена вниз, как в DOM
+    pub fn plane_point_to_viewport(&self, x: f32, y: f32) -> Vec2 {
+        let uv = self.plane_uv(&Vec3::new(x, y, self.viewing_plane_z));
+        Vec2::new(uv.x, 1.0 - uv.y)
     }
-} 
\ No newline at end of file
+    
+    // Центральная проекция точки из наблюдателя на видовую плоскость и далее
+    // в нормированные координаты окна. None - точка не перед наблюдателем
+    pub fn project_to_viewport(&self, point: &Vec3) -> Option<Vec2> {
+        let depth = point.z - self.observer_position.z;
+        let plane_depth = self.viewing_plane_z - self.observer_position.z;
+        if depth <= 1e-4 || plane_depth <= 0.0 {
+            return None;
+        }
+        
+        let on_plane = self.observer_position.truncate()
+            + (point.truncate() - self.observer_position.truncate()) * (plane_depth / depth);
+        Some(self.plane_point_to_viewport(on_plane.x, on_plane.y))
+    }
+    
+    // Пикселей окна на единицу длины в точке на расстоянии depth от наблюдателя по Z
+    pub fn pixels_per_unit(&self, depth: f32) -> f32 {
+        let plane_depth = self.viewing_plane_z - self.observer_position.z;
+        if depth <= 1e-4 || plane_depth <= 0.0 {
+            return 0.0;
+        }
+        
+        self.screen_size.x / self.get_viewport_dimensions().x * (plane_depth / depth)
+    }
+    
+    // Применить ориентацию устройства (DeviceOrientationEvent, градусы).
+    // alpha (курс по компасу) не используется: он дрейфует и не связан с наклоном экрана
+    pub fn set_device_orientation(&mut self, _alpha: f32, beta: f32, gamma: f32) {
+        if !beta.is_finite() || !gamma.is_finite() {
+            return;
+        }
+        
+        self.device_tilt = Vec2::new(
+            (beta - NEUTRAL_DEVICE_BETA).to_radians().clamp(-MAX_DEVICE_TILT, MAX_DEVICE_TILT),
+            gamma.to_radians().clamp(-MAX_DEVICE_TILT, MAX_DEVICE_TILT)
+        );
+    }
+    
+    // Ввод свободного полета: сглаженное смещение наблюдателя и поворот взгляда.
+    // Наблюдатель остается в границах пространства и перед видовой плоскостью;
+    // скорость вдоль оси, в которую он уперся, гасится
+    pub fn apply_navigation_input(&mut self, move_input: Vec3, look_input: Vec2, dt: f32) {
+        if !move_input.is_finite() || !look_input.is_finite() || !dt.is_finite() || dt <= 0.0 {
+            return;
+        }
+        
+        let target = self.observer_position + self.navigation.step(move_input, look_input, dt);
+        let min = Vec3::new(self.min_x, self.min_y, self.min_z);
+        let max = Vec3::new(self.max_x, self.max_y, (self.viewing_plane_z - NAVIGATION_PLANE_MARGIN).max(self.min_z));
+        self.observer_position = target.clamp(min, max);
+        
+        let blocked = self.observer_position.cmpne(target);
+        self.navigation.velocity = Vec3::select(blocked, Vec3::ZERO, self.navigation.velocity);
+    }
+    
+    // Направление взгляда наблюдателя с учетом наклона устройства и поворота
+    // свободного полета (без них - вдоль +Z)
+    pub fn view_direction(&self) -> Vec3 {
+        let tilt = self.device_tilt + self.navigation.look;
+        Vec3::new(tilt.y.tan(), tilt.x.tan(), 1.0).normalize()
+    }
+    
+    // Смещение точек появления на дальней границе в сторону, куда направлен взгляд
+    pub fn spawn_bias(&self) -> Vec2 {
+        let depth = self.max_z - self.observer_position.z;
+        Vec2::new(self.device_tilt.y.tan(), self.device_tilt.x.tan()) * depth * SPAWN_TILT_BIAS
+    }
+    
+    // Доля пути от дальней границы пространства до видовой плоскости (0..1)
+    pub fn plane_progress(&self, point: &Vec3) -> f32 {
+        let depth = self.max_z - self.viewing_plane_z;
+        if depth <= 0.0 {
+            return 1.0;
+        }
+        
+        ((self.max_z - point.z) / depth).clamp(0.0, 1.0)
+    }
+    
+    // Профиль прозрачности для типа объектов
+    pub fn opacity_profile(&self, object_type: SpaceObjectType) -> OpacityProfile {
+        self.opacity_profiles.get(&object_type).copied().unwrap_or_default()
+    }
+}
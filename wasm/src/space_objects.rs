@@ -1,5 +1,5 @@
 use wasm_bindgen::prelude::*;
-use glam::{Vec3, Quat};
+use glam::{Vec2, Vec3, Quat};
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 use rand::{Rng, rngs::StdRng, SeedableRng};
@@ -8,7 +8,41 @@ use std::any::Any;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use dashmap::DashMap;
 
-use crate::space_core::{SpaceDefinition, Vec3Wrapper};
+use crate::deltas::DeltaTracker;
+use crate::errors::OpResult;
+use crate::parallax::ParallaxLayers;
+use crate::modifiers::ModifierStack;
+use crate::warp::WarpRamp;
+use crate::transitions::TransitionPlayer;
+use crate::selection::Selection;
+use crate::despawn::SceneFade;
+use crate::portal_glow::PortalGlow;
+use crate::planes::{PlaneStyles, ViewingPlanes, MAIN_PLANE_INDEX};
+use crate::population::{DespawnReason, PopulationCounters};
+use crate::lifecycle::LifecycleEvents;
+use crate::incremental_init::PendingInit;
+use crate::debris::ImpactDebris;
+use crate::plane_physics::PlaneColliders;
+use crate::sleeping::{ObjectSleeping, SleepStep};
+use crate::morton::MortonOrdering;
+use crate::idle::IdleThrottle;
+use crate::power::PowerProfile;
+use crate::color_space::ColorSpace;
+use crate::tail_particles::TailParticlePool;
+use crate::particle_budget::{ParticleBudget, ParticleSubsystem, EFFECT_PARTICLE_COST};
+use crate::near_miss::NearMisses;
+use crate::viewpoints::Viewpoints;
+use crate::commands::{run_due_commands, CommandQueue};
+use crate::motion_vectors::MotionVectors;
+use crate::crossing_stats::CrossingStatsRing;
+use crate::analytics::AnalyticsBatcher;
+use crate::subsystems::{Subsystem, Subsystems};
+use crate::comet_effects::{is_effect_source, CometEffectPool};
+use crate::neon_comets::NeonComet;
+use crate::frame_graph::{FrameGraph, Pass};
+use crate::scene_modulation::{SceneBinding, SceneModulation};
+use crate::visibility::{visibility_state, CatchUpPolicy, MAX_CATCH_UP_SECONDS};
+use crate::space_core::{AccelerationModel, AccelerationProfile, CrossingType, SpaceDefinition, Vec2Wrapper, Vec3Wrapper};
 
 /// Типы космических объектов
 #[wasm_bindgen]
@@ -17,6 +51,8 @@ pub enum SpaceObjectType {
     NeonComet,
     EnergySphere,
     PolygonalCrystal,
+    // Вершины спроецированного гиперкуба (используется в событиях пересечений)
+    Hypercube,
 }
 
 /// JS-compatibility wrapper for Quat
@@ -301,11 +337,230 @@ pub trait SpaceObject: Any + Send + Sync {
         self.get_data_mut().active = false;
     }
     
+    // Проверить, ожидает ли объект респауна (не отображается и не движется)
+    fn is_waiting_for_respawn(&self) -> bool {
+        false
+    }
+    
+    // Рассчитать скорость объекта через dt секунд без изменения его состояния.
+    // Используется для предсказания траектории; по умолчанию скорость постоянна
+    fn predict_velocity(&self, _position: Vec3, velocity: Vec3, _dt: f32, _space: &SpaceDefinition) -> Vec3 {
+        velocity
+    }
+    
     // Преобразовать в Any для даункаста до конкретного типа
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
 }
 
+/// Пересечение объектом видовой плоскости за последний кадр
+#[derive(Clone, Debug)]
+pub struct PlaneCrossing {
+    // Уникальный в пределах системы ID пересечения (выдается по порядку)
+    pub id: u32,
+    
+    // ID пересекшего объекта
+    pub object_id: usize,
+    
+    // Тип объекта
+    pub object_type: SpaceObjectType,
+    
+    // Индекс пересеченной видовой плоскости (0 - основная)
+    pub plane: usize,
+    
+    // Точка пересечения на плоскости
+    pub position: Vec3,
+    
+    // Скорость объекта в момент пересечения
+    pub velocity: Vec3,
+    
+    // Размер объекта в момент пересечения
+    pub size: f32,
+    
+    // Доля кадра (0..1), на которой произошло пересечение
+    pub t: f32,
+    
+    // Направление пересечения
+    pub crossing_type: CrossingType,
+    
+    // Нормаль грани плоскости, через которую прошел объект (навстречу движению)
+    pub normal: Vec3,
+}
+
+// Звуковые метаданные пересечений: границы силы удара для групп
+// "тихий / средний / сильный" и громкость на краю плоскости относительно центра
+const SOUND_MEDIUM_STRENGTH: f32 = 0.25;
+const SOUND_HARD_STRENGTH: f32 = 0.75;
+const SOUND_EDGE_VOLUME: f32 = 0.5;
+
+/// Подсказка аудиодвижку для пересечения: группа силы удара (0 - тихий,
+/// 1 - средний, 2 - сильный), панорама (-1 слева .. 1 справа) и громкость (0..1)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CrossingSound {
+    pub strength_bucket: u8,
+    pub pan: f32,
+    pub volume: f32,
+}
+
+impl PlaneCrossing {
+    // Сила удара: та же величина, что и вклад пересечения в импульс камеры
+    pub fn impact_strength(&self) -> f32 {
+        self.size * self.velocity.length() * IMPACT_IMPULSE_SCALE
+    }
+    
+    // Момент пересечения относительно конца кадра длительностью frame_dt (секунды, <= 0)
+    pub fn time_offset(&self, frame_dt: f32) -> f32 {
+        -(1.0 - self.t.clamp(0.0, 1.0)) * frame_dt
+    }
+    
+    // Пространственные параметры звука по точке пересечения в координатах
+    // пересеченной плоскости (панорама дополнительных плоскостей - по ним самим)
+    pub fn sound(&self, system: &SpaceObjectSystem) -> CrossingSound {
+        let strength = self.impact_strength();
+        let uv = system.crossing_uv(self);
+        
+        let strength_bucket = if strength >= SOUND_HARD_STRENGTH {
+            2
+        } else if strength >= SOUND_MEDIUM_STRENGTH {
+            1
+        } else {
+            0
+        };
+        
+        // Удаленность от центра плоскости: 0 - центр, 1 - угол
+        let edge = ((uv - Vec2::splat(0.5)).length() / std::f32::consts::FRAC_1_SQRT_2).min(1.0);
+        let attenuation = 1.0 - (1.0 - SOUND_EDGE_VOLUME) * edge;
+        
+        CrossingSound {
+            strength_bucket,
+            pan: (uv.x * 2.0 - 1.0).clamp(-1.0, 1.0),
+            volume: ((strength / SOUND_HARD_STRENGTH).min(1.0) * attenuation).clamp(0.0, 1.0),
+        }
+    }
+}
+
+// Окно (секунды), в течение которого повторное пересечение той же плоскости
+// тем же объектом считается дубликатом (дрожание траектории у плоскости,
+// смещения переходов)
+pub const CROSSING_DEDUP_WINDOW: f32 = 0.25;
+
+/// Выдача ID пересечений и отсев повторных пересечений одной плоскости
+/// одним объектом в пределах CROSSING_DEDUP_WINDOW
+#[derive(Clone, Debug, Default)]
+pub struct CrossingDeduplicator {
+    next_id: u32,
+    // (тип объекта, ID объекта, плоскость) -> секунд с последнего пересечения
+    recent: HashMap<(SpaceObjectType, usize, usize), f32>,
+}
+
+impl CrossingDeduplicator {
+    // Состарить записи и забыть вышедшие из окна
+    pub fn advance(&mut self, dt: f32) {
+        self.recent.retain(|_, age| {
+            *age += dt;
+            *age < CROSSING_DEDUP_WINDOW
+        });
+    }
+    
+    // ID нового пересечения или None, если это дубликат недавнего
+    pub fn accept(&mut self, object_type: SpaceObjectType, object_id: usize, plane: usize) -> Option<u32> {
+        let key = (object_type, object_id, plane);
+        if self.recent.contains_key(&key) {
+            return None;
+        }
+        
+        self.recent.insert(key, 0.0);
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        Some(id)
+    }
+}
+
+// Параметры накопителя импульса камеры
+const IMPACT_IMPULSE_SCALE: f32 = 0.0002;  // Перевод размер * скорость в величину импульса
+const IMPACT_IMPULSE_DECAY: f32 = 6.0;     // Скорость экспоненциального затухания (1/сек)
+pub const MAX_CAMERA_IMPULSE: f32 = 1.5;      // Максимальная величина накопленного импульса
+
+/// Накопитель импульса от ударов объектов о видовую плоскость.
+/// Каждое пересечение добавляет толчок, который затухает со временем,
+/// чтобы камера на стороне JS могла "вздрагивать" при крупных ударах
+#[derive(Clone, Debug, Default)]
+pub struct ImpactAccumulator {
+    // Текущий вектор импульса
+    pub impulse: Vec3,
+}
+
+impl ImpactAccumulator {
+    // Добавить импульс от пересечения (зависит от размера и скорости объекта)
+    pub fn add_crossing(&mut self, crossing: &PlaneCrossing) {
+        let speed = crossing.velocity.length();
+        if speed < 0.0001 {
+            return;
+        }
+        
+        let strength = crossing.impact_strength();
+        self.impulse += crossing.velocity / speed * strength;
+        
+        // Ограничиваем накопленный импульс, чтобы серия ударов не "уносила" камеру
+        let magnitude = self.impulse.length();
+        if magnitude > MAX_CAMERA_IMPULSE {
+            self.impulse *= MAX_CAMERA_IMPULSE / magnitude;
+        }
+    }
+    
+    // Экспоненциальное затухание импульса
+    pub fn decay(&mut self, dt: f32) {
+        self.impulse *= (-IMPACT_IMPULSE_DECAY * dt).exp();
+    }
+}
+
+// Защита симуляции от некорректных шагов и "взрыва" скоростей
+const MAX_UPDATE_DT: f32 = 0.1;       // Максимальный шаг обновления (сек); больший dt обрезается
+const MAX_OBJECT_SPEED: f32 = 500.0;  // Абсолютный предел скорости любого объекта (единиц в секунду)
+
+/// Статистика последнего обновления системы
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameStats {
+    // Запрошенный шаг обновления
+    pub requested_dt: f32,
+    
+    // Фактически примененный шаг после ограничения
+    pub applied_dt: f32,
+    
+    // Количество обновленных объектов
+    pub objects_updated: usize,
+    
+    // Спящих объектов, для которых полный шаг пропущен за кадр
+    pub objects_sleeping: usize,
+    
+    // Количество пересечений видовой плоскости за кадр
+    pub crossings: usize,
+    
+    // Пересечений, отброшенных как дубликаты, за кадр
+    pub crossings_deduplicated: usize,
+    
+    // Объектов с ограниченной скоростью за кадр
+    pub speed_clamped: usize,
+    
+    // Объектов с NaN/бесконечными координатами, отправленных на респаун, за кадр
+    pub sanitized: usize,
+    
+    // То же за все время жизни системы
+    pub sanitized_total: usize,
+}
+
+// Вывести независимый seed из базового и двух уточняющих значений (SplitMix64).
+// Используется для детерминированных seed-ов объектов: seed системы + ID + номер респауна
+pub fn derive_seed(base: u64, a: u64, b: u64) -> u64 {
+    let mut z = base
+        .wrapping_add(a.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .wrapping_add(b.wrapping_mul(0xD1B5_4A32_D192_ED03));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
 /// Система управления космическими объектами
 pub struct SpaceObjectSystem {
     // Определение пространства
@@ -317,11 +572,148 @@ pub struct SpaceObjectSystem {
     // Генератор случайных чисел (thread-safe version)
     rng: StdRng,
     
+    // Базовый seed системы, из которого выводятся seed-ы объектов
+    seed: u64,
+    
     // Счетчик для генерации уникальных ID
     pub next_id: usize,
+    
+    // Пересечения видовой плоскости за последний кадр
+    pub crossings: Vec<PlaneCrossing>,
+    
+    // ID пересечений и отсев дубликатов
+    pub crossing_dedup: CrossingDeduplicator,
+    
+    // Накопитель импульса камеры от пересечений
+    pub impacts: ImpactAccumulator,
+    
+    // Свечение видовой плоскости от ударов
+    pub portal_glow: PortalGlow,
+    
+    // Дополнительные видовые плоскости и приоритеты
+    pub viewing_planes: ViewingPlanes,
+    
+    // Стили видовых плоскостей и их анимация
+    pub plane_styles: PlaneStyles,
+    
+    // Параметры сцены, модулируемые гиперкубом
+    pub modulation: SceneModulation,
+    
+    // Постоянная привязка гиперкуба к сцене
+    pub scene_binding: SceneBinding,
+    
+    // Положения вершин гиперкуба в сцене на прошлом кадре (для поиска пересечений)
+    pub hypercube_vertices: Vec<Vec3>,
+    
+    // Пересечения вершин гиперкуба, ожидающие прохода пересечений ближайшего шага
+    pub hypercube_crossings: Vec<PlaneCrossing>,
+    
+    // Статистика последнего обновления
+    pub frame_stats: FrameStats,
+    
+    // Буфер позиций до шага: переиспользуется между кадрами, чтобы не выделять карту заново
+    pub(crate) position_snapshot: HashMap<usize, Vec3>,
+    
+    // Следующий кадр - первый после возвращения во вкладку
+    pub resume_pending: bool,
+    
+    // Номер кадра (количество выполненных обновлений)
+    pub frame: u32,
+    
+    // Время симуляции: сумма шагов всех выполненных обновлений, секунды
+    pub sim_time: f64,
+    
+    // Трекер изменений объектов для дельта-экспорта
+    pub deltas: DeltaTracker,
+    
+    // Слои параллакса, привязанные к наблюдателю
+    pub parallax: ParallaxLayers,
+    
+    // Стек модификаторов поведения (временные эффекты)
+    pub modifiers: ModifierStack,
+    
+    // Эффекты ударов комет о видовую плоскость
+    pub effects: CometEffectPool,
+    
+    // Разгон "прыжка в гиперпространство"
+    pub warp: WarpRamp,
+    
+    // Хореография переходов между страницами
+    pub transitions: TransitionPlayer,
+    
+    // Выделенный объект и его подсветка
+    pub selection: Selection,
+    
+    // Плавная очистка сцены (despawn_all)
+    pub fade: SceneFade,
+    
+    // Счетчики появления и исчезновения объектов
+    pub population: PopulationCounters,
+
+    // События жизненного цикла объектов для хоста
+    pub lifecycle: LifecycleEvents,
+
+    // Объекты, которые еще создаст continue_init
+    pub pending_init: PendingInit,
+
+    // Обломки ударов в связанном физическом мире
+    pub debris: ImpactDebris,
+
+    // Коллайдеры видовых плоскостей в физических мирах
+    pub plane_colliders: PlaneColliders,
+
+    // Сон далеких объектов (полный шаг раз в несколько кадров)
+    pub sleeping: ObjectSleeping,
+
+    // Периодическая сортировка объектов по коду Мортона
+    pub morton: MortonOrdering,
+
+    // Пониженная частота обновления и число частиц при бездействии
+    pub idle: IdleThrottle,
+
+    // Профиль энергопотребления (бюджеты частиц, подшагов, хвостов)
+    pub power_profile: PowerProfile,
+
+    // Цветовое пространство экспортируемых буферов цветов
+    pub export_color_space: ColorSpace,
+
+    // Общий пул частиц хвостов комет
+    pub tail_particles: TailParticlePool,
+
+    // Общий бюджет частиц и его распределение между подсистемами
+    pub particle_budget: ParticleBudget,
+
+    // Порог и очередь событий пролета комет мимо наблюдателя
+    pub near_misses: NearMisses,
+
+    // Закладки камеры и полет наблюдателя к ним
+    pub viewpoints: Viewpoints,
+
+    // Очередь входных команд, выполняемых на границах шагов
+    pub commands: CommandQueue,
+
+    // Смещения проекций объектов за последний шаг (размытие в движении)
+    pub motion: MotionVectors,
+
+    // Сводка пересечений плоскостей по корзинам времени
+    pub crossing_stats: CrossingStatsRing,
+
+    // Пакетная выгрузка пересечений для аналитики (выключена по умолчанию)
+    pub analytics: AnalyticsBatcher,
+
+    // Выключенные хостом подсистемы сцены
+    pub subsystems: Subsystems,
 }
 
 impl SpaceObjectSystem {
+    // UV пересечения в координатах пересеченной плоскости
+    pub fn crossing_uv(&self, crossing: &PlaneCrossing) -> Vec2 {
+        self.viewing_planes
+            .get(&self.space, crossing.plane)
+            .map(|plane| plane.uv(&crossing.position))
+            .unwrap_or_else(|| self.space.plane_uv(&crossing.position))
+    }
+
     // Accessor methods for private fields
     
     // Get a reference to the random number generator
@@ -338,15 +730,612 @@ impl SpaceObjectSystem {
     pub fn get_objects_mut(&mut self) -> &mut HashMap<SpaceObjectType, Vec<Box<dyn SpaceObject>>> {
         &mut self.objects
     }
+    
+    // Создать систему с детерминированным генератором случайных чисел
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            seed,
+            ..Default::default()
+        }
+    }
+    
+    // Базовый seed системы
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    
+    // Перезапустить генератор случайных чисел с новым seed
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+    
+    // Обновить все объекты системы на dt секунд и собрать пересечения видовой плоскости.
+    // Чистая Rust-функция без JS-обвязки - ее можно вызывать нативно из тестов и бенчмарков.
+    // Проходы выполняются в порядке, вычисленном графом UPDATE_GRAPH по их зависимостям
+    pub fn update(&mut self, dt: f32) {
+        // Огромный dt (например, после возврата во вкладку) или NaN ломает интегрирование
+        let applied_dt = if dt.is_finite() { dt.clamp(0.0, MAX_UPDATE_DT) } else { 0.0 };
+        
+        // Позиции прошлого кадра не должны дожить до проходов этого
+        let mut previous_positions = std::mem::take(&mut self.position_snapshot);
+        previous_positions.clear();
+        
+        let mut scratch = FrameScratch {
+            dt: applied_dt,
+            previous_positions,
+            stats: FrameStats {
+                requested_dt: dt,
+                applied_dt,
+                sanitized_total: self.frame_stats.sanitized_total,
+                ..Default::default()
+            },
+        };
+        
+        // Граница шага: команды этого кадра видят состояние до всех проходов
+        run_due_commands(self);
+        self.apply_scene_modulation();
+        // Объекты, появившиеся у выключенных подсистем, убираются до шага
+        self.apply_subsystems();
+        
+        for pass in UPDATE_GRAPH.ordered() {
+            if self.subsystems.skips_pass(pass.name) {
+                continue;
+            }
+            (pass.run)(self, &mut scratch);
+        }
+        
+        self.frame_stats = scratch.stats;
+        self.position_snapshot = scratch.previous_positions;
+    }
+    
+    // Продвинуть систему на dt с учетом видимости вкладки и политики догоняния
+    pub fn advance(&mut self, dt: f32) {
+        let (hidden, policy) = visibility_state();
+        self.advance_with(dt, hidden, policy);
+    }
+    
+    // То же, что advance, но с явно переданными видимостью и политикой
+    // (без глобального состояния вкладки - для нативных тестов)
+    pub fn advance_with(&mut self, dt: f32, hidden: bool, policy: CatchUpPolicy) {
+        if hidden && policy == CatchUpPolicy::Pause {
+            return;
+        }
+        
+        // В режиме покоя шаги копятся и применяются с пониженной частотой
+        let Some(dt) = self.idle.throttle(dt) else {
+            return;
+        };
+        
+        if !self.resume_pending {
+            self.update(dt);
+            return;
+        }
+        
+        // Первый кадр после возвращения: dt содержит все время в фоне
+        self.resume_pending = false;
+        match policy {
+            CatchUpPolicy::FastForward => {
+                let mut remaining = if dt.is_finite() { dt.clamp(0.0, MAX_CATCH_UP_SECONDS) } else { 0.0 };
+                while remaining > 0.0 {
+                    let step = remaining.min(MAX_UPDATE_DT);
+                    self.update(step);
+                    remaining -= step;
+                }
+            }
+            CatchUpPolicy::Skip | CatchUpPolicy::Pause => self.update(0.0),
+        }
+    }
+    
+    // Число частиц эффектов с учетом режима покоя и профиля питания (не меньше одной;
+    // 0 - частицы выключены)
+    pub fn particle_count(&self, full: usize) -> usize {
+        if !self.subsystems.is_enabled(Subsystem::Particles) {
+            return 0;
+        }
+        let scale = self.idle.particle_scale() * self.power_profile.budget().particle_scale;
+        ((full as f32 * scale).round() as usize).clamp(1, full.max(1))
+    }
+    
+    // Цвет объекта для экспорта: оттенки модификаторов и пространство экспорта
+    pub fn export_color(&self, color: [f32; 3]) -> [f32; 3] {
+        self.export_color_space.encode(self.modifiers.tint(color))
+    }
+    
+    // Find an object of any type by its ID
+    pub fn find_object(&self, object_id: usize) -> Option<&dyn SpaceObject> {
+        self.objects
+            .values()
+            .flat_map(|objects| objects.iter())
+            .find(|obj| obj.get_data().id == object_id)
+            .map(|obj| obj.as_ref())
+    }
+    
+    // Find an object of any type by its ID for modification
+    pub fn find_object_mut(&mut self, object_id: usize) -> Option<&mut Box<dyn SpaceObject>> {
+        self.objects
+            .values_mut()
+            .flat_map(|objects| objects.iter_mut())
+            .find(|obj| obj.get_data().id == object_id)
+    }
+}
+
+// Промежуточные данные кадра, которыми обмениваются проходы обновления
+struct FrameScratch {
+    dt: f32,
+    previous_positions: HashMap<usize, Vec3>,
+    stats: FrameStats,
+}
+
+type UpdatePass = fn(&mut SpaceObjectSystem, &mut FrameScratch);
+
+// Полет наблюдателя к закладке - до шага объектов, которые видят уже новое положение
+fn viewpoint_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    system.viewpoints.update(&mut system.space, scratch.dt);
+}
+
+// Запомнить позиции объектов до шага (для поиска пересечений).
+// Ожидающие респауна пропускаем: респаун переносит объект на дальнюю
+// границу, и этот скачок не должен считаться пересечением плоскости
+// (буфер приходит из update очищенным, но с емкостью прошлого кадра)
+fn snapshot_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    scratch.previous_positions.extend(system.objects
+        .values()
+        .flat_map(|objects| objects.iter())
+        .filter(|obj| !obj.is_waiting_for_respawn())
+        .map(|obj| (obj.get_data().id, obj.get_data().position)));
+}
+
+// Шаг объектов с учетом множителей скорости; retain удаляет неактивные
+fn integrate_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    let space_definition = &system.space;
+    let palette_t = system.modulation.palette_t;
+    let population = &mut system.population;
+    let lifecycle = &mut system.lifecycle;
+    let sleeping = &mut system.sleeping;
+    let frame = system.frame;
+    let substeps = system.power_profile.budget().substeps.max(1);
+    let dt = scratch.dt * system.modifiers.time_scale();
+    let mut respawned = Vec::new();
+    for (object_type, objects) in system.objects.iter_mut() {
+        objects.retain_mut(|obj| {
+            let object_dt = match sleeping.step(obj.as_mut(), dt, frame, space_definition) {
+                SleepStep::Update(object_dt) => object_dt,
+                SleepStep::Skipped => {
+                    scratch.stats.objects_sleeping += 1;
+                    return true;
+                }
+            };
+            scratch.stats.objects_updated += 1;
+            let was_waiting = obj.is_waiting_for_respawn();
+            let fresh = obj.get_data().lifetime == 0.0;
+            // Подшаги профиля питания; удаленный объект дальше не обновляется
+            let step = object_dt / substeps as f32;
+            let alive = (0..substeps).all(|_| obj.update(step, space_definition));
+            
+            // Исчезновение и респаун определяем по смене состояния объекта
+            let data = obj.get_data();
+            if !alive {
+                let reason = if data.lifetime > data.max_lifetime {
+                    DespawnReason::Lifetime
+                } else {
+                    DespawnReason::OutOfBounds
+                };
+                population.record_despawn(*object_type, reason);
+                lifecycle.despawned(data.id, *object_type, reason);
+                sleeping.forget(data.id);
+            } else if !was_waiting && obj.is_waiting_for_respawn() {
+                // Ушедшие за видовую плоскость покидают пространство после пересечения
+                let reason = if data.position.z < space_definition.viewing_plane_z {
+                    DespawnReason::Crossing
+                } else {
+                    DespawnReason::OutOfBounds
+                };
+                population.record_despawn(*object_type, reason);
+                lifecycle.despawned(data.id, *object_type, reason);
+            } else if was_waiting && !obj.is_waiting_for_respawn() {
+                population.record_respawn(*object_type);
+                lifecycle.activated(data.id, *object_type, true);
+                respawned.push(data.position);
+                // Респаун сбрасывает цвет к палитре - возвращаем смещение сцены
+                if let Some(comet) = obj.as_any_mut().downcast_mut::<NeonComet>() {
+                    comet.shift_palette(palette_t);
+                }
+            } else if fresh && !obj.is_waiting_for_respawn() {
+                lifecycle.activated(data.id, *object_type, false);
+            }
+            alive
+        });
+    }
+    
+    // Респауны этого кадра попадают в историю появлений
+    for position in respawned {
+        system.space.record_spawn(&position);
+    }
+}
+
+// Модификаторы поведения после шага (дрожание, ограничение размера)
+fn modifiers_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    if system.modifiers.is_empty() {
+        return;
+    }
+    
+    let dt = scratch.dt * system.modifiers.time_scale();
+    for obj in system.objects.values_mut().flat_map(|objects| objects.iter_mut()) {
+        system.modifiers.apply(obj.as_mut(), dt, system.frame);
+    }
+}
+
+// Разгон: дополнительное смещение по Z пропорционально множителю
+fn warp_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    let extra = system.warp.multiplier() - 1.0;
+    system.warp.advance(scratch.dt);
+    if extra.abs() < 1e-6 {
+        return;
+    }
+    
+    let dt = scratch.dt * system.modifiers.time_scale();
+    for obj in system.objects.values_mut().flat_map(|objects| objects.iter_mut()) {
+        if obj.is_waiting_for_respawn() {
+            continue;
+        }
+        let data = obj.get_data_mut();
+        data.position.z += data.velocity.z * extra * dt;
+    }
+}
+
+// Переход между страницами: смещение объектов поверх их траекторий
+fn transition_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    if system.transitions.is_idle() {
+        return;
+    }
+    
+    let objects = system.objects.values_mut().flat_map(|objects| objects.iter_mut());
+    system.transitions.apply(objects, &system.space, scratch.dt);
+}
+
+// Очистка сцены: гасим объекты, по завершении удаляем их
+fn fade_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    if !system.fade.is_active() {
+        return;
+    }
+    
+    let finished = system.fade.advance(scratch.dt);
+    if finished {
+        for (object_type, objects) in system.objects.iter_mut() {
+            for obj in objects.drain(..) {
+                system.population.record_despawn(*object_type, DespawnReason::Opacity);
+                system.lifecycle.despawned(obj.get_data().id, *object_type, DespawnReason::Opacity);
+                system.sleeping.forget(obj.get_data().id);
+            }
+        }
+        system.selection.clear();
+        return;
+    }
+    
+    let multiplier = system.fade.multiplier();
+    for obj in system.objects.values_mut().flat_map(|objects| objects.iter_mut()) {
+        obj.get_data_mut().opacity *= multiplier;
+    }
+}
+
+// Проверка корректности состояния: NaN - на респаун, слишком быстрые - ограничить
+fn sanitize_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    let space_definition = &system.space;
+    let rng = &mut system.rng;
+    
+    for obj in system.objects.values_mut().flat_map(|objects| objects.iter_mut()) {
+        let data = obj.get_data_mut();
+        if !data.position.is_finite() || !data.velocity.is_finite() {
+            // Объект "взорвался" - отправляем его заново на дальнюю плоскость
+            obj.initialize_random(rng, space_definition);
+            obj.get_data_mut().lifetime = 0.0;
+            scratch.previous_positions.remove(&obj.get_data().id);
+            scratch.stats.sanitized += 1;
+            continue;
+        }
+        
+        let speed = data.velocity.length();
+        if speed > MAX_OBJECT_SPEED {
+            data.velocity *= MAX_OBJECT_SPEED / speed;
+            scratch.stats.speed_clamped += 1;
+        }
+    }
+}
+
+// Пересечения видовой плоскости за кадр
+fn crossings_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    system.crossings.clear();
+    system.crossing_dedup.advance(scratch.dt);
+    
+    // Пересечения вершин гиперкуба идут через тот же конвейер, что и пересечения объектов
+    for crossing in std::mem::take(&mut system.hypercube_crossings) {
+        system.lifecycle.crossed_plane(crossing.object_id, crossing.object_type, crossing.crossing_type);
+        system.crossings.push(crossing);
+    }
+    
+    for obj in system.objects.values().flat_map(|objects| objects.iter()) {
+        let data = obj.get_data();
+        let Some(previous_position) = scratch.previous_positions.get(&data.id) else {
+            continue;
+        };
+        
+        let crossing = system.viewing_planes.nearest_crossing(&system.space, previous_position, &data.position);
+        if let Some((plane, t, point)) = crossing {
+            let Some(id) = system.crossing_dedup.accept(data.object_type, data.id, plane) else {
+                scratch.stats.crossings_deduplicated += 1;
+                continue;
+            };
+            let (crossing_type, normal) = system.space.crossing_orientation(&(data.position - *previous_position));
+            system.lifecycle.crossed_plane(data.id, data.object_type, crossing_type);
+            system.crossings.push(PlaneCrossing {
+                id,
+                object_id: data.id,
+                object_type: data.object_type,
+                plane,
+                position: point,
+                velocity: data.velocity,
+                size: data.size,
+                t,
+                crossing_type,
+                normal,
+            });
+        }
+    }
+}
+
+// Импульс камеры: затухание и новые удары
+fn impulse_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    system.impacts.decay(scratch.dt);
+    for crossing in &system.crossings {
+        system.impacts.add_crossing(crossing);
+    }
+}
+
+// Свечение плоскости: затухание и энергия новых ударов
+fn glow_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    system.portal_glow.decay(scratch.dt);
+    for crossing in system.crossings.iter().filter(|crossing| crossing.plane == MAIN_PLANE_INDEX) {
+        system.portal_glow.add_crossing(crossing, &system.space);
+    }
+}
+
+// Эффекты ударов: состарить существующие и создать новые из пересечений кадра
+fn effects_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    system.effects.update(scratch.dt);
+    
+    for crossing in system.crossings.iter().filter(|crossing| is_effect_source(crossing)) {
+        let color = system.objects
+            .get(&crossing.object_type)
+            .and_then(|objects| objects.iter().find(|obj| obj.get_data().id == crossing.object_id))
+            .and_then(|obj| obj.as_any().downcast_ref::<NeonComet>())
+            .map(|comet| system.modifiers.tint(comet.color))
+            .unwrap_or([1.0, 1.0, 1.0]);
+        system.effects.spawn_from_crossing(crossing, color, scratch.dt);
+    }
+}
+
+// Обломки ударов: состарить и создать новые из пересечений кадра
+fn debris_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    if !system.debris.is_enabled() {
+        return;
+    }
+    
+    let seed = system.seed;
+    system.debris.update(scratch.dt, &system.crossings, seed);
+}
+
+// Векторы движения объектов на экране за шаг
+fn motion_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    let objects = system.objects.values().flatten();
+    system.motion.update(objects, &scratch.previous_positions, &system.space);
+}
+
+// Пролеты комет мимо наблюдателя без пересечения плоскости
+fn near_miss_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    let observer = system.space.observer_position;
+    let previous_observer = system.near_misses.begin_frame(observer);
+    if system.near_misses.distance().is_none() {
+        return;
+    }
+
+    for crossing in system.crossings.iter().filter(|crossing| crossing.object_type == SpaceObjectType::NeonComet) {
+        system.near_misses.mark_crossed(crossing.object_id);
+    }
+
+    let comets = system.objects.get(&SpaceObjectType::NeonComet).map(Vec::as_slice).unwrap_or_default();
+    for obj in comets {
+        let data = obj.get_data();
+        let Some(previous_position) = scratch.previous_positions.get(&data.id) else {
+            // Позиции до шага нет только у респауна - начинается новый пролет
+            system.near_misses.forget(data.id);
+            continue;
+        };
+        system.near_misses.check(data.id, *previous_position, data.position, scratch.dt, previous_observer, observer);
+    }
+    system.near_misses.retain_crossed(|comet_id| comets.iter().any(|obj| obj.get_data().id == comet_id));
+}
+
+// Коллайдеры плоскостей следуют за геометрией плоскостей
+fn plane_physics_pass(system: &mut SpaceObjectSystem, _scratch: &mut FrameScratch) {
+    if system.plane_colliders.is_empty() {
+        return;
+    }
+    
+    let (planes, space) = (&system.viewing_planes, &system.space);
+    system.plane_colliders.update(|index| planes.get(space, index));
+}
+
+// Распределение общего бюджета частиц; эффекты и обломки сверх своей доли
+// вытесняются сразу, хвосты и вихри учитывают долю при построении
+fn particle_budget_pass(system: &mut SpaceObjectSystem, _scratch: &mut FrameScratch) {
+    let mut budget = system.particle_budget;
+    budget.update(system);
+    system.particle_budget = budget;
+
+    if let Some(allocation) = budget.allocation(ParticleSubsystem::Effects) {
+        system.effects.truncate_oldest(allocation / EFFECT_PARTICLE_COST);
+    }
+    if let Some(allocation) = budget.allocation(ParticleSubsystem::Debris) {
+        system.debris.truncate_oldest(allocation);
+    }
+}
+
+// Хвосты комет в общем пуле частиц (пул на время построения вынимается из системы)
+fn tails_pass(system: &mut SpaceObjectSystem, _scratch: &mut FrameScratch) {
+    let mut pool = std::mem::take(&mut system.tail_particles);
+    pool.rebuild(system);
+    system.tail_particles = pool;
+}
+
+// Сортировка объектов по коду Мортона - после всех читателей объектов кадра
+fn morton_pass(system: &mut SpaceObjectSystem, _scratch: &mut FrameScratch) {
+    system.morton.update(system.objects.values_mut(), &system.space);
+}
+
+// Анимация стилей видовых плоскостей
+fn plane_styles_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    system.plane_styles.advance(scratch.dt);
+}
+
+// Пульсация подсветки выделенного объекта
+fn selection_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    system.selection.advance(scratch.dt);
+}
+
+// Отметить изменения объектов для дельта-экспорта
+fn deltas_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    system.frame = system.frame.wrapping_add(1);
+    system.sim_time += scratch.dt as f64;
+    system.deltas.track(
+        system.frame,
+        system.objects.values().flat_map(|objects| objects.iter()).map(|obj| obj.get_data())
+    );
+}
+
+// Пересечения кадра в корзины сводной статистики (буфер на время записи вынимается из системы)
+fn crossing_stats_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    let mut stats = std::mem::take(&mut system.crossing_stats);
+    stats.record(
+        scratch.dt,
+        system.crossings.iter().map(|crossing| (crossing.crossing_type, crossing.velocity.length(), system.crossing_uv(crossing)))
+    );
+    system.crossing_stats = stats;
+}
+
+// Пересечения кадра в пакет аналитики
+fn analytics_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    if system.analytics.interval().is_none() {
+        return;
+    }
+    let mut analytics = std::mem::take(&mut system.analytics);
+    analytics.record(scratch.dt, system.crossings.iter().map(|crossing| (crossing, system.crossing_uv(crossing))));
+    system.analytics = analytics;
+}
+
+// Итоговая статистика кадра
+fn stats_pass(system: &mut SpaceObjectSystem, scratch: &mut FrameScratch) {
+    scratch.stats.crossings = system.crossings.len();
+    scratch.stats.sanitized_total += scratch.stats.sanitized;
+}
+
+// Граф проходов обновления системы. Порядок объявления не важен:
+// он вычисляется из версий ресурсов (см. frame_graph.rs)
+static UPDATE_GRAPH: Lazy<FrameGraph<UpdatePass>> = Lazy::new(|| {
+    FrameGraph::new(vec![
+        Pass { name: "snapshot", reads: &["objects@0"], writes: &["previous_positions@1"], run: snapshot_pass as UpdatePass },
+        Pass { name: "viewpoint", reads: &["viewpoints@0"], writes: &["viewpoints@1", "observer@1"], run: viewpoint_pass },
+        Pass { name: "integrate", reads: &["objects@0", "modifiers@0", "observer@1"], writes: &["objects@1", "stats@1", "lifecycle@1"], run: integrate_pass },
+        Pass { name: "modifiers", reads: &["objects@1", "modifiers@0"], writes: &["objects@2"], run: modifiers_pass },
+        Pass { name: "warp", reads: &["objects@2", "modifiers@0"], writes: &["objects@3", "warp@1"], run: warp_pass },
+        Pass { name: "transition", reads: &["objects@3"], writes: &["objects@4", "transitions@1"], run: transition_pass },
+        Pass { name: "fade", reads: &["objects@4", "lifecycle@1"], writes: &["objects@5", "fade@1", "selection@1", "lifecycle@2"], run: fade_pass },
+        Pass { name: "sanitize", reads: &["objects@5", "stats@1", "previous_positions@1"], writes: &["objects@6", "stats@2", "previous_positions@2"], run: sanitize_pass },
+        Pass { name: "crossings", reads: &["objects@6", "previous_positions@2", "lifecycle@2"], writes: &["crossings@1", "lifecycle@3"], run: crossings_pass },
+        Pass { name: "impulse", reads: &["crossings@1"], writes: &["camera_impulse@1"], run: impulse_pass },
+        Pass { name: "glow", reads: &["crossings@1"], writes: &["portal_glow@1"], run: glow_pass },
+        Pass { name: "effects", reads: &["crossings@1", "objects@6"], writes: &["effects@1"], run: effects_pass },
+        Pass { name: "debris", reads: &["crossings@1"], writes: &["debris@1"], run: debris_pass },
+        Pass { name: "near_miss", reads: &["objects@6", "previous_positions@2", "crossings@1"], writes: &["near_misses@1"], run: near_miss_pass },
+        Pass { name: "motion", reads: &["objects@6", "previous_positions@2"], writes: &["motion@1"], run: motion_pass },
+        Pass { name: "plane_physics", reads: &["plane_colliders@0"], writes: &["plane_colliders@1"], run: plane_physics_pass },
+        Pass { name: "deltas", reads: &["objects@6"], writes: &["deltas@1"], run: deltas_pass },
+        Pass { name: "particle_budget", reads: &["objects@6", "effects@1", "debris@1"], writes: &["particle_budget@1", "effects@2", "debris@2"], run: particle_budget_pass },
+        Pass { name: "tails", reads: &["objects@6", "warp@1", "particle_budget@1"], writes: &["tail_particles@1"], run: tails_pass },
+        Pass { name: "morton", reads: &["objects@6"], writes: &["objects@7"], run: morton_pass },
+        Pass { name: "plane_styles", reads: &["plane_styles@0"], writes: &["plane_styles@1"], run: plane_styles_pass },
+        Pass { name: "selection", reads: &["selection@1"], writes: &["selection@2"], run: selection_pass },
+        Pass { name: "crossing_stats", reads: &["crossings@1"], writes: &["crossing_stats@1"], run: crossing_stats_pass },
+        Pass { name: "analytics", reads: &["crossings@1"], writes: &["analytics@1"], run: analytics_pass },
+        Pass { name: "stats", reads: &["stats@2", "crossings@1"], writes: &["stats@3"], run: stats_pass },
+    ]).expect("update passes must form an acyclic graph")
+});
+
+// Порядок проходов обновления системы
+#[wasm_bindgen]
+pub fn get_update_graph_order() -> Vec<String> {
+    UPDATE_GRAPH.order_names()
+}
+
+// Граф проходов обновления в формате Graphviz DOT
+#[wasm_bindgen]
+pub fn get_update_graph_dot() -> String {
+    UPDATE_GRAPH.to_dot()
 }
 
 impl Default for SpaceObjectSystem {
     fn default() -> Self {
+        // Случайный seed, но запомненный - сцену можно воспроизвести
+        let seed = rand::random::<u64>();
         Self {
             space: SpaceDefinition::new(),
             objects: HashMap::new(),
-            rng: StdRng::from_entropy(),
+            rng: StdRng::seed_from_u64(seed),
+            seed,
             next_id: 0,
+            crossings: Vec::new(),
+            crossing_dedup: CrossingDeduplicator::default(),
+            impacts: ImpactAccumulator::default(),
+            portal_glow: PortalGlow::default(),
+            viewing_planes: ViewingPlanes::default(),
+            plane_styles: PlaneStyles::default(),
+            modulation: SceneModulation::default(),
+            scene_binding: SceneBinding::default(),
+            hypercube_vertices: Vec::new(),
+            hypercube_crossings: Vec::new(),
+            frame_stats: FrameStats::default(),
+            position_snapshot: HashMap::new(),
+            resume_pending: false,
+            frame: 0,
+            sim_time: 0.0,
+            deltas: DeltaTracker::default(),
+            parallax: ParallaxLayers::default(),
+            modifiers: ModifierStack::default(),
+            effects: CometEffectPool::default(),
+            warp: WarpRamp::default(),
+            transitions: TransitionPlayer::default(),
+            selection: Selection::default(),
+            fade: SceneFade::default(),
+            population: PopulationCounters::default(),
+            lifecycle: LifecycleEvents::default(),
+            pending_init: PendingInit::default(),
+            debris: ImpactDebris::default(),
+            plane_colliders: PlaneColliders::default(),
+            sleeping: ObjectSleeping::default(),
+            morton: MortonOrdering::default(),
+            idle: IdleThrottle::default(),
+            power_profile: PowerProfile::default(),
+            export_color_space: ColorSpace::default(),
+            tail_particles: TailParticlePool::default(),
+            particle_budget: ParticleBudget::default(),
+            near_misses: NearMisses::default(),
+            viewpoints: Viewpoints::default(),
+            commands: CommandQueue::default(),
+            motion: MotionVectors::default(),
+            crossing_stats: CrossingStatsRing::default(),
+            analytics: AnalyticsBatcher::default(),
+            subsystems: Subsystems::default(),
         }
     }
 }
@@ -354,11 +1343,16 @@ impl Default for SpaceObjectSystem {
 // Глобальное хранилище систем объектов - используем thread-safe DashMap
 // который является конкурентным HashMap без блокировок
 pub static SPACE_OBJECT_SYSTEMS: Lazy<DashMap<usize, SpaceObjectSystem>> = 
-    Lazy::new(|| DashMap::new());
+    Lazy::new(DashMap::new);
 
 // ID для следующей системы - используем атомик для потокобезопасного инкремента
 static NEXT_SYSTEM_ID: AtomicUsize = AtomicUsize::new(0);
 
+// Верхняя граница выданных ID систем (для обхода без блокировки всей карты)
+pub(crate) fn system_id_bound() -> usize {
+    NEXT_SYSTEM_ID.load(Ordering::SeqCst)
+}
+
 #[wasm_bindgen]
 pub fn create_space_object_system(viewport_size_percent: f32, fov_degrees: f32) -> usize {
     // Генерируем уникальный ID атомарно без блокировок
@@ -379,41 +1373,576 @@ pub fn create_space_object_system(viewport_size_percent: f32, fov_degrees: f32)
     // Устанавливаем позицию наблюдателя как в React (-25 по оси Z)
     // Это соответствует camera.position.set(0, 0, -25) в SpaceScene.tsx
     system.space.observer_position = Vec3::new(0.0, 0.0, -25.0);
+    system.parallax.set_rest_observer(system.space.observer_position);
     
     // Вставляем систему в хранилище
     SPACE_OBJECT_SYSTEMS.insert(id, system);
     id
 }
 
+// Переместить наблюдателя (камеру). Слои параллакса смещаются относительно
+// положения наблюдателя при создании системы
+#[wasm_bindgen]
+pub fn set_observer_position(system_id: usize, x: f32, y: f32, z: f32) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.space.observer_position = Vec3::new(x, y, z);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Положение наблюдателя и угол обзора (радианы) - для камеры рендерера во время
+// свободного полета и полета к закладке
+#[wasm_bindgen]
+pub fn get_observer_position(system_id: usize) -> Option<Vec3Wrapper> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| system.space.observer_position.into())
+}
+
+#[wasm_bindgen]
+pub fn get_field_of_view(system_id: usize) -> Option<f32> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| system.space.field_of_view)
+}
+
+// Ввод свободного полета за шаг dt: move_vec - смещение (x, y, z), look_vec -
+// поворот взгляда (x - вверх/вниз, y - влево/вправо), компоненты -1..1.
+// Геймпад, клавиатура и сенсор дают одинаковое сглаженное движение наблюдателя
+#[wasm_bindgen]
+pub fn set_navigation_input(system_id: usize, move_vec: &Vec3Wrapper, look_vec: &Vec2Wrapper, dt: f32) -> OpResult {
+    let move_input = Vec3::new(move_vec.x(), move_vec.y(), move_vec.z());
+    let look_input = Vec2::new(look_vec.x(), look_vec.y());
+    if !move_input.is_finite() || !look_input.is_finite() || !dt.is_finite() || dt < 0.0 {
+        return OpResult::invalid_argument("navigation input and dt must be finite");
+    }
+
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            // Ввод пользователя прерывает полет к закладке
+            if move_input != Vec3::ZERO || look_input != Vec2::ZERO {
+                system.viewpoints.cancel();
+            }
+            system.space.apply_navigation_input(move_input, look_input, dt.min(MAX_UPDATE_DT));
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Текущая скорость наблюдателя в свободном полете
+#[wasm_bindgen]
+pub fn get_observer_velocity(system_id: usize) -> Option<Vec3Wrapper> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| system.space.navigation.velocity.into())
+}
+
+// Ориентация устройства (DeviceOrientationEvent: alpha, beta, gamma в градусах):
+// наклоняет взгляд наблюдателя и смещает зону появления объектов
+#[wasm_bindgen]
+pub fn set_device_orientation(system_id: usize, alpha: f32, beta: f32, gamma: f32) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.space.set_device_orientation(alpha, beta, gamma);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Направление взгляда наблюдателя с учетом наклона устройства (для камеры рендерера)
+#[wasm_bindgen]
+pub fn get_view_direction(system_id: usize) -> Option<Vec3Wrapper> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| system.space.view_direction().into())
+}
+
 #[wasm_bindgen]
-pub fn update_space_object_system(system_id: usize, dt: f32) -> bool {
-    // Check if system exists first
-    if !SPACE_OBJECT_SYSTEMS.contains_key(&system_id) {
-        return false;
+pub fn update_space_object_system(system_id: usize, dt: f32) -> OpResult {
+    if let Some(mut system_ref) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        system_ref.advance(dt);
+        drop(system_ref);
+        
+        // Пользовательский обработчик кадра - уже без блокировки системы
+        crate::frame_hook::run_frame_hook(system_id);
+        OpResult::success()
+    } else {
+        OpResult::system_not_found(system_id)
+    }
+}
+
+// Выбрать профиль разгона объектов системы. strength - множитель
+// собственного ускорения объектов (1.0 - без изменений)
+#[wasm_bindgen]
+pub fn set_acceleration_profile(system_id: usize, profile: AccelerationProfile, strength: f32) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.space.acceleration = AccelerationModel {
+                profile,
+                strength: strength.max(0.0),
+            };
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_acceleration_profile(system_id: usize) -> Option<AccelerationProfile> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| system.space.acceleration.profile)
+}
+
+#[wasm_bindgen]
+pub fn get_acceleration_strength(system_id: usize) -> Option<f32> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| system.space.acceleration.strength)
+}
+
+// Добавить область перекрытия; объекты внутри нее помечаются скрытыми,
+// а их хвосты не строятся. Возвращает индекс области
+#[wasm_bindgen]
+pub fn add_occlusion_box(system_id: usize, min: &Vec3Wrapper, max: &Vec3Wrapper) -> Option<usize> {
+    let mut system = SPACE_OBJECT_SYSTEMS.get_mut(&system_id)?;
+    let (a, b) = (Vec3::new(min.x(), min.y(), min.z()), Vec3::new(max.x(), max.y(), max.z()));
+    system.space.occlusion_boxes.push((a.min(b), a.max(b)));
+    Some(system.space.occlusion_boxes.len() - 1)
+}
+
+#[wasm_bindgen]
+pub fn clear_occlusion_boxes(system_id: usize) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.space.occlusion_boxes.clear();
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Глубина резкости: расстояние фокуса от наблюдателя и сила размытия (0 - выключено)
+#[wasm_bindgen]
+pub fn set_depth_of_field(system_id: usize, focus_distance: f32, aperture: f32) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.space.depth_effects.focus_distance = focus_distance.max(0.0);
+            system.space.depth_effects.aperture = aperture.max(0.0);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Плотность экспоненциального тумана (0 - выключен)
+#[wasm_bindgen]
+pub fn set_fog_density(system_id: usize, density: f32) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.space.depth_effects.fog_density = density.max(0.0);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Перезапустить систему с заданным seed: новые объекты и их респауны станут воспроизводимыми
+#[wasm_bindgen]
+pub fn reseed_space_object_system(system_id: usize, seed: u64) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.reseed(seed);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_system_seed(system_id: usize) -> Option<u64> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| system.seed())
+}
+
+// Режим "энтропии": респауны подмешивают текущее время и перестают быть воспроизводимыми
+#[wasm_bindgen]
+pub fn set_entropy_mode(system_id: usize, enabled: bool) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.space.entropy_respawns = enabled;
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Перевести точку видовой плоскости (например, место пересечения кометой)
+// в нормированные координаты окна 0..1 с осью Y вниз - для размещения HTML-эффектов
+#[wasm_bindgen]
+pub fn plane_point_to_viewport(system_id: usize, px: f32, py: f32) -> Option<Vec2Wrapper> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| system.space.plane_point_to_viewport(px, py).into())
+}
+
+// Статистика последнего обновления системы (шаг, ограничения, исправленные объекты)
+#[wasm_bindgen]
+pub fn get_frame_stats(system_id: usize) -> Option<FrameStats> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| system.frame_stats)
+}
+
+/// События пересечения видовой плоскости за последний кадр
+#[wasm_bindgen]
+pub struct PlaneCrossingArray {
+    crossing_ids: Vec<u32>,
+    crossing_types: Vec<CrossingType>,
+    normals: Vec<f32>,
+    plane_indices: Vec<usize>,
+    ids: Vec<usize>,
+    types: Vec<SpaceObjectType>,
+    positions: Vec<f32>,
+    velocities: Vec<f32>,
+    uvs: Vec<f32>,
+    frame_times: Vec<f32>,
+    time_offsets: Vec<f32>,
+    impact_strengths: Vec<f32>,
+    strength_buckets: Vec<u8>,
+    pans: Vec<f32>,
+    volumes: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl PlaneCrossingArray {
+    // Уникальные ID пересечений (для сопоставления эффектов и событий)
+    #[wasm_bindgen(getter)]
+    pub fn crossing_ids(&self) -> Vec<u32> {
+        self.crossing_ids.clone()
     }
     
-    // Get a reference to the space definition first to avoid multiple borrows
-    let space_definition = {
-        let system = SPACE_OBJECT_SYSTEMS.get(&system_id).unwrap();
-        system.space.clone()
+    // Направление пересечения: к наблюдателю (Entry) или обратно (Exit)
+    #[wasm_bindgen(getter)]
+    pub fn crossing_types(&self) -> Vec<CrossingType> {
+        self.crossing_types.clone()
+    }
+    
+    // Нормали граней плоскости, через которые прошли объекты (x, y, z подряд)
+    #[wasm_bindgen(getter)]
+    pub fn normals(&self) -> Vec<f32> {
+        self.normals.clone()
+    }
+    
+    // Индексы пересеченных видовых плоскостей (0 - основная)
+    #[wasm_bindgen(getter)]
+    pub fn plane_indices(&self) -> Vec<usize> {
+        self.plane_indices.clone()
+    }
+    
+    // ID пересекших объектов
+    #[wasm_bindgen(getter)]
+    pub fn ids(&self) -> Vec<usize> {
+        self.ids.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn types(&self) -> Vec<SpaceObjectType> {
+        self.types.clone()
+    }
+    
+    // Точки пересечения (x, y, z подряд)
+    #[wasm_bindgen(getter)]
+    pub fn positions(&self) -> Vec<f32> {
+        self.positions.clone()
+    }
+    
+    // Скорости объектов в момент пересечения (x, y, z подряд)
+    #[wasm_bindgen(getter)]
+    pub fn velocities(&self) -> Vec<f32> {
+        self.velocities.clone()
+    }
+    
+    // UV-координаты точек пересечения на плоскости (u, v подряд)
+    #[wasm_bindgen(getter)]
+    pub fn uvs(&self) -> Vec<f32> {
+        self.uvs.clone()
+    }
+    
+    // Доля кадра (0..1), на которой произошло пересечение
+    #[wasm_bindgen(getter)]
+    pub fn frame_times(&self) -> Vec<f32> {
+        self.frame_times.clone()
+    }
+    
+    // Момент пересечения в секундах относительно конца кадра (<= 0):
+    // эффект "начался" столько секунд назад, его можно запустить с этим сдвигом
+    #[wasm_bindgen(getter)]
+    pub fn time_offsets(&self) -> Vec<f32> {
+        self.time_offsets.clone()
+    }
+    
+    // Сила удара (как вклад в импульс камеры)
+    #[wasm_bindgen(getter)]
+    pub fn impact_strengths(&self) -> Vec<f32> {
+        self.impact_strengths.clone()
+    }
+    
+    // Группа силы удара: 0 - тихий, 1 - средний, 2 - сильный
+    #[wasm_bindgen(getter)]
+    pub fn strength_buckets(&self) -> Vec<u8> {
+        self.strength_buckets.clone()
+    }
+    
+    // Рекомендуемая панорама звука (-1 слева .. 1 справа)
+    #[wasm_bindgen(getter)]
+    pub fn pans(&self) -> Vec<f32> {
+        self.pans.clone()
+    }
+    
+    // Рекомендуемая громкость звука (0..1)
+    #[wasm_bindgen(getter)]
+    pub fn volumes(&self) -> Vec<f32> {
+        self.volumes.clone()
+    }
+}
+
+// Получить пересечения видовой плоскости, произошедшие за последний кадр
+#[wasm_bindgen]
+pub fn get_plane_crossings(system_id: usize) -> Option<PlaneCrossingArray> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let count = system.crossings.len();
+    
+    let mut result = PlaneCrossingArray {
+        crossing_ids: Vec::with_capacity(count),
+        crossing_types: Vec::with_capacity(count),
+        normals: Vec::with_capacity(count * 3),
+        plane_indices: Vec::with_capacity(count),
+        ids: Vec::with_capacity(count),
+        types: Vec::with_capacity(count),
+        positions: Vec::with_capacity(count * 3),
+        velocities: Vec::with_capacity(count * 3),
+        uvs: Vec::with_capacity(count * 2),
+        frame_times: Vec::with_capacity(count),
+        time_offsets: Vec::with_capacity(count),
+        impact_strengths: Vec::with_capacity(count),
+        strength_buckets: Vec::with_capacity(count),
+        pans: Vec::with_capacity(count),
+        volumes: Vec::with_capacity(count),
     };
     
-    // Now do the actual update
-    if let Some(mut system_ref) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
-        // Обновляем все объекты
-        for (_type, objects) in system_ref.objects.iter_mut() {
-            // Используем retain для удаления неактивных объектов
-            objects.retain_mut(|obj| obj.update(dt, &space_definition));
+    for crossing in &system.crossings {
+        let uv = system.crossing_uv(crossing);
+        result.crossing_ids.push(crossing.id);
+        result.plane_indices.push(crossing.plane);
+        result.crossing_types.push(crossing.crossing_type);
+        result.normals.extend_from_slice(&crossing.normal.to_array());
+        result.ids.push(crossing.object_id);
+        result.types.push(crossing.object_type);
+        result.positions.extend_from_slice(&crossing.position.to_array());
+        result.velocities.extend_from_slice(&crossing.velocity.to_array());
+        result.uvs.extend_from_slice(&uv.to_array());
+        result.frame_times.push(crossing.t);
+        result.time_offsets.push(crossing.time_offset(system.frame_stats.applied_dt));
+        
+        let sound = crossing.sound(&system);
+        result.impact_strengths.push(crossing.impact_strength());
+        result.strength_buckets.push(sound.strength_bucket);
+        result.pans.push(sound.pan);
+        result.volumes.push(sound.volume);
+    }
+    
+    Some(result)
+}
+
+// Получить текущий вектор "встряски" камеры от ударов о видовую плоскость.
+// Вектор затухает с каждым кадром, поэтому его можно напрямую добавлять к смещению камеры
+#[wasm_bindgen]
+pub fn get_camera_impulse(system_id: usize) -> Vec3Wrapper {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| system.impacts.impulse.into())
+        .unwrap_or_else(|| Vec3::ZERO.into())
+}
+
+/// Предсказанная траектория объекта
+#[wasm_bindgen]
+pub struct PredictedPath {
+    points: Vec<f32>,
+    crossing_time: Option<f32>,
+    crossing_point: Option<Vec3>,
+}
+
+#[wasm_bindgen]
+impl PredictedPath {
+    // Точки траектории (x, y, z подряд), включая текущую позицию
+    #[wasm_bindgen(getter)]
+    pub fn points(&self) -> Vec<f32> {
+        self.points.clone()
+    }
+    
+    // Время до пересечения видовой плоскости (в секундах), если оно ожидается
+    #[wasm_bindgen(getter)]
+    pub fn crossing_time(&self) -> Option<f32> {
+        self.crossing_time
+    }
+    
+    // Точка пересечения видовой плоскости, если оно ожидается
+    #[wasm_bindgen(getter)]
+    pub fn crossing_point(&self) -> Option<Vec3Wrapper> {
+        self.crossing_point.map(Vec3Wrapper::from)
+    }
+}
+
+// Проинтегрировать движение объекта вперед по его модели скорости/ускорения
+pub fn predict_path(object: &dyn SpaceObject, space: &SpaceDefinition, seconds: f32, steps: usize) -> PredictedPath {
+    let data = object.get_data();
+    let step_dt = seconds / steps as f32;
+    
+    let mut position = data.position;
+    let mut velocity = data.velocity;
+    let mut points = Vec::with_capacity((steps + 1) * 3);
+    let mut crossing_time = None;
+    let mut crossing_point = None;
+    
+    points.extend_from_slice(&[position.x, position.y, position.z]);
+    
+    for step in 0..steps {
+        velocity = object.predict_velocity(position, velocity, step_dt, space);
+        let next_position = position + velocity * step_dt;
+        
+        // Запоминаем первое пересечение видовой плоскости
+        if crossing_time.is_none() {
+            if let Some((t, point)) = space.segment_plane_crossing(&position, &next_position) {
+                crossing_time = Some((step as f32 + t) * step_dt);
+                crossing_point = Some(point);
+            }
         }
-        true
+        
+        position = next_position;
+        points.extend_from_slice(&[position.x, position.y, position.z]);
+    }
+    
+    PredictedPath {
+        points,
+        crossing_time,
+        crossing_point,
+    }
+}
+
+// Предел шагов предсказанной траектории
+pub const MAX_PREDICTED_PATH_STEPS: usize = 1000;
+
+// Предсказать траекторию объекта на seconds секунд вперед с разбиением на steps шагов
+// (не больше MAX_PREDICTED_PATH_STEPS), чтобы UI мог рисовать линии упреждения
+// или заранее планировать эффекты
+#[wasm_bindgen]
+pub fn predict_object_path(system_id: usize, object_id: usize, seconds: f32, steps: usize) -> Option<PredictedPath> {
+    if !seconds.is_finite() || seconds <= 0.0 || steps == 0 {
+        return None;
+    }
+    // Число шагов приходит из JS без ограничений
+    let steps = steps.min(MAX_PREDICTED_PATH_STEPS);
+    
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let object = system.find_object(object_id)?;
+    
+    // Объекты, ожидающие респауна, никуда не движутся
+    if object.is_waiting_for_respawn() {
+        return None;
+    }
+    
+    Some(predict_path(object, &system.space, seconds, steps))
+}
+
+// Шаг интегрирования при поиске предстоящих пересечений (в секундах)
+const UPCOMING_CROSSING_STEP: f32 = 1.0 / 30.0;
+const MAX_UPCOMING_CROSSING_STEPS: usize = 300;
+// Наибольший горизонт поиска (секунды): дальше шаг интегрирования растет
+// и предсказание теряет точность
+pub const MAX_UPCOMING_CROSSING_HORIZON: f32 = 10.0;
+
+/// Объекты, которые пересекут видовую плоскость в ближайшее время
+#[wasm_bindgen]
+pub struct UpcomingCrossings {
+    ids: Vec<usize>,
+    etas: Vec<f32>,
+    uvs: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl UpcomingCrossings {
+    #[wasm_bindgen(getter)]
+    pub fn ids(&self) -> Vec<usize> {
+        self.ids.clone()
+    }
+    
+    // Ожидаемое время до пересечения (в секундах), по возрастанию
+    #[wasm_bindgen(getter)]
+    pub fn etas(&self) -> Vec<f32> {
+        self.etas.clone()
+    }
+    
+    // Ожидаемые UV-координаты точки удара (u, v подряд)
+    #[wasm_bindgen(getter)]
+    pub fn uvs(&self) -> Vec<f32> {
+        self.uvs.clone()
+    }
+}
+
+// Получить список объектов, которые пересекут видовую плоскость в пределах horizon_seconds,
+// отсортированный по времени до пересечения - для упреждающих DOM-анимаций.
+// Горизонт ограничен MAX_UPCOMING_CROSSING_HORIZON, NaN дает пустой список
+#[wasm_bindgen]
+pub fn get_upcoming_crossings(system_id: usize, horizon_seconds: f32) -> Option<UpcomingCrossings> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    
+    let horizon_seconds = if horizon_seconds > 0.0 {
+        horizon_seconds.min(MAX_UPCOMING_CROSSING_HORIZON)
     } else {
-        // This should never happen since we checked above
-        false
+        0.0
+    };
+    let steps = ((horizon_seconds / UPCOMING_CROSSING_STEP).ceil() as usize).clamp(1, MAX_UPCOMING_CROSSING_STEPS);
+    let mut upcoming: Vec<(usize, f32, Vec3)> = Vec::new();
+    
+    if horizon_seconds > 0.0 {
+        for object in system.objects.values().flat_map(|objects| objects.iter()) {
+            if object.is_waiting_for_respawn() {
+                continue;
+            }
+            
+            let path = predict_path(object.as_ref(), &system.space, horizon_seconds, steps);
+            if let (Some(eta), Some(point)) = (path.crossing_time, path.crossing_point) {
+                upcoming.push((object.get_data().id, eta, point));
+            }
+        }
+    }
+    
+    upcoming.sort_by(|a, b| a.1.total_cmp(&b.1));
+    
+    let mut result = UpcomingCrossings {
+        ids: Vec::with_capacity(upcoming.len()),
+        etas: Vec::with_capacity(upcoming.len()),
+        uvs: Vec::with_capacity(upcoming.len() * 2),
+    };
+    
+    for (id, eta, point) in upcoming {
+        let uv = system.space.plane_uv(&point);
+        result.ids.push(id);
+        result.etas.push(eta);
+        result.uvs.push(uv.x);
+        result.uvs.push(uv.y);
     }
+    
+    Some(result)
 }
 
-// Вспомогательные функции для генерации случайных значений
+// Вспомогательные функции для генерации случайных значений.
+// Точка появления на дальней плоскости; с минимальным расстоянием между
+// появлениями выбирается из нескольких кандидатов (см. spawn_spacing.rs)
 pub fn random_position_on_far_plane(rng: &mut StdRng, space: &SpaceDefinition) -> Vec3 {
+    space.spawn_spacing.place(space, || sample_far_plane(rng, space))
+}
+
+fn sample_far_plane(rng: &mut StdRng, space: &SpaceDefinition) -> Vec3 {
     // Генерируем позицию на дальней плоскости (z = max_z)
     // Расчет ширины и высоты дальней плоскости для размещения комет
     let viewport = space.get_viewport_dimensions();
@@ -424,7 +1953,11 @@ pub fn random_position_on_far_plane(rng: &mut StdRng, space: &SpaceDefinition) -
     let max_height = viewport.y * 1.5; // Было 1.2
     
     // Более равномерное распределение с акцентом на центральную область
-    let (x, y) = if rng.gen::<f32>() < 0.7 {
+    let (x, y) = if let Some(map) = &space.spawn_density {
+        // Карта плотности задает распределение по всей зоне появления (v вниз)
+        let uv = map.sample(rng);
+        (max_width * (uv.x * 2.0 - 1.0), max_height * (1.0 - uv.y * 2.0))
+    } else if rng.gen::<f32>() < 0.7 {
         // 70% появляются в центральной части плоскости
         (
             rng.gen_range(-max_width * 0.7..max_width * 0.7),
@@ -444,7 +1977,10 @@ pub fn random_position_on_far_plane(rng: &mut StdRng, space: &SpaceDefinition) -
     let z_variation = rng.gen_range(-1.0..1.0);
     let z = space.max_z + z_variation;
     
-    Vec3::new(x, y, z)
+    // Наклон устройства слегка сдвигает зону появления туда, куда смотрит наблюдатель
+    let bias = space.spawn_bias();
+    
+    Vec3::new(x + bias.x, y + bias.y, z)
 }
 
 pub fn random_trajectory_through_viewport(
@@ -0,0 +1,126 @@
+/*
+ * spawn_density.rs
+ *
+ * Карта плотности появлений на дальней плоскости. Дизайнер задает сетку
+ * width x height весов (например, больше комет у краев страницы и почти
+ * ничего за заголовком), и точки появления выбираются пропорционально
+ * весам вместо встроенного распределения "70% в центре".
+ *
+ * Сетка покрывает всю зону появления (1.5 размера окна в каждую сторону от
+ * центра, как и встроенное распределение); строка 0 - верх, как у
+ * изображения или страницы. Ячейка выбирается по накопленной сумме весов
+ * двоичным поиском, точка внутри ячейки - равномерно. Карта хранится за Arc:
+ * определение пространства копируется при каждом появлении.
+ */
+
+use std::sync::Arc;
+
+use rand::Rng;
+use rand::rngs::StdRng;
+use wasm_bindgen::prelude::*;
+use glam::Vec2;
+
+use crate::errors::OpResult;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+// Предел размера карты (ячеек)
+pub const MAX_DENSITY_MAP_CELLS: usize = 256 * 256;
+
+/// Карта плотности: накопленные веса ячеек по строкам
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpawnDensityMap {
+    width: usize,
+    height: usize,
+    cumulative: Vec<f32>,
+}
+
+impl SpawnDensityMap {
+    // Построить карту из весов; веса должны быть конечными, неотрицательными,
+    // не все нулевыми, а их сумма должна помещаться в f32
+    pub fn new(width: usize, height: usize, weights: &[f32]) -> Result<Self, String> {
+        if width == 0 || height == 0 || width.saturating_mul(height) > MAX_DENSITY_MAP_CELLS {
+            return Err(format!("density map must have between 1 and {} cells", MAX_DENSITY_MAP_CELLS));
+        }
+        if weights.len() != width * height {
+            return Err(format!("expected {} weights, got {}", width * height, weights.len()));
+        }
+        if weights.iter().any(|weight| !weight.is_finite() || *weight < 0.0) {
+            return Err("density weights must be finite and non-negative".to_string());
+        }
+
+        let cumulative: Vec<f32> = weights
+            .iter()
+            .scan(0.0, |total, weight| {
+                *total += weight;
+                Some(*total)
+            })
+            .collect();
+        if cumulative.last().is_none_or(|total| *total <= 0.0) {
+            return Err("density map must have a positive weight".to_string());
+        }
+        // Переполненная сумма ломает gen_range(0.0..total) в sample
+        if cumulative.last().is_some_and(|total| !total.is_finite()) {
+            return Err("total density weight must be finite".to_string());
+        }
+
+        Ok(Self { width, height, cumulative })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // Случайная точка карты в UV (0..1, v вниз) пропорционально весам
+    pub fn sample(&self, rng: &mut StdRng) -> Vec2 {
+        let total = self.cumulative.last().copied().unwrap_or(0.0);
+        let target = rng.gen_range(0.0..total);
+        // Первая ячейка с накопленной суммой больше target (пустые ячейки пропускаются)
+        let cell = self.cumulative.partition_point(|sum| *sum <= target).min(self.cumulative.len() - 1);
+
+        let (column, row) = (cell % self.width, cell / self.width);
+        Vec2::new(
+            (column as f32 + rng.gen::<f32>()) / self.width as f32,
+            (row as f32 + rng.gen::<f32>()) / self.height as f32,
+        )
+    }
+}
+
+// Задать карту плотности появлений: width x height весов по строкам, строка 0 - верх
+#[wasm_bindgen]
+pub fn set_spawn_density_map(system_id: usize, width: usize, height: usize, weights: &[f32]) -> OpResult {
+    let map = match SpawnDensityMap::new(width, height, weights) {
+        Ok(map) => map,
+        Err(message) => return OpResult::invalid_argument(message),
+    };
+
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.space.spawn_density = Some(Arc::new(map));
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Вернуть встроенное распределение появлений
+#[wasm_bindgen]
+pub fn clear_spawn_density_map(system_id: usize) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.space.spawn_density = None;
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn has_spawn_density_map(system_id: usize) -> bool {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .is_some_and(|system| system.space.spawn_density.is_some())
+}
@@ -0,0 +1,135 @@
+/*
+ * spawn_spacing.rs
+ *
+ * Разреженное размещение появлений на дальней плоскости. Равномерно
+ * случайные точки иногда ложатся кучно, и несколько комет летят к зрителю
+ * одной стайкой. С включенным минимальным расстоянием точка появления
+ * выбирается по схеме "лучшего кандидата" (Mitchell): берется до
+ * SPACING_CANDIDATES случайных точек, и первая, отстоящая от недавних
+ * появлений не меньше чем на min_separation, принимается сразу; если такой
+ * нет - берется самая удаленная. Получается шум с синим спектром без
+ * жесткой сетки и без бесконечных повторных попыток.
+ *
+ * Расстояние измеряется в проекции на окно (в долях высоты окна), потому
+ * что кучность видит зритель: далекие точки с большим разносом в
+ * пространстве на экране могут совпасть. Учитываются RECENT_SPAWNS последних
+ * появлений. Респауны, случившиеся в одном кадре, друг друга не учитывают -
+ * они попадают в историю после шага объектов.
+ */
+
+use std::collections::VecDeque;
+
+use wasm_bindgen::prelude::*;
+use glam::{Vec2, Vec3};
+
+use crate::errors::OpResult;
+use crate::space_core::SpaceDefinition;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+// Кандидатов на одно появление
+pub const SPACING_CANDIDATES: usize = 8;
+
+// Сколько последних появлений учитывается
+pub const RECENT_SPAWNS: usize = 16;
+
+/// Минимальное расстояние между появлениями и их недавняя история
+#[derive(Clone, Debug, Default)]
+pub struct SpawnSpacing {
+    min_separation: Option<f32>,
+    recent: VecDeque<Vec2>,
+}
+
+// Точка на экране в долях высоты окна (x растянут по пропорциям окна)
+fn screen_point(space: &SpaceDefinition, position: &Vec3) -> Option<Vec2> {
+    let viewport = space.get_viewport_dimensions();
+    let aspect = if viewport.y > 0.0 { viewport.x / viewport.y } else { 1.0 };
+    space.project_to_viewport(position).map(|uv| Vec2::new(uv.x * aspect, uv.y))
+}
+
+impl SpawnSpacing {
+    pub fn min_separation(&self) -> Option<f32> {
+        self.min_separation
+    }
+
+    pub fn set_min_separation(&mut self, separation: Option<f32>) {
+        self.min_separation = separation;
+        if separation.is_none() {
+            self.recent.clear();
+        }
+    }
+
+    // Экранное расстояние от точки до ближайшего недавнего появления
+    fn clearance(&self, space: &SpaceDefinition, position: &Vec3) -> f32 {
+        let Some(point) = screen_point(space, position) else {
+            return 0.0;
+        };
+        self.recent
+            .iter()
+            .map(|recent| recent.distance(point))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    // Выбрать точку появления из кандидатов sample(); без минимального
+    // расстояния - первый кандидат, как при равномерном размещении
+    pub fn place(&self, space: &SpaceDefinition, mut sample: impl FnMut() -> Vec3) -> Vec3 {
+        let Some(min_separation) = self.min_separation else {
+            return sample();
+        };
+
+        let mut best = (f32::NEG_INFINITY, Vec3::ZERO);
+        for _ in 0..SPACING_CANDIDATES {
+            let candidate = sample();
+            let clearance = self.clearance(space, &candidate);
+            if clearance >= min_separation {
+                return candidate;
+            }
+            if clearance > best.0 {
+                best = (clearance, candidate);
+            }
+        }
+        best.1
+    }
+
+    fn push(&mut self, point: Vec2) {
+        if self.recent.len() >= RECENT_SPAWNS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(point);
+    }
+}
+
+impl SpaceDefinition {
+    // Запомнить появление в точке position
+    pub fn record_spawn(&mut self, position: &Vec3) {
+        if self.spawn_spacing.min_separation.is_none() {
+            return;
+        }
+        if let Some(point) = screen_point(self, position) {
+            self.spawn_spacing.push(point);
+        }
+    }
+}
+
+// Минимальное экранное расстояние между появлениями в долях высоты окна
+// (0.1 - десятая часть высоты); undefined - равномерное размещение
+#[wasm_bindgen]
+pub fn set_spawn_separation(system_id: usize, separation: Option<f32>) -> OpResult {
+    if separation.is_some_and(|separation| !separation.is_finite() || separation <= 0.0) {
+        return OpResult::invalid_argument("spawn separation must be positive and finite");
+    }
+
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.space.spawn_spacing.set_min_separation(separation);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_spawn_separation(system_id: usize) -> Option<f32> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .and_then(|system| system.space.spawn_spacing.min_separation())
+}
@@ -0,0 +1,175 @@
+/*
+ * state_stream.rs
+ * 
+ * Компактный двоичный формат состояния сцены для зеркалирования через
+ * WebSocket / BroadcastChannel. Все числа little-endian.
+ * 
+ * Заголовок (16 байт):
+ *   0..4   магия b"HCSS"
+ *   4..6   версия формата (u16), сейчас STATE_STREAM_VERSION
+ *   6..8   размер заголовка в байтах (u16) - позволяет расширять заголовок
+ *   8..12  номер кадра системы (u32)
+ *   12..16 количество записей объектов (u32)
+ * 
+ * Запись объекта (24 байта), записи отсортированы по ID:
+ *   0..4   ID объекта (u32)
+ *   4      тип объекта (u8, значение SpaceObjectType)
+ *   5      прозрачность (u8, 0..255)
+ *   6      флаги (u8): бит 0 - объект ожидает респауна, бит 1 - скрыт областью перекрытия
+ *   7      зарезервировано (0)
+ *   8..20  позиция x, y, z (f32)
+ *   20..24 размер (f32)
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+
+use crate::space_objects::{SpaceObjectSystem, SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+
+pub const STATE_STREAM_MAGIC: &[u8; 4] = b"HCSS";
+pub const STATE_STREAM_VERSION: u16 = 1;
+const HEADER_SIZE: usize = 16;
+const RECORD_SIZE: usize = 24;
+
+// Флаги записи объекта
+const FLAG_WAITING_FOR_RESPAWN: u8 = 1;
+const FLAG_OCCLUDED: u8 = 1 << 1;
+
+/// Объект, прочитанный из двоичного потока
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamedObject {
+    pub id: u32,
+    pub object_type: SpaceObjectType,
+    pub opacity: u8,
+    pub waiting_for_respawn: bool,
+    pub occluded: bool,
+    pub position: Vec3,
+    pub size: f32,
+}
+
+/// Прочитанное состояние сцены
+#[derive(Clone, Debug)]
+pub struct StreamedState {
+    pub frame: u32,
+    pub objects: Vec<StreamedObject>,
+}
+
+pub(crate) fn object_type_from_u8(value: u8) -> Option<SpaceObjectType> {
+    match value {
+        0 => Some(SpaceObjectType::NeonComet),
+        1 => Some(SpaceObjectType::EnergySphere),
+        2 => Some(SpaceObjectType::PolygonalCrystal),
+        3 => Some(SpaceObjectType::Hypercube),
+        _ => None,
+    }
+}
+
+// Упаковать состояние системы в двоичный формат
+pub fn encode_state(system: &SpaceObjectSystem) -> Vec<u8> {
+    let mut objects: Vec<_> = system
+        .get_objects()
+        .values()
+        .flat_map(|objects| objects.iter())
+        .collect();
+    objects.sort_by_key(|obj| obj.get_data().id);
+    
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + objects.len() * RECORD_SIZE);
+    bytes.extend_from_slice(STATE_STREAM_MAGIC);
+    bytes.extend_from_slice(&STATE_STREAM_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(HEADER_SIZE as u16).to_le_bytes());
+    bytes.extend_from_slice(&system.frame.to_le_bytes());
+    bytes.extend_from_slice(&(objects.len() as u32).to_le_bytes());
+    
+    for obj in objects {
+        let data = obj.get_data();
+        let mut flags = 0;
+        if obj.is_waiting_for_respawn() {
+            flags |= FLAG_WAITING_FOR_RESPAWN;
+        }
+        if system.space.is_occluded(&data.position) {
+            flags |= FLAG_OCCLUDED;
+        }
+        
+        bytes.extend_from_slice(&(data.id as u32).to_le_bytes());
+        bytes.push(data.object_type as u8);
+        bytes.push((data.opacity.clamp(0.0, 1.0) * 255.0).round() as u8);
+        bytes.push(flags);
+        bytes.push(0);
+        for value in [data.position.x, data.position.y, data.position.z, data.size] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    
+    bytes
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn read_f32(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_bits(read_u32(bytes, offset))
+}
+
+// Прочитать состояние из двоичного формата
+pub fn decode_state(bytes: &[u8]) -> Result<StreamedState, String> {
+    if bytes.len() < HEADER_SIZE || &bytes[0..4] != STATE_STREAM_MAGIC {
+        return Err("Not a hypercube state stream".to_string());
+    }
+    
+    let version = read_u16(bytes, 4);
+    if version != STATE_STREAM_VERSION {
+        return Err(format!("Unsupported state stream version {}", version));
+    }
+    
+    let header_size = read_u16(bytes, 6) as usize;
+    let frame = read_u32(bytes, 8);
+    let count = read_u32(bytes, 12) as usize;
+    
+    // count приходит из недоверенных байтов: на wasm32 произведение может переполнить usize
+    let records_end = count
+        .checked_mul(RECORD_SIZE)
+        .and_then(|records| records.checked_add(header_size));
+    if header_size < HEADER_SIZE || records_end.is_none_or(|end| bytes.len() < end) {
+        return Err("Truncated state stream".to_string());
+    }
+    
+    let mut objects = Vec::with_capacity(count);
+    for index in 0..count {
+        let offset = header_size + index * RECORD_SIZE;
+        let object_type = object_type_from_u8(bytes[offset + 4])
+            .ok_or_else(|| format!("Unknown object type {}", bytes[offset + 4]))?;
+        
+        objects.push(StreamedObject {
+            id: read_u32(bytes, offset),
+            object_type,
+            opacity: bytes[offset + 5],
+            waiting_for_respawn: bytes[offset + 6] & FLAG_WAITING_FOR_RESPAWN != 0,
+            occluded: bytes[offset + 6] & FLAG_OCCLUDED != 0,
+            position: Vec3::new(
+                read_f32(bytes, offset + 8),
+                read_f32(bytes, offset + 12),
+                read_f32(bytes, offset + 16)
+            ),
+            size: read_f32(bytes, offset + 20),
+        });
+    }
+    
+    Ok(StreamedState { frame, objects })
+}
+
+// Текущее состояние системы в двоичном формате (Uint8Array в JS)
+#[wasm_bindgen]
+pub fn encode_scene_state(system_id: usize) -> Option<Vec<u8>> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| encode_state(&system))
+}
+
+// Версия двоичного формата - для согласования с зеркалирующей стороной
+#[wasm_bindgen]
+pub fn get_state_stream_version() -> u16 {
+    STATE_STREAM_VERSION
+}
@@ -0,0 +1,147 @@
+/*
+ * stereo.rs
+ *
+ * Стерео/VR: параметры двух глаз, выведенные из SpaceDefinition.
+ * Глаза смещены от наблюдателя на половину межзрачкового расстояния (IPD)
+ * вправо и влево. Видовая плоскость служит общим "экраном": у каждого глаза
+ * асимметричная (off-axis) пирамида видимости, проходящая через прямоугольник
+ * видовой плоскости, поэтому объекты на плоскости имеют нулевой параллакс,
+ * а объекты перед ней "выходят" из экрана.
+ *
+ * Пирамида задается тангенсами углов границ (left, right, bottom, top),
+ * как в WebXR / glFrustum, плюс расстояния ближней и дальней отсечки.
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+
+use crate::space_core::SpaceDefinition;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+// Ближняя отсечка для обоих глаз
+const STEREO_NEAR: f32 = 0.1;
+
+/// Пирамида видимости одного глаза
+#[derive(Clone, Copy, Debug)]
+pub struct EyeFrustum {
+    pub position: Vec3,
+    pub tan_left: f32,
+    pub tan_right: f32,
+    pub tan_bottom: f32,
+    pub tan_top: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl EyeFrustum {
+    // Пирамида глаза, смещенного от наблюдателя на eye_offset вдоль оси "вправо"
+    pub fn new(space: &SpaceDefinition, eye_offset: f32) -> Self {
+        let right = Vec3::Y.cross(space.view_direction()).normalize();
+        let position = space.observer_position + right * eye_offset;
+
+        // Прямоугольник видовой плоскости центрирован по наблюдателю (не по глазу)
+        let viewport = space.get_viewport_dimensions();
+        let distance = (space.viewing_plane_z - position.z).max(STEREO_NEAR);
+        let center_x = space.observer_position.x - position.x;
+        let center_y = space.observer_position.y - position.y;
+
+        Self {
+            position,
+            tan_left: (center_x - viewport.x * 0.5) / distance,
+            tan_right: (center_x + viewport.x * 0.5) / distance,
+            tan_bottom: (center_y - viewport.y * 0.5) / distance,
+            tan_top: (center_y + viewport.y * 0.5) / distance,
+            near: STEREO_NEAR,
+            far: (space.max_z - position.z).max(STEREO_NEAR),
+        }
+    }
+
+    // Находится ли точка внутри пирамиды глаза
+    pub fn contains(&self, point: &Vec3) -> bool {
+        let local = *point - self.position;
+        if local.z < self.near || local.z > self.far {
+            return false;
+        }
+
+        let tx = local.x / local.z;
+        let ty = local.y / local.z;
+        tx >= self.tan_left && tx <= self.tan_right && ty >= self.tan_bottom && ty <= self.tan_top
+    }
+
+    fn to_array(self) -> Vec<f32> {
+        vec![self.tan_left, self.tan_right, self.tan_bottom, self.tan_top, self.near, self.far]
+    }
+}
+
+/// Параметры стерео-вида: позиции глаз и их пирамиды
+/// [tan_left, tan_right, tan_bottom, tan_top, near, far]
+#[wasm_bindgen]
+pub struct StereoViewParams {
+    left_eye: Vec<f32>,
+    right_eye: Vec<f32>,
+    left_frustum: Vec<f32>,
+    right_frustum: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl StereoViewParams {
+    #[wasm_bindgen(getter)]
+    pub fn left_eye(&self) -> Vec<f32> {
+        self.left_eye.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn right_eye(&self) -> Vec<f32> {
+        self.right_eye.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn left_frustum(&self) -> Vec<f32> {
+        self.left_frustum.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn right_frustum(&self) -> Vec<f32> {
+        self.right_frustum.clone()
+    }
+}
+
+// Пирамиды левого и правого глаза для межзрачкового расстояния ipd
+pub fn eye_frustums(space: &SpaceDefinition, ipd: f32) -> (EyeFrustum, EyeFrustum) {
+    let half = ipd.max(0.0) * 0.5;
+    (EyeFrustum::new(space, -half), EyeFrustum::new(space, half))
+}
+
+#[wasm_bindgen]
+pub fn get_stereo_view_params(system_id: usize, ipd: f32) -> Option<StereoViewParams> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let (left, right) = eye_frustums(&system.space, ipd);
+
+    Some(StereoViewParams {
+        left_eye: left.position.to_array().to_vec(),
+        right_eye: right.position.to_array().to_vec(),
+        left_frustum: left.to_array(),
+        right_frustum: right.to_array(),
+    })
+}
+
+// ID объектов, видимых одним глазом (right_eye = false - левый)
+#[wasm_bindgen]
+pub fn get_visible_ids_for_eye(system_id: usize, ipd: f32, right_eye: bool) -> Option<Vec<usize>> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let (left, right) = eye_frustums(&system.space, ipd);
+    let eye = if right_eye { right } else { left };
+
+    let mut ids: Vec<usize> = system
+        .get_objects()
+        .values()
+        .flat_map(|objects| objects.iter())
+        .filter(|obj| !obj.is_waiting_for_respawn())
+        .map(|obj| obj.get_data())
+        .filter(|data| eye.contains(&data.position))
+        .map(|data| data.id)
+        .collect();
+    ids.sort_unstable();
+
+    Some(ids)
+}
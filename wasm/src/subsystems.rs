@@ -0,0 +1,192 @@
+/*
+ * subsystems.rs
+ *
+ * Включение и выключение подсистем сцены во время работы - для A/B-сравнения
+ * компонентов сцены хостом. Выключенная подсистема приостанавливается и
+ * скрывается, а ее проходы кадра не выполняются:
+ *
+ * - comets, spheres, crystals - объекты типа убираются из системы в
+ *   отдельное хранилище (не обновляются, не экспортируются, не
+ *   пересекают плоскости) и возвращаются в том же состоянии при включении.
+ *   Появившиеся при выключенной подсистеме объекты убираются туда же в
+ *   начале следующего шага;
+ * - particles - хвосты комет, частицы эффектов ударов и вихри сфер:
+ *   проходы хвостов и эффектов пропускаются, пулы очищаются,
+ *   particle_count дает 0;
+ * - physics - связь с rapier: обломки ударов удаляются из мира и новые не
+ *   создаются, коллайдеры видовых плоскостей не синхронизируются;
+ * - intersections - пересечения видовых плоскостей не ищутся, поэтому
+ *   нет ни событий, ни зависящих от них ударов, свечения и статистики.
+ */
+
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::errors::OpResult;
+use crate::space_objects::{SpaceObject, SpaceObjectSystem, SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+
+/// Подсистема сцены
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Subsystem {
+    Comets,
+    Crystals,
+    Spheres,
+    Particles,
+    Physics,
+    Intersections,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 6] = [
+        Subsystem::Comets,
+        Subsystem::Crystals,
+        Subsystem::Spheres,
+        Subsystem::Particles,
+        Subsystem::Physics,
+        Subsystem::Intersections,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Subsystem::Comets => "comets",
+            Subsystem::Crystals => "crystals",
+            Subsystem::Spheres => "spheres",
+            Subsystem::Particles => "particles",
+            Subsystem::Physics => "physics",
+            Subsystem::Intersections => "intersections",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|subsystem| subsystem.name() == name)
+    }
+
+    // Тип объектов подсистемы
+    fn object_type(self) -> Option<SpaceObjectType> {
+        match self {
+            Subsystem::Comets => Some(SpaceObjectType::NeonComet),
+            Subsystem::Crystals => Some(SpaceObjectType::PolygonalCrystal),
+            Subsystem::Spheres => Some(SpaceObjectType::EnergySphere),
+            _ => None,
+        }
+    }
+
+    // Проходы графа обновления, которые пропускаются при выключенной подсистеме
+    fn passes(self) -> &'static [&'static str] {
+        match self {
+            Subsystem::Particles => &["tails", "effects"],
+            Subsystem::Physics => &["debris", "plane_physics"],
+            Subsystem::Intersections => &["crossings"],
+            _ => &[],
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Выключенные подсистемы и объекты, убранные на время выключения
+#[derive(Default)]
+pub struct Subsystems {
+    disabled: u8,
+    parked: HashMap<SpaceObjectType, Vec<Box<dyn SpaceObject>>>,
+}
+
+impl Subsystems {
+    pub fn is_enabled(&self, subsystem: Subsystem) -> bool {
+        self.disabled & subsystem.bit() == 0
+    }
+
+    pub fn set_enabled(&mut self, subsystem: Subsystem, enabled: bool) {
+        if enabled {
+            self.disabled &= !subsystem.bit();
+        } else {
+            self.disabled |= subsystem.bit();
+        }
+    }
+
+    // Пропускается ли проход кадра
+    pub fn skips_pass(&self, name: &str) -> bool {
+        self.disabled != 0
+            && Subsystem::ALL
+                .into_iter()
+                .any(|subsystem| !self.is_enabled(subsystem) && subsystem.passes().contains(&name))
+    }
+
+    // Убрать объекты выключенных подсистем и вернуть объекты включенных
+    pub fn park(&mut self, objects: &mut HashMap<SpaceObjectType, Vec<Box<dyn SpaceObject>>>) {
+        for subsystem in Subsystem::ALL {
+            let Some(object_type) = subsystem.object_type() else {
+                continue;
+            };
+            let (from, to) = if self.is_enabled(subsystem) {
+                (&mut self.parked, &mut *objects)
+            } else {
+                (&mut *objects, &mut self.parked)
+            };
+            if let Some(moved) = from.remove(&object_type) {
+                to.entry(object_type).or_default().extend(moved);
+            }
+        }
+    }
+
+    // Убранных объектов
+    pub fn parked_len(&self) -> usize {
+        self.parked.values().map(Vec::len).sum()
+    }
+
+    // Объекты, убранные на время выключения подсистем
+    pub fn parked(&self) -> &HashMap<SpaceObjectType, Vec<Box<dyn SpaceObject>>> {
+        &self.parked
+    }
+}
+
+impl SpaceObjectSystem {
+    // Привести состояние системы к включенным подсистемам
+    pub fn apply_subsystems(&mut self) {
+        let mut subsystems = std::mem::take(&mut self.subsystems);
+        if subsystems.disabled != 0 || !subsystems.parked.is_empty() {
+            subsystems.park(self.get_objects_mut());
+        }
+
+        if !subsystems.is_enabled(Subsystem::Particles) {
+            self.tail_particles.clear();
+            self.effects.clear();
+        }
+        if !subsystems.is_enabled(Subsystem::Physics) {
+            self.debris.truncate_oldest(0);
+        }
+        if !subsystems.is_enabled(Subsystem::Intersections) {
+            self.crossings.clear();
+        }
+        self.subsystems = subsystems;
+    }
+}
+
+// Включить или выключить подсистему: "comets", "crystals", "spheres",
+// "particles", "physics", "intersections"
+#[wasm_bindgen]
+pub fn set_subsystem_enabled(system_id: usize, name: &str, enabled: bool) -> OpResult {
+    let Some(subsystem) = Subsystem::from_name(name) else {
+        return OpResult::invalid_argument(format!("unknown subsystem '{}'", name));
+    };
+
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.subsystems.set_enabled(subsystem, enabled);
+            system.apply_subsystems();
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// undefined - нет системы или неизвестная подсистема
+#[wasm_bindgen]
+pub fn is_subsystem_enabled(system_id: usize, name: &str) -> Option<bool> {
+    let subsystem = Subsystem::from_name(name)?;
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    Some(system.subsystems.is_enabled(subsystem))
+}
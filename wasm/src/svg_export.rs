@@ -0,0 +1,150 @@
+/*
+ * svg_export.rs
+ *
+ * Силуэты текущего кадра в SVG: спроецированные пути комет (голова и хвост)
+ * и каркас гиперкуба. Нужен для статических превью (Open Graph и т.п.),
+ * которые сервер генерирует тем же WASM-модулем без браузера и WebGL.
+ *
+ * Точки проецируются из наблюдателя через видовую плоскость
+ * (project_to_viewport) в окно width x height; отрезки с концом позади
+ * наблюдателя пропускаются. Кометы рисуются цветом из палитры (sRGB, как
+ * и положено в SVG) с их прозрачностью. Кристаллы пока не имеют
+ * сгенерированных оболочек, поэтому их контуры не экспортируются, как и в
+ * glTF. Фон прозрачный - его задает страница или компоновщик превью.
+ */
+
+use std::fmt::Write;
+
+use wasm_bindgen::prelude::*;
+use glam::{Vec2, Vec3};
+
+use crate::hypercube::Hypercube;
+use crate::neon_comets::NeonComet;
+use crate::space_core::{SpaceDefinition, Vec3Wrapper};
+use crate::space_objects::{SpaceObject, SpaceObjectSystem, SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+
+// Толщина линий силуэтов и радиус головы кометы, пиксели
+const COMET_STROKE_WIDTH: f32 = 2.0;
+const COMET_HEAD_RADIUS: f32 = 3.0;
+const WIREFRAME_STROKE_WIDTH: f32 = 1.0;
+const WIREFRAME_COLOR: &str = "#ffffff";
+
+fn to_hex(color: [f32; 3]) -> String {
+    let [r, g, b] = color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// Точка пространства -> пиксели окна
+fn project(space: &SpaceDefinition, point: &Vec3, size: Vec2) -> Option<Vec2> {
+    space.project_to_viewport(point).map(|uv| uv * size)
+}
+
+fn add_comets(svg: &mut String, system: &SpaceObjectSystem, size: Vec2) {
+    let space = &system.space;
+    let length_scale = system.warp.multiplier() * system.power_profile.budget().tail_scale;
+    let comets = system
+        .get_objects()
+        .get(&SpaceObjectType::NeonComet)
+        .into_iter()
+        .flatten()
+        .filter_map(|obj| obj.as_any().downcast_ref::<NeonComet>())
+        .filter(|comet| !comet.waiting_for_respawn && comet.is_visible(space));
+
+    let _ = write!(svg, r#"<g id="comets" fill="none" stroke-width="{}" stroke-linecap="round">"#, COMET_STROKE_WIDTH);
+    for comet in comets {
+        let data = comet.get_data();
+        if space.is_occluded(&data.position) {
+            continue;
+        }
+        let Some(head) = project(space, &data.position, size) else {
+            continue;
+        };
+
+        let color = to_hex(system.modifiers.tint(comet.color));
+        let tail_end = data.position - data.velocity.normalize_or_zero() * comet.tail_length * length_scale;
+        if let Some(tail) = project(space, &tail_end, size) {
+            let _ = write!(
+                svg,
+                r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-opacity="{:.3}"/>"#,
+                tail.x, tail.y, head.x, head.y, color, data.opacity
+            );
+        }
+        let _ = write!(
+            svg,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="{}" fill="{}" fill-opacity="{:.3}"/>"#,
+            head.x, head.y, COMET_HEAD_RADIUS, color, data.opacity
+        );
+    }
+    svg.push_str("</g>");
+}
+
+fn add_hypercube(svg: &mut String, space: &SpaceDefinition, hypercube: &Hypercube, w_camera: f64, center: Vec3, scale: f32, size: Vec2) {
+    let vertices: Vec<Option<Vec2>> = hypercube
+        .get_projected_vertices(w_camera)
+        .chunks_exact(3)
+        .map(|p| center + Vec3::new(p[0] as f32, p[1] as f32, p[2] as f32) * scale)
+        .map(|vertex| project(space, &vertex, size))
+        .collect();
+
+    let mut path = String::new();
+    for edge in hypercube.get_edges().chunks_exact(2) {
+        let endpoints = (vertices.get(edge[0] as usize).copied().flatten(), vertices.get(edge[1] as usize).copied().flatten());
+        if let (Some(a), Some(b)) = endpoints {
+            let _ = write!(path, "M{:.1} {:.1}L{:.1} {:.1}", a.x, a.y, b.x, b.y);
+        }
+    }
+
+    let _ = write!(
+        svg,
+        r#"<path id="hypercube" d="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+        path, WIREFRAME_COLOR, WIREFRAME_STROKE_WIDTH
+    );
+}
+
+// Собрать SVG кадра размером width x height пикселей
+pub fn build_silhouette_svg(
+    system: &SpaceObjectSystem,
+    width: u32,
+    height: u32,
+    hypercube: Option<(&Hypercube, f64, Vec3, f32)>,
+) -> String {
+    let size = Vec2::new(width as f32, height as f32);
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+
+    if let Some((hypercube, w_camera, center, scale)) = hypercube {
+        add_hypercube(&mut svg, &system.space, hypercube, w_camera, center, scale, size);
+    }
+    add_comets(&mut svg, system, size);
+
+    svg.push_str("</svg>");
+    svg
+}
+
+// Силуэты комет текущего кадра в SVG
+#[wasm_bindgen]
+pub fn export_silhouette_svg(system_id: usize, width: u32, height: u32) -> Option<String> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| build_silhouette_svg(&system, width, height, None))
+}
+
+// Силуэты вместе с каркасом гиперкуба, размещенного так же, как в track_hypercube_crossings
+#[wasm_bindgen]
+pub fn export_silhouette_svg_with_hypercube(
+    system_id: usize,
+    width: u32,
+    height: u32,
+    hypercube: &Hypercube,
+    w_camera: f64,
+    center: &Vec3Wrapper,
+    scale: f32,
+) -> Option<String> {
+    let center: Vec3 = center.clone().into();
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .map(|system| build_silhouette_svg(&system, width, height, Some((hypercube, w_camera, center, scale))))
+}
@@ -0,0 +1,350 @@
+/*
+ * tail_particles.rs
+ *
+ * Частицы хвостов комет в общем пуле системы. Хвост строится каждый кадр
+ * вдоль направления, обратного скорости кометы, на длину ее следа
+ * (max_trail_length с учетом разгона и профиля питания); частицы всех комет
+ * лежат в одних массивах, а каждой комете соответствует диапазон индексов.
+ * Массивы пула переиспользуются между кадрами, поэтому память не
+ * фрагментируется, а общее число частиц ограничено бюджетом системы.
+ *
+ * Бюджет раздается по приоритету: сначала кометы в пирамиде видимости, среди
+ * них - ближние к наблюдателю; кометы, которым не хватило бюджета, получают
+ * укороченный хвост или не получают его вовсе. Хвосты комет в области
+ * перекрытия не строятся, как и в экспорте tail_lengths.
+ *
+ * Частицы хвоста увлекаются полем сил сцены: ветром и тем же 4D шумом, что
+ * дает турбулентность кометам. Частица на расстоянии d от головы испущена
+ * d / speed секунд назад, и за это время ее снесло ветром и закрутило шумом,
+ * взятым в момент испускания, - поэтому завихрения плывут вдоль хвоста, а
+ * старый конец хвоста отклоняется сильнее. Сила турбулентности - общая для
+ * системы (set_tail_field) с множителем каждой кометы (tail_turbulence).
+ */
+
+use wasm_bindgen::prelude::*;
+use glam::Vec3;
+
+use crate::errors::OpResult;
+use crate::neon_comets::NeonComet;
+use crate::noise4d::simulation_noise;
+use crate::space_core::Vec3Wrapper;
+use crate::particle_budget::ParticleSubsystem;
+use crate::space_objects::{SpaceObject, SpaceObjectSystem, SpaceObjectType, SPACE_OBJECT_SYSTEMS};
+
+// Частиц на хвост одной кометы (до учета режима покоя и профиля питания)
+pub const TAIL_PARTICLES_PER_COMET: usize = 16;
+
+// Бюджет частиц хвостов системы по умолчанию
+pub const DEFAULT_TAIL_PARTICLE_BUDGET: usize = 512;
+
+// Предел бюджета, задаваемого хостом
+const MAX_TAIL_PARTICLE_BUDGET: usize = 65_536;
+
+// Поле сил хвостов по умолчанию: сила турбулентности (единиц в секунду)
+// и пространственная частота шума
+pub const DEFAULT_TAIL_TURBULENCE: f32 = 1.5;
+pub const DEFAULT_TAIL_TURBULENCE_FREQUENCY: f32 = 0.05;
+
+// Старше этого возраста частица не сносится дальше (медленные кометы)
+const MAX_TAIL_PARTICLE_AGE: f32 = 2.0;
+
+// Точек шума вдоль хвоста: шум низкочастотный, между точками - линейно
+const TAIL_NOISE_SAMPLES: usize = 4;
+
+/// Поле сил, увлекающее частицы хвостов
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TailField {
+    pub turbulence: f32,
+    pub frequency: f32,
+    pub wind: Vec3,
+}
+
+impl Default for TailField {
+    fn default() -> Self {
+        TailField {
+            turbulence: DEFAULT_TAIL_TURBULENCE,
+            frequency: DEFAULT_TAIL_TURBULENCE_FREQUENCY,
+            wind: Vec3::ZERO,
+        }
+    }
+}
+
+impl TailField {
+    // Скорость сноса частицы, испущенной age секунд назад из точки emitted
+    // (time - текущее время симуляции)
+    pub fn drift_velocity(&self, emitted: Vec3, age: f32, time: f32, strength: f32) -> Vec3 {
+        let mut velocity = self.wind;
+        if self.turbulence * strength != 0.0 {
+            velocity += simulation_noise().turbulence(emitted, time - age, self.frequency) * self.turbulence * strength;
+        }
+        velocity
+    }
+}
+
+// Возраст частицы на расстоянии distance от головы кометы
+fn particle_age(distance: f32, speed: f32) -> f32 {
+    (distance / speed.max(1e-3)).min(MAX_TAIL_PARTICLE_AGE)
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TailRange {
+    comet_id: usize,
+    start: u32,
+    count: u32,
+}
+
+/// Общий пул частиц хвостов комет системы
+#[derive(Debug)]
+pub struct TailParticlePool {
+    budget: usize,
+    field: TailField,
+    positions: Vec<f32>,
+    opacities: Vec<f32>,
+    ranges: Vec<TailRange>,
+    // Комет, получивших меньше частиц, чем нужно, на последнем кадре
+    starved: usize,
+}
+
+impl Default for TailParticlePool {
+    fn default() -> Self {
+        TailParticlePool {
+            budget: DEFAULT_TAIL_PARTICLE_BUDGET,
+            field: TailField::default(),
+            positions: Vec::new(),
+            opacities: Vec::new(),
+            ranges: Vec::new(),
+            starved: 0,
+        }
+    }
+}
+
+impl TailParticlePool {
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget.min(MAX_TAIL_PARTICLE_BUDGET);
+    }
+
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    pub fn field(&self) -> TailField {
+        self.field
+    }
+
+    pub fn set_field(&mut self, field: TailField) {
+        self.field = field;
+    }
+
+    pub fn len(&self) -> usize {
+        self.opacities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.opacities.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.positions.clear();
+        self.opacities.clear();
+        self.ranges.clear();
+        self.starved = 0;
+    }
+
+    // Построить хвосты комет системы в пределах бюджета
+    pub fn rebuild(&mut self, system: &SpaceObjectSystem) {
+        self.clear();
+
+        let space = &system.space;
+        let per_comet = system.particle_count(TAIL_PARTICLES_PER_COMET);
+        // Общий бюджет частиц системы может урезать бюджет хвостов
+        let budget = system
+            .particle_budget
+            .allocation(ParticleSubsystem::Tails)
+            .map_or(self.budget, |allocation| allocation.min(self.budget));
+        let length_scale = system.warp.multiplier() * system.power_profile.budget().tail_scale;
+        let time = system.sim_time as f32;
+
+        // Кандидаты: (не видна, расстояние до наблюдателя, комета) - по возрастанию
+        let mut candidates: Vec<(bool, f32, &NeonComet)> = system
+            .get_objects()
+            .get(&SpaceObjectType::NeonComet)
+            .into_iter()
+            .flatten()
+            .filter_map(|obj| obj.as_any().downcast_ref::<NeonComet>())
+            .filter(|comet| !comet.waiting_for_respawn && !space.is_occluded(&comet.get_data().position))
+            .map(|comet| {
+                let data = comet.get_data();
+                (!comet.is_visible(space), (data.position - space.observer_position).length(), comet)
+            })
+            .collect();
+        candidates.sort_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then(a.1.total_cmp(&b.1))
+                .then(a.2.get_data().id.cmp(&b.2.get_data().id))
+        });
+
+        for (_, _, comet) in candidates {
+            let count = per_comet.min(budget - self.len());
+            if count < per_comet {
+                self.starved += 1;
+            }
+            if count == 0 {
+                continue;
+            }
+
+            let data = comet.get_data();
+            let direction = -data.velocity.normalize_or_zero();
+            let speed = data.velocity.length();
+            let length = comet.max_trail_length * length_scale;
+            let start = self.len() as u32;
+
+            // Скорости сноса в опорных точках хвоста
+            let drift: [Vec3; TAIL_NOISE_SAMPLES] = std::array::from_fn(|k| {
+                let distance = length * k as f32 / (TAIL_NOISE_SAMPLES - 1) as f32;
+                let emitted = data.position + direction * distance;
+                self.field.drift_velocity(emitted, particle_age(distance, speed), time, comet.tail_turbulence)
+            });
+
+            for i in 1..=count {
+                // Частицы равномерно по длине хвоста, к концу - прозрачнее
+                let t = i as f32 / per_comet as f32;
+                let segment = t * (TAIL_NOISE_SAMPLES - 1) as f32;
+                let k = (segment as usize).min(TAIL_NOISE_SAMPLES - 2);
+                let velocity = drift[k].lerp(drift[k + 1], segment - k as f32);
+                let position: Vec3 = data.position + direction * length * t + velocity * particle_age(length * t, speed);
+                self.positions.extend_from_slice(&position.to_array());
+                self.opacities.push(data.opacity * (1.0 - t));
+            }
+            self.ranges.push(TailRange { comet_id: data.id, start, count: count as u32 });
+        }
+    }
+
+    // Занятая память буферов частиц: (частиц, емкость, байт занято, байт зарезервировано)
+    pub fn memory_usage(&self) -> (usize, usize, usize, usize) {
+        let float = std::mem::size_of::<f32>();
+        let range = std::mem::size_of::<TailRange>();
+        (
+            self.opacities.len(),
+            self.opacities.capacity(),
+            (self.positions.len() + self.opacities.len()) * float + self.ranges.len() * range,
+            (self.positions.capacity() + self.opacities.capacity()) * float + self.ranges.capacity() * range,
+        )
+    }
+}
+
+/// Частицы хвостов: общий пул и диапазоны комет в нем
+#[wasm_bindgen]
+pub struct TailParticleArray {
+    comet_ids: Vec<usize>,
+    range_starts: Vec<u32>,
+    range_counts: Vec<u32>,
+    positions: Vec<f32>,
+    opacities: Vec<f32>,
+    starved: usize,
+}
+
+#[wasm_bindgen]
+impl TailParticleArray {
+    #[wasm_bindgen(getter)]
+    pub fn comet_ids(&self) -> Vec<usize> {
+        self.comet_ids.clone()
+    }
+
+    // Индекс первой частицы хвоста кометы comet_ids[i] в пуле
+    #[wasm_bindgen(getter)]
+    pub fn range_starts(&self) -> Vec<u32> {
+        self.range_starts.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn range_counts(&self) -> Vec<u32> {
+        self.range_counts.clone()
+    }
+
+    // Позиции частиц пула (x, y, z)
+    #[wasm_bindgen(getter)]
+    pub fn positions(&self) -> Vec<f32> {
+        self.positions.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn opacities(&self) -> Vec<f32> {
+        self.opacities.clone()
+    }
+
+    // Комет с урезанным или отсутствующим из-за бюджета хвостом
+    #[wasm_bindgen(getter)]
+    pub fn starved(&self) -> usize {
+        self.starved
+    }
+}
+
+// Поле сил хвостов: сила турбулентности (единиц в секунду, 0 - без
+// завихрений), пространственная частота шума и ветер (единиц в секунду)
+#[wasm_bindgen]
+pub fn set_tail_field(system_id: usize, turbulence: f32, frequency: f32, wind: &Vec3Wrapper) -> OpResult {
+    let wind: Vec3 = wind.clone().into();
+    if !turbulence.is_finite() || turbulence < 0.0 || !frequency.is_finite() || frequency <= 0.0 || !wind.is_finite() {
+        return OpResult::invalid_argument("tail field requires non-negative turbulence, positive frequency and finite wind");
+    }
+
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.tail_particles.set_field(TailField { turbulence, frequency, wind });
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Множитель турбулентности хвоста отдельной кометы (1.0 - как у системы)
+#[wasm_bindgen]
+pub fn set_comet_tail_turbulence(system_id: usize, comet_id: usize, strength: f32) -> OpResult {
+    if !strength.is_finite() || strength < 0.0 {
+        return OpResult::invalid_argument("tail turbulence strength must be non-negative and finite");
+    }
+
+    let Some(mut system) = SPACE_OBJECT_SYSTEMS.get_mut(&system_id) else {
+        return OpResult::system_not_found(system_id);
+    };
+    let comet = system
+        .get_objects_mut()
+        .get_mut(&SpaceObjectType::NeonComet)
+        .into_iter()
+        .flatten()
+        .filter_map(|obj| obj.as_any_mut().downcast_mut::<NeonComet>())
+        .find(|comet| comet.data.id == comet_id);
+    match comet {
+        Some(comet) => {
+            comet.tail_turbulence = strength;
+            OpResult::success()
+        }
+        None => OpResult::not_found(format!("comet {} not found", comet_id)),
+    }
+}
+
+// Общий бюджет частиц хвостов системы
+#[wasm_bindgen]
+pub fn set_tail_particle_budget(system_id: usize, budget: usize) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.tail_particles.set_budget(budget);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_tail_particles(system_id: usize) -> Option<TailParticleArray> {
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id)?;
+    let pool = &system.tail_particles;
+    Some(TailParticleArray {
+        comet_ids: pool.ranges.iter().map(|range| range.comet_id).collect(),
+        range_starts: pool.ranges.iter().map(|range| range.start).collect(),
+        range_counts: pool.ranges.iter().map(|range| range.count).collect(),
+        positions: pool.positions.clone(),
+        opacities: pool.opacities.clone(),
+        starved: pool.starved,
+    })
+}
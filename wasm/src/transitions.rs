@@ -0,0 +1,188 @@
+/*
+ * transitions.rs
+ *
+ * Хореография переходов между страницами SPA. Переход временно
+ * накладывает на траектории объектов смещение (объекты продолжают
+ * двигаться по своим траекториям, а смещение прибавляется сверху):
+ *
+ * - SweepLeft: сцена уезжает влево и возвращается справа;
+ * - ConvergeToCenter: объекты стягиваются к центру экрана и расходятся;
+ * - ExplodeThroughPlane: объекты разлетаются от центра и проходят сквозь
+ *   видовую плоскость к наблюдателю.
+ *
+ * Огибающая смещения равна нулю в начале и в конце перехода, поэтому
+ * после завершения объекты оказываются на своих траекториях без скачка.
+ * О завершении сообщает очередь take_completed_transitions.
+ */
+
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use wasm_bindgen::prelude::*;
+use glam::{Vec2, Vec3};
+
+use crate::easing::EasingCurve;
+use crate::space_core::SpaceDefinition;
+use crate::space_objects::{SpaceObject, SPACE_OBJECT_SYSTEMS};
+
+// Насколько объекты выходят за плоскость к наблюдателю при "взрыве"
+// (доля расстояния от плоскости до наблюдателя)
+const EXPLODE_THROUGH_DEPTH: f32 = 0.8;
+
+/// Вид перехода
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionKind {
+    SweepLeft = 0,
+    ConvergeToCenter = 1,
+    ExplodeThroughPlane = 2,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ActiveTransition {
+    id: u32,
+    kind: TransitionKind,
+    duration: f32,
+    elapsed: f32,
+}
+
+/// Проигрыватель переходов системы
+#[derive(Clone, Debug, Default)]
+pub struct TransitionPlayer {
+    active: Option<ActiveTransition>,
+    // Смещения, примененные к объектам на прошлом кадре
+    applied: HashMap<usize, Vec3>,
+    completed: Vec<u32>,
+    next_id: u32,
+}
+
+impl TransitionPlayer {
+    // Начать переход; незавершенный предыдущий считается завершенным
+    pub fn play(&mut self, kind: TransitionKind, duration: f32) -> u32 {
+        if let Some(previous) = self.active.take() {
+            self.completed.push(previous.id);
+        }
+
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.active = Some(ActiveTransition {
+            id,
+            kind,
+            duration: duration.max(1e-3),
+            elapsed: 0.0,
+        });
+        id
+    }
+
+    pub fn progress(&self) -> Option<f32> {
+        self.active.map(|t| (t.elapsed / t.duration).clamp(0.0, 1.0))
+    }
+
+    pub fn take_completed(&mut self) -> Vec<u32> {
+        std::mem::take(&mut self.completed)
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.active.is_none() && self.applied.is_empty()
+    }
+
+    // Продвинуть переход на dt и обновить смещения объектов
+    pub fn apply<'a>(&mut self, objects: impl Iterator<Item = &'a mut Box<dyn SpaceObject>>, space: &SpaceDefinition, dt: f32) {
+        let state = self.active.map(|mut transition| {
+            transition.elapsed += dt;
+            transition
+        });
+
+        let finished = state.is_some_and(|t| t.elapsed >= t.duration);
+        let mut applied = HashMap::with_capacity(self.applied.len());
+
+        for obj in objects {
+            let waiting = obj.is_waiting_for_respawn();
+            let data = obj.get_data_mut();
+
+            // Снимаем смещение прошлого кадра (кроме респауна - позиция уже новая)
+            if let Some(previous) = self.applied.get(&data.id) {
+                if !waiting {
+                    data.position -= *previous;
+                }
+            }
+
+            let Some(transition) = state.filter(|_| !finished && !waiting) else {
+                continue;
+            };
+
+            let offset = transition_offset(transition.kind, transition.elapsed / transition.duration, data.position, space);
+            data.position += offset;
+            applied.insert(data.id, offset);
+        }
+
+        self.applied = applied;
+        self.active = state.filter(|_| !finished);
+        if finished {
+            if let Some(transition) = state {
+                self.completed.push(transition.id);
+            }
+        }
+    }
+}
+
+// Смещение объекта в точке перехода progress (0..1) от его собственной позиции
+fn transition_offset(kind: TransitionKind, progress: f32, position: Vec3, space: &SpaceDefinition) -> Vec3 {
+    let p = progress.clamp(0.0, 1.0);
+    let envelope = (PI * p).sin();
+    let viewport = space.get_viewport_dimensions();
+    let center = space.observer_position.truncate();
+
+    match kind {
+        TransitionKind::SweepLeft => {
+            // Уход влево за первую половину, возвращение справа за вторую
+            let shift = if p < 0.5 {
+                -EasingCurve::EaseInQuad.apply(p * 2.0)
+            } else {
+                1.0 - EasingCurve::EaseOutQuad.apply(p * 2.0 - 1.0)
+            };
+            Vec3::new(shift * viewport.x, 0.0, 0.0)
+        }
+        TransitionKind::ConvergeToCenter => {
+            let to_center = center - position.truncate();
+            (to_center * envelope).extend(0.0)
+        }
+        TransitionKind::ExplodeThroughPlane => {
+            let radial = (position.truncate() - center).try_normalize().unwrap_or(Vec2::X);
+            let outward = radial * viewport.x * 0.5 * envelope;
+            // От текущей глубины до точки между плоскостью и наблюдателем
+            let behind_plane = (position.z - space.viewing_plane_z).max(0.0);
+            let in_front = (space.viewing_plane_z - space.observer_position.z) * EXPLODE_THROUGH_DEPTH;
+            outward.extend(-(behind_plane + in_front) * envelope)
+        }
+    }
+}
+
+// Запустить переход длительностью duration секунд; возвращает ID перехода
+#[wasm_bindgen]
+pub fn play_transition(system_id: usize, kind: TransitionKind, duration: f32) -> Option<u32> {
+    if !duration.is_finite() {
+        return None;
+    }
+
+    SPACE_OBJECT_SYSTEMS
+        .get_mut(&system_id)
+        .map(|mut system| system.transitions.play(kind, duration))
+}
+
+// Прогресс текущего перехода (0..1); None - перехода нет
+#[wasm_bindgen]
+pub fn get_transition_progress(system_id: usize) -> Option<f32> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .and_then(|system| system.transitions.progress())
+}
+
+// ID переходов, завершившихся с прошлого вызова (событие завершения)
+#[wasm_bindgen]
+pub fn take_completed_transitions(system_id: usize) -> Vec<u32> {
+    SPACE_OBJECT_SYSTEMS
+        .get_mut(&system_id)
+        .map(|mut system| system.transitions.take_completed())
+        .unwrap_or_default()
+}
@@ -1,23 +1,47 @@
+use std::sync::{Mutex, MutexGuard, Once};
+
 use wasm_bindgen::prelude::*;
 
+use crate::crash;
+use crate::diagnostics;
+use crate::platform;
+
 // Функция для улучшения отображения ошибок Rust в консоли
 pub fn set_panic_hook() {
-    // When the `console_error_panic_hook` feature is enabled, we can call the
-    // `set_panic_hook` function to get better error messages if we ever panic.
-    #[cfg(feature = "console_error_panic_hook")]
-    console_error_panic_hook::set_once();
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        // When the `console_error_panic_hook` feature is enabled, we can call the
+        // `set_panic_hook` function to get better error messages if we ever panic.
+        #[cfg(feature = "console_error_panic_hook")]
+        console_error_panic_hook::set_once();
+
+        // Поверх стандартного вывода - отчет для JS-обработчика (crash.rs)
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            previous(info);
+            crash::report_panic(info);
+        }));
+    });
+}
+
+// Взять блокировку общего состояния. Если она отравлена паникой в другом
+// вызове, данные восстанавливаются как есть: лучше продолжить с возможно
+// неполным шагом, чем навсегда отключить подсистему до перезагрузки страницы
+pub(crate) fn lock_or_recover<'a, T>(mutex: &'a Mutex<T>, name: &'static str) -> MutexGuard<'a, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        mutex.clear_poison();
+        diagnostics::record_lock_recovery(name);
+        poisoned.into_inner()
+    })
 }
 
 // Функция для измерения производительности
 #[wasm_bindgen]
 pub fn measure_performance(callback: &js_sys::Function) -> Result<f64, JsValue> {
-    let window = web_sys::window().unwrap();
-    let performance = window.performance().unwrap();
-    
-    let start = performance.now();
+    let start = platform::monotonic_ms();
     let this = JsValue::NULL;
     let _ = callback.call0(&this)?;
-    let end = performance.now();
+    let end = platform::monotonic_ms();
     
     Ok(end - start)
 } 
\ No newline at end of file
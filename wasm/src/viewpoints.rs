@@ -0,0 +1,194 @@
+/*
+ * viewpoints.rs
+ *
+ * Закладки камеры для экскурсий по сцене. save_viewpoint запоминает под
+ * именем текущее положение наблюдателя, угол обзора и поворот взгляда
+ * свободного полета; fly_to_viewpoint плавно переводит наблюдателя к
+ * закладке за заданное время. Положение, угол обзора и взгляд
+ * интерполируются по кривой VIEWPOINT_EASING, поэтому полет начинается и
+ * заканчивается без рывка.
+ *
+ * О прибытии сообщает очередь take_viewpoint_arrivals (ID полетов, как у
+ * переходов). Полет, прерванный новым полетом или вводом свободного полета,
+ * прибытием не считается.
+ */
+
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+use glam::{Vec2, Vec3};
+
+use crate::easing::EasingCurve;
+use crate::errors::OpResult;
+use crate::space_core::SpaceDefinition;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+// Кривая полета между закладками
+const VIEWPOINT_EASING: EasingCurve = EasingCurve::EaseInOutQuad;
+
+/// Сохраненная точка зрения
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewpoint {
+    pub position: Vec3,
+    pub field_of_view: f32,
+    pub look: Vec2,
+}
+
+impl Viewpoint {
+    pub fn capture(space: &SpaceDefinition) -> Self {
+        Viewpoint {
+            position: space.observer_position,
+            field_of_view: space.field_of_view,
+            look: space.navigation.look,
+        }
+    }
+
+    fn lerp(&self, other: &Viewpoint, t: f32) -> Viewpoint {
+        Viewpoint {
+            position: self.position.lerp(other.position, t),
+            field_of_view: self.field_of_view + (other.field_of_view - self.field_of_view) * t,
+            look: self.look.lerp(other.look, t),
+        }
+    }
+
+    fn apply(&self, space: &mut SpaceDefinition) {
+        space.observer_position = self.position;
+        space.field_of_view = self.field_of_view;
+        space.navigation.look = self.look;
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Flight {
+    id: u32,
+    from: Viewpoint,
+    to: Viewpoint,
+    duration: f32,
+    elapsed: f32,
+}
+
+/// Закладки системы и текущий полет к одной из них
+#[derive(Clone, Debug, Default)]
+pub struct Viewpoints {
+    saved: HashMap<String, Viewpoint>,
+    flight: Option<Flight>,
+    arrivals: Vec<u32>,
+    next_id: u32,
+}
+
+impl Viewpoints {
+    pub fn save(&mut self, name: &str, viewpoint: Viewpoint) {
+        self.saved.insert(name.to_string(), viewpoint);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Viewpoint> {
+        self.saved.get(name).copied()
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.saved.remove(name).is_some()
+    }
+
+    // Начать полет от from к закладке name; None - закладки нет
+    pub fn fly_to(&mut self, name: &str, from: Viewpoint, duration: f32) -> Option<u32> {
+        let to = self.get(name)?;
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.flight = Some(Flight {
+            id,
+            from,
+            to,
+            duration: duration.max(1e-3),
+            elapsed: 0.0,
+        });
+        Some(id)
+    }
+
+    pub fn cancel(&mut self) {
+        self.flight = None;
+    }
+
+    pub fn progress(&self) -> Option<f32> {
+        self.flight.map(|flight| (flight.elapsed / flight.duration).clamp(0.0, 1.0))
+    }
+
+    pub fn take_arrivals(&mut self) -> Vec<u32> {
+        std::mem::take(&mut self.arrivals)
+    }
+
+    // Продвинуть полет на dt и перенести наблюдателя
+    pub fn update(&mut self, space: &mut SpaceDefinition, dt: f32) {
+        let Some(flight) = self.flight.as_mut() else {
+            return;
+        };
+
+        flight.elapsed += dt;
+        let t = VIEWPOINT_EASING.apply(flight.elapsed / flight.duration);
+        flight.from.lerp(&flight.to, t).apply(space);
+        // Накопленная скорость свободного полета не должна уводить с траектории
+        space.navigation.velocity = Vec3::ZERO;
+        space.navigation.look_velocity = Vec2::ZERO;
+
+        if flight.elapsed >= flight.duration {
+            self.arrivals.push(flight.id);
+            self.flight = None;
+        }
+    }
+}
+
+// Запомнить текущую точку зрения под именем name (существующая перезаписывается)
+#[wasm_bindgen]
+pub fn save_viewpoint(system_id: usize, name: &str) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            let viewpoint = Viewpoint::capture(&system.space);
+            system.viewpoints.save(name, viewpoint);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn remove_viewpoint(system_id: usize, name: &str) -> OpResult {
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            if system.viewpoints.remove(name) {
+                OpResult::success()
+            } else {
+                OpResult::not_found(format!("viewpoint '{}' not found", name))
+            }
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+// Полет к закладке name за duration секунд; возвращает ID полета
+// (None - нет системы или закладки)
+#[wasm_bindgen]
+pub fn fly_to_viewpoint(system_id: usize, name: &str, duration: f32) -> Option<u32> {
+    if !duration.is_finite() {
+        return None;
+    }
+
+    let mut system = SPACE_OBJECT_SYSTEMS.get_mut(&system_id)?;
+    let from = Viewpoint::capture(&system.space);
+    system.viewpoints.fly_to(name, from, duration)
+}
+
+// Прогресс текущего полета (0..1); None - полета нет
+#[wasm_bindgen]
+pub fn get_viewpoint_flight_progress(system_id: usize) -> Option<f32> {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .and_then(|system| system.viewpoints.progress())
+}
+
+// ID полетов, завершившихся прибытием с прошлого вызова
+#[wasm_bindgen]
+pub fn take_viewpoint_arrivals(system_id: usize) -> Vec<u32> {
+    SPACE_OBJECT_SYSTEMS
+        .get_mut(&system_id)
+        .map(|mut system| system.viewpoints.take_arrivals())
+        .unwrap_or_default()
+}
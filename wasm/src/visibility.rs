@@ -0,0 +1,100 @@
+/*
+ * visibility.rs
+ * 
+ * Обработка скрытой вкладки браузера. Пока вкладка в фоне, requestAnimationFrame
+ * не вызывается, и первый кадр после возвращения приносит огромный dt.
+ * Политика догоняния определяет, что делать с этим разрывом: пропустить его,
+ * ускоренно промотать симуляцию (с ограничением) или держать сцену на паузе.
+ */
+
+use wasm_bindgen::prelude::*;
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+use crate::utils::lock_or_recover;
+
+// Максимальное время, которое проматывается политикой FastForward (сек)
+pub(crate) const MAX_CATCH_UP_SECONDS: f32 = 2.0;
+
+/// Политика догоняния после возвращения во вкладку
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CatchUpPolicy {
+    Skip = 0,        // Разрыв отбрасывается, сцена продолжает с того же места
+    FastForward = 1, // Разрыв проматывается мелкими шагами (не больше MAX_CATCH_UP_SECONDS)
+    Pause = 2,       // Пока вкладка скрыта, симуляция стоит; разрыв отбрасывается
+}
+
+struct VisibilityState {
+    hidden: bool,
+    policy: CatchUpPolicy,
+    spawns_resume_pending: bool, // Очередь появлений еще не видела кадр после возвращения
+}
+
+static VISIBILITY: Lazy<Mutex<VisibilityState>> = Lazy::new(|| Mutex::new(VisibilityState {
+    hidden: false,
+    policy: CatchUpPolicy::Skip,
+    spawns_resume_pending: false,
+}));
+
+// Текущее состояние видимости и политика догоняния
+pub(crate) fn visibility_state() -> (bool, CatchUpPolicy) {
+    let state = lock_or_recover(&VISIBILITY, "VISIBILITY");
+    (state.hidden, state.policy)
+}
+
+// Шаг для очереди появлений с учетом видимости: None - очередь сейчас не обрабатывается
+pub(crate) fn spawn_queue_dt(dt: f32) -> Option<f32> {
+    let mut state = lock_or_recover(&VISIBILITY, "VISIBILITY");
+    
+    if state.hidden && state.policy == CatchUpPolicy::Pause {
+        return None;
+    }
+    
+    if state.spawns_resume_pending {
+        state.spawns_resume_pending = false;
+        return Some(match state.policy {
+            CatchUpPolicy::FastForward => dt.min(MAX_CATCH_UP_SECONDS),
+            CatchUpPolicy::Skip | CatchUpPolicy::Pause => 0.0,
+        });
+    }
+    
+    Some(dt)
+}
+
+// Сообщить о смене видимости вкладки (document.hidden)
+#[wasm_bindgen]
+pub fn notify_visibility_change(hidden: bool) {
+    let resumed = {
+        let mut state = lock_or_recover(&VISIBILITY, "VISIBILITY");
+        let resumed = state.hidden && !hidden;
+        state.hidden = hidden;
+        if resumed {
+            state.spawns_resume_pending = true;
+        }
+        resumed
+    };
+    
+    // Следующий кадр каждой системы будет обработан по политике догоняния
+    if resumed {
+        for mut system in SPACE_OBJECT_SYSTEMS.iter_mut() {
+            system.resume_pending = true;
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_catch_up_policy(policy: CatchUpPolicy) {
+    lock_or_recover(&VISIBILITY, "VISIBILITY").policy = policy;
+}
+
+#[wasm_bindgen]
+pub fn get_catch_up_policy() -> CatchUpPolicy {
+    lock_or_recover(&VISIBILITY, "VISIBILITY").policy
+}
+
+#[wasm_bindgen]
+pub fn is_simulation_hidden() -> bool {
+    lock_or_recover(&VISIBILITY, "VISIBILITY").hidden
+}
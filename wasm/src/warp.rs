@@ -0,0 +1,113 @@
+/*
+ * warp.rs
+ *
+ * "Прыжок в гиперпространство" при навигации по страницам: множитель
+ * скорости по оси Z плавно растет до целевого значения, а затем так же
+ * плавно возвращается к 1 - без дополнительных вызовов со стороны JS.
+ *
+ * Скорости объектов не меняются: проход warp добавляет к шагу объекта
+ * смещение по Z, соответствующее множителю, поэтому разгон, ограничения
+ * скорости и воспроизводимость симуляции не затрагиваются. Хвосты комет
+ * удлиняются тем же множителем при экспорте.
+ */
+
+use wasm_bindgen::prelude::*;
+
+use crate::easing::EasingCurve;
+use crate::errors::OpResult;
+use crate::space_objects::SPACE_OBJECT_SYSTEMS;
+
+// Ограничение целевого множителя скорости
+const MAX_WARP_MULTIPLIER: f32 = 20.0;
+
+/// Состояние разгона: рост до целевого множителя и возврат к 1
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WarpRamp {
+    target: f32,
+    ramp_seconds: f32,
+    elapsed: f32,
+    active: bool,
+}
+
+impl WarpRamp {
+    pub fn start(&mut self, target: f32, ramp_seconds: f32) {
+        // Начинаем с текущего множителя, чтобы повторный старт не давал скачка
+        let current = self.multiplier();
+        self.target = target.clamp(0.0, MAX_WARP_MULTIPLIER);
+        self.ramp_seconds = ramp_seconds.max(1e-3);
+        self.active = true;
+
+        let span = self.target - 1.0;
+        let progress = if span.abs() > 1e-6 { ((current - 1.0) / span).clamp(0.0, 1.0) } else { 0.0 };
+        self.elapsed = inverse_smoothstep(progress) * self.ramp_seconds;
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        if !self.active {
+            return;
+        }
+
+        self.elapsed += dt;
+        if self.elapsed >= self.ramp_seconds * 2.0 {
+            self.active = false;
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    // Текущий множитель скорости по Z (1 - без разгона)
+    pub fn multiplier(&self) -> f32 {
+        if !self.active {
+            return 1.0;
+        }
+
+        let phase = self.elapsed / self.ramp_seconds;
+        let level = if phase < 1.0 { phase } else { 2.0 - phase };
+        1.0 + (self.target - 1.0) * EasingCurve::Smoothstep.apply(level)
+    }
+}
+
+// Обратная функция smoothstep на 0..1 (численно)
+fn inverse_smoothstep(value: f32) -> f32 {
+    let (mut low, mut high) = (0.0f32, 1.0f32);
+    for _ in 0..20 {
+        let mid = (low + high) * 0.5;
+        if EasingCurve::Smoothstep.apply(mid) < value {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) * 0.5
+}
+
+// Начать разгон: множитель растет до target за ramp_seconds и затем
+// за то же время возвращается к 1
+#[wasm_bindgen]
+pub fn start_warp(system_id: usize, target_speed_multiplier: f32, ramp_seconds: f32) -> OpResult {
+    if !target_speed_multiplier.is_finite() || !ramp_seconds.is_finite() {
+        return OpResult::invalid_argument("warp multiplier and ramp time must be finite");
+    }
+
+    match SPACE_OBJECT_SYSTEMS.get_mut(&system_id) {
+        Some(mut system) => {
+            system.warp.start(target_speed_multiplier, ramp_seconds);
+            OpResult::success()
+        }
+        None => OpResult::system_not_found(system_id),
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_warp_multiplier(system_id: usize) -> Option<f32> {
+    SPACE_OBJECT_SYSTEMS.get(&system_id).map(|system| system.warp.multiplier())
+}
+
+#[wasm_bindgen]
+pub fn is_warping(system_id: usize) -> bool {
+    SPACE_OBJECT_SYSTEMS
+        .get(&system_id)
+        .is_some_and(|system| system.warp.is_active())
+}
@@ -0,0 +1,52 @@
+// Регрессионные тесты "золотого" состояния симуляции.
+// Запуск: cargo test --features golden
+// Обновление хешей после намеренного изменения поведения:
+// GOLDEN_BLESS=1 cargo test --features golden -- --nocapture
+#![cfg(feature = "golden")]
+
+use hypercube_wasm::golden::{run_steps, seeded_scene, state_hash};
+
+const DT: f32 = 1.0 / 60.0;
+
+// (seed, количество комет, количество шагов, ожидаемый хеш)
+const GOLDEN_SCENES: &[(u64, usize, usize, u64)] = &[
+    (1, 8, 60, 0x866482f0d9fd0c73),
+    (42, 16, 120, 0x3fca2b17edb640ca),
+    (2024, 32, 150, 0x9d850b2b5503b7a3),
+];
+
+#[test]
+fn golden_state_hashes_match() {
+    let bless = std::env::var_os("GOLDEN_BLESS").is_some();
+
+    for &(seed, comets, steps, expected) in GOLDEN_SCENES {
+        let mut system = seeded_scene(seed, comets);
+        run_steps(&mut system, steps, DT);
+        let hash = state_hash(&system);
+
+        if bless {
+            println!("    ({}, {}, {}, {:#018x}),", seed, comets, steps, hash);
+        } else {
+            assert_eq!(hash, expected, "golden hash changed for seed {}", seed);
+        }
+    }
+}
+
+#[test]
+fn seeded_scene_is_deterministic() {
+    let mut first = seeded_scene(7, 12);
+    let mut second = seeded_scene(7, 12);
+    run_steps(&mut first, 90, DT);
+    run_steps(&mut second, 90, DT);
+    assert_eq!(state_hash(&first), state_hash(&second));
+}
+
+#[test]
+fn respawns_are_reproducible() {
+    // 20 секунд симуляции: кометы успевают вылететь за границы и возродиться
+    let mut first = seeded_scene(11, 12);
+    let mut second = seeded_scene(11, 12);
+    run_steps(&mut first, 1200, DT);
+    run_steps(&mut second, 1200, DT);
+    assert_eq!(state_hash(&first), state_hash(&second));
+}
@@ -0,0 +1,2549 @@
+// Headless-тесты: симуляция работает нативно, без браузера и web_sys::window()
+
+use hypercube_wasm::{
+    create_space_object_system, get_active_neon_comets_count, get_frame_stats,
+    get_visible_neon_comets, process_neon_comet_spawns, spawn_neon_comet, spawn_neon_comets,
+    get_object_deltas, get_system_frame,
+    update_space_object_system, reseed_space_object_system, SpaceDefinition,
+    decode_state, encode_scene_state,
+    export_gltf,
+    query_entities,
+    get_update_graph_dot, get_update_graph_order,
+    add_parallax_layer, get_parallax_offsets, set_observer_position,
+    eye_frustums,
+    set_depth_of_field, set_fog_density,
+    add_occlusion_box, Vec3Wrapper,
+    push_speed_modifier, pop_modifier, get_modifier_count,
+    PlaneCrossing, SpaceObjectType, ImpactAccumulator, get_camera_impulse, MAX_CAMERA_IMPULSE,
+    CometEffect, CometEffectKind, CometEffectPool, MAX_COMET_EFFECTS, get_comet_effects,
+    CrossingDeduplicator, CROSSING_DEDUP_WINDOW,
+    evaluate_curve, evaluate_easing, create_bezier_curve, create_spring_curve, EasingCurve,
+    start_warp, get_warp_multiplier, is_warping,
+    play_transition, get_transition_progress, take_completed_transitions, TransitionKind,
+    set_screen_size, hit_test,
+    select_object, clear_selection, get_selected_object,
+    get_population_stats,
+    set_diagnostics_mode, get_diagnostics_stats, log_limited,
+    CrossingType,
+    PortalGlow, PORTAL_GLOW_GRID, get_portal_glow,
+    get_scene_readiness,
+    despawn_all, get_despawn_progress, take_completed_despawns,
+    predict_object_path, MAX_PREDICTED_PATH_STEPS, get_upcoming_crossings, MAX_UPCOMING_CROSSING_HORIZON,
+    SPACE_OBJECT_SYSTEMS, Hypercube, MAX_EDGE_SEGMENTS, Noise4D, MAX_FRACTAL_OCTAVES,
+    bind_hypercube_to_scene, unbind_hypercube_from_scene, get_scene_modulation, NeonComet,
+    track_hypercube_crossings, get_plane_crossings,
+    check_points_in_space, check_points_in_view_frustum, calculate_distances_to_viewing_plane, get_plane_corners,
+    set_plane_style, set_scroll_offset, get_plane_style, ViewingPlane, ViewingPlanes, MAIN_PLANE_INDEX,
+    spawn_energy_spheres, get_energy_spheres, VORTEX_PARTICLES, sample_distortion, sample_distortion_grid,
+    take_lifecycle_events, LifecycleEventKind, LifecycleReason, ErrorCode, init, get_last_crash_info,
+    get_module_info, get_state_stream_version, API_LEVEL, create_system_async, continue_init, get_init_progress,
+    get_minimal_state, mark_all_dirty, init_world, step_simulation, enable_impact_debris, disable_impact_debris, get_impact_debris,
+    sync_plane_to_physics, unsync_plane_from_physics, get_plane_collider_center, PhysicsSyncMode,
+    set_object_sleeping, get_sleep_skipped_total,
+    get_packed_neon_comets, PackedColorFormat, f32_to_f16, f16_to_f32, to_unorm8,
+    set_morton_ordering, get_morton_stats, morton_code,
+    FramePacer, drive_frame, is_frame_driver_running, stop_frame_driver, get_frame_driver_frames,
+    notify_user_idle, is_user_idle, IDLE_UPDATE_HZ,
+    set_power_profile, get_power_profile, get_power_budget, PowerProfile,
+    set_export_color_space, get_export_color_space, convert_colors, srgb_to_linear, linear_to_srgb, ColorSpace,
+    get_comet_palette, get_sphere_palette,
+    set_tail_particle_budget, get_tail_particles, TAIL_PARTICLES_PER_COMET,
+    arbitrate, set_particle_budget, set_particle_priority, get_particle_budget, ParticleSubsystem,
+    closest_approach, NearMisses, set_near_miss_distance, take_near_miss_events,
+    set_navigation_input, get_observer_velocity, get_view_direction, Vec2Wrapper,
+    save_viewpoint, remove_viewpoint, fly_to_viewpoint, get_viewpoint_flight_progress, take_viewpoint_arrivals,
+    get_observer_position, get_field_of_view,
+    get_scene_bounds, frame_scene, frame_bounds, Bounds,
+    export_silhouette_svg,
+    Clock,
+    encode_sync_packet, create_scene_mirror, apply_sync_packet, update_scene_mirror, get_scene_mirror_state,
+    get_scene_mirror_frame, destroy_scene_mirror,
+    enqueue_command, get_queued_command_count, take_command_log,
+    get_plane_occupancy, octant_index,
+    set_spawn_separation, get_spawn_separation,
+    set_spawn_density_map, clear_spawn_density_map, has_spawn_density_map, SpawnDensityMap,
+    set_protected_screen_rect, clear_protected_screen_rect, crosses_protected_rect, segment_hits_rect,
+    set_tail_field, set_comet_tail_turbulence, ObjectBounds,
+    CrossingStatsRing, get_crossing_stats, heat_cell_rect, MAX_STATS_WINDOW,
+    AnalyticsBatcher, set_analytics_batch_interval, take_analytics_batches, flush_analytics_batch,
+    set_subsystem_enabled, is_subsystem_enabled,
+    presimulate,
+    AccelerationModel, AccelerationProfile, set_acceleration_profile, get_acceleration_profile, get_acceleration_strength,
+    CatchUpPolicy,
+    OpacityProfile, set_opacity_profile, get_opacity_profile,
+    get_memory_report,
+    set_device_orientation, random_position_on_far_plane,
+    create_exponential_curve,
+    add_viewing_plane,
+    create_comet_effect_at_intersection,
+    MAX_SPHERES_PER_SPAWN,
+    get_bound_hypercube,
+    MAX_BATCH_RECORDS,
+};
+use glam::{Vec2, Vec3};
+
+#[test]
+fn comet_system_runs_headless() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 3).ok());
+
+    // Первая группа комет появляется без задержки
+    process_neon_comet_spawns(0.0);
+    assert!(get_active_neon_comets_count(system_id) > 0);
+
+    let before = get_visible_neon_comets(system_id).expect("comet data").positions();
+
+    for _ in 0..30 {
+        process_neon_comet_spawns(1.0 / 60.0);
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+
+    let after = get_visible_neon_comets(system_id).expect("comet data").positions();
+    assert!(!after.is_empty());
+    assert_ne!(before[..3], after[..3], "comets must move between frames");
+}
+
+#[test]
+fn missing_system_is_reported() {
+    assert!(!update_space_object_system(usize::MAX, 0.016).ok());
+    assert!(get_visible_neon_comets(usize::MAX).is_none());
+
+    // Коды ошибок различают причину отказа
+    assert_eq!(update_space_object_system(usize::MAX, 0.016).code(), ErrorCode::SystemNotFound);
+    assert!(!spawn_neon_comets(usize::MAX, 1).message().is_empty());
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert_eq!(start_warp(system_id, f32::NAN, 1.0).code(), ErrorCode::InvalidArgument);
+    assert_eq!(select_object(system_id, usize::MAX).code(), ErrorCode::NotFound);
+    assert_eq!(update_space_object_system(system_id, 0.016).code(), ErrorCode::Ok);
+}
+
+#[test]
+fn viewing_plane_crossing_is_interpolated() {
+    let space = SpaceDefinition::new();
+
+    let (t, point) = space
+        .segment_plane_crossing(&Vec3::new(1.0, 2.0, 10.0), &Vec3::new(1.0, 2.0, -10.0))
+        .expect("crossing toward the observer");
+    assert!((t - 0.5).abs() < 1e-6);
+    assert!(point.z.abs() < 1e-6);
+
+    // Движение от наблюдателя и промах мимо видового экрана не считаются пересечением
+    assert!(space
+        .segment_plane_crossing(&Vec3::new(0.0, 0.0, -10.0), &Vec3::new(0.0, 0.0, 10.0))
+        .is_none());
+    assert!(space
+        .segment_plane_crossing(&Vec3::new(90.0, 0.0, 10.0), &Vec3::new(90.0, 0.0, -10.0))
+        .is_none());
+
+    // В обоих направлениях: тип и нормаль грани - по фактическому смещению
+    let (from, to) = (Vec3::new(0.0, 0.0, -10.0), Vec3::new(0.0, 0.0, 10.0));
+    assert!(space.any_plane_crossing(&from, &to).is_some());
+    assert_eq!(space.crossing_orientation(&(to - from)), (CrossingType::Exit, Vec3::NEG_Z));
+    assert_eq!(space.crossing_orientation(&(from - to)), (CrossingType::Entry, Vec3::Z));
+
+    // Центр плоскости - центр окна; точка выше наблюдателя - в верхней половине окна
+    let center = space.plane_point_to_viewport(space.observer_position.x, space.observer_position.y);
+    assert!((center.x - 0.5).abs() < 1e-6 && (center.y - 0.5).abs() < 1e-6);
+    assert!(space.plane_point_to_viewport(0.0, 10.0).y < 0.5);
+
+    // Звук удара справа от центра панорамируется вправо; быстрый крупный объект - сильный удар
+    let crossing = PlaneCrossing {
+        id: 0,
+        object_id: 0,
+        object_type: SpaceObjectType::NeonComet,
+        plane: 0,
+        position: Vec3::new(5.0, 0.0, 0.0),
+        velocity: Vec3::new(0.0, 0.0, -100.0),
+        size: 60.0,
+        t: 0.5,
+        crossing_type: CrossingType::Entry,
+        normal: Vec3::Z,
+    };
+    let system_id = create_space_object_system(25.0, 60.0);
+    let sound = crossing.sound(&SPACE_OBJECT_SYSTEMS.get(&system_id).expect("system"));
+    assert!(sound.pan > 0.0);
+    assert_eq!(sound.strength_bucket, 2);
+    assert!(sound.volume > 0.0 && sound.volume <= 1.0);
+    assert!((crossing.time_offset(1.0 / 60.0) + 0.5 / 60.0).abs() < 1e-6);
+
+    // Повторное пересечение той же плоскости в пределах окна - дубликат
+    let mut dedup = CrossingDeduplicator::default();
+    let first = dedup.accept(SpaceObjectType::NeonComet, 7, 0).expect("first crossing");
+    assert!(dedup.accept(SpaceObjectType::NeonComet, 7, 0).is_none());
+    assert!(dedup.accept(SpaceObjectType::Hypercube, 7, 0).is_some());
+    dedup.advance(CROSSING_DEDUP_WINDOW);
+    assert!(dedup.accept(SpaceObjectType::NeonComet, 7, 0).is_some_and(|id| id > first));
+
+    // Свечение портала: энергия удара попадает в ячейку точки пересечения и затухает
+    let mut glow = PortalGlow::default();
+    glow.add_crossing(&crossing, &space);
+    let (hottest, peak) = glow.hottest_cell();
+    assert!(peak > 0.0 && (glow.total_energy() - peak).abs() < 1e-6);
+    assert!((hottest - space.plane_uv(&crossing.position)).abs().max_element() <= 0.5 / PORTAL_GLOW_GRID as f32);
+    glow.decay(1.0);
+    assert!(glow.total_energy() < peak);
+}
+
+#[test]
+fn camera_impulse_follows_impacts_and_decays() {
+    let crossing = PlaneCrossing {
+        id: 0,
+        object_id: 0,
+        object_type: SpaceObjectType::NeonComet,
+        plane: 0,
+        position: Vec3::ZERO,
+        velocity: Vec3::new(0.0, 0.0, -100.0),
+        size: 60.0,
+        t: 1.0,
+        crossing_type: CrossingType::Entry,
+        normal: Vec3::Z,
+    };
+
+    // Толчок направлен по движению объекта
+    let mut impacts = ImpactAccumulator::default();
+    impacts.add_crossing(&crossing);
+    let single = impacts.impulse;
+    assert!(single.z < 0.0 && single.x == 0.0 && single.y == 0.0);
+    assert!((single.length() - crossing.impact_strength()).abs() < 1e-5);
+
+    // Затухание экспоненциальное: два полушага равны целому шагу
+    let (mut halves, mut whole) = (impacts.clone(), impacts.clone());
+    halves.decay(0.05);
+    halves.decay(0.05);
+    whole.decay(0.1);
+    assert!((halves.impulse - whole.impulse).length() < 1e-6);
+    assert!(whole.impulse.length() < single.length());
+
+    // Серия ударов не уносит камеру
+    for _ in 0..100 {
+        impacts.add_crossing(&crossing);
+    }
+    assert!(impacts.impulse.length() <= MAX_CAMERA_IMPULSE + 1e-5);
+    assert_eq!(Vec3::from(get_camera_impulse(usize::MAX)), Vec3::ZERO);
+}
+
+#[test]
+fn predicted_path_matches_simulation_and_clamps_steps() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(reseed_space_object_system(system_id, 4127).ok());
+    assert!(enqueue_command(system_id, r#"{"type": "spawn", "object_type": "NeonComet", "count": 3}"#).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    let comets = get_visible_neon_comets(system_id).expect("comets");
+    let (id, start) = (comets.ids()[0], Vec3::from_slice(&comets.positions()[..3]));
+    let path = predict_object_path(system_id, id, 0.5, 30).expect("path");
+    let points = path.points();
+    assert_eq!(points.len(), 31 * 3);
+    assert_eq!(Vec3::from_slice(&points[..3]), start);
+
+    // Предсказание на один кадр совпадает с шагом симуляции
+    let predicted = Vec3::from_slice(&points[3..6]);
+    let expected_step = predicted - start;
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    let comets = get_visible_neon_comets(system_id).expect("comets");
+    let index = comets.ids().iter().position(|comet| *comet == id).expect("same comet");
+    let actual = Vec3::from_slice(&comets.positions()[index * 3..index * 3 + 3]);
+    assert!((actual - predicted).length() <= expected_step.length() * 0.1 + 1e-3);
+
+    // Число шагов из JS ограничено
+    let clamped = predict_object_path(system_id, id, 1.0, usize::MAX).expect("clamped path");
+    assert_eq!(clamped.points().len(), (MAX_PREDICTED_PATH_STEPS + 1) * 3);
+    assert!(predict_object_path(system_id, id, f32::NAN, 10).is_none());
+    assert!(predict_object_path(system_id, id, 1.0, 0).is_none());
+    assert!(predict_object_path(system_id, usize::MAX, 1.0, 10).is_none());
+}
+
+#[test]
+fn upcoming_crossings_predict_real_impacts() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    // Очередь выдает не больше трех комет за кадр - создаем их сразу
+    {
+        let mut system = SPACE_OBJECT_SYSTEMS.get_mut(&system_id).expect("system");
+        for _ in 0..24 {
+            spawn_neon_comet(&mut system);
+        }
+    }
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    // Направления случайны - ждем удара не у самого края плоскости: там турбулентность
+    // может вынести комету за границы пространства раньше пересечения
+    let central = |uvs: &[f32]| uvs.chunks(2).position(|uv| uv.iter().all(|c| (0.1..=0.9).contains(c)));
+    let mut upcoming = get_upcoming_crossings(system_id, 8.0).expect("upcoming");
+    for _ in 0..1200 {
+        if central(&upcoming.uvs()).is_some() {
+            break;
+        }
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+        upcoming = get_upcoming_crossings(system_id, 8.0).expect("upcoming");
+    }
+    let etas = upcoming.etas();
+    assert!(!etas.is_empty());
+    assert_eq!(upcoming.uvs().len(), etas.len() * 2);
+    assert!(etas.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(etas.iter().all(|eta| (0.0..=8.0).contains(eta)));
+    assert!(get_upcoming_crossings(system_id, 0.0).expect("empty horizon").ids().is_empty());
+    assert!(get_upcoming_crossings(system_id, f32::NAN).expect("empty horizon").ids().is_empty());
+
+    // Бесконечный горизонт из JS ограничен и не дает NaN
+    let unbounded = get_upcoming_crossings(system_id, f32::INFINITY).expect("clamped horizon");
+    assert!(!unbounded.etas().is_empty());
+    assert!(unbounded.etas().iter().all(|eta| (0.0..=MAX_UPCOMING_CROSSING_HORIZON).contains(eta)));
+    assert!(unbounded.uvs().iter().all(|uv| uv.is_finite()));
+
+    // Предсказанный удар действительно происходит около eta
+    let index = central(&upcoming.uvs()).expect("central crossing");
+    let (id, eta) = (upcoming.ids()[index], etas[index]);
+    let mut elapsed = 0.0;
+    let mut crossed_at = None;
+    while elapsed < eta + 0.5 && crossed_at.is_none() {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+        elapsed += 1.0 / 60.0;
+        let system = SPACE_OBJECT_SYSTEMS.get(&system_id).expect("system");
+        if system.crossings.iter().any(|crossing| crossing.object_id == id) {
+            crossed_at = Some(elapsed);
+        }
+    }
+    let crossed_at = crossed_at.expect("predicted crossing happened");
+    assert!((crossed_at - eta).abs() < 0.1, "predicted {eta}, crossed at {crossed_at}");
+}
+
+#[test]
+fn schlegel_projection_nests_cells_and_ignores_rotation() {
+    let mut hypercube = Hypercube::new(2.0);
+    assert!(hypercube.project_schlegel(8).is_none());
+
+    // Ячейка w = +1 - внешний куб, противоположная - внутренний, втрое меньше
+    let layout = hypercube.project_schlegel(7).expect("cell");
+    assert_eq!(layout.len(), 16 * 3);
+    for (i, point) in layout.chunks(3).enumerate() {
+        let extent = if i & 8 != 0 { 1.0 } else { 1.0 / 3.0 };
+        assert!(point.iter().all(|c| (c.abs() - extent).abs() < 1e-9), "vertex {i}: {point:?}");
+    }
+
+    // Раскладка привязана к топологии, а не к ориентации
+    hypercube.rotate(0.3, -0.7, 1.1, 0.2, 0.9, -0.4);
+    let rotated = hypercube.project_schlegel(7).expect("cell");
+    assert!(layout.iter().zip(&rotated).all(|(a, b)| (a - b).abs() < 1e-9));
+}
+
+#[test]
+fn fractal_noise_clamps_octaves() {
+    let noise = Noise4D::new(4134);
+    let capped = noise.fractal(0.3, -1.2, 2.5, 0.7, MAX_FRACTAL_OCTAVES);
+
+    // Огромное число октав не зависает и равно максимуму
+    assert_eq!(noise.fractal(0.3, -1.2, 2.5, 0.7, usize::MAX), capped);
+    assert_eq!(noise.fractal(0.3, -1.2, 2.5, 0.7, 0), noise.fractal(0.3, -1.2, 2.5, 0.7, 1));
+    assert_ne!(noise.fractal(0.3, -1.2, 2.5, 0.7, MAX_FRACTAL_OCTAVES - 1), capped);
+    assert!((0.0..=1.0).contains(&capped));
+}
+
+#[test]
+fn hypercube_binding_persists_across_updates_and_respawns() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    // Очередь выдает не больше трех комет за кадр - создаем их сразу
+    {
+        let mut system = SPACE_OBJECT_SYSTEMS.get_mut(&system_id).expect("system");
+        for _ in 0..12 {
+            spawn_neon_comet(&mut system);
+        }
+    }
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    // Цвет каждой активной кометы совпадает с ее палитрой, смещенной на t
+    let assert_shifted = |t: f32| {
+        let system = SPACE_OBJECT_SYSTEMS.get(&system_id).expect("system");
+        let comets = &system.get_objects()[&SpaceObjectType::NeonComet];
+        for obj in comets.iter().filter(|obj| !obj.is_waiting_for_respawn()) {
+            let comet = obj.as_any().downcast_ref::<NeonComet>().expect("comet");
+            let mut expected = comet.clone();
+            expected.shift_palette(t);
+            assert!(
+                comet.color.iter().zip(&expected.color).all(|(a, b)| (a - b).abs() < 1e-5),
+                "comet {}, t {t}: {:?}", comet.data.id, comet.color
+            );
+        }
+    };
+
+    let mut hypercube = Hypercube::new(1.0);
+    hypercube.rotate(0.0, 0.0, 0.6, 0.0, 0.4, 0.0);
+    assert!(bind_hypercube_to_scene(system_id, &hypercube, 2.0).ok());
+    let modulation = get_scene_modulation(system_id).expect("modulation");
+    assert!(modulation.palette_t > 0.05);
+    assert_shifted(modulation.palette_t);
+
+    // Команда palette не перебивает привязанный гиперкуб даже на шаге, где она выполнилась
+    assert!(enqueue_command(system_id, r#"{"type": "palette", "shift": 0.0}"#).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    assert_eq!(get_scene_modulation(system_id).expect("modulation").palette_t, modulation.palette_t);
+    assert_shifted(modulation.palette_t);
+
+    // Пока гиперкуб привязан, его модуляция переживает шаги и респауны
+    for _ in 0..1200 {
+        assert!(update_space_object_system(system_id, 1.0 / 30.0).ok());
+    }
+    let respawned = SPACE_OBJECT_SYSTEMS.get(&system_id).expect("system").get_objects()[&SpaceObjectType::NeonComet]
+        .iter()
+        .any(|obj| obj.as_any().downcast_ref::<NeonComet>().is_some_and(|comet| comet.respawn_count > 0));
+    assert!(respawned);
+    assert_eq!(get_scene_modulation(system_id).expect("modulation").palette_t, modulation.palette_t);
+    assert_shifted(modulation.palette_t);
+
+    // Снятие привязки возвращает нейтральную модуляцию и исходные цвета
+    assert!(unbind_hypercube_from_scene(system_id).ok());
+    assert_eq!(get_scene_modulation(system_id).expect("modulation").spawn_rate, 1.0);
+    assert_shifted(0.0);
+
+    // Без привязки команда palette сдвигает цвета уже на своем шаге
+    assert!(enqueue_command(system_id, r#"{"type": "palette", "shift": 0.3}"#).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    assert_shifted(0.3);
+    assert!(!bind_hypercube_to_scene(usize::MAX, &hypercube, 2.0).ok());
+}
+
+#[test]
+fn rotating_hypercube_reaches_scene_only_after_rebind() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    let mut hypercube = Hypercube::new(1.0);
+    assert!(bind_hypercube_to_scene(system_id, &hypercube, 2.0).ok());
+    let before = get_scene_modulation(system_id).expect("modulation");
+
+    // Привязка хранит снимок: поворот сам по себе сцену не меняет
+    hypercube.rotate(0.0, 0.0, 0.6, 0.0, 0.4, 0.0);
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    assert_eq!(get_scene_modulation(system_id).expect("modulation").palette_t, before.palette_t);
+
+    // Повторная привязка в кадре после поворота обновляет модуляцию
+    assert!(bind_hypercube_to_scene(system_id, &hypercube, 2.0).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    let after = get_scene_modulation(system_id).expect("modulation");
+    assert!((after.palette_t - before.palette_t).abs() > 0.01);
+    assert!(after.spawn_rate != before.spawn_rate);
+}
+
+#[test]
+fn subdivided_edges_follow_perspective_along_w() {
+    let hypercube = Hypercube::new(2.0);
+    let vertices = hypercube.get_projected_vertices(3.0);
+    let edges = hypercube.get_edges();
+
+    let polylines = hypercube.get_projected_edges_subdivided(4, 3.0);
+    assert_eq!(polylines.len(), 32 * 5 * 3);
+    assert_eq!(hypercube.get_projected_edges_subdivided(0, 3.0).len(), 32 * 2 * 3);
+    // Число отрезков из JS ограничено
+    assert_eq!(
+        hypercube.get_projected_edges_subdivided(usize::MAX, 3.0).len(),
+        32 * (MAX_EDGE_SEGMENTS + 1) * 3
+    );
+
+    // Концы ломаных совпадают с проекцией вершин
+    for (edge, polyline) in edges.chunks(2).zip(polylines.chunks(5 * 3)) {
+        let (start, end) = (edge[0] as usize * 3, edge[1] as usize * 3);
+        assert!(polyline[..3].iter().zip(&vertices[start..start + 3]).all(|(a, b)| (a - b).abs() < 1e-9));
+        assert!(polyline[12..].iter().zip(&vertices[end..end + 3]).all(|(a, b)| (a - b).abs() < 1e-9));
+    }
+
+    // Ребро 0-8 идет вдоль W: равные 4D шаги растягиваются по мере приближения к камере
+    let edge = edges.chunks(2).position(|e| e == [0, 8]).expect("w edge");
+    let xs: Vec<f64> = polylines[edge * 15..(edge + 1) * 15].iter().step_by(3).copied().collect();
+    let steps: Vec<f64> = xs.windows(2).map(|p| (p[1] - p[0]).abs()).collect();
+    assert!(steps.windows(2).all(|p| p[1] > p[0]), "{xs:?}");
+    assert!((xs[2] - -1.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn hypercube_shading_lights_along_w_and_fogs_far_cell() {
+    let hypercube = Hypercube::new(2.0);
+    assert!(hypercube.compute_shading(&[0.0, 0.0, 1.0], 3.0).is_none());
+    assert!(hypercube.compute_shading(&[0.0; 4], 3.0).is_none());
+
+    let shading = hypercube.compute_shading(&[0.0, 0.0, 0.0, 2.0], 3.0).expect("shading");
+    let intensities = shading.vertex_intensities();
+    let fog = shading.vertex_fog();
+    assert_eq!((intensities.len(), fog.len()), (16, 16));
+    for i in 0..16 {
+        // Свет вдоль +W: ближняя ячейка освещена, дальняя - только фоновым светом и в тумане
+        let (intensity, haze) = if i & 8 != 0 { (0.625, 0.0) } else { (0.25, 0.5) };
+        assert!((intensities[i] - intensity).abs() < 1e-6, "vertex {i}: {}", intensities[i]);
+        assert!((fog[i] - haze).abs() < 1e-6, "vertex {i}: {}", fog[i]);
+    }
+
+    let faces = shading.face_indices();
+    let face_intensities = shading.face_intensities();
+    assert_eq!((faces.len(), face_intensities.len()), (24 * 4, 24));
+    for (quad, intensity) in faces.chunks(4).zip(&face_intensities) {
+        let w_bits: Vec<bool> = quad.iter().map(|&i| i & 8 != 0).collect();
+        let expected = if w_bits.iter().all(|&b| b) {
+            0.25 + 0.75 * std::f32::consts::FRAC_1_SQRT_2
+        } else {
+            0.25
+        };
+        assert!((intensity - expected).abs() < 1e-5, "face {quad:?}: {intensity}");
+    }
+}
+
+#[test]
+fn drag_rotation_maps_planes_and_coasts_to_rest() {
+    // Освещение вдоль W зависит только от W-координат вершин
+    let w_profile = |hypercube: &Hypercube| hypercube.compute_shading(&[0.0, 0.0, 0.0, 1.0], 3.0).expect("shading").vertex_intensities();
+    let changed = |a: &[f32], b: &[f32]| a.iter().zip(b).any(|(x, y)| (x - y).abs() > 1e-4);
+
+    // Без модификатора вращение в XZ/XY не затрагивает W
+    let mut hypercube = Hypercube::new(2.0);
+    let rest = hypercube.get_projected_vertices(3.0);
+    let w_rest = w_profile(&hypercube);
+    hypercube.apply_drag_rotation(30.0, -20.0, false);
+    assert!(rest.iter().zip(hypercube.get_projected_vertices(3.0)).any(|(a, b)| (a - b).abs() > 1e-4));
+    assert!(!changed(&w_rest, &w_profile(&hypercube)));
+
+    // С модификатором гиперкуб поворачивается через W
+    let mut hypercube = Hypercube::new(2.0);
+    hypercube.apply_drag_rotation(30.0, 0.0, true);
+    assert!(changed(&w_rest, &w_profile(&hypercube)));
+
+    // Инерция продолжает вращение и затухает до остановки
+    let after_drag = w_profile(&hypercube);
+    assert!(hypercube.update_inertia(1.0 / 60.0));
+    assert!(changed(&after_drag, &w_profile(&hypercube)));
+    let mut frames = 0;
+    while hypercube.update_inertia(1.0 / 60.0) {
+        frames += 1;
+        assert!(frames < 60 * 10, "inertia never settled");
+    }
+    let settled = w_profile(&hypercube);
+    assert!(!hypercube.update_inertia(1.0 / 60.0));
+    assert!(!changed(&settled, &w_profile(&hypercube)));
+
+    // stop_inertia останавливает сразу
+    hypercube.apply_drag_rotation(0.0, 50.0, true);
+    hypercube.stop_inertia();
+    assert!(!hypercube.update_inertia(1.0 / 60.0));
+}
+
+#[test]
+fn hypercube_crossings_reach_frame_consumers() {
+    let world_id = init_world();
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(enable_impact_debris(system_id, world_id, 0.0).ok());
+    assert!(set_analytics_batch_interval(system_id, Some(1.0)).ok());
+
+    // Гиперкуб проходит сквозь видовую плоскость к наблюдателю
+    let hypercube = Hypercube::new(1.0);
+    assert_eq!(track_hypercube_crossings(system_id, &hypercube, 3.0, &Vec3Wrapper::new(0.0, 0.0, 2.0), 1.0, 1.0 / 60.0), 0);
+    assert_eq!(track_hypercube_crossings(system_id, &hypercube, 3.0, &Vec3Wrapper::new(0.0, 0.0, -2.0), 1.0, 1.0 / 60.0), 16);
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    // Пересечения вершин видят все потребители кадра, как и пересечения комет
+    let crossings = get_plane_crossings(system_id).expect("crossings");
+    assert_eq!(crossings.ids().len(), 16);
+    assert!(crossings.types().iter().all(|t| *t == SpaceObjectType::Hypercube));
+    assert!(crossings.crossing_types().iter().all(|t| *t == CrossingType::Entry));
+    assert!(Vec3::from(get_camera_impulse(system_id)).length() > 0.0);
+    assert!(get_portal_glow(system_id).expect("glow").total_energy > 0.0);
+    assert!(!get_comet_effects(system_id).expect("effects").kinds().is_empty());
+    assert!(!get_impact_debris(system_id).expect("debris").positions().is_empty());
+    assert_eq!(get_crossing_stats(system_id, 1.0).expect("stats").entries(), 16);
+    let events = take_lifecycle_events(system_id).expect("events");
+    assert_eq!(events.kinds(), vec![LifecycleEventKind::CrossedPlane; 16]);
+    assert!(flush_analytics_batch(system_id).ok());
+    let batches = take_analytics_batches(system_id);
+    let records: serde_json::Value = serde_json::from_str(&batches[0]).expect("json array");
+    assert_eq!(records.as_array().expect("array").len(), 16);
+    assert_eq!(records[0]["type"], "Hypercube");
+
+    // Пересечения не повторяются на следующем шаге
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    assert!(get_plane_crossings(system_id).expect("crossings").ids().is_empty());
+}
+
+#[test]
+fn huge_and_invalid_dt_are_clamped() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 3).ok());
+    process_neon_comet_spawns(0.0);
+
+    // Вкладка долго была в фоне - приходит dt в минуту
+    assert!(update_space_object_system(system_id, 60.0).ok());
+    let stats = get_frame_stats(system_id).expect("frame stats");
+    assert_eq!(stats.requested_dt, 60.0);
+    assert!(stats.applied_dt <= 0.1);
+    assert_eq!(stats.sanitized, 0);
+
+    assert!(update_space_object_system(system_id, f32::NAN).ok());
+    assert_eq!(get_frame_stats(system_id).expect("frame stats").applied_dt, 0.0);
+
+    let positions = get_visible_neon_comets(system_id).expect("comet data").positions();
+    assert!(positions.iter().all(|p| p.is_finite()));
+}
+
+#[test]
+fn object_deltas_contain_only_changes() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 3).ok());
+    process_neon_comet_spawns(0.0);
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    let full = get_object_deltas(system_id, 0).expect("deltas");
+    assert!(full.keyframe());
+    assert!(!full.ids().is_empty());
+    assert_eq!(full.positions().len(), full.ids().len() * 3);
+
+    // С текущего кадра ничего не изменилось
+    let frame = get_system_frame(system_id).expect("frame");
+    let none = get_object_deltas(system_id, frame).expect("deltas");
+    assert!(!none.keyframe());
+    assert!(none.ids().is_empty());
+
+    // После шага движущиеся кометы попадают в дельту с позицией
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    let moved = get_object_deltas(system_id, frame).expect("deltas");
+    assert!(!moved.ids().is_empty());
+    assert!(moved.masks().iter().all(|mask| mask & 1 != 0));
+}
+
+#[test]
+fn binary_state_stream_round_trips() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 3).ok());
+    process_neon_comet_spawns(0.0);
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    let bytes = encode_scene_state(system_id).expect("state bytes");
+    assert_eq!(&bytes[0..4], b"HCSS");
+
+    let state = decode_state(&bytes).expect("valid stream");
+    assert_eq!(state.frame, get_system_frame(system_id).expect("frame"));
+    assert_eq!(bytes.len(), 16 + state.objects.len() * 24);
+    assert!(state.objects.windows(2).all(|pair| pair[0].id < pair[1].id));
+
+    assert!(decode_state(&bytes[..10]).is_err());
+
+    // Число записей из заголовка не доверяется: огромный count - это обрезанный поток
+    let mut forged = bytes.clone();
+    forged[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(decode_state(&forged).err().as_deref(), Some("Truncated state stream"));
+
+    // Область перекрытия на все пространство скрывает каждый объект
+    let (min, max) = (Vec3Wrapper::new(-1000.0, -1000.0, -1000.0), Vec3Wrapper::new(1000.0, 1000.0, 1000.0));
+    assert_eq!(add_occlusion_box(system_id, &min, &max), Some(0));
+    let state = decode_state(&encode_scene_state(system_id).expect("state bytes")).expect("valid stream");
+    assert!(state.objects.iter().all(|obj| obj.occluded));
+}
+
+#[test]
+fn gltf_export_is_valid_glb() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 3).ok());
+    process_neon_comet_spawns(0.0);
+
+    let glb = export_gltf(system_id).expect("glb bytes");
+    let read_u32 = |offset: usize| u32::from_le_bytes(glb[offset..offset + 4].try_into().unwrap());
+
+    assert_eq!(&glb[0..4], b"glTF");
+    assert_eq!(read_u32(4), 2);
+    assert_eq!(read_u32(8) as usize, glb.len());
+
+    // JSON-чанк выровнен на 4 байта и описывает облако комет
+    let json_length = read_u32(12) as usize;
+    assert_eq!(json_length % 4, 0);
+    let json = std::str::from_utf8(&glb[20..20 + json_length]).expect("utf-8 json");
+    assert!(json.contains("\"NeonComets\""));
+    assert_eq!(&glb[20 + json_length + 4..20 + json_length + 8], b"BIN\0");
+}
+
+#[test]
+fn entity_query_filters_by_region_and_mask() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 3).ok());
+    process_neon_comet_spawns(0.0);
+
+    // Позиция и прозрачность (биты 0 и 3) для всех объектов
+    let all = query_entities(system_id, 0b1001, None).expect("query result");
+    assert!(!all.ids().is_empty());
+    assert_eq!(all.positions().len(), all.ids().len() * 3);
+    assert_eq!(all.opacities().len(), all.ids().len());
+    assert!(all.velocities().is_empty());
+
+    // Новые кометы появляются на дальней плоскости - ближняя половина пространства пуста
+    let near = query_entities(system_id, 1, Some(vec![-1000.0, -1000.0, -1000.0, 1000.0, 1000.0, 0.0]))
+        .expect("query result");
+    assert!(near.ids().is_empty());
+
+    assert!(query_entities(system_id, 1, Some(vec![0.0; 5])).is_none());
+
+    // Дальние кометы вне фокуса видовой плоскости размыты и частично скрыты туманом
+    assert!(set_depth_of_field(system_id, 25.0, 1.0).ok());
+    assert!(set_fog_density(system_id, 0.01).ok());
+    let depth = query_entities(system_id, (1 << 6) | (1 << 7), None).expect("query result");
+    assert_eq!(depth.blurs().len(), depth.ids().len());
+    assert!(depth.blurs().iter().all(|&blur| blur > 0.5 && blur <= 1.0));
+    assert!(depth.fogs().iter().all(|&fog| fog > 0.0 && fog < 1.0));
+}
+
+#[test]
+fn update_passes_follow_declared_dependencies() {
+    let order = get_update_graph_order();
+    let position = |name: &str| order.iter().position(|pass| pass == name).expect(name);
+
+    assert!(position("snapshot") < position("integrate"));
+    assert!(position("integrate") < position("modifiers"));
+    assert!(position("modifiers") < position("warp"));
+    assert!(position("warp") < position("transition"));
+    assert!(position("transition") < position("fade"));
+    assert!(position("fade") < position("sanitize"));
+    assert!(position("sanitize") < position("crossings"));
+    assert!(position("crossings") < position("impulse"));
+    assert!(position("crossings") < position("stats"));
+    assert!(position("viewpoint") < position("integrate"));
+
+    assert!(get_update_graph_dot().contains("\"sanitize\" -> \"crossings\""));
+}
+
+#[test]
+fn parallax_layers_move_against_observer() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert_eq!(add_parallax_layer(system_id, 0.0), Some(0));
+    assert_eq!(add_parallax_layer(system_id, 1.0), Some(1));
+    assert_eq!(get_parallax_offsets(system_id).expect("offsets"), vec![0.0; 4]);
+
+    assert!(set_observer_position(system_id, 10.0, 0.0, -25.0).ok());
+    let offsets = get_parallax_offsets(system_id).expect("offsets");
+
+    // Ближний слой сдвигается сильнее дальнего и в обратную сторону
+    assert!(offsets[0] < 0.0 && offsets[2] < 0.0);
+    assert!(offsets[0].abs() > offsets[2].abs());
+}
+
+#[test]
+fn stereo_eyes_converge_on_viewing_plane() {
+    let space = SpaceDefinition::new();
+    let (left, right) = eye_frustums(&space, 0.064);
+
+    assert!(left.position.x < space.observer_position.x);
+    assert!(right.position.x > space.observer_position.x);
+
+    // Off-axis пирамиды: прямоугольник плоскости смещен к центру относительно каждого глаза
+    assert!(left.tan_right > -left.tan_left);
+    assert!(-right.tan_left > right.tan_right);
+    let plane_center = Vec3::new(0.0, 0.0, space.viewing_plane_z);
+    assert!(left.contains(&plane_center) && right.contains(&plane_center));
+    assert!(!left.contains(&Vec3::new(0.0, 0.0, space.observer_position.z - 1.0)));
+}
+
+#[test]
+fn speed_modifier_push_and_pop() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 3).ok());
+    process_neon_comet_spawns(0.0);
+    // Позиции по ID: общая очередь появления может добавить кометы между замерами
+    let positions = || {
+        let result = query_entities(system_id, 1, None).expect("query result");
+        result.ids().into_iter()
+            .zip(result.positions().chunks(3).map(|p| p.to_vec()))
+            .collect::<std::collections::HashMap<_, _>>()
+    };
+
+    // Нулевой множитель скорости замораживает объекты
+    assert!(push_speed_modifier(system_id, 0.0).is_some());
+    let frozen = positions();
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    let after = positions();
+    assert!(frozen.iter().all(|(id, p)| after.get(id) == Some(p)));
+
+    assert!(pop_modifier(system_id).ok());
+    assert_eq!(get_modifier_count(system_id), Some(0));
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    let after = positions();
+    assert!(frozen.iter().all(|(id, p)| after.get(id) != Some(p)));
+}
+
+#[test]
+fn comet_effects_expire_and_recycle() {
+    let mut pool = CometEffectPool::default();
+    let flash = CometEffect::new(CometEffectKind::Flash, Vec3::ZERO, Vec3::NEG_Z, [1.0; 3], 1.0, 2.0);
+
+    for _ in 0..MAX_COMET_EFFECTS + 10 {
+        pool.spawn(flash);
+    }
+    assert_eq!(pool.len(), MAX_COMET_EFFECTS);
+    assert_eq!(pool.recycled_total(), 10);
+
+    // Вспышка живет меньше секунды: слоты освобождаются и переиспользуются
+    pool.update(1.0);
+    assert!(pool.is_empty());
+    pool.spawn(flash);
+    assert_eq!(pool.len(), 1);
+    assert_eq!(pool.memory_usage().0, MAX_COMET_EFFECTS);
+
+    // Удар в середине кадра: эффект уже прожил половину кадра
+    let crossing = PlaneCrossing {
+        id: 0,
+        object_id: 0,
+        object_type: SpaceObjectType::NeonComet,
+        plane: 0,
+        position: Vec3::ZERO,
+        velocity: Vec3::new(0.0, 0.0, -100.0),
+        size: 60.0,
+        t: 0.5,
+        crossing_type: CrossingType::Entry,
+        normal: Vec3::Z,
+    };
+    let mut pool = CometEffectPool::default();
+    pool.spawn_from_crossing(&crossing, [1.0; 3], 0.1);
+    assert_eq!(pool.len(), 3);
+    assert!(pool.iter().all(|effect| (effect.age - 0.05).abs() < 1e-6));
+}
+
+#[test]
+fn curve_registry_matches_builtin_easing() {
+    // Дескрипторы 0..6 - встроенные кривые
+    let smoothstep = EasingCurve::Smoothstep as u32;
+    assert_eq!(evaluate_curve(smoothstep, 0.3), Some(evaluate_easing(EasingCurve::Smoothstep, 0.3)));
+    assert_eq!(evaluate_curve(u32::MAX, 0.3), None);
+
+    // cubic-bezier(0, 0, 1, 1) - прямая
+    let linear = create_bezier_curve(0.0, 0.0, 1.0, 1.0).expect("bezier handle");
+    assert!((evaluate_curve(linear, 0.42).unwrap() - 0.42).abs() < 1e-3);
+    assert!(create_bezier_curve(1.5, 0.0, 0.5, 1.0).is_none());
+
+    // Жесткая пружина к концу отрезка почти успокаивается
+    let spring = create_spring_curve(400.0, 20.0).expect("spring handle");
+    assert!((evaluate_curve(spring, 1.0).unwrap() - 1.0).abs() < 0.01);
+}
+
+#[test]
+fn warp_ramps_up_and_back_down() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(start_warp(system_id, 5.0, 0.5).ok());
+    assert_eq!(get_warp_multiplier(system_id), Some(1.0));
+
+    for _ in 0..30 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+    let peak = get_warp_multiplier(system_id).expect("multiplier");
+    assert!(peak > 4.5 && peak <= 5.0);
+
+    for _ in 0..40 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+    assert!(!is_warping(system_id));
+    assert_eq!(get_warp_multiplier(system_id), Some(1.0));
+}
+
+#[test]
+fn transition_completes_and_reports_event() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 3).ok());
+    process_neon_comet_spawns(0.0);
+
+    let id = play_transition(system_id, TransitionKind::ConvergeToCenter, 0.25).expect("transition id");
+    assert_eq!(get_transition_progress(system_id), Some(0.0));
+
+    for _ in 0..10 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+    assert!(take_completed_transitions(system_id).is_empty());
+
+    for _ in 0..10 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+    assert_eq!(get_transition_progress(system_id), None);
+    assert_eq!(take_completed_transitions(system_id), vec![id]);
+    assert!(take_completed_transitions(system_id).is_empty());
+}
+
+#[test]
+fn hit_test_finds_object_under_cursor() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 3).ok());
+    process_neon_comet_spawns(0.0);
+
+    // Без размера окна попаданий нет
+    assert!(hit_test(system_id, 0.0, 0.0, 5.0).expect("result").ids().is_empty());
+    assert!(set_screen_size(system_id, 1000.0, 800.0).ok());
+
+    let space = SpaceDefinition::new();
+    let objects = query_entities(system_id, 1, None).expect("query result");
+    let (id, p) = (objects.ids()[0], &objects.positions()[0..3]);
+    let screen = space.project_to_viewport(&Vec3::new(p[0], p[1], p[2])).expect("in front") * glam::Vec2::new(1000.0, 800.0);
+
+    let hits = hit_test(system_id, screen.x, screen.y, 1.0).expect("result");
+    assert!(hits.ids().contains(&id));
+    assert!(hits.depths().windows(2).all(|pair| pair[0] <= pair[1]));
+
+    // Выделение найденного объекта: подсветка пульсирует, у остальных - 0
+    assert!(select_object(system_id, id).ok());
+    assert!(!select_object(system_id, usize::MAX).ok());
+    assert_eq!(get_selected_object(system_id), Some(id));
+    for _ in 0..10 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+    let highlighted = query_entities(system_id, 1 << 8, None).expect("query result");
+    for (object, highlight) in highlighted.ids().iter().zip(highlighted.highlights()) {
+        assert_eq!(highlight > 0.0, *object == id);
+    }
+    assert!(clear_selection(system_id).ok());
+    assert_eq!(get_selected_object(system_id), None);
+}
+
+#[test]
+fn population_stats_track_lifecycle() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert_eq!(get_scene_readiness(system_id), Some(1.0));
+    assert!(spawn_neon_comets(system_id, 4).ok());
+
+    // Заявки в очереди и кометы без единого шага сцену еще не оживляют
+    assert!(get_scene_readiness(system_id).expect("readiness") < 1.0);
+    process_neon_comet_spawns(0.0);
+    for _ in 0..1200 {
+        assert!(update_space_object_system(system_id, 1.0 / 30.0).ok());
+    }
+
+    let stats = get_population_stats(system_id).expect("population stats");
+    let comets = stats.types().iter().position(|t| *t == SpaceObjectType::NeonComet).expect("comet row");
+    assert!(stats.spawned()[comets] > 0);
+
+    // Каждый респаун предваряется исчезновением; удаленные не возвращаются
+    let despawned_waiting = stats.despawned_out_of_bounds()[comets] + stats.despawned_crossing()[comets];
+    assert!(stats.respawned()[comets] <= despawned_waiting);
+    let alive = stats.active()[comets] + stats.waiting()[comets];
+    assert_eq!(alive + stats.despawned_lifetime()[comets], stats.spawned()[comets]);
+    assert!(get_scene_readiness(system_id).expect("readiness") > 0.0);
+    assert!(get_population_stats(usize::MAX).is_none());
+}
+
+#[test]
+fn diagnostics_rate_limit_counts_suppressed() {
+    assert!(set_diagnostics_mode(true, 3600.0).ok());
+    for _ in 0..5 {
+        log_limited("headless_test_key", || "headless diagnostics".to_string());
+    }
+
+    let stats = get_diagnostics_stats();
+    let key = stats.keys().iter().position(|key| key == "headless_test_key").expect("key tracked");
+    assert_eq!(stats.suppressed()[key], 4);
+    assert!(stats.recovered_locks().is_empty(), "no lock was poisoned");
+    assert!(!set_diagnostics_mode(true, -1.0).ok());
+    assert!(set_diagnostics_mode(true, 2.0).ok());
+}
+
+#[test]
+fn batched_point_queries_match_single_point() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    let points = [0.0, 0.0, 10.0, 0.0, 0.0, -5.0, 1000.0, 0.0, 0.0];
+
+    assert_eq!(check_points_in_space(system_id, &points), Some(vec![1, 1, 0]));
+    assert_eq!(calculate_distances_to_viewing_plane(system_id, &points), Some(vec![10.0, -5.0, 0.0]));
+    let space = SpaceDefinition::new();
+    let frustum = check_points_in_view_frustum(system_id, &points).expect("frustum flags");
+    for (flag, p) in frustum.iter().zip(points.chunks_exact(3)) {
+        assert_eq!(*flag == 1, space.is_in_view_frustum(&Vec3::new(p[0], p[1], p[2])));
+    }
+    assert!(check_points_in_space(system_id, &points[..4]).is_none());
+
+    // Углы видовой плоскости в порядке UV
+    let corners = get_plane_corners(system_id, 0).expect("main plane corners");
+    let expected = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+    for (corner, (u, v)) in corners.chunks_exact(3).zip(expected) {
+        let uv = space.plane_uv(&Vec3::new(corner[0], corner[1], corner[2]));
+        assert!((uv.x - u).abs() < 1e-5 && (uv.y - v).abs() < 1e-5);
+        assert_eq!(corner[2], space.viewing_plane_z);
+    }
+    assert!(get_plane_corners(system_id, 1).is_none());
+
+    // Стиль плоскости "дышит" со временем, градиент сдвигается прокруткой
+    assert!(set_plane_style(system_id, 0, 0.2, 0.4, 1.0, 0.5, 1.0, 1.0, 0.5).ok());
+    assert!(set_scroll_offset(system_id, 1.5).ok());
+    let before = get_plane_style(system_id, 0).expect("plane style");
+    assert!((before.gradient_offset - 0.75).abs() < 1e-6);
+    assert!(update_space_object_system(system_id, 0.05).ok());
+    let after = get_plane_style(system_id, 0).expect("plane style");
+    assert!(after.pulse > before.pulse && after.a > before.a);
+    assert_eq!(set_plane_style(system_id, 0, f32::NAN, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0).code(), ErrorCode::InvalidArgument);
+    assert_eq!(set_plane_style(system_id, 7, 0.2, 0.4, 1.0, 0.5, 1.0, 1.0, 0.5).code(), ErrorCode::NotFound);
+    assert_eq!(set_plane_style(usize::MAX, 0, 0.2, 0.4, 1.0, 0.5, 1.0, 1.0, 0.5).code(), ErrorCode::SystemNotFound);
+}
+
+#[test]
+fn nearest_viewing_plane_wins_crossing() {
+    let space = SpaceDefinition::new();
+    let mut planes = ViewingPlanes::default();
+    let panel = ViewingPlane { center: Vec3::new(0.0, 0.0, 10.0), size: glam::Vec2::new(20.0, 20.0), priority: 0 };
+    let front = planes.add(panel).expect("panel plane");
+
+    // Объект на пути к наблюдателю сначала проходит панель перед основной плоскостью
+    let (from, to) = (Vec3::new(1.0, 1.0, 20.0), Vec3::new(1.0, 1.0, -5.0));
+    let (plane, t, point) = planes.nearest_crossing(&space, &from, &to).expect("crossing");
+    assert_eq!(plane, front);
+    assert!((point.z - 10.0).abs() < 1e-5 && t < 0.5);
+
+    // Одновременное пересечение: побеждает плоскость с большим приоритетом
+    let twin = planes.add(ViewingPlane { center: Vec3::new(0.0, 0.0, 10.0), priority: 5, ..panel }).expect("twin plane");
+    assert_eq!(planes.nearest_crossing(&space, &from, &to).map(|c| c.0), Some(twin));
+
+    // Мимо панелей - только основная плоскость
+    let (from, to) = (Vec3::new(15.0, 0.0, 20.0), Vec3::new(15.0, 0.0, -5.0));
+    assert_eq!(planes.nearest_crossing(&space, &from, &to).map(|c| c.0), Some(MAIN_PLANE_INDEX));
+    assert!(planes.remove(twin) && !planes.remove(MAIN_PLANE_INDEX));
+}
+
+#[test]
+fn despawn_all_fades_out_and_reports_completion() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 3).ok());
+    process_neon_comet_spawns(0.0);
+    for _ in 0..30 {
+        assert!(update_space_object_system(system_id, 1.0 / 30.0).ok());
+    }
+
+    let id = despawn_all(system_id, 0.5).expect("despawn id");
+    assert!(update_space_object_system(system_id, 0.25).ok());
+    assert!(get_despawn_progress(system_id).is_some_and(|p| p > 0.0 && p < 1.0));
+    assert!(take_completed_despawns(system_id).is_empty());
+
+    // Появление остановлено: очередь и автоподпитка систему не пополняют
+    for _ in 0..30 {
+        process_neon_comet_spawns(0.1);
+        assert!(update_space_object_system(system_id, 0.1).ok());
+    }
+    assert_eq!(take_completed_despawns(system_id), vec![id]);
+    assert_eq!(get_despawn_progress(system_id), None);
+    assert_eq!(get_active_neon_comets_count(system_id), 0);
+    assert!(despawn_all(usize::MAX, 1.0).is_none());
+}
+
+#[test]
+fn energy_sphere_vortex_orbits_sphere() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_energy_spheres(system_id, 2).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    let spheres = get_energy_spheres(system_id).expect("sphere data");
+    assert_eq!(spheres.ids().len(), 2);
+    assert_eq!(spheres.vortex_particle_count(), VORTEX_PARTICLES);
+    let vortex = spheres.vortex_positions();
+    assert_eq!(vortex.len(), 2 * VORTEX_PARTICLES * 3);
+
+    // Все частицы кольца лежат на одной орбите вокруг центра сферы
+    let center = Vec3::from_slice(&spheres.positions()[..3]);
+    let orbit = Vec3::from_slice(&vortex[..3]).distance(center);
+    assert!(orbit > spheres.radii()[0]);
+    for particle in vortex[..VORTEX_PARTICLES * 3].chunks(3) {
+        assert!((Vec3::from_slice(particle).distance(center) - orbit).abs() < 1e-3);
+    }
+
+    // Кольцо обращается между кадрами
+    assert!(update_space_object_system(system_id, 0.1).ok());
+    let moved = get_energy_spheres(system_id).expect("sphere data");
+    let shift = Vec3::from_slice(&moved.vortex_positions()[..3]) - Vec3::from_slice(&moved.positions()[..3]);
+    assert!(shift.distance(Vec3::from_slice(&vortex[..3]) - center) > 1e-3);
+    assert!(get_energy_spheres(usize::MAX).is_none());
+}
+
+#[test]
+fn distortion_pulls_toward_energy_spheres() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert_eq!(sample_distortion_grid(system_id, MAIN_PLANE_INDEX, 4, 3).map(|grid| grid.len()), Some(36));
+    assert!(sample_distortion_grid(system_id, MAIN_PLANE_INDEX, 0, 3).is_none());
+
+    assert!(spawn_energy_spheres(system_id, 1).ok());
+    for _ in 0..20 {
+        assert!(update_space_object_system(system_id, 0.1).ok());
+    }
+    let spheres = get_energy_spheres(system_id).expect("sphere data");
+    assert!(spheres.opacities()[0] > 0.0);
+
+    // Точка на границе сферы смещается к ее центру, далекая точка - нет
+    let center = Vec3::from_slice(&spheres.positions()[..3]);
+    let edge = center + Vec3::X * spheres.radii()[0];
+    let shift = sample_distortion(system_id, edge.x, edge.y, edge.z).expect("distortion");
+    assert!(shift.x() < 0.0);
+    let far = sample_distortion(system_id, center.x + 1000.0, center.y, center.z).expect("distortion");
+    assert_eq!(far.x(), 0.0);
+    assert!(sample_distortion(usize::MAX, 0.0, 0.0, 0.0).is_none());
+}
+
+#[test]
+fn lifecycle_events_follow_objects() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_energy_spheres(system_id, 2).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    let events = take_lifecycle_events(system_id).expect("events");
+    let kinds = events.kinds();
+    assert_eq!(kinds.iter().filter(|kind| **kind == LifecycleEventKind::Spawned).count(), 2);
+    assert_eq!(kinds.iter().filter(|kind| **kind == LifecycleEventKind::Activated).count(), 2);
+    assert!(events.reasons().iter().all(|reason| *reason == LifecycleReason::None));
+    assert!(take_lifecycle_events(system_id).expect("events").ids().is_empty());
+
+    // Очистка сцены удаляет объекты с причиной "погас"
+    despawn_all(system_id, 0.0).expect("despawn id");
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    let events = take_lifecycle_events(system_id).expect("events");
+    assert_eq!(events.kinds(), vec![LifecycleEventKind::Despawned; 2]);
+    assert_eq!(events.reasons(), vec![LifecycleReason::Opacity; 2]);
+    assert!(take_lifecycle_events(usize::MAX).is_none());
+}
+
+#[test]
+fn panic_hook_records_crash_info() {
+    init();
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_energy_spheres(system_id, 1).ok());
+
+    let result = std::panic::catch_unwind(|| panic!("headless crash {}", 42));
+    assert!(result.is_err());
+
+    let crash = get_last_crash_info().expect("crash info");
+    assert_eq!(crash.message(), "headless crash 42");
+    assert_eq!(crash.module(), "headless");
+    assert!(crash.line() > 0);
+    assert!(crash.systems() >= 1 && crash.energy_spheres() >= 1);
+}
+
+#[test]
+fn module_info_reports_versions() {
+    let info = get_module_info();
+    assert_eq!(info.crate_version(), env!("CARGO_PKG_VERSION"));
+    assert_eq!(info.api_level(), API_LEVEL);
+    assert_eq!(info.state_stream_version(), get_state_stream_version());
+    assert!(info.supports_api_level(API_LEVEL) && !info.supports_api_level(API_LEVEL + 1));
+    assert_eq!(info.features().contains(&"golden".to_string()), cfg!(feature = "golden"));
+    assert_eq!(info.features().contains(&"web".to_string()), cfg!(feature = "web"));
+    assert_eq!(info.features().contains(&"headless".to_string()), cfg!(feature = "headless"));
+}
+
+#[test]
+fn incremental_init_fills_system_in_portions() {
+    let system_id = create_system_async(25.0, 60.0, 40, 2);
+    assert_eq!(get_init_progress(system_id), Some(0.0));
+    assert_eq!(get_active_neon_comets_count(system_id), 0);
+
+    // Нулевой бюджет: одна порция из одного объекта за вызов
+    let progress = continue_init(system_id, 0.0).expect("progress");
+    assert!(progress > 0.0 && progress < 1.0);
+
+    let mut calls = 1;
+    while continue_init(system_id, 1.0).expect("progress") < 1.0 {
+        calls += 1;
+        assert!(calls <= 42, "init must finish");
+    }
+    assert_eq!(get_active_neon_comets_count(system_id), 40);
+    assert_eq!(get_energy_spheres(system_id).expect("sphere data").ids().len(), 2);
+    assert!(continue_init(usize::MAX, 1.0).is_none());
+}
+
+#[test]
+fn context_loss_fallback_state_and_full_resync() {
+    let system_id = create_system_async(25.0, 60.0, 5, 1);
+    assert_eq!(continue_init(system_id, 1000.0), Some(1.0));
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    let minimal = get_minimal_state(system_id).expect("minimal state");
+    assert_eq!(minimal.ids().len(), 6);
+    assert_eq!(minimal.objects().len(), 6 * 4);
+
+    // Клиент в курсе текущего кадра - изменений нет, пока кэши не сброшены
+    let frame = get_system_frame(system_id).expect("frame");
+    assert!(get_object_deltas(system_id, frame).expect("deltas").ids().is_empty());
+    assert!(mark_all_dirty(system_id).ok());
+    let full = get_object_deltas(system_id, frame).expect("deltas");
+    assert!(full.keyframe());
+    assert_eq!(full.ids().len(), 6);
+    assert!(!get_object_deltas(system_id, frame).expect("deltas").keyframe());
+    assert!(get_minimal_state(usize::MAX).is_none());
+}
+
+#[test]
+fn plane_impacts_spawn_fading_physics_debris() {
+    let world_id = init_world();
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert_eq!(enable_impact_debris(system_id, usize::MAX, 0.0).code(), ErrorCode::NotFound);
+    assert!(enable_impact_debris(system_id, world_id, 0.0).ok());
+
+    // Сферы дрейфуют к видовому экрану и входят в плоскость
+    assert!(spawn_energy_spheres(system_id, 3).ok());
+    let mut debris = Vec::new();
+    for _ in 0..1200 {
+        assert!(update_space_object_system(system_id, 0.1).ok());
+        assert!(step_simulation(world_id, 0.1).ok());
+        debris = get_impact_debris(system_id).expect("debris").opacities();
+        if !debris.is_empty() {
+            break;
+        }
+    }
+    assert!(!debris.is_empty(), "an impact must spawn debris");
+    assert!(debris.iter().all(|opacity| *opacity > 0.0 && *opacity <= 1.0));
+
+    assert!(disable_impact_debris(system_id).ok());
+    assert!(get_impact_debris(system_id).expect("debris").positions().is_empty());
+}
+
+#[test]
+fn plane_colliders_follow_viewing_planes() {
+    let world_id = init_world();
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(sync_plane_to_physics(system_id, MAIN_PLANE_INDEX, world_id, PhysicsSyncMode::Static).ok());
+    assert_eq!(sync_plane_to_physics(system_id, 99, world_id, PhysicsSyncMode::Static).code(), ErrorCode::NotFound);
+
+    // Основная плоскость следует за наблюдателем - коллайдер тоже
+    assert!(set_observer_position(system_id, 5.0, 0.0, -25.0).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    let corners = get_plane_corners(system_id, MAIN_PLANE_INDEX).expect("corners");
+    let center = get_plane_collider_center(system_id, MAIN_PLANE_INDEX).expect("collider");
+    assert!((center[0] - (corners[0] + corners[3]) * 0.5).abs() < 1e-4);
+
+    // Кинематический режим: новое положение применяется к следующему шагу мира
+    assert!(sync_plane_to_physics(system_id, MAIN_PLANE_INDEX, world_id, PhysicsSyncMode::Kinematic).ok());
+    assert!(set_observer_position(system_id, -5.0, 0.0, -25.0).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    assert!(step_simulation(world_id, 1.0 / 60.0).ok());
+    assert!(get_plane_collider_center(system_id, MAIN_PLANE_INDEX).expect("collider")[0] < 0.0);
+
+    // Пересинхронизация в несуществующий мир не снимает рабочий коллайдер
+    assert_eq!(
+        sync_plane_to_physics(system_id, MAIN_PLANE_INDEX, usize::MAX, PhysicsSyncMode::Static).code(),
+        ErrorCode::NotFound
+    );
+    assert!(get_plane_collider_center(system_id, MAIN_PLANE_INDEX).is_some());
+
+    assert!(unsync_plane_from_physics(system_id, MAIN_PLANE_INDEX).ok());
+    assert!(get_plane_collider_center(system_id, MAIN_PLANE_INDEX).is_none());
+}
+
+#[test]
+fn distant_hidden_objects_sleep_between_full_steps() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_energy_spheres(system_id, 16).ok());
+    assert!(set_object_sleeping(system_id, true, 4, 0.0).ok());
+    assert_eq!(set_object_sleeping(system_id, true, 4, f32::NAN).code(), ErrorCode::InvalidArgument);
+
+    // Наблюдатель далеко в стороне: все объекты вне видимости и засыпают
+    assert!(set_observer_position(system_id, 10_000.0, 0.0, -25.0).ok());
+    let (mut updated, mut sleeping) = (0, 0);
+    for _ in 0..8 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+        let stats = get_frame_stats(system_id).expect("frame stats");
+        assert_eq!(stats.objects_updated + stats.objects_sleeping, 16);
+        updated += stats.objects_updated;
+        sleeping += stats.objects_sleeping;
+    }
+    // Только что появившиеся на дальней границе сферы видимы всегда, остальные
+    // получают полный шаг раз в 4 кадра
+    assert!(sleeping > 0 && updated >= 16 * 2);
+    assert_eq!(get_sleep_skipped_total(system_id), Some(sleeping as f64));
+
+    assert!(set_object_sleeping(system_id, false, 4, 0.0).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    assert_eq!(get_frame_stats(system_id).expect("frame stats").objects_sleeping, 0);
+}
+
+#[test]
+fn packed_export_halves_comet_buffers() {
+    // Точные значения, денормализованные числа, переполнение и округление
+    for value in [0.0f32, -0.0, 1.0, -2.5, 65504.0, 6.0e-8, 0.333_333] {
+        let restored = f16_to_f32(f32_to_f16(value));
+        assert!((restored - value).abs() <= value.abs() * 1e-3 + 6.0e-8, "{value} -> {restored}");
+    }
+    assert_eq!(f32_to_f16(1.0e6), 0x7c00);
+    assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+    assert_eq!((to_unorm8(-1.0), to_unorm8(0.5), to_unorm8(2.0)), (0, 128, 255));
+
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 5).ok());
+    process_neon_comet_spawns(0.0);
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    let full = get_visible_neon_comets(system_id).expect("comets");
+    let half = get_packed_neon_comets(system_id, PackedColorFormat::Half).expect("packed");
+    assert_eq!(half.ids(), full.ids());
+    for (packed, exact) in half.positions().iter().zip(full.positions()) {
+        assert!((f16_to_f32(*packed) - exact).abs() < 0.1);
+    }
+    assert_eq!(half.colors_half().len(), full.colors().len());
+    assert!(half.colors_unorm8().is_empty());
+
+    let compact = get_packed_neon_comets(system_id, PackedColorFormat::Unorm8).expect("packed");
+    assert_eq!(compact.colors_unorm8().len(), full.colors().len());
+    assert!(compact.colors_half().is_empty());
+    assert!(get_module_info().packed_layout().starts_with("positions:f16x3"));
+}
+
+#[test]
+fn morton_ordering_sorts_objects_periodically() {
+    let space = SpaceDefinition::new();
+    assert_eq!(morton_code(Vec3::splat(-100.0), &space), 0);
+    assert_eq!(morton_code(Vec3::splat(100.0), &space), (1 << 30) - 1);
+    assert!(morton_code(Vec3::new(100.0, -100.0, -100.0), &space) < morton_code(Vec3::new(-100.0, 100.0, -100.0), &space));
+
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_energy_spheres(system_id, 64).ok());
+    assert!(set_morton_ordering(system_id, true, 10).ok());
+
+    for _ in 0..20 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+    let stats = get_morton_stats(system_id).expect("morton stats");
+    assert_eq!(stats.reorders, 2);
+    assert!(stats.last_objects > 0);
+    assert!(stats.total_cost_ms >= stats.last_cost_ms);
+
+    // Сферы в массиве идут по возрастанию кода Мортона
+    let positions = get_energy_spheres(system_id).expect("spheres").positions();
+    let codes: Vec<u32> = positions.chunks(3).map(|p| morton_code(Vec3::new(p[0], p[1], p[2]), &space)).collect();
+    let sorted = codes.windows(2).filter(|pair| pair[0] <= pair[1]).count();
+    assert!(sorted * 10 >= (codes.len() - 1) * 8, "{sorted} of {}", codes.len() - 1);
+}
+
+#[test]
+fn frame_pacer_derives_steps_from_timestamps() {
+    let mut pacer = FramePacer::default();
+    assert_eq!(pacer.tick(1000.0), 0.0);
+    assert!((pacer.tick(1016.0) - 0.016).abs() < 1e-6);
+    assert_eq!(pacer.tick(1010.0), 0.0);
+    assert!((pacer.tick(1510.0) - 0.5).abs() < 1e-6);
+    assert_eq!(pacer.frames(), 4);
+
+    // Без окна драйвер не запускается
+    assert!(!is_frame_driver_running());
+    assert_eq!(stop_frame_driver().code(), ErrorCode::NotFound);
+    assert_eq!(get_frame_driver_frames(), 0.0);
+}
+
+#[test]
+fn driver_frame_spawns_queued_comets() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 3).ok());
+
+    // Кометы из очереди появляются без вызова process_neon_comet_spawns из JS
+    let mut pacer = FramePacer::default();
+    assert_eq!(drive_frame(&mut pacer, &[system_id], 1000.0), 0.0);
+    assert!(get_active_neon_comets_count(system_id) > 0);
+
+    let before = get_visible_neon_comets(system_id).expect("comet data").positions();
+    assert!((drive_frame(&mut pacer, &[system_id], 1016.0) - 0.016).abs() < 1e-6);
+    let after = get_visible_neon_comets(system_id).expect("comet data").positions();
+    assert_ne!(before[..3], after[..3], "driver must update the system");
+    assert_eq!(pacer.frames(), 2);
+}
+
+#[test]
+fn idle_systems_update_at_reduced_rate() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_energy_spheres(system_id, 2).ok());
+    assert!(notify_user_idle(system_id, true).ok());
+    assert_eq!(is_user_idle(system_id), Some(true));
+
+    // Шаги копятся до 1/IDLE_UPDATE_HZ и применяются одним шагом
+    let dt = 0.4 / IDLE_UPDATE_HZ;
+    let frame = get_system_frame(system_id).expect("frame");
+    for _ in 0..2 {
+        assert!(update_space_object_system(system_id, dt).ok());
+    }
+    assert_eq!(get_system_frame(system_id), Some(frame));
+    assert!(update_space_object_system(system_id, dt).ok());
+    assert_eq!(get_system_frame(system_id), Some(frame + 1));
+    assert!((get_frame_stats(system_id).expect("stats").applied_dt - dt * 3.0).abs() < 1e-6);
+
+    // Частиц вихря становится вдвое меньше, после взаимодействия - снова полное число
+    for _ in 0..60 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+    assert_eq!(get_energy_spheres(system_id).expect("spheres").vortex_particle_count(), VORTEX_PARTICLES / 2);
+    assert!(notify_user_idle(system_id, false).ok());
+    for _ in 0..60 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+    assert_eq!(get_energy_spheres(system_id).expect("spheres").vortex_particle_count(), VORTEX_PARTICLES);
+}
+
+#[test]
+fn power_profiles_map_to_budgets() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert_eq!(get_power_profile(system_id), Some(PowerProfile::Balanced));
+    assert!(spawn_neon_comets(system_id, 3).ok());
+    assert!(spawn_energy_spheres(system_id, 1).ok());
+    process_neon_comet_spawns(0.0);
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    let full_tails = get_visible_neon_comets(system_id).expect("comets").tail_lengths();
+
+    assert!(set_power_profile(system_id, PowerProfile::Saver).ok());
+    let budget = get_power_budget(system_id).expect("budget");
+    let saver_tails = get_visible_neon_comets(system_id).expect("comets").tail_lengths();
+    for (saver, full) in saver_tails.iter().zip(&full_tails) {
+        assert!((saver - full * budget.tail_scale).abs() < 1e-4);
+    }
+    assert_eq!(get_energy_spheres(system_id).expect("spheres").vortex_particle_count(), VORTEX_PARTICLES / 2);
+
+    assert!(set_power_profile(system_id, PowerProfile::Performance).ok());
+    assert_eq!(get_power_budget(system_id).expect("budget").substeps, 2);
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    assert_eq!(get_energy_spheres(system_id).expect("spheres").vortex_particle_count(), VORTEX_PARTICLES);
+}
+
+#[test]
+fn colors_are_exported_in_requested_space() {
+    assert_eq!(srgb_to_linear(0.0), 0.0);
+    assert!((srgb_to_linear(1.0) - 1.0).abs() < 1e-6);
+    assert!((srgb_to_linear(0.5) - 0.214_041).abs() < 1e-5);
+    for value in [0.0, 0.02, 0.3, 0.9] {
+        assert!((linear_to_srgb(srgb_to_linear(value)) - value).abs() < 1e-5);
+    }
+    let converted = convert_colors(vec![0.5, 0.5, 0.5], ColorSpace::Srgb, ColorSpace::Linear);
+    assert_eq!(convert_colors(converted.clone(), ColorSpace::Linear, ColorSpace::Linear), converted);
+
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert_eq!(get_export_color_space(system_id), Some(ColorSpace::Srgb));
+    assert!(spawn_neon_comets(system_id, 3).ok());
+    process_neon_comet_spawns(0.0);
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    let srgb = get_visible_neon_comets(system_id).expect("comets").colors();
+
+    assert!(set_export_color_space(system_id, ColorSpace::Linear).ok());
+    let linear = get_visible_neon_comets(system_id).expect("comets").colors();
+    assert_eq!(linear, convert_colors(srgb, ColorSpace::Srgb, ColorSpace::Linear));
+}
+
+#[test]
+fn glowing_objects_export_hdr_emissive() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 3).ok());
+    assert!(spawn_energy_spheres(system_id, 2).ok());
+    process_neon_comet_spawns(0.0);
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    // Без выделения яркость кометы совпадает с ее свечением
+    let comets = get_visible_neon_comets(system_id).expect("comets");
+    assert_eq!(comets.emissive(), comets.glow_intensities());
+
+    // Яркость сфер выше 1 (HDR), а не втиснута в прозрачность
+    let spheres = get_energy_spheres(system_id).expect("spheres");
+    assert_eq!(spheres.emissive().len(), spheres.ids().len());
+    assert!(spheres.emissive().iter().all(|&emissive| emissive > 1.0));
+}
+
+#[test]
+fn color_seeds_and_palette_indices_are_stable() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 3).ok());
+    assert!(spawn_energy_spheres(system_id, 2).ok());
+    process_neon_comet_spawns(0.0);
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    let comets = get_visible_neon_comets(system_id).expect("comets");
+    let spheres = get_energy_spheres(system_id).expect("spheres");
+    let palette = get_comet_palette();
+    for (index, &palette_index) in comets.palette_indices().iter().enumerate() {
+        let base = &palette[palette_index as usize * 3..palette_index as usize * 3 + 3];
+        assert_eq!(base, &comets.colors()[index * 3..index * 3 + 3]);
+    }
+    assert!(spheres.palette_indices().iter().all(|&index| (index as usize) < get_sphere_palette().len() / 3));
+
+    // Сиды не меняются от кадра к кадру
+    for _ in 0..10 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+    let later = get_visible_neon_comets(system_id).expect("comets");
+    for (id, seed) in comets.ids().iter().zip(comets.color_seeds()) {
+        if let Some(index) = later.ids().iter().position(|later_id| later_id == id) {
+            assert_eq!(later.color_seeds()[index], seed);
+        }
+    }
+    assert_eq!(get_energy_spheres(system_id).expect("spheres").color_seeds(), spheres.color_seeds());
+}
+
+#[test]
+fn tail_particles_share_a_budgeted_pool() {
+    // Кометы создаются напрямую: общая очередь появлений делится с параллельными тестами
+    let system_id = create_space_object_system(25.0, 60.0);
+    for _ in 0..3 {
+        spawn_neon_comet(&mut SPACE_OBJECT_SYSTEMS.get_mut(&system_id).expect("system"));
+    }
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    // Диапазоны комет идут в пуле подряд и не перекрываются
+    let tails = get_tail_particles(system_id).expect("tails");
+    let mut next = 0;
+    for (start, count) in tails.range_starts().iter().zip(tails.range_counts()) {
+        assert_eq!(*start, next);
+        assert_eq!(count as usize, TAIL_PARTICLES_PER_COMET);
+        next += count;
+    }
+    assert_eq!(next as usize, 3 * TAIL_PARTICLES_PER_COMET);
+    assert_eq!(tails.positions().len(), next as usize * 3);
+    assert_eq!(tails.starved(), 0);
+
+    // Бюджет меньше одного хвоста: первая по приоритету комета получает укороченный хвост
+    assert!(set_tail_particle_budget(system_id, 5).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    let tails = get_tail_particles(system_id).expect("tails");
+    assert_eq!(tails.opacities().len(), 5);
+    assert_eq!(tails.range_counts(), vec![5]);
+    assert!(tails.starved() >= 1);
+}
+
+#[test]
+fn particle_budget_is_shared_by_priority() {
+    // Хватает на всех - каждый получает свой спрос
+    assert_eq!(arbitrate(100, &[10, 20, 30], &[1.0, 1.0, 1.0]), vec![10, 20, 30]);
+    // Недостаток: малый спрос удовлетворяется, остаток делится по весам
+    assert_eq!(arbitrate(100, &[10, 200, 200], &[1.0, 1.0, 3.0]), vec![10, 22, 67]);
+    assert_eq!(arbitrate(100, &[50, 50], &[0.0, 1.0]), vec![0, 50]);
+
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 3).ok());
+    assert!(spawn_energy_spheres(system_id, 4).ok());
+    process_neon_comet_spawns(0.0);
+    assert!(set_particle_budget(system_id, Some(64)).ok());
+    assert!(set_particle_priority(system_id, ParticleSubsystem::Effects, 0.0).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    let budget = get_particle_budget(system_id).expect("budget");
+    assert_eq!(budget.total(), Some(64));
+    assert!(budget.allocations().iter().sum::<u32>() <= 64);
+    assert_eq!(budget.allocations()[ParticleSubsystem::Effects as usize], 0);
+
+    // Подсистемы укладываются в свои доли
+    let tails = get_tail_particles(system_id).expect("tails");
+    assert!(tails.opacities().len() as u32 <= budget.allocations()[ParticleSubsystem::Tails as usize]);
+    let spheres = get_energy_spheres(system_id).expect("spheres");
+    let vortex_particles = spheres.vortex_positions().len() as u32 / 3;
+    assert!(vortex_particles <= budget.allocations()[ParticleSubsystem::Vortices as usize]);
+
+    assert!(set_particle_budget(system_id, None).ok());
+    assert_eq!(get_particle_budget(system_id).expect("budget").total(), None);
+}
+
+#[test]
+fn near_misses_report_closest_approach_once() {
+    let observer = Vec3::new(0.0, 0.0, -25.0);
+    // Сближение внутри отрезка; еще приближается или уже удаляется - не здесь
+    let (t, point) = closest_approach(Vec3::new(3.0, 0.0, -15.0), Vec3::new(3.0, 0.0, -35.0), observer).expect("approach");
+    assert!((t - 0.5).abs() < 1e-6 && (point - Vec3::new(3.0, 0.0, -25.0)).length() < 1e-5);
+    assert!(closest_approach(Vec3::new(3.0, 0.0, -5.0), Vec3::new(3.0, 0.0, -15.0), observer).is_none());
+    assert!(closest_approach(Vec3::new(3.0, 0.0, -35.0), Vec3::new(3.0, 0.0, -45.0), observer).is_none());
+
+    let mut near_misses = NearMisses::default();
+    near_misses.check(1, Vec3::new(3.0, 0.0, -15.0), Vec3::new(3.0, 0.0, -35.0), 0.5, observer, observer);
+    assert!(near_misses.is_empty(), "disabled by default");
+    near_misses.set_distance(Some(5.0));
+    near_misses.check(1, Vec3::new(3.0, 0.0, -15.0), Vec3::new(3.0, 0.0, -35.0), 0.5, observer, observer);
+    near_misses.check(2, Vec3::new(9.0, 0.0, -15.0), Vec3::new(9.0, 0.0, -35.0), 0.5, observer, observer);
+    let events = near_misses.drain();
+    assert_eq!(events.comet_ids(), vec![1]);
+    assert!((events.distances()[0] - 3.0).abs() < 1e-5);
+    assert!((events.relative_speeds()[0] - 40.0).abs() < 1e-4);
+    assert!((events.time_offsets()[0] + 0.25).abs() < 1e-6);
+
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert_eq!(set_near_miss_distance(system_id, Some(-1.0)).code(), ErrorCode::InvalidArgument);
+    assert!(set_near_miss_distance(system_id, Some(1000.0)).ok());
+    assert!(spawn_neon_comets(system_id, 3).ok());
+    process_neon_comet_spawns(0.0);
+    for _ in 0..10 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+    let events = take_near_miss_events(system_id).expect("events");
+    assert_eq!(events.points().len(), events.comet_ids().len() * 3);
+    assert!(events.distances().iter().all(|&distance| distance <= 1000.0));
+    assert!(set_near_miss_distance(system_id, None).ok());
+    assert!(take_near_miss_events(system_id).expect("events").comet_ids().is_empty());
+}
+
+#[test]
+fn navigation_input_flies_observer_smoothly() {
+    let mut space = SpaceDefinition::new();
+    let start = space.observer_position;
+
+    // Скорость нарастает плавно; диагональ клавиатуры не быстрее стика
+    space.apply_navigation_input(Vec3::new(1.0, 0.0, 0.0), Vec2::ZERO, 1.0 / 60.0);
+    let first_speed = space.navigation.velocity.length();
+    assert!(first_speed > 0.0 && first_speed < 5.0);
+    for _ in 0..120 {
+        space.apply_navigation_input(Vec3::new(1.0, 1.0, 0.0), Vec2::ZERO, 1.0 / 60.0);
+    }
+    let top_speed = space.navigation.velocity.length();
+    assert!(top_speed > 25.0 && top_speed <= 30.0 + 1e-3);
+    assert!(space.observer_position.x > start.x && space.observer_position.y > start.y);
+
+    // Наблюдатель не пролетает видовую плоскость, скорость вдоль упора гасится
+    for _ in 0..300 {
+        space.apply_navigation_input(Vec3::Z, Vec2::ZERO, 1.0 / 60.0);
+    }
+    assert!(space.observer_position.z < space.viewing_plane_z);
+    assert_eq!(space.navigation.velocity.z, 0.0);
+    assert!(space.observer_position.x <= space.max_x && space.observer_position.y <= space.max_y);
+
+    // Без ввода наблюдатель тормозит
+    for _ in 0..120 {
+        space.apply_navigation_input(Vec3::ZERO, Vec2::ZERO, 1.0 / 60.0);
+    }
+    assert!(space.navigation.velocity.length() < 0.1);
+
+    let system_id = create_space_object_system(25.0, 60.0);
+    let stick = Vec3Wrapper::new(0.0, 0.0, 0.0);
+    let look_right = Vec2Wrapper::new(0.0, 1.0);
+    for _ in 0..30 {
+        assert!(set_navigation_input(system_id, &stick, &look_right, 1.0 / 60.0).ok());
+    }
+    assert!(get_view_direction(system_id).expect("direction").x() > 0.0);
+    assert_eq!(get_observer_velocity(system_id).expect("velocity").z(), 0.0);
+    assert_eq!(set_navigation_input(system_id, &stick, &look_right, f32::NAN).code(), ErrorCode::InvalidArgument);
+}
+
+#[test]
+fn viewpoint_flight_eases_and_reports_arrival() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(save_viewpoint(system_id, "start").ok());
+    assert!(set_observer_position(system_id, 10.0, 0.0, -40.0).ok());
+    assert!(save_viewpoint(system_id, "side").ok());
+    assert!(fly_to_viewpoint(system_id, "missing", 1.0).is_none());
+
+    let flight = fly_to_viewpoint(system_id, "start", 1.0).expect("flight");
+    // Кривая с плавным стартом: за первую десятую времени пройдено меньше десятой пути
+    for _ in 0..6 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+    let progress = get_viewpoint_flight_progress(system_id).expect("progress");
+    assert!((progress - 0.1).abs() < 1e-3);
+    let position = Vec3::from(get_observer_position(system_id).expect("observer"));
+    assert!(position.x < 10.0 && position.x > 9.0);
+    assert!(take_viewpoint_arrivals(system_id).is_empty());
+
+    for _ in 0..60 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+    assert_eq!(take_viewpoint_arrivals(system_id), vec![flight]);
+    assert!(get_viewpoint_flight_progress(system_id).is_none());
+    assert_eq!(get_field_of_view(system_id), Some(std::f32::consts::PI / 3.0));
+    assert_eq!(Vec3::from(get_observer_position(system_id).expect("observer")), Vec3::new(0.0, 0.0, -25.0));
+
+    // Ввод свободного полета прерывает полет без события прибытия
+    assert!(fly_to_viewpoint(system_id, "side", 1.0).is_some());
+    assert!(set_navigation_input(system_id, &Vec3Wrapper::new(1.0, 0.0, 0.0), &Vec2Wrapper::new(0.0, 0.0), 1.0 / 60.0).ok());
+    assert!(get_viewpoint_flight_progress(system_id).is_none());
+    assert!(update_space_object_system(system_id, 1.0).ok());
+    assert!(take_viewpoint_arrivals(system_id).is_empty());
+
+    assert!(remove_viewpoint(system_id, "side").ok());
+    assert_eq!(remove_viewpoint(system_id, "side").code(), ErrorCode::NotFound);
+}
+
+#[test]
+fn scene_bounds_frame_all_active_objects() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(get_scene_bounds(system_id).is_none());
+    assert!(spawn_energy_spheres(system_id, 4).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    let bounds = get_scene_bounds(system_id).expect("bounds");
+    assert_eq!(bounds.count(), 4);
+    let spheres = get_energy_spheres(system_id).expect("spheres").positions();
+    for (axis, (&min, &max)) in bounds.min().iter().zip(bounds.max().iter()).enumerate() {
+        assert!(spheres.chunks(3).all(|p| p[axis] >= min && p[axis] <= max));
+    }
+
+    // Кадр: наблюдатель напротив центра, описанная сфера внутри угла обзора
+    let framing = frame_scene(system_id, 0.1).expect("framing");
+    let position = Vec3::from_slice(&framing.position());
+    let center = Vec3::from_slice(&bounds.center());
+    assert!((position.truncate() - center.truncate()).length() < 1e-4);
+    assert!(position.z < center.z);
+    let half_angle = (bounds.radius() * 1.1 / (center.z - position.z)).asin();
+    assert!(half_angle <= framing.field_of_view() * 0.5 + 1e-4);
+
+    // Далекая сцена не помещается перед ближней границей - угол расширяется
+    let space = SpaceDefinition::new();
+    let wide = Bounds { min: Vec3::new(-100.0, -100.0, -90.0), max: Vec3::new(100.0, 100.0, -80.0), count: 2 };
+    let (position, fov) = frame_bounds(&wide, 0.0, &space);
+    assert_eq!(position.z, space.min_z);
+    assert!(fov > space.field_of_view);
+}
+
+#[test]
+fn silhouette_svg_contains_comets_and_wireframe() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    // Кометы с фиксированным seed и без общей очереди: превью не зависит от соседних тестов
+    assert!(reseed_space_object_system(system_id, 4212).ok());
+    {
+        let mut system = SPACE_OBJECT_SYSTEMS.get_mut(&system_id).expect("system");
+        for _ in 0..3 {
+            spawn_neon_comet(&mut system);
+        }
+    }
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+
+    let svg = export_silhouette_svg(system_id, 1200, 630).expect("svg");
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"1200\" height=\"630\""));
+    assert!(svg.ends_with("</svg>"));
+    assert!(svg.contains("<circle") && !svg.contains("NaN"));
+    assert!(!svg.contains("id=\"hypercube\""));
+    assert!(export_silhouette_svg(usize::MAX, 100, 100).is_none());
+}
+
+#[test]
+fn custom_clock_implements_time_sources() {
+    // Часы сервера с собственным расписанием кадров: только время, без requestAnimationFrame
+    struct FixedClock(f64);
+
+    impl Clock for FixedClock {
+        fn now_ms(&self) -> f64 {
+            1_700_000_000_000.0 + self.0
+        }
+
+        fn monotonic_ms(&self) -> f64 {
+            self.0
+        }
+    }
+
+    let clock: Box<dyn Clock> = Box::new(FixedClock(16.0));
+    assert_eq!(clock.monotonic_ms(), 16.0);
+    assert!(clock.now_ms() > clock.monotonic_ms());
+}
+
+#[test]
+fn scene_mirror_reorders_packets_and_interpolates() {
+    use std::collections::HashMap;
+
+    let host_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(host_id, 3).ok());
+    process_neon_comet_spawns(0.0);
+
+    let dt = 1.0 / 60.0;
+    let visible = |system_id| -> HashMap<u32, Vec3> {
+        let comets = get_visible_neon_comets(system_id).expect("comet data");
+        comets.ids().iter().zip(comets.positions().chunks(3)).map(|(&id, p)| (id as u32, Vec3::from_slice(p))).collect()
+    };
+
+    // Хост: полный снимок, затем две дельты подряд
+    assert!(update_space_object_system(host_id, dt).ok());
+    let keyframe = encode_sync_packet(host_id, 0).expect("keyframe");
+    let first = get_system_frame(host_id).expect("frame");
+    assert!(update_space_object_system(host_id, dt).ok());
+    let second = encode_sync_packet(host_id, first).expect("delta");
+    let before = visible(host_id);
+    assert!(update_space_object_system(host_id, dt).ok());
+    let third = encode_sync_packet(host_id, first + 1).expect("delta");
+    let after = visible(host_id);
+
+    // Зеркало с задержкой в полкадра хоста
+    let mirror_id = create_scene_mirror(dt * 0.5);
+    assert!(get_scene_mirror_state(mirror_id).expect("state").needs_keyframe());
+    assert!(apply_sync_packet(mirror_id, &keyframe).ok());
+    assert!(!get_scene_mirror_state(mirror_id).expect("state").needs_keyframe());
+    assert_eq!(get_scene_mirror_frame(mirror_id), Some(first));
+
+    // Пакет "из будущего" ждет предыдущий, повтор уже примененного отбрасывается
+    assert!(apply_sync_packet(mirror_id, &third).ok());
+    assert_eq!(get_scene_mirror_frame(mirror_id), Some(first));
+    assert!(apply_sync_packet(mirror_id, &second).ok());
+    assert_eq!(get_scene_mirror_frame(mirror_id), Some(first + 2));
+    assert!(apply_sync_packet(mirror_id, &second).ok());
+    assert_eq!(get_scene_mirror_frame(mirror_id), Some(first + 2));
+
+    // Часы показа догоняют хост и останавливаются между двумя последними кадрами
+    assert!(update_scene_mirror(mirror_id, 1.0).ok());
+    let state = get_scene_mirror_state(mirror_id).expect("state");
+    assert_eq!(state.ids().len(), state.positions().len() / 3);
+    let mut compared = 0;
+    for (id, position) in state.ids().iter().zip(state.positions().chunks(3)) {
+        if let (Some(a), Some(b)) = (before.get(id), after.get(id)) {
+            assert!((Vec3::from_slice(position) - (*a + *b) * 0.5).length() < 0.02);
+            compared += 1;
+        }
+    }
+    assert!(compared > 0);
+
+    assert_eq!(apply_sync_packet(mirror_id, b"HCSP").code(), ErrorCode::InvalidArgument);
+    assert!(destroy_scene_mirror(mirror_id).ok());
+    assert_eq!(apply_sync_packet(mirror_id, &keyframe).code(), ErrorCode::NotFound);
+}
+
+#[test]
+fn queued_commands_replay_deterministically() {
+    let dt = 1.0 / 60.0;
+    let run = |commands: &[String]| {
+        let system_id = create_space_object_system(25.0, 60.0);
+        assert!(reseed_space_object_system(system_id, 4215).ok());
+        for command in commands {
+            assert!(enqueue_command(system_id, command).ok(), "{}", command);
+        }
+        for _ in 0..20 {
+            assert!(update_space_object_system(system_id, dt).ok());
+        }
+        (system_id, get_visible_neon_comets(system_id).expect("comet data").positions())
+    };
+
+    let live = [
+        r#"{"type": "spawn", "object_type": "NeonComet", "count": 4}"#.to_string(),
+        r#"{"type": "attract", "point": [0, 0, -20], "strength": 3, "radius": 200, "frame": 10}"#.to_string(),
+        r#"{"type": "palette", "shift": 0.5, "frame": 5}"#.to_string(),
+    ];
+    let (system_id, positions) = run(&live);
+    assert_eq!(get_active_neon_comets_count(system_id), 4);
+    assert_eq!(get_queued_command_count(system_id), Some(0));
+
+    // Журнал упорядочен по кадрам выполнения и воспроизводит сцену в системе с тем же seed
+    let log = take_command_log(system_id);
+    assert_eq!(log.len(), 3);
+    assert!(log[1].contains(r#""type":"palette""#) && log[1].contains(r#""frame":5"#));
+    assert!(take_command_log(system_id).is_empty());
+    let (_, replayed) = run(&log);
+    assert_eq!(positions, replayed);
+
+    // Команды на будущие кадры ждут в очереди; некорректные отклоняются
+    assert!(enqueue_command(system_id, r#"{"type": "palette", "shift": 1, "frame": 1000}"#).ok());
+    assert_eq!(get_queued_command_count(system_id), Some(1));
+    assert_eq!(enqueue_command(system_id, r#"{"type": "rotate_plane"}"#).code(), ErrorCode::InvalidArgument);
+    assert_eq!(enqueue_command(system_id, r#"{"type": "attract", "point": [0, 0, 0], "strength": 1, "radius": 0}"#).code(), ErrorCode::InvalidArgument);
+    assert_eq!(enqueue_command(usize::MAX, r#"{"type": "palette", "shift": 0}"#).code(), ErrorCode::SystemNotFound);
+}
+
+#[test]
+fn plane_occupancy_counts_objects_per_octant() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    let empty = get_plane_occupancy(system_id, MAIN_PLANE_INDEX).expect("occupancy");
+    assert_eq!(empty.count(), 0);
+    assert_eq!(empty.octant_counts(), vec![0; 8]);
+    assert!(empty.octant_densities().iter().all(|&density| density == 0.0));
+
+    assert!(spawn_energy_spheres(system_id, 6).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    let occupancy = get_plane_occupancy(system_id, MAIN_PLANE_INDEX).expect("occupancy");
+    assert_eq!(occupancy.octant_counts().iter().sum::<u32>(), occupancy.count());
+    assert!(occupancy.count() <= 6);
+    let least = occupancy.least_occupied_octant();
+    assert!(occupancy.octant_counts().iter().all(|&count| count >= occupancy.octant_counts()[least]));
+    let (min, max) = (Vec3::from_slice(&occupancy.min()), Vec3::from_slice(&occupancy.max()));
+    assert!(min.cmplt(max).all());
+
+    // Октанты 4..7 - дальняя половина объема
+    let center = (min + max) * 0.5;
+    assert_eq!(octant_index(center + Vec3::new(-1.0, 1.0, 1.0), center), 6);
+    assert_eq!(octant_index(center - Vec3::ONE, center), 0);
+    assert!(get_plane_occupancy(system_id, 99).is_none());
+}
+
+#[test]
+fn spawn_separation_spreads_spawns_on_far_plane() {
+    // Наименьшее расстояние между появившимися сферами (все на дальней плоскости)
+    let closest_pair = |separation: Option<f32>| {
+        let system_id = create_space_object_system(25.0, 60.0);
+        assert!(reseed_space_object_system(system_id, 4217).ok());
+        assert!(set_spawn_separation(system_id, separation).ok());
+        assert_eq!(get_spawn_separation(system_id), separation);
+        assert!(spawn_energy_spheres(system_id, 12).ok());
+
+        let positions = get_energy_spheres(system_id).expect("spheres").positions();
+        let points: Vec<Vec2> = positions.chunks(3).map(|p| Vec2::new(p[0], p[1])).collect();
+        let mut closest = f32::INFINITY;
+        for (i, a) in points.iter().enumerate() {
+            for b in &points[i + 1..] {
+                closest = closest.min(a.distance(*b));
+            }
+        }
+        closest
+    };
+
+    assert!(closest_pair(Some(0.2)) > closest_pair(None));
+    assert_eq!(set_spawn_separation(0, Some(-1.0)).code(), ErrorCode::InvalidArgument);
+    assert_eq!(set_spawn_separation(usize::MAX, None).code(), ErrorCode::SystemNotFound);
+}
+
+#[test]
+fn spawn_density_map_weights_far_plane_regions() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(!has_spawn_density_map(system_id));
+
+    // Вес только у правой верхней ячейки: все появления справа сверху
+    assert!(set_spawn_density_map(system_id, 2, 2, &[0.0, 1.0, 0.0, 0.0]).ok());
+    assert!(has_spawn_density_map(system_id));
+    assert!(spawn_energy_spheres(system_id, 10).ok());
+    let positions = get_energy_spheres(system_id).expect("spheres").positions();
+    assert!(positions.chunks(3).all(|p| p[0] >= 0.0 && p[1] >= 0.0));
+
+    assert!(clear_spawn_density_map(system_id).ok());
+    assert!(!has_spawn_density_map(system_id));
+
+    assert!(SpawnDensityMap::new(2, 2, &[1.0; 3]).is_err());
+    assert!(SpawnDensityMap::new(1, 1, &[0.0]).is_err());
+    assert!(SpawnDensityMap::new(1, 2, &[1.0, f32::NAN]).is_err());
+    assert!(SpawnDensityMap::new(1, 2, &[3e38, 3e38]).is_err());
+    assert_eq!(set_spawn_density_map(system_id, 1, 2, &[3e38, 3e38]).code(), ErrorCode::InvalidArgument);
+    assert_eq!(set_spawn_density_map(system_id, 0, 0, &[]).code(), ErrorCode::InvalidArgument);
+    assert_eq!(clear_spawn_density_map(usize::MAX).code(), ErrorCode::SystemNotFound);
+}
+
+#[test]
+fn protected_screen_rect_rerolls_crossing_trajectories() {
+    let crossing_count = |protect_before_spawn: bool| {
+        let system_id = create_space_object_system(25.0, 60.0);
+        assert!(reseed_space_object_system(system_id, 4219).ok());
+        if protect_before_spawn {
+            assert!(set_protected_screen_rect(system_id, 0.3, 0.3, 0.4, 0.4, 0.0, 50.0).ok());
+        }
+        assert!(spawn_energy_spheres(system_id, 20).ok());
+        assert!(set_protected_screen_rect(system_id, 0.3, 0.3, 0.4, 0.4, 0.0, 50.0).ok());
+        let ids = get_energy_spheres(system_id).expect("spheres").ids();
+        ids.iter().filter(|&&id| crosses_protected_rect(system_id, id) == Some(true)).count()
+    };
+
+    // Сферы дрейфуют к случайной точке окна: без защиты часть летит через центр.
+    // Вдали все траектории сходятся к центру, поэтому проверяются глубины до 50
+    assert!(crossing_count(false) > 0);
+    assert_eq!(crossing_count(true), 0);
+
+    assert!(segment_hits_rect(Vec2::new(-1.0, 0.5), Vec2::new(2.0, 0.5), Vec2::ZERO, Vec2::ONE));
+    assert!(!segment_hits_rect(Vec2::new(-1.0, 2.0), Vec2::new(2.0, 1.5), Vec2::ZERO, Vec2::ONE));
+    assert_eq!(set_protected_screen_rect(0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0).code(), ErrorCode::InvalidArgument);
+    assert_eq!(clear_protected_screen_rect(usize::MAX).code(), ErrorCode::SystemNotFound);
+}
+
+#[test]
+fn tail_particles_drift_with_wind_and_turbulence() {
+    // Одинаковые сцены с разным полем хвостов
+    let tails = |turbulence: f32, wind: Vec3| {
+        let system_id = create_space_object_system(25.0, 60.0);
+        assert!(reseed_space_object_system(system_id, 4220).ok());
+        assert!(enqueue_command(system_id, r#"{"type": "spawn", "object_type": "NeonComet", "count": 3}"#).ok());
+        assert!(set_tail_field(system_id, turbulence, 0.05, &Vec3Wrapper::new(wind.x, wind.y, wind.z)).ok());
+        for _ in 0..10 {
+            assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+        }
+        (system_id, get_tail_particles(system_id).expect("tails").positions())
+    };
+
+    let (system_id, still) = tails(0.0, Vec3::ZERO);
+    let (_, windy) = tails(0.0, Vec3::new(20.0, 0.0, 0.0));
+    let (_, turbulent) = tails(3.0, Vec3::ZERO);
+    assert!(!still.is_empty());
+    assert_eq!(still.len(), windy.len());
+
+    // Ветер сносит частицы только по своей оси, старые - дальше
+    let drift: Vec<Vec3> = still.chunks(3).zip(windy.chunks(3))
+        .map(|(a, b)| Vec3::from_slice(b) - Vec3::from_slice(a))
+        .collect();
+    assert!(drift.iter().all(|d| d.x >= 0.0 && d.y.abs() < 1e-4 && d.z.abs() < 1e-4));
+    assert!(drift.iter().any(|d| d.x > 0.0));
+    assert_ne!(still, turbulent);
+
+    let comet_id = get_visible_neon_comets(system_id).expect("comets").ids()[0];
+    assert!(set_comet_tail_turbulence(system_id, comet_id, 0.5).ok());
+    assert_eq!(set_comet_tail_turbulence(system_id, usize::MAX, 0.5).code(), ErrorCode::NotFound);
+    assert_eq!(set_tail_field(system_id, -1.0, 0.05, &Vec3Wrapper::new(0.0, 0.0, 0.0)).code(), ErrorCode::InvalidArgument);
+}
+
+#[test]
+fn motion_vectors_follow_projected_movement() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(reseed_space_object_system(system_id, 4221).ok());
+    assert!(enqueue_command(system_id, r#"{"type": "spawn", "object_type": "NeonComet", "count": 4}"#).ok());
+    assert!(enqueue_command(system_id, r#"{"type": "spawn", "object_type": "EnergySphere", "count": 2}"#).ok());
+    for _ in 0..3 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+
+    // По два числа (UV-смещение за шаг) на объект
+    let comets = get_visible_neon_comets(system_id).expect("comets");
+    let vectors = comets.motion_vectors();
+    assert_eq!(vectors.len(), comets.ids().len() * 2);
+    assert!(vectors.iter().all(|v| v.is_finite() && v.abs() < 1.0));
+    assert!(vectors.iter().any(|v| *v != 0.0));
+
+    let spheres = get_energy_spheres(system_id).expect("spheres");
+    assert_eq!(spheres.motion_vectors().len(), spheres.ids().len() * 2);
+}
+
+#[test]
+fn object_bounds_cover_tails_and_vortex_rings() {
+    // Кольцо в плоскости XY выступает по X и Y, по Z - только тело
+    let ringed = ObjectBounds::ringed(Vec3::ZERO, 1.0, Vec3::Z, 2.0);
+    assert!((ringed.max - Vec3::new(2.0, 2.0, 1.0)).length() < 1e-5);
+    assert_eq!(ringed.radius, 2.0);
+    // Сфера капсулы теснее описанной вокруг AABB
+    let capsule = ObjectBounds::capsule(Vec3::ZERO, Vec3::new(0.0, 0.0, 10.0), 1.0);
+    assert_eq!(capsule.radius, 6.0);
+    assert!(capsule.radius < (capsule.max - capsule.min).length() * 0.5);
+
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(reseed_space_object_system(system_id, 4222).ok());
+    assert!(enqueue_command(system_id, r#"{"type": "spawn", "object_type": "NeonComet", "count": 4}"#).ok());
+    assert!(enqueue_command(system_id, r#"{"type": "spawn", "object_type": "EnergySphere", "count": 2}"#).ok());
+    for _ in 0..30 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+
+    let comets = get_visible_neon_comets(system_id).expect("comets");
+    let (min, max, spheres) = (comets.bounds_min(), comets.bounds_max(), comets.bounding_spheres());
+    assert!(!comets.ids().is_empty());
+    assert_eq!(spheres.len(), comets.ids().len() * 4);
+    for (i, (position, tail)) in comets.positions().chunks(3).zip(comets.tail_lengths()).enumerate() {
+        let (position, min, max) = (Vec3::from_slice(position), Vec3::from_slice(&min[i * 3..]), Vec3::from_slice(&max[i * 3..]));
+        assert!(position.cmpge(min).all() && position.cmple(max).all());
+        // Хвост вытягивает габариты
+        assert!((max - min).max_element() >= tail);
+        let sphere = &spheres[i * 4..i * 4 + 4];
+        assert!(Vec3::from_slice(sphere).distance(position) <= sphere[3] + 1e-4);
+    }
+
+    let energy = get_energy_spheres(system_id).expect("spheres");
+    for (radius, sphere) in energy.radii().iter().zip(energy.bounding_spheres().chunks(4)) {
+        assert!(sphere[3] > *radius);
+    }
+}
+
+#[test]
+fn crossing_stats_aggregate_over_time_windows() {
+    let mut ring = CrossingStatsRing::default();
+    // Первые 10 с - по входу в правом верхнем углу со скоростью 10, затем 10 с тишины
+    for frame in 0..1200 {
+        if frame < 600 && frame % 60 == 0 {
+            ring.record(1.0 / 60.0, [(CrossingType::Entry, 10.0, Vec2::new(0.9, 0.9))].into_iter());
+        } else {
+            ring.record(1.0 / 60.0, std::iter::empty());
+        }
+    }
+    ring.record(0.0, [(CrossingType::Exit, 20.0, Vec2::new(0.1, 0.1))].into_iter());
+
+    let all = ring.summary(30.0);
+    assert_eq!((all.entries, all.exits), (10, 1));
+    assert!((all.mean_speed - 120.0 / 11.0).abs() < 1e-4);
+    assert!((all.window - 20.0).abs() < 1e-3);
+    let (min, max) = heat_cell_rect(all.hottest_cell().expect("hottest"));
+    assert!(min.cmple(Vec2::splat(0.9)).all() && max.cmpge(Vec2::splat(0.9)).all());
+
+    // В последние 5 с попал только выход
+    let recent = ring.summary(5.0);
+    assert_eq!((recent.entries, recent.exits), (0, 1));
+
+    // Старые корзины вытесняются
+    for _ in 0..(MAX_STATS_WINDOW as usize + 1) {
+        ring.record(1.0, std::iter::empty());
+    }
+    assert_eq!(ring.summary(MAX_STATS_WINDOW).count(), 0);
+    assert!(ring.summary(MAX_STATS_WINDOW).hottest_cell().is_none());
+
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    let stats = get_crossing_stats(system_id, 10.0).expect("stats");
+    assert_eq!(stats.heat().len(), stats.heat_grid() * stats.heat_grid());
+    assert!(get_crossing_stats(system_id, 0.0).is_none());
+    assert!(get_crossing_stats(usize::MAX, 10.0).is_none());
+}
+
+#[test]
+fn analytics_batches_crossings_per_interval() {
+    let crossing = PlaneCrossing {
+        id: 3,
+        object_id: 9,
+        object_type: SpaceObjectType::NeonComet,
+        plane: 0,
+        position: Vec3::ZERO,
+        velocity: Vec3::new(0.0, 0.0, -12.345),
+        size: 1.0,
+        t: 0.5,
+        crossing_type: CrossingType::Entry,
+        normal: Vec3::Z,
+    };
+
+    // Выключенная выгрузка ничего не копит
+    let mut batcher = AnalyticsBatcher::default();
+    batcher.record(0.1, [(&crossing, Vec2::new(0.25, 0.75))].into_iter());
+    assert_eq!(batcher.pending_len(), 0);
+
+    batcher.set_interval(Some(1.0));
+    for frame in 0..20 {
+        let crossings: Vec<_> = if frame % 5 == 0 { vec![(&crossing, Vec2::new(0.25, 0.75))] } else { Vec::new() };
+        batcher.record(0.1, crossings.into_iter());
+    }
+    // Два интервала по секунде - два пакета по два пересечения
+    let batches = batcher.take_batches();
+    assert_eq!(batches.len(), 2);
+    let records: serde_json::Value = serde_json::from_str(&batches[0]).expect("json array");
+    let records = records.as_array().expect("array");
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["dir"], "entry");
+    assert_eq!(records[0]["type"], "NeonComet");
+    assert_eq!(records[0]["obj"], 9);
+    assert_eq!(records[0]["speed"].as_f64().map(|speed| (speed * 100.0).round()), Some(1235.0));
+    assert_eq!(records[0]["t"].as_f64(), Some(0.05));
+    assert!(batcher.take_batches().is_empty());
+
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert_eq!(set_analytics_batch_interval(system_id, Some(0.0)).code(), ErrorCode::InvalidArgument);
+    assert!(set_analytics_batch_interval(system_id, Some(5.0)).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    assert!(flush_analytics_batch(system_id).ok());
+    assert!(take_analytics_batches(system_id).iter().all(|batch| batch.starts_with('[')));
+    assert!(take_analytics_batches(usize::MAX).is_empty());
+}
+
+#[test]
+fn subsystems_pause_and_hide_at_runtime() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(reseed_space_object_system(system_id, 4227).ok());
+    assert!(enqueue_command(system_id, r#"{"type": "spawn", "object_type": "NeonComet", "count": 4}"#).ok());
+    assert!(enqueue_command(system_id, r#"{"type": "spawn", "object_type": "EnergySphere", "count": 2}"#).ok());
+    for _ in 0..5 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+    let positions = get_visible_neon_comets(system_id).expect("comets").positions();
+    assert!(!positions.is_empty());
+
+    // Выключенные кометы не видны и не двигаются, сферы продолжают жить
+    assert!(set_subsystem_enabled(system_id, "comets", false).ok());
+    assert_eq!(is_subsystem_enabled(system_id, "comets"), Some(false));
+    assert!(get_visible_neon_comets(system_id).is_none_or(|comets| comets.ids().is_empty()));
+    for _ in 0..5 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+    assert!(get_tail_particles(system_id).expect("tails").positions().is_empty());
+    assert_eq!(get_energy_spheres(system_id).expect("spheres").ids().len(), 2);
+
+    // После включения кометы продолжают с того же места
+    assert!(set_subsystem_enabled(system_id, "comets", true).ok());
+    assert_eq!(get_visible_neon_comets(system_id).expect("comets").positions(), positions);
+
+    // Без частиц нет ни хвостов, ни вихрей
+    assert!(set_subsystem_enabled(system_id, "particles", false).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    assert!(get_tail_particles(system_id).expect("tails").positions().is_empty());
+    assert!(get_energy_spheres(system_id).expect("spheres").vortex_positions().is_empty());
+    assert!(set_subsystem_enabled(system_id, "particles", true).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    assert!(!get_tail_particles(system_id).expect("tails").positions().is_empty());
+
+    assert!(set_subsystem_enabled(system_id, "intersections", false).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    assert!(get_plane_crossings(system_id).expect("crossings").ids().is_empty());
+    assert!(set_subsystem_enabled(system_id, "crystals", false).ok());
+    assert_eq!(set_subsystem_enabled(system_id, "stars", false).code(), ErrorCode::InvalidArgument);
+    assert_eq!(is_subsystem_enabled(system_id, "stars"), None);
+}
+
+#[test]
+fn presimulate_warms_up_queued_comets() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(spawn_neon_comets(system_id, 3).ok());
+
+    // Группы появляются с задержками до нескольких секунд - прогрев выдает их все до первого кадра
+    let simulated = presimulate(system_id, 60.0);
+    assert!((simulated - 10.0).abs() < 1e-3);
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id).expect("system");
+    assert!(system.frame >= 300);
+    let comets = &system.get_objects()[&SpaceObjectType::NeonComet];
+    assert!(comets.len() >= 3);
+    assert!(comets.iter().all(|obj| obj.get_data().lifetime > 0.0));
+    drop(system);
+
+    assert_eq!(presimulate(system_id, f32::NAN), 0.0);
+    assert_eq!(presimulate(system_id, -1.0), 0.0);
+    assert_eq!(presimulate(usize::MAX, 1.0), 0.0);
+}
+
+#[test]
+fn acceleration_profiles_shape_approach_speed() {
+    let model = |profile| AccelerationModel { profile, strength: 1.0 };
+    let dt = 0.1;
+
+    // Постоянная скорость не меняется, линейный профиль усиливается вблизи наблюдателя
+    assert_eq!(model(AccelerationProfile::ConstantVelocity).next_speed(10.0, 5.0, 2.0, 0.5, 100.0, dt), 10.0);
+    let linear = model(AccelerationProfile::Linear);
+    assert!((linear.next_speed(10.0, 5.0, 1.0, 0.5, 100.0, dt) - 10.5).abs() < 1e-5);
+    assert!((linear.next_speed(10.0, 5.0, 2.0, 0.5, 100.0, dt) - 11.0).abs() < 1e-5);
+    assert_eq!(linear.next_speed(99.9, 5.0, 1.0, 0.5, 100.0, dt), 100.0);
+
+    // Экспоненциальный прирост пропорционален текущей скорости
+    let exponential = model(AccelerationProfile::Exponential);
+    assert!((exponential.next_speed(10.0, 1.0, 1.0, 0.5, 100.0, dt) - 10.0 * 0.1f32.exp()).abs() < 1e-4);
+    assert!(exponential.next_speed(20.0, 1.0, 1.0, 0.5, 100.0, dt) > 2.0 * exponential.next_speed(10.0, 1.0, 1.0, 0.5, 100.0, dt) - 1e-3);
+
+    // EaseInOut разгоняет в первой половине пути и тормозит во второй, не опускаясь ниже минимума
+    let ease = model(AccelerationProfile::EaseInOut);
+    assert!(ease.next_speed(50.0, 1.0, 1.0, 0.1, 100.0, dt) > 50.0);
+    assert!(ease.next_speed(50.0, 1.0, 1.0, 0.9, 100.0, dt) < 50.0);
+    assert_eq!(ease.next_speed(20.0, 10.0, 1.0, 1.0, 100.0, dt), 20.0);
+
+    // Сила профиля масштабирует собственное ускорение объекта
+    let strong = AccelerationModel { profile: AccelerationProfile::Linear, strength: 2.0 };
+    assert!((strong.next_speed(10.0, 5.0, 1.0, 0.5, 100.0, dt) - 11.0).abs() < 1e-5);
+
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(set_acceleration_profile(system_id, AccelerationProfile::EaseInOut, -1.0).ok());
+    assert_eq!(get_acceleration_profile(system_id), Some(AccelerationProfile::EaseInOut));
+    assert_eq!(get_acceleration_strength(system_id), Some(0.0));
+    assert_eq!(set_acceleration_profile(usize::MAX, AccelerationProfile::Linear, 1.0).code(), ErrorCode::SystemNotFound);
+}
+
+#[test]
+fn catch_up_policies_handle_resume_gap() {
+    // Сколько шагов сделает система на первом кадре после возвращения во вкладку
+    let resume_steps = |dt: f32, hidden: bool, policy: CatchUpPolicy| {
+        let system_id = create_space_object_system(25.0, 60.0);
+        let mut system = SPACE_OBJECT_SYSTEMS.get_mut(&system_id).expect("system");
+        spawn_neon_comet(&mut system);
+        system.resume_pending = true;
+        let frame = system.frame;
+        system.advance_with(dt, hidden, policy);
+        (system.frame - frame, system.resume_pending)
+    };
+
+    // Минута в фоне: FastForward проматывает не больше MAX_CATCH_UP_SECONDS шагами по 0.1 с
+    let (steps, pending) = resume_steps(60.0, false, CatchUpPolicy::FastForward);
+    assert!((20..=21).contains(&steps), "{steps}");
+    assert!(!pending);
+    assert_eq!(resume_steps(f32::NAN, false, CatchUpPolicy::FastForward), (0, false));
+
+    // Skip отбрасывает разрыв: один нулевой шаг
+    assert_eq!(resume_steps(60.0, false, CatchUpPolicy::Skip), (1, false));
+
+    // Pause стоит, пока вкладка скрыта, и не тратит кадр возвращения
+    assert_eq!(resume_steps(60.0, true, CatchUpPolicy::Pause), (0, true));
+    assert_eq!(resume_steps(60.0, false, CatchUpPolicy::Pause), (1, false));
+
+    // Без возвращения кадр обрабатывается обычным шагом
+    let system_id = create_space_object_system(25.0, 60.0);
+    let mut system = SPACE_OBJECT_SYSTEMS.get_mut(&system_id).expect("system");
+    system.advance_with(1.0 / 60.0, false, CatchUpPolicy::FastForward);
+    assert_eq!(system.frame, 1);
+}
+
+#[test]
+fn opacity_profile_covers_fade_in_travel_and_fade_out() {
+    let mut profile = OpacityProfile::new();
+    profile.fade_in_seconds = 2.0;
+    profile.fade_in_curve = EasingCurve::EaseInQuad;
+    profile.travel_min_opacity = 0.4;
+    profile.fade_out_distance = 10.0;
+
+    // Появление идет по своей кривой, затем прозрачность следует расстоянию, но не ниже предела
+    assert!((profile.opacity(1.0, 1.0, 0.0) - 0.25).abs() < 1e-6);
+    assert_eq!(profile.opacity(3.0, 0.9, 0.0), 0.9);
+    assert_eq!(profile.opacity(3.0, 0.1, 0.0), 0.4);
+
+    // За плоскостью объект гаснет на fade_out_distance
+    assert!((profile.opacity(3.0, 1.0, 5.0) - 0.5).abs() < 1e-6);
+    assert_eq!(profile.opacity(3.0, 1.0, 20.0), 0.0);
+    profile.fade_out_distance = 0.0;
+    assert_eq!(profile.opacity(3.0, 1.0, 20.0), 1.0);
+
+    // Профиль системы действует на кометы: долгое появление держит их почти прозрачными
+    let system_id = create_space_object_system(25.0, 60.0);
+    let mut slow = OpacityProfile::new();
+    slow.fade_in_seconds = 100.0;
+    assert!(set_opacity_profile(system_id, SpaceObjectType::NeonComet, &slow).ok());
+    assert_eq!(get_opacity_profile(system_id, SpaceObjectType::NeonComet).expect("profile").fade_in_seconds, 100.0);
+    assert_eq!(get_opacity_profile(system_id, SpaceObjectType::EnergySphere).expect("profile").fade_in_seconds, 1.0);
+    spawn_neon_comet(&mut SPACE_OBJECT_SYSTEMS.get_mut(&system_id).expect("system"));
+    for _ in 0..10 {
+        assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    }
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id).expect("system");
+    assert!(system.get_objects()[&SpaceObjectType::NeonComet].iter().all(|obj| obj.get_data().opacity < 0.01));
+    drop(system);
+    assert_eq!(set_opacity_profile(usize::MAX, SpaceObjectType::NeonComet, &slow).code(), ErrorCode::SystemNotFound);
+}
+
+#[test]
+fn empty_scene_exports_schema_valid_gltf() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    let glb = export_gltf(system_id).expect("glb bytes");
+    let read_u32 = |offset: usize| u32::from_le_bytes(glb[offset..offset + 4].try_into().unwrap());
+
+    // Без объектов нет ни пустых массивов, ни буфера нулевой длины, ни BIN-чанка
+    let json_length = read_u32(12) as usize;
+    assert_eq!(read_u32(8) as usize, glb.len());
+    assert_eq!(glb.len(), 20 + json_length);
+    let json: serde_json::Value = serde_json::from_slice(&glb[20..]).expect("json");
+    assert_eq!(json["asset"]["version"], "2.0");
+    assert_eq!(json["scenes"].as_array().expect("scenes").len(), 1);
+    for key in ["nodes", "meshes", "accessors", "bufferViews", "buffers"] {
+        assert!(json.get(key).is_none(), "{key}");
+    }
+}
+
+#[test]
+fn memory_report_covers_all_system_stores() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    for _ in 0..3 {
+        spawn_neon_comet(&mut SPACE_OBJECT_SYSTEMS.get_mut(&system_id).expect("system"));
+    }
+    
+    let report = get_memory_report();
+    let names = report.names();
+    let lens = report.lens();
+    let stores = [
+        "objects", "parked_objects", "crossings", "hypercube_vertices", "hypercube_crossings",
+        "position_snapshot", "deltas", "effects", "tail_particles", "lifecycle", "debris",
+        "commands", "crossing_stats", "analytics", "near_misses",
+    ];
+    for store in stores {
+        let name = format!("system:{}:{}", system_id, store);
+        assert!(names.contains(&name), "{name}");
+    }
+    
+    // Спавн пишет события жизненного цикла, и отчёт это видит
+    let lifecycle = names.iter().position(|name| *name == format!("system:{}:lifecycle", system_id)).expect("lifecycle");
+    assert!(lens[lifecycle] >= 3);
+    let reported = names.iter().filter(|name| name.starts_with(&format!("system:{}:", system_id))).count();
+    assert_eq!(reported, stores.len());
+}
+
+#[test]
+fn device_orientation_tilts_view_and_spawn_zone() {
+    use rand::{rngs::StdRng, SeedableRng};
+    
+    // Обычное удержание телефона (beta = 45) - взгляд вдоль +Z, без смещения
+    let mut space = SpaceDefinition::new();
+    space.set_device_orientation(0.0, 45.0, 0.0);
+    assert!((space.view_direction() - Vec3::Z).length() < 1e-6);
+    assert_eq!(space.spawn_bias(), Vec2::ZERO);
+    
+    // Наклон вперед и влево: взгляд вверх и влево, зона появления туда же
+    space.set_device_orientation(0.0, 55.0, -10.0);
+    let view = space.view_direction();
+    assert!(view.y > 0.0 && view.x < 0.0 && view.z > 0.0);
+    let bias = space.spawn_bias();
+    assert!(bias.y > 0.0 && bias.x < 0.0);
+    
+    // Нечисловые углы игнорируются, наклон остается прежним
+    space.set_device_orientation(0.0, f32::NAN, f32::INFINITY);
+    assert_eq!(space.spawn_bias(), bias);
+    
+    // Экстремальный наклон ограничен 20 градусами
+    space.set_device_orientation(0.0, 180.0, 0.0);
+    let clamped = space.view_direction();
+    assert!((clamped.y / clamped.z - 20f32.to_radians().tan()).abs() < 1e-4);
+    
+    // С тем же сидом точка появления сдвигается ровно на spawn_bias
+    let neutral = SpaceDefinition::new();
+    let mut tilted = SpaceDefinition::new();
+    tilted.set_device_orientation(0.0, 55.0, -10.0);
+    for seed in 0..8 {
+        let base = random_position_on_far_plane(&mut StdRng::seed_from_u64(seed), &neutral);
+        let shifted = random_position_on_far_plane(&mut StdRng::seed_from_u64(seed), &tilted);
+        assert!((shifted - base - tilted.spawn_bias().extend(0.0)).length() < 1e-3, "seed {seed}");
+    }
+    
+    // Через API системы наклон доходит до направления взгляда
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(set_device_orientation(system_id, 0.0, 55.0, -10.0).ok());
+    let direction: Vec3 = get_view_direction(system_id).expect("view direction").into();
+    assert!((direction - view).length() < 1e-6);
+    assert_eq!(set_device_orientation(usize::MAX, 0.0, 45.0, 0.0).code(), ErrorCode::SystemNotFound);
+}
+
+#[test]
+fn steep_exponential_curve_stays_finite() {
+    // Без ограничения exp(k) переполняется при k ~ 88 и кривая дает NaN
+    for steepness in [100.0, 1000.0, -1000.0] {
+        let handle = create_exponential_curve(steepness).expect("exponential handle");
+        let mut previous = 0.0;
+        for step in 0..=10 {
+            let value = evaluate_curve(handle, step as f32 / 10.0).expect("value");
+            assert!(value.is_finite() && (0.0..=1.0 + 1e-6).contains(&value), "k = {steepness}: {value}");
+            assert!(value >= previous - 1e-6);
+            previous = value;
+        }
+        assert!((evaluate_curve(handle, 1.0).unwrap() - 1.0).abs() < 1e-5);
+    }
+    assert!(create_exponential_curve(f32::NAN).is_none());
+}
+
+#[test]
+fn crossing_sound_pans_by_crossed_plane() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    let plane = add_viewing_plane(system_id, 40.0, 0.0, 0.0, 20.0, 20.0, 0).expect("plane");
+    assert_ne!(plane, MAIN_PLANE_INDEX);
+    
+    // Левый край плоскости справа от главной: по главной плоскости звук ушел бы вправо
+    let crossing = PlaneCrossing {
+        id: 0,
+        object_id: 0,
+        object_type: SpaceObjectType::NeonComet,
+        plane,
+        position: Vec3::new(32.0, 0.0, 0.0),
+        velocity: Vec3::new(0.0, 0.0, -100.0),
+        size: 60.0,
+        t: 0.5,
+        crossing_type: CrossingType::Entry,
+        normal: Vec3::Z,
+    };
+    let system = SPACE_OBJECT_SYSTEMS.get(&system_id).expect("system");
+    let sound = crossing.sound(&system);
+    assert!(sound.pan < 0.0, "{}", sound.pan);
+    assert!((sound.pan - (system.crossing_uv(&crossing).x * 2.0 - 1.0)).abs() < 1e-6);
+    
+    let on_main = PlaneCrossing { plane: MAIN_PLANE_INDEX, ..crossing };
+    assert!(on_main.sound(&system).pan > 0.0);
+}
+
+#[test]
+fn manual_comet_effect_reports_errors() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(create_comet_effect_at_intersection(system_id, CometEffectKind::Flash, 1.0, 2.0, 0.0, 1.0, 0.5, 0.2, 1.0, 3.0).ok());
+    assert_eq!(get_comet_effects(system_id).expect("effects").kinds().len(), 1);
+    
+    let invalid = create_comet_effect_at_intersection(system_id, CometEffectKind::Flash, f32::NAN, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0);
+    assert_eq!(invalid.code(), ErrorCode::InvalidArgument);
+    let missing = create_comet_effect_at_intersection(usize::MAX, CometEffectKind::Flash, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0);
+    assert_eq!(missing.code(), ErrorCode::SystemNotFound);
+    assert_eq!(get_comet_effects(system_id).expect("effects").kinds().len(), 1);
+}
+
+#[test]
+fn error_codes_keep_their_wire_values() {
+    // Коды видит JS: удаленный код 4 (LockPoisoned) не переиспользуется
+    assert_eq!(ErrorCode::Ok as u32, 0);
+    assert_eq!(ErrorCode::SystemNotFound as u32, 1);
+    assert_eq!(ErrorCode::NotFound as u32, 2);
+    assert_eq!(ErrorCode::InvalidArgument as u32, 3);
+    assert_eq!(ErrorCode::SpawningStopped as u32, 5);
+}
+
+#[test]
+fn incremental_init_caps_counts_and_ignores_non_finite_budget() {
+    // Нечисловой бюджет считается нулевым: ровно один объект за вызов
+    let system_id = create_system_async(25.0, 60.0, 4, 0);
+    for budget in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let before = get_active_neon_comets_count(system_id);
+        assert!(continue_init(system_id, budget).expect("progress") < 1.0);
+        assert_eq!(get_active_neon_comets_count(system_id), before + 1);
+    }
+    
+    // Огромные счетчики не переполняют сумму и ограничиваются
+    let huge = create_system_async(25.0, 60.0, usize::MAX, usize::MAX);
+    let progress = continue_init(huge, 0.0).expect("progress");
+    assert!(progress > 0.0 && progress < 0.01);
+}
+
+#[test]
+fn energy_sphere_spawn_count_is_capped() {
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert_eq!(spawn_energy_spheres(system_id, usize::MAX).code(), ErrorCode::InvalidArgument);
+    assert_eq!(spawn_energy_spheres(system_id, MAX_SPHERES_PER_SPAWN + 1).code(), ErrorCode::InvalidArgument);
+    assert_eq!(get_energy_spheres(system_id).expect("sphere data").ids().len(), 0);
+    
+    assert!(spawn_energy_spheres(system_id, MAX_SPHERES_PER_SPAWN).ok());
+    assert_eq!(get_energy_spheres(system_id).expect("sphere data").ids().len(), MAX_SPHERES_PER_SPAWN);
+}
+
+#[test]
+fn physics_worlds_are_safe_to_create_and_step_concurrently() {
+    let threads: Vec<_> = (0..8)
+        .map(|_| {
+            std::thread::spawn(|| {
+                let world_id = init_world();
+                for _ in 0..10 {
+                    assert!(step_simulation(world_id, 1.0 / 60.0).ok());
+                }
+                world_id
+            })
+        })
+        .collect();
+    let mut ids: Vec<usize> = threads.into_iter().map(|thread| thread.join().expect("thread")).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), 8);
+    assert_eq!(step_simulation(usize::MAX, 1.0 / 60.0).code(), ErrorCode::NotFound);
+}
+
+#[test]
+fn near_misses_use_relative_motion_and_skip_crossed_comets() {
+    let mut near_misses = NearMisses::default();
+    near_misses.set_distance(Some(5.0));
+    
+    // Неподвижная комета, мимо которой пролетает наблюдатель
+    let comet = Vec3::new(3.0, 0.0, -25.0);
+    near_misses.check(1, comet, comet, 0.5, Vec3::new(0.0, 0.0, -15.0), Vec3::new(0.0, 0.0, -35.0));
+    // Комета и наблюдатель летят вместе: сближения нет
+    near_misses.check(2, Vec3::new(3.0, 0.0, -15.0), Vec3::new(3.0, 0.0, -35.0), 0.5, Vec3::new(0.0, 0.0, -15.0), Vec3::new(0.0, 0.0, -35.0));
+    let events = near_misses.drain();
+    assert_eq!(events.comet_ids(), vec![1]);
+    assert!((events.distances()[0] - 3.0).abs() < 1e-5);
+    assert!((events.relative_speeds()[0] - 40.0).abs() < 1e-4);
+    assert_eq!(events.points(), comet.to_array().to_vec());
+    
+    // После пересечения плоскости пролет не засчитывается до респауна
+    let observer = Vec3::new(0.0, 0.0, -25.0);
+    near_misses.mark_crossed(3);
+    near_misses.check(3, Vec3::new(3.0, 0.0, -15.0), Vec3::new(3.0, 0.0, -35.0), 0.5, observer, observer);
+    assert!(near_misses.is_empty());
+    near_misses.forget(3);
+    near_misses.check(3, Vec3::new(3.0, 0.0, -15.0), Vec3::new(3.0, 0.0, -35.0), 0.5, observer, observer);
+    assert_eq!(near_misses.len(), 1);
+    
+    // Наблюдатель прошлого кадра: первый кадр берет текущее положение
+    let mut frames = NearMisses::default();
+    assert_eq!(frames.begin_frame(observer), observer);
+    assert_eq!(frames.begin_frame(Vec3::ZERO), observer);
+}
+
+#[test]
+fn rotate_command_turns_bound_hypercube() {
+    // Без привязки команда принимается, но ничего не меняет
+    let system_id = create_space_object_system(25.0, 60.0);
+    assert!(enqueue_command(system_id, r#"{"type": "rotate", "angles": [0, 0, 0.6, 0, 0.4, 0]}"#).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    assert!(get_bound_hypercube(system_id).is_none());
+    assert_eq!(get_scene_modulation(system_id).expect("modulation").palette_t, 0.0);
+    
+    // Команда поворачивает копию гиперкуба так же, как поворот в JS с повторной привязкой
+    let hypercube = Hypercube::new(1.0);
+    assert!(bind_hypercube_to_scene(system_id, &hypercube, 2.0).ok());
+    let before = get_scene_modulation(system_id).expect("modulation");
+    assert!(enqueue_command(system_id, r#"{"type": "rotate", "angles": [0, 0, 0.6, 0, 0.4, 0]}"#).ok());
+    assert!(update_space_object_system(system_id, 1.0 / 60.0).ok());
+    let rotated = get_scene_modulation(system_id).expect("modulation");
+    assert!((rotated.palette_t - before.palette_t).abs() > 0.01);
+    
+    let mut expected = hypercube.clone();
+    expected.rotate(0.0, 0.0, 0.6, 0.0, 0.4, 0.0);
+    let reference = create_space_object_system(25.0, 60.0);
+    assert!(bind_hypercube_to_scene(reference, &expected, 2.0).ok());
+    let reference_modulation = get_scene_modulation(reference).expect("modulation");
+    assert_eq!(rotated.palette_t, reference_modulation.palette_t);
+    assert_eq!(rotated.spawn_rate, reference_modulation.spawn_rate);
+    let bound = get_bound_hypercube(system_id).expect("bound hypercube");
+    assert_eq!(bound.get_projected_vertices(2.0), expected.get_projected_vertices(2.0));
+    
+    // Поворот попадает в журнал для воспроизведения
+    assert!(take_command_log(system_id).iter().any(|entry| entry.contains("rotate")));
+    assert_eq!(enqueue_command(system_id, r#"{"type": "rotate", "angles": [0, 0, 1e400, 0, 0, 0]}"#).code(), ErrorCode::InvalidArgument);
+    assert_eq!(enqueue_command(system_id, r#"{"type": "rotate", "angles": [0, 0]}"#).code(), ErrorCode::InvalidArgument);
+    
+    assert!(unbind_hypercube_from_scene(system_id).ok());
+    assert!(get_bound_hypercube(system_id).is_none());
+}
+
+#[test]
+fn analytics_flushes_full_batch_early_instead_of_dropping() {
+    let crossing = PlaneCrossing {
+        id: 1,
+        object_id: 2,
+        object_type: SpaceObjectType::NeonComet,
+        plane: 0,
+        position: Vec3::ZERO,
+        velocity: Vec3::new(0.0, 0.0, -10.0),
+        size: 1.0,
+        t: 0.5,
+        crossing_type: CrossingType::Entry,
+        normal: Vec3::Z,
+    };
+    
+    let mut batcher = AnalyticsBatcher::default();
+    batcher.set_interval(Some(10.0));
+    let burst = 2 * MAX_BATCH_RECORDS + 5;
+    batcher.record(0.1, std::iter::repeat_n((&crossing, Vec2::splat(0.5)), burst));
+    
+    // Полные пакеты собраны досрочно, остаток ждет конца интервала
+    let batches = batcher.take_batches();
+    assert_eq!(batches.len(), 2);
+    for batch in &batches {
+        let records: serde_json::Value = serde_json::from_str(batch).expect("json array");
+        assert_eq!(records.as_array().expect("array").len(), MAX_BATCH_RECORDS);
+    }
+    assert_eq!(batcher.pending_len(), 5);
+    batcher.flush();
+    assert_eq!(batcher.take_batches().len(), 1);
+}